@@ -15,6 +15,8 @@ criterion_group!(
     serialization::benchmark_unsigned_integers,
     // Deserialization Benchmarks
     deserialization::benchmark_complex_deserialization_docbuf_map,
+    deserialization::benchmark_deserialization_scaling,
+    deserialization::benchmark_vtable_decode_scaling,
     // Roundtrip Benchmarks
     roundtrip::benchmark_complex_roundtrip_field_mutation,
 );
@@ -1,7 +1,7 @@
-use criterion::{black_box, BenchmarkId, Criterion};
+use criterion::{black_box, BenchmarkId, Criterion, Throughput};
 use docbuf_core::{
     traits::{DocBuf, DocBufMap},
-    vtable::VTable,
+    vtable::{DocBufReader, VTable},
 };
 use docbuf_tests::{complex, test_deps::*};
 
@@ -46,7 +46,7 @@ pub fn benchmark_complex_deserialization_docbuf_map(c: &mut Criterion) {
         |b, buffer| {
             b.iter(|| {
                 black_box(
-                    complex::Document::from_docbuf(&mut buffer.clone())
+                    complex::Document::from_docbuf(buffer)
                         .expect("Failed to deserialize docbuf bytecode")
                         .metadata
                         .signature
@@ -74,3 +74,87 @@ pub fn benchmark_complex_deserialization_docbuf_map(c: &mut Criterion) {
 
     group.finish();
 }
+
+// Sizes used to benchmark how decode time scales with the size of the input.
+const SCALING_SIZES: [usize; 4] = [1_000, 10_000, 100_000, 1_000_000];
+
+// Benchmark the deserialization of documents with an increasing number of map entries and
+// byte array length. The throughput is reported per input byte, so a constant throughput
+// across sizes shows that decode time grows linearly with the size of the input.
+pub fn benchmark_deserialization_scaling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("benchmark_deserialization_scaling");
+
+    for size in SCALING_SIZES {
+        let mut doc = complex::Document::dummy();
+
+        // Many small fields
+        doc.metadata.hash_map_data = (0..size / 100)
+            .map(|i| (i.to_string(), i.to_string()))
+            .collect();
+
+        // A large byte array
+        doc.metadata.byte_data = vec![1; size];
+
+        let mut buffer = Vec::with_capacity(size * 2);
+        doc.to_docbuf(&mut buffer)
+            .expect("failed to serialize docbuf");
+
+        group.throughput(Throughput::Bytes(buffer.len() as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("DocBuf Deserialization", size),
+            &buffer,
+            |b, buffer| {
+                b.iter(|| {
+                    black_box(
+                        complex::Document::from_docbuf(buffer)
+                            .expect("Failed to deserialize docbuf bytecode")
+                            .metadata
+                            .byte_data
+                            .len(),
+                    )
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+// Benchmark the decoding of back to back vtables from a single buffer. The throughput is
+// reported per input byte, so a constant throughput across sizes shows that decode time
+// grows linearly with the size of the input.
+pub fn benchmark_vtable_decode_scaling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("benchmark_vtable_decode_scaling");
+
+    let vtable = complex::Document::vtable().expect("failed to return vtable");
+
+    let mut vtable_buffer = Vec::new();
+    vtable
+        .write_to_buffer(&mut vtable_buffer)
+        .expect("failed to serialize vtable");
+
+    for count in [10, 100, 1_000] {
+        let buffer = vtable_buffer.repeat(count);
+
+        group.throughput(Throughput::Bytes(buffer.len() as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("VTable Decode", count),
+            &buffer,
+            |b, buffer| {
+                b.iter(|| {
+                    let mut reader = DocBufReader::new(buffer);
+
+                    for _ in 0..count {
+                        black_box(
+                            VTable::read_from_buffer(&mut reader).expect("failed to decode vtable"),
+                        );
+                    }
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
@@ -52,7 +52,7 @@ pub fn benchmark_complex_roundtrip_field_mutation(c: &mut Criterion) {
                     doc.to_docbuf(&mut buffer)
                         .expect("failed to serialize docbuf");
 
-                    let mut doc2 = complex::Document::from_docbuf(&mut buffer.clone())
+                    let mut doc2 = complex::Document::from_docbuf(&buffer)
                         .expect("Failed to deserialize docbuf bytecode");
 
                     doc2.metadata.signature.signature = [1; 32];
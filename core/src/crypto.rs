@@ -0,0 +1,325 @@
+//! Signature and hash algorithms of signed document buffers.
+//!
+//! Algorithms are identified by a single byte in signed document buffers, see
+//! `signed::DocBufSignature`, so that receivers can verify documents signed
+//! with any of the supported algorithms:
+//!
+//! | id | signature algorithm | feature   |
+//! |----|---------------------|-----------|
+//! | 1  | `ed25519`           | `crypto`  |
+//! | 2  | `p256`, ECDSA P-256 | `p256`    |
+//! | 3  | `secp256k1`, ECDSA  | `k256`    |
+//!
+//! | id | hash algorithm | feature  |
+//! |----|----------------|----------|
+//! | 1  | `sha256`       | `crypto` |
+//! | 2  | `sha512`       | `crypto` |
+//! | 3  | `sha3-256`     | `sha3`   |
+//! | 4  | `blake3`       | `blake3` |
+//!
+//! The names are the values of the docbuf `crypto` and `hash` attributes.
+//! Algorithms of disabled features are unknown to receivers.
+//!
+//! ECDSA public keys are SEC1 compressed points, and signatures are the 64
+//! bytes of `r || s`, with `s` normalized to the lower half of the curve order.
+//!
+//! Ed25519 signing keys are loaded, generated and rotated with a `Keyring`.
+
+mod keyring;
+
+pub use keyring::*;
+
+// Re-export the necessary crypto libraries for signing
+pub use digest;
+pub use ed25519;
+pub use ed25519_dalek;
+pub use sha2;
+
+#[cfg(feature = "blake3")]
+pub use blake3;
+#[cfg(feature = "k256")]
+pub use k256;
+#[cfg(feature = "p256")]
+pub use p256;
+#[cfg(feature = "sha3")]
+pub use sha3;
+
+use ed25519_dalek::Signer;
+use serde_derive::{Deserialize, Serialize};
+use sha2::Digest;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Unknown signature algorithm id: {0}")]
+    UnknownSignatureAlgorithm(u8),
+    #[error("Unknown hash algorithm id: {0}")]
+    UnknownHashAlgorithm(u8),
+    #[error("Invalid {0} public key")]
+    InvalidPublicKey(SignatureAlgorithm),
+    #[error("Invalid {0} signature")]
+    InvalidSignature(SignatureAlgorithm),
+    #[error("Failed to sign with {0}")]
+    SigningFailed(SignatureAlgorithm),
+    #[error("Signature verification failed")]
+    VerificationFailed,
+    #[error("Invalid key encoding: {0}")]
+    InvalidKeyEncoding(String),
+    #[error("Key not found in keyring: {0}")]
+    KeyNotFound(KeyId),
+    #[error("Key is not trusted by keyring: {0}")]
+    UntrustedKey(KeyId),
+    #[error("Keyring has no current signing key")]
+    NoSigningKey,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// The signature algorithm of a signed document buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum SignatureAlgorithm {
+    Ed25519 = 1,
+    #[cfg(feature = "p256")]
+    EcdsaP256 = 2,
+    #[cfg(feature = "k256")]
+    EcdsaSecp256k1 = 3,
+}
+
+impl SignatureAlgorithm {
+    /// Return the id of the algorithm in signed document buffers.
+    #[inline]
+    pub fn id(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Return the algorithm of the id of a signed document buffer.
+    pub fn from_id(id: u8) -> Result<Self, Error> {
+        match id {
+            1 => Ok(Self::Ed25519),
+            #[cfg(feature = "p256")]
+            2 => Ok(Self::EcdsaP256),
+            #[cfg(feature = "k256")]
+            3 => Ok(Self::EcdsaSecp256k1),
+            id => Err(Error::UnknownSignatureAlgorithm(id)),
+        }
+    }
+
+    /// Verify the signature of the message with the encoded public key.
+    pub fn verify(&self, public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<(), Error> {
+        match self {
+            Self::Ed25519 => {
+                let public_key = public_key
+                    .try_into()
+                    .ok()
+                    .and_then(|bytes| ed25519_dalek::VerifyingKey::from_bytes(bytes).ok())
+                    .ok_or(Error::InvalidPublicKey(*self))?;
+
+                let signature = ed25519::Signature::from_slice(signature)
+                    .map_err(|_| Error::InvalidSignature(*self))?;
+
+                // Reject malleable signatures and weak public keys
+                public_key
+                    .verify_strict(message, &signature)
+                    .map_err(|_| Error::VerificationFailed)
+            }
+            #[cfg(feature = "p256")]
+            Self::EcdsaP256 => {
+                use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+
+                let public_key = VerifyingKey::from_sec1_bytes(public_key)
+                    .map_err(|_| Error::InvalidPublicKey(*self))?;
+
+                let signature = Signature::from_slice(signature)
+                    .ok()
+                    .filter(|signature| signature.normalize_s().is_none())
+                    .ok_or(Error::InvalidSignature(*self))?;
+
+                public_key
+                    .verify(message, &signature)
+                    .map_err(|_| Error::VerificationFailed)
+            }
+            #[cfg(feature = "k256")]
+            Self::EcdsaSecp256k1 => {
+                use k256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+
+                let public_key = VerifyingKey::from_sec1_bytes(public_key)
+                    .map_err(|_| Error::InvalidPublicKey(*self))?;
+
+                // Malleable signatures are rejected by the secp256k1 verifier
+                let signature =
+                    Signature::from_slice(signature).map_err(|_| Error::InvalidSignature(*self))?;
+
+                public_key
+                    .verify(message, &signature)
+                    .map_err(|_| Error::VerificationFailed)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for SignatureAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ed25519 => write!(f, "ed25519"),
+            #[cfg(feature = "p256")]
+            Self::EcdsaP256 => write!(f, "p256"),
+            #[cfg(feature = "k256")]
+            Self::EcdsaSecp256k1 => write!(f, "secp256k1"),
+        }
+    }
+}
+
+/// The hash algorithm of the message signed in a signed document buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum HashAlgorithm {
+    Sha256 = 1,
+    Sha512 = 2,
+    #[cfg(feature = "sha3")]
+    Sha3_256 = 3,
+    #[cfg(feature = "blake3")]
+    Blake3 = 4,
+}
+
+impl HashAlgorithm {
+    /// Return the id of the algorithm in signed document buffers.
+    #[inline]
+    pub fn id(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Return the algorithm of the id of a signed document buffer.
+    pub fn from_id(id: u8) -> Result<Self, Error> {
+        match id {
+            1 => Ok(Self::Sha256),
+            2 => Ok(Self::Sha512),
+            #[cfg(feature = "sha3")]
+            3 => Ok(Self::Sha3_256),
+            #[cfg(feature = "blake3")]
+            4 => Ok(Self::Blake3),
+            id => Err(Error::UnknownHashAlgorithm(id)),
+        }
+    }
+
+    /// Hash the concatenated parts of a message.
+    pub fn hash(&self, parts: &[&[u8]]) -> Vec<u8> {
+        match self {
+            Self::Sha256 => hash_parts::<sha2::Sha256>(parts),
+            Self::Sha512 => hash_parts::<sha2::Sha512>(parts),
+            #[cfg(feature = "sha3")]
+            Self::Sha3_256 => hash_parts::<sha3::Sha3_256>(parts),
+            #[cfg(feature = "blake3")]
+            Self::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+
+                for part in parts {
+                    hasher.update(part);
+                }
+
+                hasher.finalize().as_bytes().to_vec()
+            }
+        }
+    }
+}
+
+/// Hash the concatenated parts of a message with the digest.
+fn hash_parts<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+    let mut digest = D::new();
+
+    for part in parts {
+        digest.update(part);
+    }
+
+    digest.finalize().to_vec()
+}
+
+impl std::fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sha256 => write!(f, "sha256"),
+            Self::Sha512 => write!(f, "sha512"),
+            #[cfg(feature = "sha3")]
+            Self::Sha3_256 => write!(f, "sha3-256"),
+            #[cfg(feature = "blake3")]
+            Self::Blake3 => write!(f, "blake3"),
+        }
+    }
+}
+
+/// A private key signing document buffers.
+pub trait DocBufSigner {
+    /// Return the signature algorithm of the key.
+    fn algorithm(&self) -> SignatureAlgorithm;
+
+    /// Return the encoded public key, embedded in signed document buffers.
+    fn public_key(&self) -> Vec<u8>;
+
+    /// Sign the message, returning the encoded signature.
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+impl DocBufSigner for ed25519_dalek::SigningKey {
+    fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::Ed25519
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        self.verifying_key().to_bytes().to_vec()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        let signature = self
+            .try_sign(message)
+            .map_err(|_| Error::SigningFailed(SignatureAlgorithm::Ed25519))?;
+
+        Ok(signature.to_vec())
+    }
+}
+
+#[cfg(feature = "p256")]
+impl DocBufSigner for p256::ecdsa::SigningKey {
+    fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::EcdsaP256
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        self.verifying_key()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        let signature: p256::ecdsa::Signature = self
+            .try_sign(message)
+            .map_err(|_| Error::SigningFailed(SignatureAlgorithm::EcdsaP256))?;
+
+        // Normalize the signature, as verifiers reject malleable signatures
+        let signature = signature.normalize_s().unwrap_or(signature);
+
+        Ok(signature.to_vec())
+    }
+}
+
+#[cfg(feature = "k256")]
+impl DocBufSigner for k256::ecdsa::SigningKey {
+    fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::EcdsaSecp256k1
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        self.verifying_key()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        // Signatures are normalized by the secp256k1 signer
+        let signature: k256::ecdsa::Signature = self
+            .try_sign(message)
+            .map_err(|_| Error::SigningFailed(SignatureAlgorithm::EcdsaSecp256k1))?;
+
+        Ok(signature.to_vec())
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::BTreeMap;
+
+use ed25519_dalek::pkcs8::{
+    spki::der::pem::LineEnding, DecodePrivateKey, DecodePublicKey, EncodePrivateKey,
+    EncodePublicKey,
+};
+use ed25519_dalek::{SigningKey, VerifyingKey, PUBLIC_KEY_LENGTH, SECRET_KEY_LENGTH};
+use rand_core::{OsRng, RngCore};
+use serde_derive::{Deserialize, Serialize};
+
+use super::{Error, HashAlgorithm, SignatureAlgorithm};
+
+/// Size of a key id, in bytes.
+pub const KEY_ID_SIZE: usize = 8;
+
+/// Marker at the start of PEM encoded keys.
+const PEM_BEGIN: &[u8] = b"-----BEGIN";
+
+/// The id of a key, the first bytes of the SHA-256 hash of its encoded public
+/// key. Key ids are displayed as hex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct KeyId([u8; KEY_ID_SIZE]);
+
+impl KeyId {
+    /// Return the id of the encoded public key.
+    pub fn from_public_key(public_key: &[u8]) -> Self {
+        let hash = HashAlgorithm::Sha256.hash(&[public_key]);
+
+        let mut id = [0u8; KEY_ID_SIZE];
+        id.copy_from_slice(&hash[..KEY_ID_SIZE]);
+
+        Self(id)
+    }
+}
+
+impl AsRef<[u8]> for KeyId {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for KeyId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+/// The encoding of a key file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFormat {
+    /// PKCS#8 private keys and SPKI public keys, PEM encoded.
+    Pem,
+    /// PKCS#8 private keys and SPKI public keys, DER encoded.
+    Der,
+    /// The raw 32 bytes of the key.
+    Raw,
+}
+
+/// The ed25519 signing keys of a process and the public keys it trusts.
+///
+/// The current signing key signs new documents, while documents are verified
+/// against any trusted key. Keys are rotated by making a new key current, and
+/// revoking the previous key once the documents it signed are no longer
+/// accepted:
+///
+/// ```ignore
+/// let mut keyring = Keyring::new();
+/// keyring.load_signing_key("/path/to/keypair")?;
+///
+/// let next = keyring.rotate();
+/// keyring.write_public_key(next, "/path/to/next.pub", KeyFormat::Pem)?;
+///
+/// doc.to_signed_docbuf(keyring.signing_key()?, &mut buffer)?;
+/// let doc = Doc::from_trusted_signed_docbuf(&buffer, &keyring)?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Keyring {
+    /// The id of the key signing new documents.
+    current: Option<KeyId>,
+    signing_keys: BTreeMap<KeyId, SigningKey>,
+    trusted_keys: BTreeMap<KeyId, VerifyingKey>,
+}
+
+impl Keyring {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Generate a new signing key. The key becomes the current signing key if
+    /// the keyring has none.
+    pub fn generate(&mut self) -> KeyId {
+        let mut secret = [0u8; SECRET_KEY_LENGTH];
+        OsRng.fill_bytes(&mut secret);
+
+        self.add_signing_key(SigningKey::from_bytes(&secret))
+    }
+
+    /// Generate a new signing key and make it the current signing key. The
+    /// previous keys are still trusted, until revoked.
+    pub fn rotate(&mut self) -> KeyId {
+        let id = self.generate();
+        self.current = Some(id);
+
+        id
+    }
+
+    /// Add a signing key, and trust its public key. The key becomes the current
+    /// signing key if the keyring has none.
+    pub fn add_signing_key(&mut self, key: SigningKey) -> KeyId {
+        let id = self.add_trusted_key(key.verifying_key());
+
+        self.signing_keys.insert(id, key);
+        self.current.get_or_insert(id);
+
+        id
+    }
+
+    /// Trust the public key of a signer.
+    pub fn add_trusted_key(&mut self, key: VerifyingKey) -> KeyId {
+        let id = KeyId::from_public_key(key.as_bytes());
+
+        self.trusted_keys.insert(id, key);
+
+        id
+    }
+
+    /// Make the signing key of the id the current signing key.
+    pub fn set_current(&mut self, id: KeyId) -> Result<(), Error> {
+        if !self.signing_keys.contains_key(&id) {
+            return Err(Error::KeyNotFound(id));
+        }
+
+        self.current = Some(id);
+
+        Ok(())
+    }
+
+    /// Remove the key of the id, no longer trusting the documents it signed.
+    /// The keyring has no current signing key if the current key is revoked.
+    pub fn revoke(&mut self, id: KeyId) -> Result<(), Error> {
+        self.trusted_keys
+            .remove(&id)
+            .ok_or(Error::KeyNotFound(id))?;
+        self.signing_keys.remove(&id);
+
+        if self.current == Some(id) {
+            self.current = None;
+        }
+
+        Ok(())
+    }
+
+    /// Return the id of the current signing key.
+    pub fn current_key_id(&self) -> Option<KeyId> {
+        self.current
+    }
+
+    /// Return the current signing key, signing document buffers.
+    pub fn signing_key(&self) -> Result<&SigningKey, Error> {
+        self.current
+            .and_then(|id| self.signing_keys.get(&id))
+            .ok_or(Error::NoSigningKey)
+    }
+
+    /// Return the trusted public key of the id.
+    pub fn trusted_key(&self, id: &KeyId) -> Option<&VerifyingKey> {
+        self.trusted_keys.get(id)
+    }
+
+    /// Return the ids of the trusted keys.
+    pub fn key_ids(&self) -> impl Iterator<Item = &KeyId> {
+        self.trusted_keys.keys()
+    }
+
+    /// Check the encoded public key of a signature is trusted, returning its id.
+    pub fn trust(&self, algorithm: SignatureAlgorithm, public_key: &[u8]) -> Result<KeyId, Error> {
+        let id = KeyId::from_public_key(public_key);
+
+        match self.trusted_keys.get(&id) {
+            Some(key)
+                if algorithm == SignatureAlgorithm::Ed25519 && key.as_bytes() == public_key =>
+            {
+                Ok(id)
+            }
+            _ => Err(Error::UntrustedKey(id)),
+        }
+    }
+
+    /// Verify the signature of the message against the trusted keys, returning
+    /// the id of the key of the signature.
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> Result<KeyId, Error> {
+        self.trusted_keys
+            .iter()
+            .find(|(_, key)| {
+                SignatureAlgorithm::Ed25519
+                    .verify(key.as_bytes(), message, signature)
+                    .is_ok()
+            })
+            .map(|(id, _)| *id)
+            .ok_or(Error::VerificationFailed)
+    }
+
+    /// Read a signing key from a file, see `decode_signing_key`.
+    #[cfg(feature = "std")]
+    pub fn load_signing_key(&mut self, path: impl AsRef<std::path::Path>) -> Result<KeyId, Error> {
+        let bytes = std::fs::read(path)?;
+
+        Ok(self.add_signing_key(decode_signing_key(&bytes)?))
+    }
+
+    /// Read a trusted public key from a file, see `decode_verifying_key`.
+    #[cfg(feature = "std")]
+    pub fn load_trusted_key(&mut self, path: impl AsRef<std::path::Path>) -> Result<KeyId, Error> {
+        let bytes = std::fs::read(path)?;
+
+        Ok(self.add_trusted_key(decode_verifying_key(&bytes)?))
+    }
+
+    /// Write the signing key of the id to a new file, readable by its owner
+    /// only. Existing files are not overwritten.
+    #[cfg(feature = "std")]
+    pub fn write_signing_key(
+        &self,
+        id: KeyId,
+        path: impl AsRef<std::path::Path>,
+        format: KeyFormat,
+    ) -> Result<(), Error> {
+        use std::io::Write;
+
+        let key = self.signing_keys.get(&id).ok_or(Error::KeyNotFound(id))?;
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options.open(path)?;
+
+        match format {
+            KeyFormat::Pem => file.write_all(
+                key.to_pkcs8_pem(LineEnding::LF)
+                    .map_err(|e| Error::InvalidKeyEncoding(e.to_string()))?
+                    .as_bytes(),
+            )?,
+            KeyFormat::Der => file.write_all(
+                key.to_pkcs8_der()
+                    .map_err(|e| Error::InvalidKeyEncoding(e.to_string()))?
+                    .as_bytes(),
+            )?,
+            KeyFormat::Raw => file.write_all(key.as_bytes())?,
+        }
+
+        Ok(())
+    }
+
+    /// Write the trusted public key of the id to a file.
+    #[cfg(feature = "std")]
+    pub fn write_public_key(
+        &self,
+        id: KeyId,
+        path: impl AsRef<std::path::Path>,
+        format: KeyFormat,
+    ) -> Result<(), Error> {
+        let key = self.trusted_keys.get(&id).ok_or(Error::KeyNotFound(id))?;
+
+        let bytes = match format {
+            KeyFormat::Pem => key
+                .to_public_key_pem(LineEnding::LF)
+                .map_err(|e| Error::InvalidKeyEncoding(e.to_string()))?
+                .into_bytes(),
+            KeyFormat::Der => key
+                .to_public_key_der()
+                .map_err(|e| Error::InvalidKeyEncoding(e.to_string()))?
+                .into_vec(),
+            KeyFormat::Raw => key.to_bytes().to_vec(),
+        };
+
+        std::fs::write(path, bytes)?;
+
+        Ok(())
+    }
+}
+
+/// Decode an ed25519 signing key, either a PEM or DER encoded PKCS#8 private
+/// key, the raw 32 bytes of the secret key, or the raw 64 bytes of the secret
+/// key followed by its public key.
+pub fn decode_signing_key(bytes: &[u8]) -> Result<SigningKey, Error> {
+    let invalid = |e: ed25519_dalek::pkcs8::Error| Error::InvalidKeyEncoding(e.to_string());
+
+    match bytes.len() {
+        _ if bytes.starts_with(PEM_BEGIN) => {
+            let pem =
+                std::str::from_utf8(bytes).map_err(|e| Error::InvalidKeyEncoding(e.to_string()))?;
+
+            SigningKey::from_pkcs8_pem(pem).map_err(invalid)
+        }
+        SECRET_KEY_LENGTH => Ok(SigningKey::from_bytes(
+            bytes.try_into().expect("secret key length"),
+        )),
+        ed25519_dalek::KEYPAIR_LENGTH => {
+            let (secret, public) = bytes.split_at(SECRET_KEY_LENGTH);
+            let key = SigningKey::from_bytes(secret.try_into().expect("secret key length"));
+
+            // The public key must be the public key of the secret key
+            match key.verifying_key().as_bytes() == public {
+                true => Ok(key),
+                false => Err(Error::InvalidPublicKey(SignatureAlgorithm::Ed25519)),
+            }
+        }
+        _ => SigningKey::from_pkcs8_der(bytes).map_err(invalid),
+    }
+}
+
+/// Decode an ed25519 public key, either a PEM or DER encoded SPKI public key,
+/// or the raw 32 bytes of the public key.
+pub fn decode_verifying_key(bytes: &[u8]) -> Result<VerifyingKey, Error> {
+    let invalid = |e: ed25519_dalek::pkcs8::spki::Error| Error::InvalidKeyEncoding(e.to_string());
+
+    match bytes.len() {
+        _ if bytes.starts_with(PEM_BEGIN) => {
+            let pem =
+                std::str::from_utf8(bytes).map_err(|e| Error::InvalidKeyEncoding(e.to_string()))?;
+
+            VerifyingKey::from_public_key_pem(pem).map_err(invalid)
+        }
+        PUBLIC_KEY_LENGTH => VerifyingKey::from_bytes(bytes.try_into().expect("public key length"))
+            .map_err(|_| Error::InvalidPublicKey(SignatureAlgorithm::Ed25519)),
+        _ => VerifyingKey::from_public_key_der(bytes).map_err(invalid),
+    }
+}
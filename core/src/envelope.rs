@@ -0,0 +1,132 @@
+//! Optional envelope for document buffers.
+//!
+//! A raw document buffer holds no marker of the vtable that produced it. The
+//! envelope prefixes the document buffer with a fixed size header, so that
+//! receivers can detect and route documents of mixed types:
+//!
+//! | bytes | field                                  |
+//! |-------|----------------------------------------|
+//! | 4     | magic bytes, `DBUF`                    |
+//! | 1     | wire format version                    |
+//! | 1     | flags                                  |
+//! | 8     | vtable id of the document              |
+//! | 4     | body length, little endian             |
+//! | n     | document buffer body                   |
+
+use crate::vtable::{DocBufReader, VTableId};
+
+/// Magic bytes at the start of every enveloped document buffer.
+pub const ENVELOPE_MAGIC: [u8; 4] = *b"DBUF";
+
+/// Wire format version of the document buffer, i.e. `pragma docbuf v1`.
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// Size of the envelope header in bytes.
+pub const ENVELOPE_HEADER_SIZE: usize = 18;
+
+/// Envelope flags understood by this version. Unknown flags are rejected
+/// when reading, rather than silently misreading the body.
+pub const ENVELOPE_KNOWN_FLAGS: u8 = 0;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Invalid envelope magic bytes: {0:?}")]
+    InvalidMagic([u8; 4]),
+    #[error("Unsupported envelope version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("Unknown envelope flags: {0:#010b}")]
+    UnknownFlags(u8),
+    #[error("VTable id mismatch. Expected: {expected}; found: {found}")]
+    VTableIdMismatch { expected: VTableId, found: VTableId },
+    #[error("Envelope body length mismatch. Expected: {expected}; found: {found}")]
+    BodyLengthMismatch { expected: usize, found: usize },
+    #[error("Envelope body length exceeds max: {0}")]
+    BodyTooLarge(usize),
+    #[error("Envelope header truncated")]
+    Truncated,
+}
+
+/// The header of an enveloped document buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocBufEnvelope {
+    pub version: u8,
+    pub flags: u8,
+    pub vtable_id: VTableId,
+    pub body_len: u32,
+}
+
+impl DocBufEnvelope {
+    pub fn new(vtable_id: VTableId, body_len: u32) -> Self {
+        Self {
+            version: ENVELOPE_VERSION,
+            flags: 0,
+            vtable_id,
+            body_len,
+        }
+    }
+
+    /// Serialize the envelope header into the buffer.
+    #[inline]
+    pub fn write_to_buffer(&self, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(&ENVELOPE_MAGIC);
+        buffer.push(self.version);
+        buffer.push(self.flags);
+        buffer.extend_from_slice(self.vtable_id.as_ref());
+        buffer.extend_from_slice(&self.body_len.to_le_bytes());
+    }
+
+    /// Read the envelope header from the front of the buffer, returning the
+    /// header and the document buffer body. The vtable id is not checked,
+    /// allowing the caller to route the body by its vtable id.
+    pub fn read_from_buffer(buffer: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let mut reader = DocBufReader::new(buffer);
+
+        let magic: [u8; 4] = reader.read_array().map_err(|_| Error::Truncated)?;
+        if magic != ENVELOPE_MAGIC {
+            return Err(Error::InvalidMagic(magic));
+        }
+
+        let version = reader.read_u8().map_err(|_| Error::Truncated)?;
+        if version != ENVELOPE_VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        let flags = reader.read_u8().map_err(|_| Error::Truncated)?;
+        if flags & !ENVELOPE_KNOWN_FLAGS != 0 {
+            return Err(Error::UnknownFlags(flags));
+        }
+
+        let vtable_id = VTableId::new(reader.read_array().map_err(|_| Error::Truncated)?);
+        let body_len = u32::from_le_bytes(reader.read_array().map_err(|_| Error::Truncated)?);
+
+        let body = reader.remaining();
+        if body.len() != body_len as usize {
+            return Err(Error::BodyLengthMismatch {
+                expected: body_len as usize,
+                found: body.len(),
+            });
+        }
+
+        Ok((
+            Self {
+                version,
+                flags,
+                vtable_id,
+                body_len,
+            },
+            body,
+        ))
+    }
+
+    /// Check the envelope was produced by the expected vtable.
+    #[inline]
+    pub fn check_vtable_id(&self, expected: &VTableId) -> Result<(), Error> {
+        match &self.vtable_id == expected {
+            true => Ok(()),
+            false => Err(Error::VTableIdMismatch {
+                expected: expected.to_owned(),
+                found: self.vtable_id.to_owned(),
+            }),
+        }
+    }
+}
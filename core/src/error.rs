@@ -15,6 +15,23 @@ pub enum Error {
     Ed25519Signature(#[from] ed25519::signature::Error),
     #[error(transparent)]
     VTable(#[from] crate::vtable::Error),
+    #[cfg(feature = "crypto")]
+    #[error(transparent)]
+    Crypto(#[from] crate::crypto::Error),
+    #[cfg(feature = "crypto")]
+    #[error(transparent)]
+    Signed(#[from] crate::signed::Error),
+    #[cfg(feature = "crypto")]
+    #[error(transparent)]
+    Merkle(#[from] crate::merkle::Error),
+    #[error(transparent)]
+    Envelope(#[from] crate::envelope::Error),
+    #[cfg(feature = "json")]
+    #[error(transparent)]
+    Json(#[from] crate::json::Error),
+    #[cfg(feature = "std")]
+    #[error(transparent)]
+    Registry(#[from] crate::registry::Error),
     /// UTF-8 Error
     #[error(transparent)]
     Utf8(#[from] std::str::Utf8Error),
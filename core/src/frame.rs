@@ -0,0 +1,46 @@
+//! Length framing for writing document buffers and vtables to a stream.
+//!
+//! Each frame is the 4-byte little endian length of the body followed by the
+//! body, so several frames can be written back to back to the same socket,
+//! pipe or file and read back one at a time.
+
+use std::io::{Read, Write};
+
+/// Number of bytes used to encode the length of a frame body.
+pub const FRAME_LENGTH_LE_BYTES: usize = 4;
+
+/// Write a single frame to the writer.
+pub fn write_frame(mut writer: impl Write, body: &[u8]) -> std::io::Result<()> {
+    let len = u32::try_from(body.len()).map_err(|_| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("frame body length exceeds max: {}", body.len()),
+        )
+    })?;
+
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(body)?;
+    writer.flush()
+}
+
+/// Read a single frame from the reader, returning the frame body.
+///
+/// Returns an error of kind `UnexpectedEof` if the reader is at the end of
+/// the stream or the stream ends in the middle of a frame.
+pub fn read_frame(mut reader: impl Read) -> std::io::Result<Vec<u8>> {
+    let mut len = [0u8; FRAME_LENGTH_LE_BYTES];
+    reader.read_exact(&mut len)?;
+
+    let len = u32::from_le_bytes(len) as usize;
+
+    // Read through `take` rather than allocating the length up front, so a
+    // corrupt length does not allocate more than the stream contains.
+    let mut body = Vec::new();
+    reader.take(len as u64).read_to_end(&mut body)?;
+
+    if body.len() != len {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+
+    Ok(body)
+}
@@ -0,0 +1,557 @@
+//! JSON transcoding of document buffers, driven by the vtable of the document.
+//!
+//! A document buffer is decoded into a JSON value with `VTable::decode_json`
+//! and a JSON value is encoded into a document buffer with
+//! `VTable::encode_json`, without the Rust type of the document.
+//!
+//! Values map to JSON as follows:
+//!
+//! - Structs map to objects, with their fields in vtable order.
+//! - Enums use the externally tagged form of serde, e.g. `"Idle"` for unit
+//!   variants and `{ "Active": 9 }` or `{ "Faulted": { "code": 500 } }` otherwise.
+//! - `Option` maps to `null` when `None` and to the inner value otherwise.
+//!   `Option` fields missing from an object are `None`.
+//! - Bytes map to standard base64 strings and UUIDs to hyphenated strings.
+//! - `HashMap` maps to an object, with its keys as strings.
+//! - `Vec`, arrays and tuples map to arrays, and `()` maps to `null`.
+//! - `u128` and `i128` values outside the range of 64 bit integers, and float
+//!   values that are not finite, map to strings.
+//!
+//! Struct fields and map entries keep the order of the document buffer, so a
+//! buffer converted to JSON and back reproduces the same buffer. The field
+//! rules of the vtable are checked on the way in, as `DocBuf::to_docbuf` does.
+//!
+//! The JSON values of a document are described by the JSON Schema returned
+//! by `VTable::to_json_schema`.
+
+mod schema;
+
+pub use schema::JSON_SCHEMA_DIALECT;
+pub use serde_json;
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::{Map, Number, Value as Json};
+
+use crate::value::Value;
+use crate::vtable::{
+    NumericValue, VTable, VTableFieldFormat, VTableFieldType, VTableFields, VTableItem,
+    VTableVariantKind,
+};
+
+/// The path of the root value, in error messages.
+const ROOT_PATH: &str = "$";
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Invalid JSON value at {path}, expected: {expected}; found: {found}")]
+    InvalidValue {
+        path: String,
+        expected: String,
+        found: String,
+    },
+    #[error("Missing JSON value at {0}")]
+    MissingField(String),
+    #[error("Unknown JSON field at {0}")]
+    UnknownField(String),
+    #[error("Unknown enum variant at {path}: {variant}")]
+    UnknownVariant { path: String, variant: String },
+    #[error(transparent)]
+    Base64(#[from] base64::DecodeError),
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+}
+
+/// Return the name of the kind of JSON value, used in error messages.
+fn json_kind(json: &Json) -> String {
+    match json {
+        Json::Null => "null".to_string(),
+        Json::Bool(value) => format!("bool {}", value),
+        Json::Number(value) => format!("number {}", value),
+        Json::String(value) => format!("string {:?}", value),
+        Json::Array(values) => format!("array of {} elements", values.len()),
+        Json::Object(_) => "object".to_string(),
+    }
+}
+
+/// Return the error for a JSON value that is not of the field type.
+fn invalid_json(path: &str, r#type: &VTableFieldType, json: &Json) -> Error {
+    Error::InvalidValue {
+        path: path.to_owned(),
+        expected: r#type.to_string(),
+        found: json_kind(json),
+    }
+}
+
+/// Return the error for a decoded value that is not of the field type.
+fn invalid_value(r#type: &VTableFieldType, value: &Value) -> crate::error::Error {
+    crate::vtable::Error::InvalidValueType(r#type.to_string(), value.kind()).into()
+}
+
+/// Return the JSON value of an integer or float.
+fn numeric_to_json(value: &NumericValue) -> Json {
+    let number = match *value {
+        NumericValue::U8(value) => Some(Number::from(value)),
+        NumericValue::U16(value) => Some(Number::from(value)),
+        NumericValue::U32(value) => Some(Number::from(value)),
+        NumericValue::U64(value) => Some(Number::from(value)),
+        NumericValue::USIZE(value) => Some(Number::from(value)),
+        NumericValue::U128(value) => u64::try_from(value).ok().map(Number::from),
+        NumericValue::I8(value) => Some(Number::from(value)),
+        NumericValue::I16(value) => Some(Number::from(value)),
+        NumericValue::I32(value) => Some(Number::from(value)),
+        NumericValue::I64(value) => Some(Number::from(value)),
+        NumericValue::ISIZE(value) => Some(Number::from(value)),
+        NumericValue::I128(value) => i64::try_from(value).ok().map(Number::from),
+        // The shortest representation of the `f32` value, rather than its
+        // widened `f64` value, e.g. `21.5` rather than `21.499999046325684`.
+        NumericValue::F32(value) => value
+            .to_string()
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64),
+        NumericValue::F64(value) => Number::from_f64(value),
+    };
+
+    match number {
+        Some(number) => Json::Number(number),
+        None => Json::String(value.to_string()),
+    }
+}
+
+/// Parse an integer or float of the type from a string, e.g. a map key.
+fn numeric_from_str(r#type: &VTableFieldType, data: &str) -> Option<NumericValue> {
+    let value = match r#type {
+        VTableFieldType::U8 => NumericValue::U8(data.parse().ok()?),
+        VTableFieldType::U16 => NumericValue::U16(data.parse().ok()?),
+        VTableFieldType::U32 => NumericValue::U32(data.parse().ok()?),
+        VTableFieldType::U64 => NumericValue::U64(data.parse().ok()?),
+        VTableFieldType::U128 => NumericValue::U128(data.parse().ok()?),
+        VTableFieldType::USIZE => NumericValue::USIZE(data.parse().ok()?),
+        VTableFieldType::I8 => NumericValue::I8(data.parse().ok()?),
+        VTableFieldType::I16 => NumericValue::I16(data.parse().ok()?),
+        VTableFieldType::I32 => NumericValue::I32(data.parse().ok()?),
+        VTableFieldType::I64 => NumericValue::I64(data.parse().ok()?),
+        VTableFieldType::I128 => NumericValue::I128(data.parse().ok()?),
+        VTableFieldType::ISIZE => NumericValue::ISIZE(data.parse().ok()?),
+        VTableFieldType::F32 => NumericValue::F32(data.parse().ok()?),
+        VTableFieldType::F64 => NumericValue::F64(data.parse().ok()?),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+/// Return the integer or float of the type from a JSON number or string.
+fn numeric_from_json(r#type: &VTableFieldType, json: &Json) -> Option<NumericValue> {
+    let number = match json {
+        Json::Number(number) => number,
+        Json::String(data) => return numeric_from_str(r#type, data),
+        _ => return None,
+    };
+
+    let value = match r#type {
+        VTableFieldType::U8 => NumericValue::U8(number.as_u64()?.try_into().ok()?),
+        VTableFieldType::U16 => NumericValue::U16(number.as_u64()?.try_into().ok()?),
+        VTableFieldType::U32 => NumericValue::U32(number.as_u64()?.try_into().ok()?),
+        VTableFieldType::U64 => NumericValue::U64(number.as_u64()?),
+        VTableFieldType::U128 => NumericValue::U128(number.as_u64()?.into()),
+        VTableFieldType::USIZE => NumericValue::USIZE(number.as_u64()?.try_into().ok()?),
+        VTableFieldType::I8 => NumericValue::I8(number.as_i64()?.try_into().ok()?),
+        VTableFieldType::I16 => NumericValue::I16(number.as_i64()?.try_into().ok()?),
+        VTableFieldType::I32 => NumericValue::I32(number.as_i64()?.try_into().ok()?),
+        VTableFieldType::I64 => NumericValue::I64(number.as_i64()?),
+        VTableFieldType::I128 => NumericValue::I128(number.as_i64()?.into()),
+        VTableFieldType::ISIZE => NumericValue::ISIZE(number.as_i64()?.try_into().ok()?),
+        VTableFieldType::F32 => NumericValue::F32(number.as_f64()? as f32),
+        VTableFieldType::F64 => NumericValue::F64(number.as_f64()?),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+/// Parse a hyphenated or simple UUID string.
+fn uuid_from_str(data: &str) -> Option<[u8; 16]> {
+    if data.len() != 32 && !VTableFieldFormat::Uuid.is_valid(data) {
+        return None;
+    }
+
+    hex::decode(data.replace('-', "")).ok()?.try_into().ok()
+}
+
+/// Return the string of a map key, e.g. `"3"` for the `u8` key `3`.
+fn key_to_string(r#type: &VTableFieldType, key: &Value) -> crate::Result<String> {
+    match key {
+        Value::String(key) => Ok(key.to_owned()),
+        Value::Char(key) => Ok(key.to_string()),
+        Value::Bool(_) | Value::Numeric(_) | Value::Uuid(_) => Ok(key.to_string()),
+        key => Err(invalid_value(r#type, key)),
+    }
+}
+
+/// Parse a map key of the type from the string of a JSON object key.
+fn key_from_str(path: &str, r#type: &VTableFieldType, key: &str) -> Result<Value, Error> {
+    let value = match r#type {
+        VTableFieldType::String | VTableFieldType::Str => Some(Value::from(key)),
+        VTableFieldType::Char => {
+            let mut chars = key.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(key), None) => Some(Value::Char(key)),
+                _ => None,
+            }
+        }
+        VTableFieldType::Bool => key.parse().ok().map(Value::Bool),
+        VTableFieldType::Uuid => uuid_from_str(key).map(Value::Uuid),
+        r#type => numeric_from_str(r#type, key).map(Value::Numeric),
+    };
+
+    value.ok_or_else(|| invalid_json(path, r#type, &Json::String(key.to_owned())))
+}
+
+impl VTable {
+    /// Decode a document buffer into a JSON value, with the vtable alone,
+    /// e.g. a vtable read with `VTable::from_file`.
+    pub fn decode_json(&self, buffer: &[u8]) -> crate::Result<Json> {
+        let value = self.decode_value(buffer)?;
+
+        self.item_to_json(self.root_item()?, &value)
+    }
+
+    /// Encode a JSON value of the root item of the vtable into a document
+    /// buffer, checking the field rules of the vtable.
+    pub fn encode_json(&self, json: &Json) -> crate::Result<Vec<u8>> {
+        let value = self.json_to_item(ROOT_PATH, self.root_item()?, json)?;
+
+        self.encode_value(&value)
+    }
+
+    /// Return the JSON value of a struct, or an enum variant and its payload fields.
+    fn item_to_json(&self, item: &VTableItem, value: &Value) -> crate::Result<Json> {
+        match (item, value) {
+            (VTableItem::Struct(vtable_struct), Value::Struct { fields, .. }) => {
+                self.fields_to_json(&vtable_struct.fields, fields)
+            }
+            (
+                VTableItem::Enum(vtable_enum),
+                Value::Enum {
+                    variant, fields, ..
+                },
+            ) => {
+                let variant = vtable_enum.variant_by_name(variant)?;
+
+                let payload = match variant.kind {
+                    VTableVariantKind::Unit => return Ok(Json::String(variant.name.to_owned())),
+                    VTableVariantKind::Newtype => {
+                        match (variant.fields.iter().next(), fields.first()) {
+                            (Some(field), Some((_, value))) => {
+                                self.value_to_json(&field.r#type, value)?
+                            }
+                            _ => Json::Null,
+                        }
+                    }
+                    VTableVariantKind::Tuple => Json::Array(
+                        variant
+                            .fields
+                            .iter()
+                            .zip(fields)
+                            .map(|(field, (_, value))| self.value_to_json(&field.r#type, value))
+                            .collect::<crate::Result<_>>()?,
+                    ),
+                    VTableVariantKind::Struct => self.fields_to_json(&variant.fields, fields)?,
+                };
+
+                let mut object = Map::new();
+                object.insert(variant.name.to_owned(), payload);
+
+                Ok(Json::Object(object))
+            }
+            (item, value) => Err(invalid_value(
+                &VTableFieldType::Struct(item.name().to_owned()),
+                value,
+            )),
+        }
+    }
+
+    /// Return the JSON object of the fields of a struct or enum variant.
+    fn fields_to_json(
+        &self,
+        vtable_fields: &VTableFields,
+        fields: &[(String, Value)],
+    ) -> crate::Result<Json> {
+        let mut object = Map::new();
+
+        for (field, (name, value)) in vtable_fields.iter().zip(fields) {
+            object.insert(name.to_owned(), self.value_to_json(&field.r#type, value)?);
+        }
+
+        Ok(Json::Object(object))
+    }
+
+    /// Return the JSON value of a value of the field type.
+    fn value_to_json(&self, r#type: &VTableFieldType, value: &Value) -> crate::Result<Json> {
+        let json = match (r#type, value) {
+            (VTableFieldType::Struct(name) | VTableFieldType::Enum(name), value) => {
+                self.item_to_json(self.item_by_name(name)?, value)?
+            }
+            (VTableFieldType::Option(_), Value::Option(None)) => Json::Null,
+            (VTableFieldType::Option(inner), Value::Option(Some(value))) => {
+                self.value_to_json(inner, value)?
+            }
+            (VTableFieldType::Vec(elem), Value::List(values))
+            | (VTableFieldType::Array { elem, .. }, Value::Array(values)) => Json::Array(
+                values
+                    .iter()
+                    .map(|value| self.value_to_json(elem, value))
+                    .collect::<crate::Result<_>>()?,
+            ),
+            (VTableFieldType::Tuple(types), Value::Tuple(values)) => Json::Array(
+                types
+                    .iter()
+                    .zip(values)
+                    .map(|(r#type, value)| self.value_to_json(r#type, value))
+                    .collect::<crate::Result<_>>()?,
+            ),
+            (VTableFieldType::HashMap { key, value: elem }, Value::Map(entries)) => {
+                let mut object = Map::new();
+
+                for (entry_key, entry_value) in entries {
+                    object.insert(
+                        key_to_string(key, entry_key)?,
+                        self.value_to_json(elem, entry_value)?,
+                    );
+                }
+
+                Json::Object(object)
+            }
+            (VTableFieldType::Bytes, Value::Bytes(value)) => Json::String(STANDARD.encode(value)),
+            (VTableFieldType::Uuid, Value::Uuid(_)) => Json::String(value.to_string()),
+            (VTableFieldType::String | VTableFieldType::Str, Value::String(value)) => {
+                Json::String(value.to_owned())
+            }
+            (VTableFieldType::Char, Value::Char(value)) => Json::String(value.to_string()),
+            (VTableFieldType::Bool, Value::Bool(value)) => Json::Bool(*value),
+            (VTableFieldType::Unit, Value::Unit) => Json::Null,
+            (r#type, Value::Numeric(value)) if value.field_type() == *r#type => {
+                numeric_to_json(value)
+            }
+            (r#type, value) => return Err(invalid_value(r#type, value)),
+        };
+
+        Ok(json)
+    }
+
+    /// Return the value of a struct, or an enum variant and its payload
+    /// fields, from its JSON value.
+    fn json_to_item(&self, path: &str, item: &VTableItem, json: &Json) -> crate::Result<Value> {
+        match item {
+            VTableItem::Struct(vtable_struct) => Ok(Value::Struct {
+                name: vtable_struct.name.to_owned(),
+                fields: self.json_to_fields(path, &vtable_struct.fields, json)?,
+            }),
+            VTableItem::Enum(vtable_enum) => {
+                let (name, payload) = match json {
+                    Json::String(name) => (name, None),
+                    Json::Object(object) if object.len() == 1 => {
+                        let (name, payload) = object.iter().next().expect("single variant");
+
+                        (name, Some(payload))
+                    }
+                    json => {
+                        return Err(Error::InvalidValue {
+                            path: path.to_owned(),
+                            expected: format!("enum {}", vtable_enum.name),
+                            found: json_kind(json),
+                        }
+                        .into())
+                    }
+                };
+
+                let variant =
+                    vtable_enum
+                        .variant_by_name(name)
+                        .map_err(|_| Error::UnknownVariant {
+                            path: path.to_owned(),
+                            variant: name.to_owned(),
+                        })?;
+
+                let path = format!("{}.{}", path, variant.name);
+
+                let fields = match (variant.kind, payload) {
+                    (VTableVariantKind::Unit, None) => Vec::new(),
+                    (VTableVariantKind::Newtype, Some(payload)) => {
+                        match variant.fields.iter().next() {
+                            Some(field) => vec![(
+                                field.name.to_owned(),
+                                self.json_to_value(&path, &field.r#type, payload)?,
+                            )],
+                            None => Vec::new(),
+                        }
+                    }
+                    (VTableVariantKind::Tuple, Some(Json::Array(values)))
+                        if values.len() == variant.fields.len() =>
+                    {
+                        variant
+                            .fields
+                            .iter()
+                            .zip(values)
+                            .enumerate()
+                            .map(|(index, (field, value))| {
+                                let path = format!("{}[{}]", path, index);
+
+                                Ok((
+                                    field.name.to_owned(),
+                                    self.json_to_value(&path, &field.r#type, value)?,
+                                ))
+                            })
+                            .collect::<crate::Result<_>>()?
+                    }
+                    (VTableVariantKind::Struct, Some(payload)) => {
+                        self.json_to_fields(&path, &variant.fields, payload)?
+                    }
+                    (_, payload) => {
+                        return Err(Error::InvalidValue {
+                            path,
+                            expected: format!("{:?} variant {}", variant.kind, variant.name),
+                            found: payload
+                                .map(json_kind)
+                                .unwrap_or_else(|| "unit variant".to_string()),
+                        }
+                        .into())
+                    }
+                };
+
+                Ok(Value::Enum {
+                    name: vtable_enum.name.to_owned(),
+                    variant: variant.name.to_owned(),
+                    fields,
+                })
+            }
+        }
+    }
+
+    /// Return the values of the fields of a struct or enum variant, in vtable
+    /// order, from a JSON object.
+    fn json_to_fields(
+        &self,
+        path: &str,
+        fields: &VTableFields,
+        json: &Json,
+    ) -> crate::Result<Vec<(String, Value)>> {
+        let object = match json {
+            Json::Object(object) => object,
+            json => {
+                return Err(Error::InvalidValue {
+                    path: path.to_owned(),
+                    expected: "object".to_string(),
+                    found: json_kind(json),
+                }
+                .into())
+            }
+        };
+
+        if let Some(name) = object
+            .keys()
+            .find(|name| fields.iter().all(|field| &field.name != *name))
+        {
+            return Err(Error::UnknownField(format!("{}.{}", path, name)).into());
+        }
+
+        fields
+            .iter()
+            .map(|field| {
+                let path = format!("{}.{}", path, field.name);
+
+                let value = match (object.get(&field.name), &field.r#type) {
+                    (Some(json), r#type) => self.json_to_value(&path, r#type, json)?,
+                    (None, VTableFieldType::Option(_)) => Value::Option(None),
+                    (None, VTableFieldType::Unit) => Value::Unit,
+                    (None, _) => return Err(Error::MissingField(path).into()),
+                };
+
+                Ok((field.name.to_owned(), value))
+            })
+            .collect()
+    }
+
+    /// Return the value of the field type from its JSON value.
+    fn json_to_value(
+        &self,
+        path: &str,
+        r#type: &VTableFieldType,
+        json: &Json,
+    ) -> crate::Result<Value> {
+        let value = match (r#type, json) {
+            (VTableFieldType::Struct(name) | VTableFieldType::Enum(name), json) => {
+                self.json_to_item(path, self.item_by_name(name)?, json)?
+            }
+            (VTableFieldType::Option(_), Json::Null) => Value::Option(None),
+            (VTableFieldType::Option(inner), json) => {
+                Value::Option(Some(Box::new(self.json_to_value(path, inner, json)?)))
+            }
+            (VTableFieldType::Vec(elem), Json::Array(values)) => Value::List(self.json_to_values(
+                path,
+                values.iter().map(|_| elem.as_ref()),
+                values,
+            )?),
+            (VTableFieldType::Array { elem, len }, Json::Array(values)) if values.len() == *len => {
+                Value::Array(self.json_to_values(
+                    path,
+                    values.iter().map(|_| elem.as_ref()),
+                    values,
+                )?)
+            }
+            (VTableFieldType::Tuple(types), Json::Array(values)) if values.len() == types.len() => {
+                Value::Tuple(self.json_to_values(path, types.iter(), values)?)
+            }
+            (VTableFieldType::HashMap { key, value }, Json::Object(object)) => Value::Map(
+                object
+                    .iter()
+                    .map(|(entry_key, entry_value)| {
+                        let path = format!("{}[{:?}]", path, entry_key);
+
+                        Ok((
+                            key_from_str(&path, key, entry_key)?,
+                            self.json_to_value(&path, value, entry_value)?,
+                        ))
+                    })
+                    .collect::<crate::Result<_>>()?,
+            ),
+            (VTableFieldType::Bytes, Json::String(data)) => {
+                Value::Bytes(STANDARD.decode(data).map_err(Error::from)?)
+            }
+            (VTableFieldType::Uuid, Json::String(data)) => match uuid_from_str(data) {
+                Some(value) => Value::Uuid(value),
+                None => return Err(invalid_json(path, r#type, json).into()),
+            },
+            (VTableFieldType::String | VTableFieldType::Str, Json::String(data)) => {
+                Value::from(data.as_str())
+            }
+            (VTableFieldType::Char, Json::String(data)) => key_from_str(path, r#type, data)?,
+            (VTableFieldType::Bool, Json::Bool(value)) => Value::Bool(*value),
+            (VTableFieldType::Unit, Json::Null) => Value::Unit,
+            (r#type, json) => match numeric_from_json(r#type, json) {
+                Some(value) => Value::Numeric(value),
+                None => return Err(invalid_json(path, r#type, json).into()),
+            },
+        };
+
+        Ok(value)
+    }
+
+    /// Return the values of the types from the elements of a JSON array.
+    fn json_to_values<'a>(
+        &self,
+        path: &str,
+        types: impl Iterator<Item = &'a VTableFieldType>,
+        values: &[Json],
+    ) -> crate::Result<Vec<Value>> {
+        types
+            .zip(values)
+            .enumerate()
+            .map(|(index, (r#type, value))| {
+                self.json_to_value(&format!("{}[{}]", path, index), r#type, value)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,314 @@
+use serde_json::{json, Map, Value as Json};
+
+use super::numeric_to_json;
+use crate::vtable::{
+    VTable, VTableFieldFormat, VTableFieldRules, VTableFieldType, VTableFields, VTableItem,
+    VTableVariantKind,
+};
+
+/// The JSON Schema dialect of the schemas returned by `VTable::to_json_schema`.
+pub const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Return the JSON Schema format of a field format.
+fn schema_format(format: VTableFieldFormat) -> &'static str {
+    match format {
+        VTableFieldFormat::Email => "email",
+        VTableFieldFormat::Uri => "uri",
+        VTableFieldFormat::Uuid => "uuid",
+        VTableFieldFormat::Rfc3339 => "date-time",
+        VTableFieldFormat::Ipv4 => "ipv4",
+        VTableFieldFormat::Ipv6 => "ipv6",
+    }
+}
+
+/// Return the schema of an integer type, bounded by the range of the type.
+/// `u128` and `i128` values outside the range of 64 bit integers are strings.
+fn integer_schema(r#type: &VTableFieldType) -> Json {
+    match r#type {
+        VTableFieldType::U8 => json!({ "type": "integer", "minimum": 0, "maximum": u8::MAX }),
+        VTableFieldType::U16 => json!({ "type": "integer", "minimum": 0, "maximum": u16::MAX }),
+        VTableFieldType::U32 => json!({ "type": "integer", "minimum": 0, "maximum": u32::MAX }),
+        VTableFieldType::U64 => json!({ "type": "integer", "minimum": 0, "maximum": u64::MAX }),
+        VTableFieldType::USIZE => {
+            json!({ "type": "integer", "minimum": 0, "maximum": usize::MAX })
+        }
+        VTableFieldType::I8 => {
+            json!({ "type": "integer", "minimum": i8::MIN, "maximum": i8::MAX })
+        }
+        VTableFieldType::I16 => {
+            json!({ "type": "integer", "minimum": i16::MIN, "maximum": i16::MAX })
+        }
+        VTableFieldType::I32 => {
+            json!({ "type": "integer", "minimum": i32::MIN, "maximum": i32::MAX })
+        }
+        VTableFieldType::I64 => {
+            json!({ "type": "integer", "minimum": i64::MIN, "maximum": i64::MAX })
+        }
+        VTableFieldType::ISIZE => {
+            json!({ "type": "integer", "minimum": isize::MIN, "maximum": isize::MAX })
+        }
+        VTableFieldType::U128 => {
+            json!({ "type": ["integer", "string"], "minimum": 0, "pattern": "^[0-9]+$" })
+        }
+        VTableFieldType::I128 => json!({ "type": ["integer", "string"], "pattern": "^-?[0-9]+$" }),
+        // Float values that are not finite are strings.
+        _ => json!({ "type": ["number", "string"], "pattern": "^(NaN|inf|-inf)$" }),
+    }
+}
+
+/// Insert a keyword into an object schema.
+fn insert(schema: &mut Json, keyword: &str, value: Json) {
+    if let Json::Object(schema) = schema {
+        schema.insert(keyword.to_owned(), value);
+    }
+}
+
+/// Return the schema of a string, with the string rules of the field.
+fn string_schema(rules: &VTableFieldRules) -> Json {
+    let mut schema = json!({ "type": "string" });
+
+    let (min_length, max_length) = match rules.length {
+        Some(length) => (Some(length), Some(length)),
+        None => (rules.min_length, rules.max_length),
+    };
+
+    // The `not_empty` rule is a min length of one.
+    let min_length = match (min_length, rules.not_empty) {
+        (min_length, false) => min_length,
+        (min_length, true) => Some(min_length.unwrap_or_default().max(1)),
+    };
+
+    if let Some(min_length) = min_length {
+        insert(&mut schema, "minLength", min_length.into());
+    }
+
+    if let Some(max_length) = max_length {
+        insert(&mut schema, "maxLength", max_length.into());
+    }
+
+    if let Some(regex) = &rules.regex {
+        insert(&mut schema, "pattern", regex.as_str().into());
+    }
+
+    if let Some(format) = rules.format {
+        insert(&mut schema, "format", schema_format(format).into());
+    }
+
+    if let Some(one_of) = &rules.one_of {
+        insert(&mut schema, "enum", one_of.clone().into());
+    }
+
+    schema
+}
+
+/// Return the schema of an integer or float, with the value rules of the field.
+fn numeric_schema(r#type: &VTableFieldType, rules: &VTableFieldRules) -> Json {
+    let mut schema = integer_schema(r#type);
+
+    if let Some(min_value) = &rules.min_value {
+        insert(&mut schema, "minimum", numeric_to_json(min_value));
+    }
+
+    if let Some(max_value) = &rules.max_value {
+        insert(&mut schema, "maximum", numeric_to_json(max_value));
+    }
+
+    if let Some(one_of) = &rules.one_of_values {
+        insert(
+            &mut schema,
+            "enum",
+            one_of.iter().map(numeric_to_json).collect(),
+        );
+    }
+
+    schema
+}
+
+/// Return the reference to the schema of an item in `$defs`.
+fn item_ref(name: &str) -> Json {
+    json!({ "$ref": format!("#/$defs/{}", name) })
+}
+
+impl VTable {
+    /// Return the JSON Schema, draft 2020-12, of the JSON values of the
+    /// document, as converted by `VTable::decode_json` and `VTable::encode_json`.
+    ///
+    /// Every struct and enum item of the vtable is defined in `$defs`, and the
+    /// root item is referenced by the top level `$ref`. The length, value,
+    /// regex, `one_of`, `format` and `not_empty` field rules map to the
+    /// matching keywords. The length rules of strings count bytes in the
+    /// document, and characters in JSON Schema, which only agree for ASCII
+    /// strings. The digits and whitespace rules, and the length rules of
+    /// bytes, have no matching keyword and are only checked when the JSON
+    /// value is encoded.
+    pub fn to_json_schema(&self) -> Json {
+        let defs = self
+            .items
+            .iter()
+            .map(|item| (item.name().to_owned(), self.item_schema(item)))
+            .collect::<Map<_, _>>();
+
+        json!({
+            "$schema": JSON_SCHEMA_DIALECT,
+            "title": self.root,
+            "$ref": format!("#/$defs/{}", self.root),
+            "$defs": defs,
+        })
+    }
+
+    /// Return the schema of a struct, or of the variants of an enum.
+    fn item_schema(&self, item: &VTableItem) -> Json {
+        match item {
+            VTableItem::Struct(vtable_struct) => self.fields_schema(&vtable_struct.fields),
+            VTableItem::Enum(vtable_enum) => {
+                let variants = vtable_enum
+                    .variants
+                    .iter()
+                    .map(|variant| {
+                        let payload = match variant.kind {
+                            VTableVariantKind::Unit => return json!({ "const": variant.name }),
+                            VTableVariantKind::Newtype => match variant.fields.iter().next() {
+                                Some(field) => self.field_schema(&field.r#type, &field.rules),
+                                None => json!({ "type": "null" }),
+                            },
+                            VTableVariantKind::Tuple => {
+                                let items = variant
+                                    .fields
+                                    .iter()
+                                    .map(|field| self.field_schema(&field.r#type, &field.rules))
+                                    .collect::<Vec<_>>();
+
+                                json!({
+                                    "type": "array",
+                                    "prefixItems": items,
+                                    "items": false,
+                                    "minItems": variant.fields.len(),
+                                })
+                            }
+                            VTableVariantKind::Struct => self.fields_schema(&variant.fields),
+                        };
+
+                        json!({
+                            "type": "object",
+                            "properties": { variant.name.to_owned(): payload },
+                            "required": [variant.name],
+                            "additionalProperties": false,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                json!({ "oneOf": variants })
+            }
+        }
+    }
+
+    /// Return the object schema of the fields of a struct or enum variant.
+    /// `Option` and `()` fields may be missing.
+    fn fields_schema(&self, fields: &VTableFields) -> Json {
+        let properties = fields
+            .iter()
+            .map(|field| {
+                (
+                    field.name.to_owned(),
+                    self.field_schema(&field.r#type, &field.rules),
+                )
+            })
+            .collect::<Map<_, _>>();
+
+        let required = fields
+            .iter()
+            .filter(|field| {
+                !matches!(
+                    field.r#type,
+                    VTableFieldType::Option(_) | VTableFieldType::Unit
+                )
+            })
+            .map(|field| Json::from(field.name.as_str()))
+            .collect::<Vec<_>>();
+
+        json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        })
+    }
+
+    /// Return the schema of the string of a map key, e.g. `"3"` for the `u8` key `3`.
+    fn key_schema(&self, r#type: &VTableFieldType, rules: &VTableFieldRules) -> Json {
+        match r#type {
+            VTableFieldType::String | VTableFieldType::Str => string_schema(rules),
+            VTableFieldType::Uuid | VTableFieldType::Char => self.field_schema(r#type, rules),
+            VTableFieldType::Bool => json!({ "enum": ["true", "false"] }),
+            _ => json!({ "type": "string" }),
+        }
+    }
+
+    /// Return the schema of a value of the type, which is the field type or a
+    /// type nested in it, e.g. the element type of a list. The rules of the
+    /// field apply to nested strings, numbers and collections, as they do
+    /// when the value is encoded.
+    fn field_schema(&self, r#type: &VTableFieldType, rules: &VTableFieldRules) -> Json {
+        match r#type {
+            VTableFieldType::Struct(name) | VTableFieldType::Enum(name) => item_ref(name),
+            VTableFieldType::Option(inner) => json!({
+                "anyOf": [self.field_schema(inner, rules), { "type": "null" }],
+            }),
+            VTableFieldType::Vec(elem) => {
+                let mut schema = json!({
+                    "type": "array",
+                    "items": self.field_schema(elem, rules),
+                });
+
+                if rules.not_empty {
+                    insert(&mut schema, "minItems", 1.into());
+                }
+
+                schema
+            }
+            VTableFieldType::Array { elem, len } => json!({
+                "type": "array",
+                "items": self.field_schema(elem, rules),
+                "minItems": len,
+                "maxItems": len,
+            }),
+            VTableFieldType::Tuple(types) => json!({
+                "type": "array",
+                "prefixItems": types
+                    .iter()
+                    .map(|r#type| self.field_schema(r#type, rules))
+                    .collect::<Vec<_>>(),
+                "items": false,
+                "minItems": types.len(),
+            }),
+            VTableFieldType::HashMap { key, value } => {
+                let mut schema = json!({
+                    "type": "object",
+                    "propertyNames": self.key_schema(key, rules),
+                    "additionalProperties": self.field_schema(value, rules),
+                });
+
+                if rules.not_empty {
+                    insert(&mut schema, "minProperties", 1.into());
+                }
+
+                schema
+            }
+            VTableFieldType::String | VTableFieldType::Str => string_schema(rules),
+            VTableFieldType::Bytes => json!({ "type": "string", "contentEncoding": "base64" }),
+            VTableFieldType::Uuid => json!({ "type": "string", "format": "uuid" }),
+            VTableFieldType::Char => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+            VTableFieldType::Bool => match &rules.one_of {
+                Some(one_of) => json!({
+                    "enum": one_of
+                        .iter()
+                        .filter_map(|value| value.parse::<bool>().ok())
+                        .collect::<Vec<_>>(),
+                }),
+                None => json!({ "type": "boolean" }),
+            },
+            VTableFieldType::Unit => json!({ "type": "null" }),
+            r#type => numeric_schema(r#type, rules),
+        }
+    }
+}
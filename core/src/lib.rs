@@ -1,21 +1,25 @@
+pub mod envelope;
 pub mod error;
+#[cfg(feature = "std")]
+pub mod frame;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "crypto")]
+pub mod merkle;
+#[cfg(feature = "std")]
+pub mod registry;
 pub mod serde;
+#[cfg(feature = "crypto")]
+pub mod signed;
 pub mod traits;
+pub mod value;
 pub mod vtable;
 
 #[cfg(feature = "crypto")]
-pub mod crypto {
-    // Re-export the necessary crypto libraries for signing
-    pub use digest;
-    pub use ed25519;
-    pub use sha2;
-}
+pub mod crypto;
 
 #[cfg(feature = "validate")]
-pub mod validate {
-    // Re-export the necessary validation libraries for validating fields
-    pub use regex;
-}
+pub mod validate;
 
 #[cfg(feature = "db")]
 pub mod db {
@@ -25,6 +29,7 @@ pub mod db {
 // Re-export the necessary deps for the docbuf core crate
 pub mod deps {
     pub use hex;
+    pub use serde;
 
     #[cfg(feature = "uuid")]
     pub use uuid;
@@ -0,0 +1,364 @@
+//! Merkle field commitments, for the selective disclosure of signed documents.
+//!
+//! The fields of a document buffer are committed to as the leaves of a Merkle
+//! tree, in the order of the document's `VTableFieldOffsets`. Each leaf hashes
+//! the encoded value of a field with a random salt, so that hidden fields
+//! cannot be guessed from the hashes of the tree:
+//!
+//! ```text
+//! leaf = hash(0x00 || salt || item index || field index || encoded value)
+//! node = hash(0x01 || left || right)
+//! ```
+//!
+//! A node without a sibling is carried to the next level of the tree. The
+//! signature covers the hash of the `MERKLE_MESSAGE_TAG`, the vtable id, the
+//! number of leaves and the root of the tree.
+//!
+//! The owner of the document discloses a subset of the fields as a
+//! `DocBufRedacted` document, with an inclusion proof per field. Receivers
+//! verify the proofs against the signed root using only the vtable of the
+//! document:
+//!
+//! ```ignore
+//! let tree = contract.commit_fields(&signing_key)?;
+//! let redacted = tree.disclose(&[amount.offset_index()])?;
+//!
+//! redacted.verify(Contract::vtable()?)?;
+//! let amount = redacted.value(Contract::vtable()?, amount.offset_index())?;
+//! ```
+
+use rand_core::{OsRng, RngCore};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::crypto::{self, DocBufSigner, HashAlgorithm, SignatureAlgorithm};
+use crate::value::Value;
+use crate::vtable::{DocBufReader, VTable, VTableFieldOffsetIndex, VTableFieldOffsets, VTableId};
+
+/// Size of the random salt of each field, in bytes.
+pub const MERKLE_SALT_SIZE: usize = 32;
+
+/// Domain separation tag of the message signed for the root of a merkle tree,
+/// so that it is never the message signed for a document, see
+/// `signed::SIGNED_MESSAGE_TAG`.
+pub const MERKLE_MESSAGE_TAG: &[u8] = b"docbuf-merkle-v1";
+
+const LEAF_PREFIX: [u8; 1] = [0x00];
+const NODE_PREFIX: [u8; 1] = [0x01];
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Field is not committed to by the merkle tree: {0:?}")]
+    FieldNotCommitted(VTableFieldOffsetIndex),
+    #[error("Field is not disclosed: {0:?}")]
+    FieldNotDisclosed(VTableFieldOffsetIndex),
+    #[error("Invalid inclusion proof of field: {0:?}")]
+    InvalidProof(VTableFieldOffsetIndex),
+    #[error("VTable id mismatch. Expected: {expected}; found: {found}")]
+    VTableIdMismatch { expected: VTableId, found: VTableId },
+    #[error("Merkle tree has too many leaves: {0}")]
+    TooManyLeaves(usize),
+    #[error(transparent)]
+    Crypto(#[from] crypto::Error),
+}
+
+/// The committed value of a field, a leaf of the merkle tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocBufMerkleLeaf {
+    /// The offset index of the field.
+    pub index: VTableFieldOffsetIndex,
+    pub salt: [u8; MERKLE_SALT_SIZE],
+    /// The encoded value of the field, including its length prefix.
+    pub data: Vec<u8>,
+}
+
+impl DocBufMerkleLeaf {
+    /// Return the hash of the leaf.
+    pub fn hash(&self, hash_algorithm: HashAlgorithm) -> Vec<u8> {
+        hash_algorithm.hash(&[
+            &LEAF_PREFIX,
+            &self.salt,
+            &[self.index.0, self.index.1],
+            &self.data,
+        ])
+    }
+}
+
+/// Return the hash of the parent of two nodes.
+fn hash_node(hash_algorithm: HashAlgorithm, left: &[u8], right: &[u8]) -> Vec<u8> {
+    hash_algorithm.hash(&[&NODE_PREFIX, left, right])
+}
+
+/// Return the root of the levels of a merkle tree. The root of a document
+/// without fields is the hash of no leaves.
+fn tree_root(hash_algorithm: HashAlgorithm, levels: &[Vec<Vec<u8>>]) -> Vec<u8> {
+    match levels.last().and_then(|level| level.first()) {
+        Some(root) => root.to_owned(),
+        None => hash_algorithm.hash(&[]),
+    }
+}
+
+/// Return the message signed for the root of the merkle tree.
+fn signed_message(
+    hash_algorithm: HashAlgorithm,
+    vtable_id: &VTableId,
+    num_leaves: u32,
+    root: &[u8],
+) -> Vec<u8> {
+    hash_algorithm.hash(&[
+        MERKLE_MESSAGE_TAG,
+        vtable_id.as_ref(),
+        &num_leaves.to_le_bytes(),
+        root,
+    ])
+}
+
+/// A signed merkle tree over the fields of a document buffer, held by the
+/// owner of the document to disclose its fields.
+#[derive(Debug, Clone)]
+pub struct DocBufMerkleTree {
+    pub signature_algorithm: SignatureAlgorithm,
+    pub hash_algorithm: HashAlgorithm,
+    pub vtable_id: VTableId,
+    /// The encoded public key of the signer.
+    pub public_key: Vec<u8>,
+    /// The signature of the root of the tree.
+    pub signature: Vec<u8>,
+    leaves: Vec<DocBufMerkleLeaf>,
+    /// The hashes of each level of the tree, from the leaves to the root.
+    levels: Vec<Vec<Vec<u8>>>,
+}
+
+impl DocBufMerkleTree {
+    /// Commit to the fields of the document buffer, with a random salt per
+    /// field, and sign the root of the tree.
+    pub fn new(
+        vtable: &VTable,
+        hash_algorithm: HashAlgorithm,
+        signer: &impl DocBufSigner,
+        buffer: &[u8],
+        offsets: &VTableFieldOffsets,
+    ) -> crate::Result<Self> {
+        let leaves = offsets
+            .as_ref()
+            .iter()
+            .map(|offset| {
+                let field = vtable.get_field_by_offset_index(offset.0)?;
+                let range = vtable.encoded_range(field, &field.r#type, buffer, offset.range())?;
+
+                let mut salt = [0u8; MERKLE_SALT_SIZE];
+                OsRng.fill_bytes(&mut salt);
+
+                Ok(DocBufMerkleLeaf {
+                    index: offset.0,
+                    salt,
+                    data: buffer[range].to_vec(),
+                })
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let num_leaves =
+            u32::try_from(leaves.len()).map_err(|_| Error::TooManyLeaves(leaves.len()))?;
+
+        let mut levels = vec![leaves
+            .iter()
+            .map(|leaf| leaf.hash(hash_algorithm))
+            .collect::<Vec<_>>()];
+
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let next = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_node(hash_algorithm, left, right),
+                    _ => pair[0].to_owned(),
+                })
+                .collect();
+
+            levels.push(next);
+        }
+
+        let root = tree_root(hash_algorithm, &levels);
+
+        let message = signed_message(hash_algorithm, vtable.id(), num_leaves, &root);
+        let signature = signer.sign(&message).map_err(Error::from)?;
+
+        Ok(Self {
+            signature_algorithm: signer.algorithm(),
+            hash_algorithm,
+            vtable_id: vtable.id().to_owned(),
+            public_key: signer.public_key(),
+            signature,
+            leaves,
+            levels,
+        })
+    }
+
+    /// Return the root of the tree.
+    pub fn root(&self) -> Vec<u8> {
+        tree_root(self.hash_algorithm, &self.levels)
+    }
+
+    /// Return the committed fields, in the order of the document's offsets.
+    pub fn leaves(&self) -> &[DocBufMerkleLeaf] {
+        &self.leaves
+    }
+
+    /// Return the inclusion proof of the leaf at the position: the hashes of
+    /// the siblings of the leaf and of its ancestors.
+    fn proof(&self, mut position: usize) -> Vec<Vec<u8>> {
+        let mut proof = Vec::new();
+
+        for level in self.levels[..self.levels.len() - 1].iter() {
+            if let Some(sibling) = level.get(position ^ 1) {
+                proof.push(sibling.to_owned());
+            }
+
+            position /= 2;
+        }
+
+        proof
+    }
+
+    /// Return the redacted document disclosing the fields at the offset
+    /// indices, with an inclusion proof per field. Other fields are hidden.
+    pub fn disclose(&self, indices: &[VTableFieldOffsetIndex]) -> Result<DocBufRedacted, Error> {
+        let fields = indices
+            .iter()
+            .map(|index| {
+                let position = self
+                    .leaves
+                    .iter()
+                    .position(|leaf| &leaf.index == index)
+                    .ok_or(Error::FieldNotCommitted(*index))?;
+
+                Ok(DocBufDisclosedField {
+                    position: position as u32,
+                    leaf: self.leaves[position].clone(),
+                    proof: self.proof(position),
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(DocBufRedacted {
+            signature_algorithm: self.signature_algorithm,
+            hash_algorithm: self.hash_algorithm,
+            vtable_id: self.vtable_id.to_owned(),
+            public_key: self.public_key.to_owned(),
+            signature: self.signature.to_owned(),
+            num_leaves: self.leaves.len() as u32,
+            root: self.root(),
+            fields,
+        })
+    }
+}
+
+/// A disclosed field of a redacted document, with its inclusion proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocBufDisclosedField {
+    /// The position of the leaf in the merkle tree.
+    pub position: u32,
+    pub leaf: DocBufMerkleLeaf,
+    /// The hashes of the siblings of the leaf and of its ancestors.
+    pub proof: Vec<Vec<u8>>,
+}
+
+/// A redacted document, disclosing a subset of the fields of a signed
+/// document with their inclusion proofs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocBufRedacted {
+    pub signature_algorithm: SignatureAlgorithm,
+    pub hash_algorithm: HashAlgorithm,
+    pub vtable_id: VTableId,
+    /// The encoded public key of the signer.
+    pub public_key: Vec<u8>,
+    /// The signature of the root of the merkle tree.
+    pub signature: Vec<u8>,
+    pub num_leaves: u32,
+    pub root: Vec<u8>,
+    pub fields: Vec<DocBufDisclosedField>,
+}
+
+impl DocBufRedacted {
+    /// Verify the signature of the merkle root, with the public key of the
+    /// signature, and the inclusion proof of each disclosed field against it.
+    ///
+    /// The public key is only checked to match the signature. Whether the
+    /// signer is trusted is left to the caller.
+    pub fn verify(&self, vtable: &VTable) -> crate::Result<()> {
+        if &self.vtable_id != vtable.id() {
+            return Err(Error::VTableIdMismatch {
+                expected: vtable.id().to_owned(),
+                found: self.vtable_id.to_owned(),
+            }
+            .into());
+        }
+
+        let message = signed_message(
+            self.hash_algorithm,
+            &self.vtable_id,
+            self.num_leaves,
+            &self.root,
+        );
+
+        self.signature_algorithm
+            .verify(&self.public_key, &message, &self.signature)
+            .map_err(Error::from)?;
+
+        for field in self.fields.iter() {
+            // The field must be a field of the vtable.
+            vtable.get_field_by_offset_index(field.leaf.index)?;
+
+            if !self.verify_proof(field) {
+                return Err(Error::InvalidProof(field.leaf.index).into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if the inclusion proof of the field leads to the root.
+    fn verify_proof(&self, field: &DocBufDisclosedField) -> bool {
+        if field.position >= self.num_leaves {
+            return false;
+        }
+
+        let mut hash = field.leaf.hash(self.hash_algorithm);
+        let mut proof = field.proof.iter();
+
+        let mut position = field.position;
+        let mut len = self.num_leaves;
+
+        while len > 1 {
+            // The last node of a level without a sibling is carried up.
+            if position ^ 1 < len {
+                let Some(sibling) = proof.next() else {
+                    return false;
+                };
+
+                hash = match position % 2 {
+                    0 => hash_node(self.hash_algorithm, &hash, sibling),
+                    _ => hash_node(self.hash_algorithm, sibling, &hash),
+                };
+            }
+
+            position /= 2;
+            len = len.div_ceil(2);
+        }
+
+        proof.next().is_none() && hash == self.root
+    }
+
+    /// Return the disclosed value of the field at the offset index. The
+    /// redacted document should be verified first.
+    pub fn value(&self, vtable: &VTable, index: VTableFieldOffsetIndex) -> crate::Result<Value> {
+        let leaf = self
+            .fields
+            .iter()
+            .map(|field| &field.leaf)
+            .find(|leaf| leaf.index == index)
+            .ok_or(Error::FieldNotDisclosed(index))?;
+
+        let field = vtable.get_field_by_offset_index(index)?;
+
+        vtable.decode_field_value(field, &field.r#type, &mut DocBufReader::new(&leaf.data))
+    }
+}
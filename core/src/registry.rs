@@ -0,0 +1,261 @@
+//! Registry of document types, for dispatch by vtable id at runtime.
+//!
+//! Services that receive documents of many types only learn the type of a
+//! document from its vtable id, e.g. from the envelope of the document
+//! buffer. Document types are registered once, typically at start up:
+//!
+//! ```ignore
+//! docbuf_core::registry::register::<Order>()?;
+//! docbuf_core::registry::register::<Invoice>()?;
+//!
+//! let (registration, order) = DocBufRegistry::global().decode_enveloped(&buffer)?;
+//! ```
+//!
+//! Registrations are looked up by vtable id or by `namespace::Root` name, and
+//! decode document buffers to the Rust type of the document, as a
+//! `Box<dyn Any + Send>`, or to an untyped `Value`.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::{
+    envelope::DocBufEnvelope,
+    error,
+    traits::DocBuf,
+    value::Value,
+    vtable::{VTable, VTableId},
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("VTable id {id} of {found} collides with the registered {registered}")]
+    IdCollision {
+        id: VTableId,
+        registered: String,
+        found: String,
+    },
+    #[error("Name {name} of vtable {found} is registered with vtable {registered}")]
+    NameCollision {
+        name: String,
+        registered: VTableId,
+        found: VTableId,
+    },
+    #[error("VTable id not registered: {0}")]
+    IdNotRegistered(VTableId),
+    #[error("Name not registered: {0}")]
+    NameNotRegistered(String),
+    #[error("Registry lock poisoned")]
+    Poisoned,
+}
+
+/// Decode a document buffer to the Rust type of the document.
+type DecodeFn = fn(&[u8]) -> Result<Box<dyn Any + Send>, error::Error>;
+
+/// A registered document type.
+#[derive(Debug, Clone)]
+pub struct DocBufRegistration {
+    /// The vtable id of the document.
+    pub id: VTableId,
+    /// The `namespace::Root` name of the document.
+    pub name: String,
+    /// The Rust type name of the document, e.g. `my_crate::Order`.
+    pub type_name: &'static str,
+    /// The vtable of the document.
+    pub vtable: &'static VTable,
+    type_id: TypeId,
+    decode: DecodeFn,
+}
+
+impl DocBufRegistration {
+    /// Return the registration of the document type.
+    pub fn new<T>() -> Result<Self, error::Error>
+    where
+        T: DocBuf + 'static,
+        T::Doc: ::serde::de::DeserializeOwned + Send + 'static,
+    {
+        let vtable = T::vtable()?;
+
+        Ok(Self {
+            id: vtable.id().to_owned(),
+            name: registry_name(&vtable.namespace, &vtable.root),
+            type_name: std::any::type_name::<T>(),
+            vtable,
+            type_id: TypeId::of::<T>(),
+            decode: |buffer| Ok(Box::new(T::from_docbuf(buffer)?)),
+        })
+    }
+
+    /// Decode the document buffer to the Rust type of the document, checking
+    /// the field rules and `validate_with` hooks of the document.
+    pub fn decode(&self, buffer: &[u8]) -> Result<Box<dyn Any + Send>, error::Error> {
+        (self.decode)(buffer)
+    }
+
+    /// Decode the document buffer to an untyped value, with the vtable of the
+    /// document.
+    pub fn decode_value(&self, buffer: &[u8]) -> Result<Value, error::Error> {
+        self.vtable.decode_value(buffer)
+    }
+
+    /// Validate the document buffer, as decoding it would.
+    pub fn validate(&self, buffer: &[u8]) -> Result<(), error::Error> {
+        self.decode(buffer).map(|_| ())
+    }
+
+    /// Returns true if the registration is of the document type.
+    pub fn is<T: 'static>(&self) -> bool {
+        self.type_id == TypeId::of::<T>()
+    }
+}
+
+/// Return the registry name of a vtable, e.g. `shop::Order`.
+pub fn registry_name(namespace: &str, root: &str) -> String {
+    format!("{}::{}", namespace, root)
+}
+
+#[derive(Debug, Default)]
+struct Registrations {
+    by_id: HashMap<VTableId, Arc<DocBufRegistration>>,
+    by_name: HashMap<String, VTableId>,
+}
+
+/// A registry of document types, keyed by vtable id and by name.
+#[derive(Debug, Default)]
+pub struct DocBufRegistry {
+    registrations: RwLock<Registrations>,
+}
+
+impl DocBufRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the process wide registry.
+    pub fn global() -> &'static DocBufRegistry {
+        static REGISTRY: OnceLock<DocBufRegistry> = OnceLock::new();
+
+        REGISTRY.get_or_init(DocBufRegistry::new)
+    }
+
+    /// Register the document type, returning its vtable id.
+    ///
+    /// Registering a type again is a no-op. Returns `Error::IdCollision` if
+    /// another type is registered with the same vtable id, e.g. an identical
+    /// copy of the document, and `Error::NameCollision` if another vtable is
+    /// registered with the same `namespace::Root` name, e.g. another version
+    /// of the document.
+    pub fn register<T>(&self) -> Result<VTableId, error::Error>
+    where
+        T: DocBuf + 'static,
+        T::Doc: ::serde::de::DeserializeOwned + Send + 'static,
+    {
+        let registration = DocBufRegistration::new::<T>()?;
+
+        let mut registrations = self.registrations.write().map_err(|_| Error::Poisoned)?;
+
+        if let Some(registered) = registrations.by_id.get(&registration.id) {
+            if registered.type_id == registration.type_id {
+                return Ok(registration.id);
+            }
+
+            return Err(Error::IdCollision {
+                id: registration.id,
+                registered: registered.type_name.to_owned(),
+                found: registration.type_name.to_owned(),
+            }
+            .into());
+        }
+
+        if let Some(registered) = registrations.by_name.get(&registration.name) {
+            return Err(Error::NameCollision {
+                name: registration.name,
+                registered: registered.to_owned(),
+                found: registration.id,
+            }
+            .into());
+        }
+
+        let id = registration.id.to_owned();
+
+        registrations
+            .by_name
+            .insert(registration.name.to_owned(), id.to_owned());
+        registrations
+            .by_id
+            .insert(id.to_owned(), Arc::new(registration));
+
+        Ok(id)
+    }
+
+    /// Return the registration of the vtable id.
+    pub fn get(&self, id: &VTableId) -> Result<Arc<DocBufRegistration>, error::Error> {
+        let registrations = self.registrations.read().map_err(|_| Error::Poisoned)?;
+
+        match registrations.by_id.get(id) {
+            Some(registration) => Ok(registration.to_owned()),
+            None => Err(Error::IdNotRegistered(id.to_owned()).into()),
+        }
+    }
+
+    /// Return the registration of the `namespace::Root` name.
+    pub fn get_by_name(&self, name: &str) -> Result<Arc<DocBufRegistration>, error::Error> {
+        let registrations = self.registrations.read().map_err(|_| Error::Poisoned)?;
+
+        match registrations
+            .by_name
+            .get(name)
+            .and_then(|id| registrations.by_id.get(id))
+        {
+            Some(registration) => Ok(registration.to_owned()),
+            None => Err(Error::NameNotRegistered(name.to_owned()).into()),
+        }
+    }
+
+    /// Return every registration, ordered by name.
+    pub fn registrations(&self) -> Result<Vec<Arc<DocBufRegistration>>, error::Error> {
+        let registrations = self.registrations.read().map_err(|_| Error::Poisoned)?;
+
+        let mut registrations = registrations.by_id.values().cloned().collect::<Vec<_>>();
+        registrations.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(registrations)
+    }
+
+    /// Decode the document buffer of the vtable id to the Rust type of the document.
+    pub fn decode(
+        &self,
+        id: &VTableId,
+        buffer: &[u8],
+    ) -> Result<Box<dyn Any + Send>, error::Error> {
+        self.get(id)?.decode(buffer)
+    }
+
+    /// Decode the document buffer of the vtable id to an untyped value.
+    pub fn decode_value(&self, id: &VTableId, buffer: &[u8]) -> Result<Value, error::Error> {
+        self.get(id)?.decode_value(buffer)
+    }
+
+    /// Decode an enveloped document buffer to the Rust type of the document,
+    /// dispatched by the vtable id of the envelope.
+    pub fn decode_enveloped(
+        &self,
+        buffer: &[u8],
+    ) -> Result<(Arc<DocBufRegistration>, Box<dyn Any + Send>), error::Error> {
+        let (envelope, body) = DocBufEnvelope::read_from_buffer(buffer)?;
+
+        let registration = self.get(&envelope.vtable_id)?;
+        let doc = registration.decode(body)?;
+
+        Ok((registration, doc))
+    }
+}
+
+/// Register the document type with the global registry, returning its vtable id.
+pub fn register<T>() -> Result<VTableId, error::Error>
+where
+    T: DocBuf + 'static,
+    T::Doc: ::serde::de::DeserializeOwned + Send + 'static,
+{
+    DocBufRegistry::global().register::<T>()
+}
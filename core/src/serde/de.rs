@@ -1,6 +1,7 @@
-use std::collections::HashSet;
-
-use serde::de::{DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde::de::value::{MapAccessDeserializer, StrDeserializer, U32Deserializer};
+use serde::de::{
+    DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
 use serde::Deserialize;
 
 use crate::vtable::*;
@@ -10,309 +11,188 @@ use crate::{
     Result,
 };
 
+/// Element type of byte arrays that are encoded element-wise.
+static BYTE_ELEMENT_TYPE: VTableFieldType = VTableFieldType::U8;
+
+/// Deserializes a document buffer from a borrowed input slice.
+///
+/// The input is read with a `DocBufReader` and left intact, so string and byte
+/// fields can be borrowed from the input without allocation, e.g. `&'de str`,
+/// `&'de [u8]` and `Cow<'de, str>` with `#[serde(borrow)]`.
+///
+/// In compatibility mode the input is decoded with the writer's vtable, and
+/// values are visited by their encoded type rather than the type requested by
+/// the reader, so fields are matched by name and compatible values widened.
 #[derive(Debug)]
-pub struct DocBufDeserializer<'a> {
-    vtable: &'static VTable,
-    buffer: &'a mut Vec<u8>,
-    current_item_index: VTableItemIndex,
-    current_field_index: VTableFieldIndex,
-    current_item: Option<&'static VTableItem>,
-    current_field: Option<&'static VTableField>,
-    // optional_field: Option<VTableFieldType>,
-    has_visited: HashSet<(u8, u8)>,
-    has_descended: bool,
-    remaining_items: Option<u32>,
+pub struct DocBufDeserializer<'de, 'v> {
+    vtable: &'v VTable,
+    buffer: DocBufReader<'de>,
+    /// The field being deserialized.
+    current_field: Option<&'v VTableField>,
+    /// The type of the value being deserialized, which is the field type
+    /// or an inner type of the field, e.g. the element type of a list.
+    current_type: Option<&'v VTableFieldType>,
+    /// Whether the input was encoded with a different version of the vtable.
+    compat: bool,
 }
 
-impl<'de> DocBufDeserializer<'de> {
-    pub fn new(vtable: &'static VTable, buffer: &'de mut Vec<u8>) -> Result<Self> {
+impl<'de, 'v> DocBufDeserializer<'de, 'v> {
+    pub fn new(vtable: &'v VTable, input: &'de [u8]) -> Result<Self> {
         Ok(DocBufDeserializer {
             vtable,
-            buffer,
-            current_item_index: 0,
-            current_field_index: 0,
-            current_item: None,
+            buffer: DocBufReader::new(input),
             current_field: None,
-            // optional_field: None,
-            has_visited: HashSet::with_capacity(vtable.num_fields as usize),
-            has_descended: false,
-            remaining_items: None,
+            current_type: None,
+            compat: false,
         })
     }
 
-    pub fn set_visited_current_field(&mut self) {
-        self.has_visited
-            .insert((self.current_item_index, self.current_field_index));
+    /// Create a deserializer for an input buffer encoded with the writer's
+    /// vtable, to be decoded into a different version of the document.
+    pub fn compat(writer: &'v VTable, input: &'de [u8]) -> Result<Self> {
+        Ok(DocBufDeserializer {
+            compat: true,
+            ..Self::new(writer, input)?
+        })
     }
 
-    pub fn is_field_visited(&self, item_index: u8, field_index: u8) -> bool {
-        self.has_visited.contains(&(item_index, field_index))
+    /// Return the cursor position in the input.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.buffer.position()
     }
 
+    /// Return the unread bytes of the input.
     #[inline]
-    pub fn next_field(&mut self) -> Result<()> {
-        // println!(
-        //     "Next Field; Current Item Index: {}, Current Field Index: {}, Field: {:?}",
-        //     self.current_item_index, self.current_field_index, self.current_field
-        // );
-
-        self.set_visited_current_field();
-
-        let item = self.current_item()?;
-
-        // println!("\n\nCurrent Item: {item:?}\n\n");
-
-        let num_items = self.vtable.num_items;
-
-        match item {
-            VTableItem::Struct(s) => {
-                // Decrement the remaining items if this field is the last field in the struct.
-                if self.current_field_index == s.num_fields - 1 {
-                    if let Some(remaining) = self.remaining_items.as_mut() {
-                        if *remaining > 0 {
-                            // println!("Decrementing remaining items.");
-                            *remaining -= 1;
-
-                            if *remaining == 0 {
-                                // println!("Setting remaining items to None.");
-                                self.remaining_items = None;
-                            }
-                        }
-                    }
-                }
-
-                let field = s.field_by_index(&self.current_field_index)?;
-
-                // println!("Current Field: {:?}", field);
-                // println!("Buffer: {:?}", self.buffer);
-
-                // if let VTableFieldType::HashMap { .. } = field.r#type {
-                //     if let Some(remaining) = self.remaining_items {
-                //         if remaining > 0 {
-                //             println!("Decrementing remaining items.");
-                //             self.remaining_items = Some(remaining - 1);
-                //             return Ok(());
-                //         }
-
-                //         if remaining == 0 {
-                //             println!("Setting remaining items to None.");
-                //             self.remaining_items = None;
-                //         }
-                //     }
-                // }
-
-                if let VTableFieldType::Struct(_) = field.r#type {
-                    if !self.has_descended {
-                        // println!("Decrementing item index.");
-                        self.current_item_index -= 1;
-                        self.current_field_index = 0;
-                        self.current_item = None;
-                        self.current_field = None;
-
-                        return Ok(());
-                    }
-                }
+    pub fn remaining(&self) -> &'de [u8] {
+        self.buffer.remaining()
+    }
 
-                if let VTableFieldType::Option(opt) = &field.r#type {
-                    if let VTableFieldType::Struct(struct_name) = &**opt {
-                        if let Some(item_index) =
-                            self.vtable.get_struct_item_index_by_name(struct_name)
-                        {
-                            // println!("\n\nFound Optional Struct field: {:?}\n\n", field);
-                            // println!("Decrementing item index.");
-
-                            self.current_item_index = item_index;
-                            self.current_field_index = 0;
-                            self.current_item = None;
-                            self.current_field = None;
-                            return Ok(());
-                        }
-                    }
-                }
+    #[inline]
+    pub fn current_field(&self) -> Result<&'v VTableField> {
+        self.current_field
+            .ok_or(Error::VTable(crate::vtable::Error::FieldNotFound))
+    }
 
-                if let VTableFieldType::Vec(inner) = &field.r#type {
-                    // println!("Found Vec Field");
-
-                    if self.remaining_items.is_none() {
-                        return Ok(());
-                    }
-
-                    if let VTableFieldType::Struct(struct_name) = &**inner {
-                        if let Some(item_index) =
-                            self.vtable.get_struct_item_index_by_name(struct_name)
-                        {
-                            // println!("Set Vec inner Struct");
-
-                            self.current_item_index = item_index;
-                            self.current_field_index = 0;
-                            self.current_item = None;
-                            self.current_field = None;
-                            return Ok(());
-                        }
-                    }
-                }
+    #[inline]
+    pub fn current_type(&self) -> Result<&'v VTableFieldType> {
+        self.current_type
+            .ok_or(Error::VTable(crate::vtable::Error::FieldNotFound))
+    }
 
-                // Increment the field if there are more fields.
-                if self.current_field_index < s.num_fields - 1 {
-                    // println!("Incrementing field index.");
-                    self.current_field_index += 1;
-                    self.current_field = None;
-                // Decrement the item index if the current item has no more fields,
-                // and the current item index has not yet reached the zeroth index.
-                } else if self.current_item_index > 0 && !self.has_descended {
-                    // println!("Decrementing item index.");
-                    self.current_item_index -= 1;
-                    self.current_field_index = 0;
-                    self.current_field = None;
-                    self.current_item = None;
-                // Increment the item index if the current item has reached the zeroth index,
-                } else if self.current_item_index == 0
-                    && self.current_field_index == s.num_fields - 1
-                    && !self.has_descended
-                    && self.current_item_index < num_items - 1
-                {
-                    // println!("Incrementing item index: {}", self.current_item_index);
-                    self.has_descended = true;
-                    self.current_item_index += 1;
-                    self.current_field_index = 0;
-
-                    // skip a field if it has already been visited.
-                    while self.is_field_visited(self.current_item_index, self.current_field_index) {
-                        let num_fields =
-                            self.vtable.num_fields_by_index(self.current_item_index)?;
-                        // println!(
-                        //     "Has visited: Item {}, Field {}",
-                        //     self.current_item_index, self.current_field_index
-                        // );
-                        if self.current_field_index < num_fields - 1 {
-                            self.current_field_index += 1;
-                        } else {
-                            self.current_item_index += 1;
-                            self.current_field_index = 0;
-                            break;
-                        }
-                    }
-
-                    self.current_item = None;
-                    self.current_field = None;
-                    // return self.next_field();
-                } else {
-                    // println!("Current Item Index: {:?}", self.current_item_index);
-                    // println!("Current Field Index: {:?}", self.current_field_index);
-                    // println!("Current Item: {:?}", self.current_item);
-
-                    // println!("Buffer: {:?}", self.buffer);
-                }
-            }
-        }
+    /// Set the field to deserialize next.
+    #[inline]
+    pub fn set_field(&mut self, field: &'v VTableField) {
+        self.current_field = Some(field);
+        self.current_type = Some(&field.r#type);
+    }
 
-        Ok(())
+    /// Decode the current field value from the input.
+    #[inline]
+    fn decode<T>(&mut self) -> Result<T>
+    where
+        VTableField: DocBufDecodeField<T>,
+    {
+        Ok(self.current_field()?.decode(&mut self.buffer)?)
     }
 
-    // Return the current field or find it in the vtable based on the
-    // current_item_index and current_field_index
+    /// Return a decode error for the current field.
     #[inline]
-    pub fn current_field(&mut self) -> Result<&'static VTableField> {
-        Ok(match self.current_field {
-            Some(field) => field,
-            _ => self.set_current_field()?,
-        })
+    fn decode_error(&self) -> Result<Error> {
+        Ok(Error::VTable(crate::vtable::Error::DocBufDecodeFieldType(
+            Box::new(self.current_field()?.to_owned()),
+        )))
     }
 
+    /// Consume a length prefixed string, borrowing it from the input.
     #[inline]
-    pub fn set_remaining_items(&mut self, field_type: &VTableFieldType) -> Result<()> {
-        // println!(
-        //     "set_remaining_items {:?}; Buffer: {:?}; Field Type: {:?}",
-        //     self.remaining_items, self.buffer, field_type
-        // );
-
-        if self.buffer.is_empty() {
-            self.remaining_items = Some(0);
-            return Ok(());
-        }
+    fn read_str(&mut self) -> Result<&'de str> {
+        match self.current_type()? {
+            t if t.holds(&VTableFieldType::String) || t.holds(&VTableFieldType::Str) => {
+                let len = self.current_field()?.decode_bytes_len(&mut self.buffer)?;
 
-        match (&field_type, self.remaining_items) {
-            (VTableFieldType::Uuid, None) => {
-                self.remaining_items = Some(15);
-            }
-            (VTableFieldType::Bytes, None) => {
-                let data_len = u32::from_le_bytes([
-                    self.buffer[0],
-                    self.buffer[1],
-                    self.buffer[2],
-                    self.buffer[3],
-                ]) - 1;
-
-                self.buffer.drain(0..4);
-
-                // println!("Setting Remaining Item Length: {:?}", data_len);
-                self.remaining_items = Some(data_len);
+                Ok(self.buffer.read_str(len)?)
             }
-            (VTableFieldType::Vec(_) | VTableFieldType::HashMap { .. }, None) => {
-                let data_len = u32::from_le_bytes([
-                    self.buffer[0],
-                    self.buffer[1],
-                    self.buffer[2],
-                    self.buffer[3],
-                ]);
-
-                self.buffer.drain(0..4);
-
-                // println!("Setting Remaining Item Length: {:?}", data_len);
-                self.remaining_items = Some(data_len);
+            _ => Err(self.decode_error()?),
+        }
+    }
+
+    /// Consume a byte array, borrowing it from the input. Uuids are encoded as
+    /// raw 16 bytes, fixed size arrays as their elements, and other byte arrays
+    /// are prefixed with their length.
+    #[inline]
+    fn read_byte_array(&mut self) -> Result<&'de [u8]> {
+        match self.current_type()? {
+            t if t.holds(&VTableFieldType::Uuid) => Ok(self.buffer.read_bytes(16)?),
+            VTableFieldType::Array { elem, len } if **elem == VTableFieldType::U8 => {
+                Ok(self.buffer.read_bytes(*len)?)
             }
-            (_, Some(n)) => {
-                // println!("Decrementing remaining items.");
-                if n == 1 {
-                    self.remaining_items = None;
-                } else {
-                    self.remaining_items = Some(n - 1);
-                }
+            t if t.holds(&VTableFieldType::Bytes) => {
+                let len = self.current_field()?.decode_bytes_len(&mut self.buffer)?;
+
+                Ok(self.buffer.read_bytes(len)?)
             }
-            _ => {}
+            _ => Err(self.decode_error()?),
         }
-
-        Ok(())
     }
 
+    /// Read the variant discriminant of an enum and return the encoded variant.
     #[inline]
-    pub fn set_current_field(&mut self) -> Result<&'static VTableField> {
-        let field = self
+    fn read_variant(&mut self, name: &str) -> Result<&'v VTableVariant> {
+        let vtable_enum = self
             .vtable
-            .struct_by_index(self.current_item_index)?
-            .field_by_index(&self.current_field_index)?;
+            .enum_by_name(name)
+            .map_err(|_| Error::Serde(format!("Enum {} not found in the vtable", name)))?;
 
-        self.current_field = Some(field);
-        Ok(field)
-    }
+        let index = self.buffer.read_u8()?;
 
-    // Return the current item or find it in the vtable based on the current item index
-    #[inline]
-    pub fn current_item(&mut self) -> Result<&'static VTableItem> {
-        Ok(match self.current_item {
-            Some(item) => item,
-            _ => self.set_current_item()?,
-        })
+        Ok(vtable_enum.variant_by_index(index)?)
     }
+}
 
-    // Set the current item based on the current item index
-    #[inline]
-    pub fn set_current_item(&mut self) -> Result<&'static VTableItem> {
-        // println!("Setting current item to {:?}", self.current_item_index);
-        // let field = self.current_field()?;
-        let item = self.vtable.item_by_index(self.current_item_index)?;
-        // self.current_item_index = field.item_index;
-        self.current_item = Some(item);
-        Ok(item)
+/// Deserialize a document from the input buffer. The input is left intact,
+/// and the document may borrow string and byte fields from it.
+pub fn from_docbuf<'de, T>(input: &'de [u8]) -> Result<T>
+where
+    T: Deserialize<'de> + DocBuf,
+{
+    let vtable = T::vtable()?;
+    let mut deserializer = DocBufDeserializer::new(vtable, input)?;
+    let t = T::deserialize(&mut deserializer)?;
+
+    match deserializer.buffer.is_empty() {
+        true => Ok(t),
+        false => Err(Error::Serde("Unhandled trailing bytes".to_string())),
     }
 }
 
-pub fn from_docbuf<'de, T>(buffer: &'de mut Vec<u8>) -> Result<T>
+/// Deserialize a document from an input buffer encoded with the writer's
+/// vtable, e.g. a buffer stored before fields were added, removed or
+/// reordered in the document.
+///
+/// Fields are matched by name and compatible type. Fields unknown to the
+/// reader are skipped, and fields missing from the writer decode as `None`
+/// or their serde default. Changes that cannot be reconciled, such as a type
+/// change on the same field name, return `vtable::Error::IncompatibleVTable`.
+pub fn from_docbuf_compat<'de, T>(writer: &VTable, input: &'de [u8]) -> Result<T>
 where
     T: Deserialize<'de> + DocBuf,
 {
-    let vtable = T::vtable()?;
-    let mut deserializer = DocBufDeserializer::new(vtable, buffer)?;
+    let reader = T::vtable()?;
+
+    if writer == reader {
+        return from_docbuf(input);
+    }
+
+    let compatibility = writer.compatibility(reader);
+
+    if !compatibility.is_compatible() {
+        return Err(Error::VTable(crate::vtable::Error::IncompatibleVTable(
+            compatibility,
+        )));
+    }
+
+    let mut deserializer = DocBufDeserializer::compat(writer, input)?;
     let t = T::deserialize(&mut deserializer)?;
 
     match deserializer.buffer.is_empty() {
@@ -321,145 +201,295 @@ where
     }
 }
 
-impl<'de> MapAccess<'de> for &mut DocBufDeserializer<'de> {
+/// Access the fields of a struct or enum variant, in vtable order.
+struct FieldsAccess<'a, 'de: 'a, 'v> {
+    de: &'a mut DocBufDeserializer<'de, 'v>,
+    fields: std::slice::Iter<'v, VTableField>,
+}
+
+impl<'de, 'a, 'v> MapAccess<'de> for FieldsAccess<'a, 'de, 'v> {
     type Error = Error;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
     where
         K: DeserializeSeed<'de>,
     {
-        if self.buffer.is_empty() {
-            return Ok(None);
+        match self.fields.next() {
+            Some(field) => {
+                self.de.set_field(field);
+
+                seed.deserialize(StrDeserializer::<Error>::new(field.name.as_str()))
+                    .map(Some)
+            }
+            None => Ok(None),
         }
+    }
 
-        let field = self.current_field()?;
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut *self.de)
+    }
 
-        // println!("Current Field: {:?}", field);
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len())
+    }
+}
 
-        // Set the hash map field items to the length of the data.
-        if let VTableFieldType::HashMap { .. } = &field.r#type {
-            match self.remaining_items {
-                None => {
-                    self.remaining_items = Some(u32::from_le_bytes([
-                        self.buffer[0],
-                        self.buffer[1],
-                        self.buffer[2],
-                        self.buffer[3],
-                    ]));
+impl<'de, 'a, 'v> SeqAccess<'de> for FieldsAccess<'a, 'de, 'v> {
+    type Error = Error;
 
-                    // drain the length from the buffer
-                    self.buffer.drain(0..4);
-                }
-                Some(0) => {
-                    self.remaining_items = None;
-                    self.next_field()?;
-                    return Ok(None);
-                }
-                _ => {}
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.fields.next() {
+            Some(field) => {
+                self.de.set_field(field);
+
+                seed.deserialize(&mut *self.de).map(Some)
             }
+            None => Ok(None),
         }
+    }
 
-        // println!("Next Key Seed: Remaining Items: {:?}", self.remaining_items);
-
-        seed.deserialize(&mut **self).map(Some)
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len())
     }
+}
 
-    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+/// Access the elements of a list or byte array.
+struct ElementsAccess<'a, 'de: 'a, 'v> {
+    de: &'a mut DocBufDeserializer<'de, 'v>,
+    field: Option<&'v VTableField>,
+    element_type: &'v VTableFieldType,
+    remaining: usize,
+}
+
+impl<'de, 'a, 'v> SeqAccess<'de> for ElementsAccess<'a, 'de, 'v> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
     where
-        V: DeserializeSeed<'de>,
+        T: DeserializeSeed<'de>,
     {
-        let value = seed.deserialize(&mut **self);
+        if self.remaining == 0 {
+            return Ok(None);
+        }
 
-        // Decrement the remaining field items if it is set
-        if let VTableFieldType::HashMap { .. } | VTableFieldType::Vec(_) =
-            &self.current_field()?.r#type
-        {
-            // Decrement the remaining field items if it is set
-            self.remaining_items.as_mut().map(|x| *x -= 1);
-        };
+        self.remaining -= 1;
 
-        value
+        self.de.current_field = self.field;
+        self.de.current_type = Some(self.element_type);
+
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
     }
 }
 
-impl<'de> SeqAccess<'de> for DocBufDeserializer<'de> {
+/// Access the elements of a tuple, each with its own type.
+struct TupleAccess<'a, 'de: 'a, 'v> {
+    de: &'a mut DocBufDeserializer<'de, 'v>,
+    field: Option<&'v VTableField>,
+    types: std::slice::Iter<'v, VTableFieldType>,
+}
+
+impl<'de, 'a, 'v> SeqAccess<'de> for TupleAccess<'a, 'de, 'v> {
     type Error = Error;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
     where
         T: DeserializeSeed<'de>,
     {
-        // let item = self.current_item()?;
-        let field = self.current_field()?;
+        match self.types.next() {
+            Some(element_type) => {
+                self.de.current_field = self.field;
+                self.de.current_type = Some(element_type);
 
-        // println!("Next Element Seed: {field:?}");
-        // println!("Remaining Items: {:?}", self.remaining_items);
+                seed.deserialize(&mut *self.de).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
 
-        if self.buffer.is_empty() {
-            // println!("Buffer is empty");
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.types.len())
+    }
+}
+
+/// Access the entries of a map.
+struct EntriesAccess<'a, 'de: 'a, 'v> {
+    de: &'a mut DocBufDeserializer<'de, 'v>,
+    field: Option<&'v VTableField>,
+    key_type: &'v VTableFieldType,
+    value_type: &'v VTableFieldType,
+    remaining: usize,
+}
+
+impl<'de, 'a, 'v> MapAccess<'de> for EntriesAccess<'a, 'de, 'v> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
             return Ok(None);
         }
 
-        match &field.r#type {
-            VTableFieldType::Struct(_) => {
-                self.next_field()?;
-            }
-            VTableFieldType::HashMap { .. } => {
-                // println!("Deserialize HashMap");
-            }
-            VTableFieldType::Vec(_) => {
-                // println!("Deserialize Vec");
-                self.set_remaining_items(&field.r#type)?;
+        self.remaining -= 1;
+
+        self.de.current_field = self.field;
+        self.de.current_type = Some(self.key_type);
+
+        seed.deserialize(&mut *self.de).map(Some)
+    }
 
-                // if let Some(remaining) = self.remaining_items {
-                //     println!("Remaining Items: {:?}", remaining);
-                // }
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        self.de.current_field = self.field;
+        self.de.current_type = Some(self.value_type);
+
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// Access the encoded variant of an enum.
+struct VariantAccessor<'a, 'de: 'a, 'v> {
+    de: &'a mut DocBufDeserializer<'de, 'v>,
+    variant: &'v VTableVariant,
+}
+
+impl<'de, 'a, 'v> EnumAccess<'de> for VariantAccessor<'a, 'de, 'v> {
+    type Error = Error;
+    type Variant = Self;
 
-                self.next_field()?;
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        // Variant indexes may differ between vtable versions, match by name instead.
+        let value = match self.de.compat {
+            true => seed.deserialize(StrDeserializer::<Error>::new(self.variant.name.as_str()))?,
+            false => seed.deserialize(U32Deserializer::<Error>::new(self.variant.index as u32))?,
+        };
+
+        Ok((value, self))
+    }
+}
+
+impl<'de, 'a, 'v> VariantAccess<'de> for VariantAccessor<'a, 'de, 'v> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        match self.variant.kind {
+            VTableVariantKind::Unit => Ok(()),
+            _ => Err(Error::Serde(format!(
+                "Expected unit variant, found {:?} variant {}",
+                self.variant.kind, self.variant.name
+            ))),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.variant.kind {
+            VTableVariantKind::Newtype => {
+                self.de.set_field(self.variant.field_by_index(&0)?);
+
+                seed.deserialize(&mut *self.de)
+            }
+            // Self-describing visitors, e.g. `IgnoredAny`, visit all variants as newtypes.
+            VTableVariantKind::Unit => seed.deserialize(().into_deserializer()),
+            VTableVariantKind::Tuple | VTableVariantKind::Struct => {
+                seed.deserialize(MapAccessDeserializer::new(FieldsAccess {
+                    de: self.de,
+                    fields: self.variant.fields.iter(),
+                }))
             }
-            _ => {}
         }
+    }
 
-        seed.deserialize(&mut *self).map(Some)
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(FieldsAccess {
+            de: self.de,
+            fields: self.variant.fields.iter(),
+        })
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(FieldsAccess {
+            de: self.de,
+            fields: self.variant.fields.iter(),
+        })
     }
 }
 
-impl<'de> serde::de::Deserializer<'de> for &mut DocBufDeserializer<'de> {
+impl<'de, 'v> serde::de::Deserializer<'de> for &mut DocBufDeserializer<'de, 'v> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        // println!("Deserialize Any");
+        match self.current_type()? {
+            VTableFieldType::Struct(name) => {
+                let vtable_struct = self.vtable.struct_by_name(name)?;
+
+                visitor.visit_map(FieldsAccess {
+                    de: self,
+                    fields: vtable_struct.fields.iter(),
+                })
+            }
+            VTableFieldType::Enum(name) => {
+                let variant = self.read_variant(name)?;
 
-        let field = self.current_field()?;
-        // self.optional_field.as_ref().unwrap_or()
-        match &field.r#type {
-            VTableFieldType::Struct(_) => {
-                self.next_field()?;
-                self.deserialize_map(visitor)
+                visitor.visit_enum(VariantAccessor { de: self, variant })
+            }
+            VTableFieldType::Bool => visitor.visit_bool(self.decode()?),
+            VTableFieldType::I8 => visitor.visit_i8(self.decode()?),
+            VTableFieldType::I16 => visitor.visit_i16(self.decode()?),
+            VTableFieldType::I32 => visitor.visit_i32(self.decode()?),
+            VTableFieldType::I64 | VTableFieldType::ISIZE => visitor.visit_i64(self.decode()?),
+            VTableFieldType::I128 => visitor.visit_i128(self.decode()?),
+            VTableFieldType::U8 => visitor.visit_u8(self.decode()?),
+            VTableFieldType::U16 => visitor.visit_u16(self.decode()?),
+            VTableFieldType::U32 => visitor.visit_u32(self.decode()?),
+            VTableFieldType::U64 | VTableFieldType::USIZE => visitor.visit_u64(self.decode()?),
+            VTableFieldType::U128 => visitor.visit_u128(self.decode()?),
+            VTableFieldType::F32 => visitor.visit_f32(self.decode()?),
+            VTableFieldType::F64 => visitor.visit_f64(self.decode()?),
+            VTableFieldType::String | VTableFieldType::Str => {
+                visitor.visit_borrowed_str(self.read_str()?)
+            }
+            VTableFieldType::Bytes | VTableFieldType::Uuid => {
+                visitor.visit_borrowed_bytes(self.read_byte_array()?)
             }
-            VTableFieldType::Bool => self.deserialize_bool(visitor),
-            VTableFieldType::I8 => self.deserialize_i8(visitor),
-            VTableFieldType::I16 => self.deserialize_i16(visitor),
-            VTableFieldType::I32 => self.deserialize_i32(visitor),
-            VTableFieldType::I64 | VTableFieldType::ISIZE => self.deserialize_i64(visitor),
-            VTableFieldType::I128 => self.deserialize_i128(visitor),
-            VTableFieldType::U8 => self.deserialize_u8(visitor),
-            VTableFieldType::U16 => self.deserialize_u16(visitor),
-            VTableFieldType::U32 => self.deserialize_u32(visitor),
-            VTableFieldType::U64 | VTableFieldType::USIZE => self.deserialize_u64(visitor),
-            VTableFieldType::U128 => self.deserialize_u128(visitor),
-            VTableFieldType::F32 => self.deserialize_f32(visitor),
-            VTableFieldType::F64 => self.deserialize_f64(visitor),
-            VTableFieldType::String => self.deserialize_string(visitor),
-            VTableFieldType::Bytes => self.deserialize_bytes(visitor),
-            VTableFieldType::Str => self.deserialize_str(visitor),
             VTableFieldType::Vec(_) => self.deserialize_seq(visitor),
-            VTableFieldType::HashMap { .. } => visitor.visit_map(self),
+            VTableFieldType::HashMap { .. } => self.deserialize_map(visitor),
             VTableFieldType::Option(_) => self.deserialize_option(visitor),
-            VTableFieldType::Uuid => unimplemented!("UUID not implemented"),
+            VTableFieldType::Char => visitor.visit_char(self.decode()?),
+            VTableFieldType::Unit => visitor.visit_unit(),
+            VTableFieldType::Array { len, .. } => self.deserialize_tuple(*len, visitor),
+            VTableFieldType::Tuple(types) => self.deserialize_tuple(types.len(), visitor),
         }
     }
 
@@ -467,323 +497,351 @@ impl<'de> serde::de::Deserializer<'de> for &mut DocBufDeserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        // println!("Deserialize Bool");
-
-        let value = self.current_field()?.decode(self.buffer)?;
-
-        self.next_field()?;
-
-        visitor.visit_bool(value)
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_bool(self.decode()?),
+        }
     }
 
     fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        // println!("Deserialize I8");
-
-        let value = self.current_field()?.decode(self.buffer)?;
-
-        self.next_field()?;
-
-        visitor.visit_i8(value)
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_i8(self.decode()?),
+        }
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        // println!("Deserialize I16");
-
-        let value = self.current_field()?.decode(self.buffer)?;
-
-        self.next_field()?;
-
-        visitor.visit_i16(value)
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_i16(self.decode()?),
+        }
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let value = self.current_field()?.decode(self.buffer)?;
-        // println!("deserialize_i32");
-
-        // Increment the field index
-        self.next_field()?;
-
-        visitor.visit_i32(value)
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_i32(self.decode()?),
+        }
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let value = self.current_field()?.decode(self.buffer)?;
-        // println!("deserialize_i64");
-
-        // Increment the field index
-        self.next_field()?;
-
-        visitor.visit_i64(value)
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_i64(self.decode()?),
+        }
     }
 
     fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let value = self.current_field()?.decode(self.buffer)?;
-        // println!("deserialize_i128");
-
-        // Increment the field index
-        self.next_field()?;
-
-        visitor.visit_i128(value)
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_i128(self.decode()?),
+        }
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        // println!("Deserialize U8; Buffer: {:?}", self.buffer);
-        let value = self.current_field()?.decode(self.buffer)?;
-
-        self.next_field()?;
-
-        visitor.visit_u8(value)
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_u8(self.decode()?),
+        }
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let field = self.current_field()?;
-        // println!("deserialize_u16");
-
-        let value = field.decode(self.buffer)?;
-
-        self.next_field()?;
-
-        visitor.visit_u16(value)
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_u16(self.decode()?),
+        }
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let value = self.current_field()?.decode(self.buffer)?;
-        // println!("deserialize_u32");
-
-        self.next_field()?;
-
-        visitor.visit_u32(value)
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_u32(self.decode()?),
+        }
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let value = self.current_field()?.decode(self.buffer)?;
-        // println!("deserialize_u64");
-
-        // Increment the field index
-        self.next_field()?;
-
-        visitor.visit_u64(value)
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_u64(self.decode()?),
+        }
     }
 
-    /// Hint that the `Deserialize` type is expecting an `u128` value.
-    ///
-    /// The default behavior unconditionally returns an error.
     fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let value = self.current_field()?.decode(self.buffer)?;
-        // println!("deserialize_u128");
-
-        // Increment the field index
-        self.next_field()?;
-
-        visitor.visit_u128(value)
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_u128(self.decode()?),
+        }
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let value = self.current_field()?.decode(self.buffer)?;
-        // println!("deserialize_f32");
-
-        // Increment the field index
-        self.next_field()?;
-
-        visitor.visit_f32(value)
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_f32(self.decode()?),
+        }
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let value = self.current_field()?.decode(self.buffer)?;
-        // println!("deserialize_f64");
-
-        // Increment the field index
-        self.next_field()?;
-
-        visitor.visit_f64(value)
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_f64(self.decode()?),
+        }
     }
 
-    fn deserialize_char<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unimplemented!("deserialize_char")
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_char(self.decode()?),
+        }
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let field = self.current_field()?;
-        // println!("deserialize_str");
-
-        let value: String = field.decode(self.buffer)?;
-
-        self.next_field()?;
-
-        visitor.visit_str(value.as_str())
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            // Uuids are encoded as raw bytes, rather than as strings.
+            false if self.current_type()?.holds(&VTableFieldType::Uuid) => {
+                visitor.visit_borrowed_bytes(self.read_byte_array()?)
+            }
+            false => visitor.visit_borrowed_str(self.read_str()?),
+        }
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let field = self.current_field()?;
-        // println!("deserialize_string");
-
-        let value = field.decode(self.buffer)?;
-
-        match field.r#type {
-            // Ignore Uuid field.
-            VTableFieldType::Uuid | VTableFieldType::HashMap { .. } => {}
-            _ => {
-                self.next_field()?;
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false if self.current_type()?.holds(&VTableFieldType::Uuid) => {
+                visitor.visit_borrowed_bytes(self.read_byte_array()?)
             }
+            false => visitor.visit_string(self.read_str()?.to_owned()),
         }
-
-        visitor.visit_string(value)
     }
 
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let field = self.current_field()?;
-        // println!("deserialize_bytes");
-
-        // println!("Deserialize Bytes: {:?}", field);
-
-        let value: Vec<u8> = field.decode(self.buffer)?;
-
-        self.next_field()?;
-
-        visitor.visit_bytes(value.as_slice())
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_borrowed_bytes(self.read_byte_array()?),
+        }
     }
 
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let value = self.current_field()?.decode(self.buffer)?;
-        // println!("deserialize_byte_buf");
-
-        self.next_field()?;
-
-        visitor.visit_byte_buf(value)
+        match self.compat {
+            true => self.deserialize_any(visitor),
+            false => visitor.visit_byte_buf(self.read_byte_array()?.to_vec()),
+        }
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        // println!("Deserialize Option");
-        let field = self.current_field()?;
-        match field.decode_option(self.buffer)? {
-            None => {
-                self.next_field()?;
-                return visitor.visit_none();
-            }
-            Some(_) => {
-                self.next_field()?;
+        // A value written without an option is read into an option as `Some`.
+        if self.compat && !matches!(self.current_type()?, VTableFieldType::Option(_)) {
+            return visitor.visit_some(self);
+        }
+
+        match self.current_field()?.decode_option(&mut self.buffer)? {
+            None => visitor.visit_none(),
+            Some(()) => {
+                if let Some(VTableFieldType::Option(inner)) = self.current_type {
+                    self.current_type = Some(inner);
+                }
 
-                return visitor.visit_some(self);
+                visitor.visit_some(self)
             }
         }
     }
 
-    fn deserialize_unit<V>(self, _visitor: V) -> Result<V::Value>
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unimplemented!("deserialize_unit")
+        // Unit values are not encoded
+        visitor.visit_unit()
     }
 
-    fn deserialize_unit_struct<V>(self, _name: &'static str, _visitor: V) -> Result<V::Value>
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unimplemented!("deserialize_unit_struct")
+        visitor.visit_unit()
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, _visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unimplemented!("deserialize_newtype_struct")
+        // Newtypes of other crates, which are not in the vtable, are transparent.
+        if let Ok(vtable_struct) = self.vtable.struct_by_name(name) {
+            self.set_field(vtable_struct.field_by_index(&0)?);
+        }
+
+        visitor.visit_newtype_struct(self)
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        // println!("\n\nDeserialize Seq\n\n");
-        // println!("Buffer: {:?}", self.buffer);
-        // let field = self.current_field()?;
+        let element_type = match self.current_type()? {
+            VTableFieldType::Vec(inner) => inner,
+            VTableFieldType::Bytes => &BYTE_ELEMENT_TYPE,
+            r#type => r#type,
+        };
 
-        // println!("Field: {field:?}");
+        let remaining = self.current_field()?.decode_len(&mut self.buffer)?;
+        let field = self.current_field;
 
-        visitor.visit_seq(self)
+        visitor.visit_seq(ElementsAccess {
+            de: self,
+            field,
+            element_type,
+            remaining,
+        })
     }
 
-    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        // println!("deserialize_tuple");
+        let field = self.current_field;
 
-        visitor.visit_seq(self)
+        let element_type = match self.current_type()? {
+            VTableFieldType::Bytes => {
+                // Byte arrays are prefixed with their length
+                self.current_field()?.decode_len(&mut self.buffer)?;
+
+                &BYTE_ELEMENT_TYPE
+            }
+            VTableFieldType::Array { elem, len: array_len } => {
+                if *array_len != len {
+                    return Err(Error::Serde(format!(
+                        "Expected array of length {}, found {}",
+                        len, array_len
+                    )));
+                }
+
+                elem.as_ref()
+            }
+            VTableFieldType::Tuple(types) => {
+                if types.len() != len {
+                    return Err(Error::Serde(format!(
+                        "Expected tuple of {} elements, found {}",
+                        len,
+                        types.len()
+                    )));
+                }
+
+                return visitor.visit_seq(TupleAccess {
+                    de: self,
+                    field,
+                    types: types.iter(),
+                });
+            }
+            r#type => r#type,
+        };
+
+        visitor.visit_seq(ElementsAccess {
+            de: self,
+            field,
+            element_type,
+            remaining: len,
+        })
     }
 
     fn deserialize_tuple_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         _len: usize,
-        _visitor: V,
+        visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unimplemented!("deserialize_tuple_struct")
+        let vtable_struct = self
+            .vtable
+            .struct_by_name(name)
+            .map_err(|_| Error::Serde(format!("Struct {} not found in the vtable", name)))?;
+
+        visitor.visit_seq(FieldsAccess {
+            de: self,
+            fields: vtable_struct.fields.iter(),
+        })
     }
 
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        // println!("Deserialize Map");
+        let (key_type, value_type) = match self.current_type()? {
+            VTableFieldType::HashMap { key, value } => (key.as_ref(), value.as_ref()),
+            r#type => {
+                return Err(Error::Serde(format!(
+                    "Expected map field type, found {}",
+                    r#type
+                )))
+            }
+        };
 
-        visitor.visit_map(self)
+        let remaining = self.current_field()?.decode_len(&mut self.buffer)?;
+        let field = self.current_field;
+
+        visitor.visit_map(EntriesAccess {
+            de: self,
+            field,
+            key_type,
+            value_type,
+            remaining,
+        })
     }
 
     fn deserialize_struct<V>(
@@ -795,53 +853,36 @@ impl<'de> serde::de::Deserializer<'de> for &mut DocBufDeserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        // println!("Deserializing Struct: {name}");
-
-        let current_struct = self.vtable.struct_by_name(name)?;
-
-        self.current_item_index = current_struct.item_index;
-        self.current_field_index = 0;
+        let vtable_struct = self
+            .vtable
+            .struct_by_name(name)
+            .map_err(|_| Error::Serde(format!("Struct {} not found in the vtable", name)))?;
 
-        self.deserialize_seq(visitor)
+        visitor.visit_map(FieldsAccess {
+            de: self,
+            fields: vtable_struct.fields.iter(),
+        })
     }
 
     fn deserialize_enum<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         _variants: &'static [&'static str],
-        _visitor: V,
+        visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        unimplemented!("deserialize_enum")
+        let variant = self.read_variant(name)?;
+
+        visitor.visit_enum(VariantAccessor { de: self, variant })
     }
 
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let field = self.current_field()?;
-        // println!("deserialize_identifier");
-
-        match &field.r#type {
-            VTableFieldType::Struct(_) => {
-                self.next_field()?;
-                visitor.visit_str(&field.name)
-            }
-            VTableFieldType::HashMap { key, .. } => match key.as_ref() {
-                VTableFieldType::String => {
-                    // NOTE: Do not increment the field index here,
-                    // as we are processing the key of the hash map.
-                    let data = field.decode(self.buffer)?;
-                    visitor.visit_string(data)
-                }
-                _ => {
-                    unimplemented!("deserialize_identifier for hash map key type: {:?}", key);
-                }
-            },
-            _ => visitor.visit_str(&field.name),
-        }
+        self.deserialize_str(visitor)
     }
 
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
@@ -1,5 +1,3 @@
-use std::collections::VecDeque;
-
 use serde::Serialize;
 use tracing::debug;
 
@@ -12,17 +10,38 @@ use crate::{
 
 const DEFAULT_CAPACITY_MULTIPLIER: usize = 10;
 
+/// A struct or enum variant that is currently being serialized.
+#[derive(Debug)]
+pub struct DocBufSerializerItem {
+    pub item: &'static VTableItem,
+    /// The enum variant being serialized, if the item is an enum.
+    pub variant: Option<&'static VTableVariant>,
+    /// The field containing the item, restored once the item is serialized.
+    pub parent_field: Option<&'static VTableField>,
+    /// The buffer position at the start of the item.
+    pub offset_start: usize,
+    /// The index of the next positional field, used by tuple structs and variants.
+    pub next_field_index: VTableFieldIndex,
+}
+
+/// A fixed size array, tuple, list or map that is currently being serialized.
+#[derive(Debug)]
+pub struct DocBufSerializerTuple {
+    /// The field containing the array, tuple, list or map.
+    pub field: &'static VTableField,
+    /// The buffer position at the start of the elements.
+    pub offset_start: usize,
+}
+
 #[derive(Debug)]
 pub struct DocBufSerializer<'a> {
     pub vtable: &'static VTable,
     pub buffer: &'a mut Vec<u8>,
-    pub current_item_index: u8,
-    pub current_field_index: u8,
-    pub previous_item_index: u8,
-    pub current_item: Option<&'static VTableItem>,
     pub current_field: Option<&'static VTableField>,
-    pub previous_item: Option<&'static VTableItem>,
-    pub previous_items: VecDeque<&'static VTableItem>,
+    /// Stack of items being serialized, the last item is the current item.
+    pub items: Vec<DocBufSerializerItem>,
+    /// Stack of arrays, tuples, lists and maps being serialized, the last is the innermost.
+    pub tuples: Vec<DocBufSerializerTuple>,
     pub offsets: VTableFieldOffsets,
 }
 
@@ -34,45 +53,255 @@ impl<'a> DocBufSerializer<'a> {
         Self {
             vtable,
             buffer,
-            current_item_index: 0,
-            current_field_index: 0,
-            previous_item_index: 0,
-            current_item: None,
             current_field: None,
-            previous_item: None,
-            previous_items: VecDeque::new(),
+            items: Vec::with_capacity(vtable.num_items as usize),
+            tuples: Vec::new(),
             offsets: VTableFieldOffsets::with_capacity(
                 vtable.num_items as usize * DEFAULT_CAPACITY_MULTIPLIER,
             ),
         }
     }
 
-    // Return the current field or find it in the vtable based on the
-    // current_item_index and current_field_index
+    // Return the current field being serialized
     pub fn current_field(&self) -> Result<&'static VTableField> {
-        Ok(match self.current_field {
-            Some(field) => field,
-            _ => self
-                .vtable
-                .struct_by_index(self.current_item_index)?
-                .field_by_index(&self.current_field_index)?,
-        })
+        self.current_field
+            .ok_or(Error::VTable(crate::vtable::Error::FieldNotFound))
+    }
+
+    // Return the current item being serialized
+    pub fn current_item(&self) -> Result<&DocBufSerializerItem> {
+        self.items
+            .last()
+            .ok_or(Error::VTable(crate::vtable::Error::ItemNotFound))
+    }
+
+    /// Returns true if the serializer is within an enum variant payload.
+    /// Enum values are recorded as a single offset, rather than an offset per payload field.
+    pub fn is_variant_payload(&self) -> bool {
+        self.items.iter().any(|item| item.variant.is_some())
+    }
+
+    /// Add the field offset, unless the field is part of an enum variant payload,
+    /// or an element of an array, tuple, list or map. These are recorded as a
+    /// single offset once all elements are serialized.
+    pub fn push_offset(&mut self, offset: VTableFieldOffset) {
+        if !self.is_variant_payload() && self.tuples.is_empty() {
+            self.offsets.push(offset);
+        }
     }
 
     pub fn encode_array_start(&mut self, num_elements: usize) -> Result<()> {
         let field = self.current_field()?;
 
-        // println!("encode_array_start: {:?}", field);
+        // Check for the not empty rule of lists
+        #[cfg(feature = "validate")]
+        field.rules.check_collection(num_elements)?;
 
-        field.encode_array_start(num_elements, &mut self.buffer)?;
+        field.encode_array_start(num_elements, self.buffer)?;
 
         Ok(())
     }
 
     // Encode the beginning of a map structure
     pub fn encode_map_start(&mut self, num_entries: usize) -> Result<()> {
-        self.current_field()?
-            .encode_map_start(num_entries, &mut self.buffer)?;
+        let field = self.current_field()?;
+
+        // Check for the not empty rule of maps
+        #[cfg(feature = "validate")]
+        field.rules.check_collection(num_entries)?;
+
+        field.encode_map_start(num_entries, self.buffer)?;
+
+        Ok(())
+    }
+
+    // Encode a field value, adding its offset
+    pub fn encode_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        VTableField: DocBufEncodeField<T>,
+    {
+        let offset = self.current_field()?.encode(value, self.buffer)?;
+        self.push_offset(offset);
+
+        Ok(())
+    }
+
+    /// Begin serializing a struct, given its name.
+    pub fn begin_struct(&mut self, name: &str) -> Result<()> {
+        let item = self.vtable.item_by_name(name).map_err(|_| {
+            Error::Serde(format!("Struct {} not found in the vtable", name))
+        })?;
+
+        self.items.push(DocBufSerializerItem {
+            item,
+            variant: None,
+            parent_field: self.current_field,
+            offset_start: self.buffer.len(),
+            next_field_index: 0,
+        });
+
+        Ok(())
+    }
+
+    /// Finish serializing the current struct, restoring the parent field.
+    pub fn end_struct(&mut self) -> Result<()> {
+        let item = self
+            .items
+            .pop()
+            .ok_or(Error::VTable(crate::vtable::Error::ItemNotFound))?;
+
+        self.current_field = item.parent_field;
+
+        // Nested structs are recorded as a single offset spanning their fields,
+        // in addition to the offsets of the fields.
+        if let Some(field) = item.parent_field {
+            self.push_offset(field.as_offset(item.offset_start..self.buffer.len()));
+        }
+
+        Ok(())
+    }
+
+    /// Begin serializing an enum variant, encoding the variant discriminant.
+    pub fn begin_variant(&mut self, name: &str, variant_index: u32) -> Result<()> {
+        let item = self.vtable.item_by_name(name).map_err(|_| {
+            Error::Serde(format!("Enum {} not found in the vtable", name))
+        })?;
+
+        let variant = match item {
+            VTableItem::Enum(vtable_enum) => {
+                let index = VTableVariantIndex::try_from(variant_index).map_err(|_| {
+                    crate::vtable::Error::VariantIndexExceedsMax(variant_index)
+                })?;
+
+                vtable_enum.variant_by_index(index)?
+            }
+            _ => return Err(Error::VTable(crate::vtable::Error::EnumNotFound)),
+        };
+
+        let offset_start = self.buffer.len();
+
+        // Encode the variant discriminant
+        self.buffer.push(variant.index);
+
+        self.items.push(DocBufSerializerItem {
+            item,
+            variant: Some(variant),
+            parent_field: self.current_field,
+            offset_start,
+            next_field_index: 0,
+        });
+
+        Ok(())
+    }
+
+    /// Finish serializing the current enum variant, adding a single offset
+    /// for the enum value and restoring the parent field.
+    pub fn end_variant(&mut self) -> Result<()> {
+        let item = self
+            .items
+            .pop()
+            .ok_or(Error::VTable(crate::vtable::Error::ItemNotFound))?;
+
+        self.current_field = item.parent_field;
+
+        // Root enums are addressed as the first field of the enum item.
+        let index = match item.parent_field {
+            Some(field) => field.offset_index(),
+            None => (item.item.item_index(), 0),
+        };
+
+        self.push_offset(VTableFieldOffset(index, item.offset_start..self.buffer.len()));
+
+        Ok(())
+    }
+
+    /// Begin serializing a fixed size array or tuple of `len` elements.
+    pub fn begin_tuple(&mut self, len: usize) -> Result<()> {
+        let field = self.current_field()?;
+
+        let r#type = match &field.r#type {
+            VTableFieldType::Option(inner) => inner.as_ref(),
+            r#type => r#type,
+        };
+
+        // Check the field value, rather than the arrays and tuples nested in it.
+        if self.tuples.is_empty() {
+            match r#type {
+                // Byte arrays of a `Bytes` field are prefixed with their length
+                VTableFieldType::Bytes => self.encode_array_start(len)?,
+                VTableFieldType::Array { len: array_len, .. } if *array_len != len => {
+                    return Err(Error::Serde(format!(
+                        "Expected array of length {} for field {}, found {}",
+                        array_len, field.name, len
+                    )));
+                }
+                VTableFieldType::Tuple(types) if types.len() != len => {
+                    return Err(Error::Serde(format!(
+                        "Expected tuple of {} elements for field {}, found {}",
+                        types.len(),
+                        field.name,
+                        len
+                    )));
+                }
+                // The length rule applies to the number of array elements
+                #[cfg(feature = "validate")]
+                VTableFieldType::Array { .. } => field.rules.check_length(len)?,
+                _ => (),
+            }
+        }
+
+        self.tuples.push(DocBufSerializerTuple {
+            field,
+            offset_start: self.buffer.len(),
+        });
+
+        Ok(())
+    }
+
+    /// Begin serializing the elements of a list or map, after its length prefix.
+    pub fn begin_collection(&mut self) -> Result<()> {
+        let field = self.current_field()?;
+
+        self.tuples.push(DocBufSerializerTuple {
+            field,
+            offset_start: self.buffer.len(),
+        });
+
+        Ok(())
+    }
+
+    /// Finish serializing the current array, tuple, list or map, adding a single
+    /// offset for its elements and restoring the containing field.
+    pub fn end_tuple(&mut self) -> Result<()> {
+        let tuple = self
+            .tuples
+            .pop()
+            .ok_or(Error::Serde("Tuple not found".to_string()))?;
+
+        self.current_field = Some(tuple.field);
+
+        self.push_offset(tuple.field.as_offset(tuple.offset_start..self.buffer.len()));
+
+        Ok(())
+    }
+
+    /// Set the next positional field of the current tuple struct or enum variant.
+    pub fn next_field(&mut self) -> Result<()> {
+        let item = self
+            .items
+            .last_mut()
+            .ok_or(Error::VTable(crate::vtable::Error::ItemNotFound))?;
+
+        let field = match (item.item, item.variant) {
+            (_, Some(variant)) => variant.field_by_index(&item.next_field_index)?,
+            (VTableItem::Struct(vtable_struct), None) => {
+                vtable_struct.field_by_index(&item.next_field_index)?
+            }
+            _ => return Err(Error::VTable(crate::vtable::Error::FieldNotFound)),
+        };
+
+        item.next_field_index += 1;
+        self.current_field = Some(field);
 
         Ok(())
     }
@@ -86,8 +315,6 @@ where
 
     value.serialize(&mut serializer)?;
 
-    // println!("Buffer: {:?}", buffer);
-
     Ok(serializer.offsets)
 }
 
@@ -105,171 +332,79 @@ impl<'a, 'b> serde::ser::Serializer for &'a mut DocBufSerializer<'b> {
     type SerializeStructVariant = Self;
 
     fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self> {
-        // println!("serialize_struct: {}", name);
-
-        for vtable_item in self.vtable.items.iter() {
-            match vtable_item {
-                VTableItem::Struct(vtable_struct) => {
-                    if vtable_struct.name == name {
-                        self.previous_item_index = self.current_item_index;
-                        self.current_item_index = vtable_struct.item_index;
-                        self.previous_item = self.current_item;
-
-                        // Push the item to the previous items
-                        if let Some(item) = self.current_item {
-                            self.previous_items.push_back(item);
-                        }
-
-                        self.current_item = Some(vtable_item);
-                        return Ok(self);
-                    }
-                }
-            }
-        }
+        self.begin_struct(name)?;
 
-        Err(Error::Serde(format!(
-            "Struct {} not found in the vtable",
-            name
-        )))
+        Ok(self)
     }
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
-        let offset = self.current_field()?.encode(&v, &mut self.buffer)?;
-        self.offsets.push(offset);
-
-        Ok(())
+        self.encode_field(&v)
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
-        let offset = self.current_field()?.encode(&v, &mut self.buffer)?;
-        self.offsets.push(offset);
-
-        Ok(())
+        self.encode_field(&v)
     }
 
-    fn serialize_char(self, _v: char) -> Result<Self::Ok> {
-        unimplemented!("serialize_char")
+    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+        self.encode_field(&v)
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
-        let offset = self
-            .current_field()?
-            .encode(&NumericValue::F32(v), &mut self.buffer)?;
-        self.offsets.push(offset);
-
-        Ok(())
+        self.encode_field(&NumericValue::F32(v))
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
-        let offset = self
-            .current_field()?
-            .encode(&NumericValue::F64(v), &mut self.buffer)?;
-        self.offsets.push(offset);
-
-        Ok(())
+        self.encode_field(&NumericValue::F64(v))
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
-        let offset = self
-            .current_field()?
-            .encode(&NumericValue::I8(v), &mut self.buffer)?;
-        self.offsets.push(offset);
-
-        Ok(())
+        self.encode_field(&NumericValue::I8(v))
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
-        let offset = self
-            .current_field()?
-            .encode(&NumericValue::I16(v), &mut self.buffer)?;
-        self.offsets.push(offset);
-
-        Ok(())
+        self.encode_field(&NumericValue::I16(v))
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
-        let offset = self
-            .current_field()?
-            .encode(&NumericValue::I32(v), &mut self.buffer)?;
-        self.offsets.push(offset);
-
-        Ok(())
+        self.encode_field(&NumericValue::I32(v))
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
-        let offset = self
-            .current_field()?
-            .encode(&NumericValue::I64(v), &mut self.buffer)?;
-        self.offsets.push(offset);
-
-        Ok(())
+        self.encode_field(&NumericValue::I64(v))
     }
 
     fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
-        let offset = self
-            .current_field()?
-            .encode(&NumericValue::I128(v), &mut self.buffer)?;
-        self.offsets.push(offset);
-
-        Ok(())
+        self.encode_field(&NumericValue::I128(v))
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok> {
-        let offset = self.current_field()?.encode(&v, &mut self.buffer)?;
-        self.offsets.push(offset);
-
-        Ok(())
+        self.encode_field(&v)
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
-        let offset = self
-            .current_field()?
-            .encode(&NumericValue::U8(v), &mut self.buffer)?;
-        self.offsets.push(offset);
-
-        Ok(())
+        self.encode_field(&NumericValue::U8(v))
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
-        let offset = self
-            .current_field()?
-            .encode(&NumericValue::U16(v), &mut self.buffer)?;
-        self.offsets.push(offset);
-
-        Ok(())
+        self.encode_field(&NumericValue::U16(v))
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
-        let offset = self
-            .current_field()?
-            .encode(&NumericValue::U32(v), &mut self.buffer)?;
-        self.offsets.push(offset);
-
-        Ok(())
+        self.encode_field(&NumericValue::U32(v))
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
-        let offset = self
-            .current_field()?
-            .encode(&NumericValue::U64(v), &mut self.buffer)?;
-        self.offsets.push(offset);
-
-        Ok(())
+        self.encode_field(&NumericValue::U64(v))
     }
 
     fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
-        let offset = self
-            .current_field()?
-            .encode(&NumericValue::U128(v), &mut self.buffer)?;
-        self.offsets.push(offset);
-
-        Ok(())
+        self.encode_field(&NumericValue::U128(v))
     }
 
     fn serialize_none(self) -> Result<Self::Ok> {
-        let offset = self.current_field()?.encode_none(&mut self.buffer)?;
+        let offset = self.current_field()?.encode_none(self.buffer)?;
 
-        self.offsets.push(offset);
+        self.push_offset(offset);
 
         Ok(())
     }
@@ -283,98 +418,122 @@ impl<'a, 'b> serde::ser::Serializer for &'a mut DocBufSerializer<'b> {
     }
 
     fn serialize_unit(self) -> Result<Self::Ok> {
-        unimplemented!("serialize_unit")
+        // Unit values are not encoded, add an empty offset for the field.
+        if let Some(field) = self.current_field {
+            let offset_start = self.buffer.len();
+            self.push_offset(field.as_offset(offset_start..offset_start));
+        }
+
+        Ok(())
     }
 
     fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
-        unimplemented!("serialize_unit_struct")
+        // Unit structs have no fields to encode.
+        Ok(())
     }
 
     fn serialize_unit_variant(
         self,
-        _name: &'static str,
-        _variant_index: u32,
+        name: &'static str,
+        variant_index: u32,
         _variant: &'static str,
     ) -> Result<Self::Ok> {
-        unimplemented!("serialize_unit_variant")
+        self.begin_variant(name, variant_index)?;
+        self.end_variant()
     }
 
     fn serialize_newtype_struct<T: ?Sized>(
         self,
-        _name: &'static str,
-        _value: &T,
+        name: &'static str,
+        value: &T,
     ) -> Result<Self::Ok>
     where
         T: Serialize,
     {
-        unimplemented!("serialize_newtype_struct")
+        // Newtypes of other crates, which are not in the vtable, are transparent.
+        if self.vtable.struct_by_name(name).is_err() {
+            return value.serialize(self);
+        }
+
+        self.begin_struct(name)?;
+        self.next_field()?;
+
+        value.serialize(&mut *self)?;
+
+        self.end_struct()
     }
 
     fn serialize_newtype_variant<T: ?Sized>(
         self,
-        _name: &'static str,
-        _variant_index: u32,
+        name: &'static str,
+        variant_index: u32,
         _variant: &'static str,
-        _value: &T,
+        value: &T,
     ) -> Result<Self::Ok>
     where
         T: Serialize,
     {
-        unimplemented!("serialize_newtype_variant")
+        self.begin_variant(name, variant_index)?;
+        self.next_field()?;
+
+        value.serialize(&mut *self)?;
+
+        self.end_variant()
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
-        // println!("Serialize Sequence");
-
         self.encode_array_start(len.unwrap_or_default())?;
+        self.begin_collection()?;
 
         Ok(self)
     }
 
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
-        let field = self.current_field()?;
-
-        if let VTableFieldType::Bytes = field.r#type {
-            // Encode the length of the byte array
-            self.encode_array_start(len)?;
-        }
+        self.begin_tuple(len)?;
 
         Ok(self)
     }
 
     fn serialize_tuple_struct(
         self,
-        _name: &'static str,
+        name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
-        unimplemented!("serialize_tuple_struct")
+        self.begin_struct(name)?;
+
+        Ok(self)
     }
 
     fn serialize_tuple_variant(
         self,
-        _name: &'static str,
-        _variant_index: u32,
+        name: &'static str,
+        variant_index: u32,
         _variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        unimplemented!("serialize_tuple_variant")
+        self.begin_variant(name, variant_index)?;
+
+        Ok(self)
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
-        // // Encode the number of entries in the map
+        // Encode the number of entries in the map
         self.encode_map_start(len.unwrap_or_default())?;
+        self.begin_collection()?;
 
         Ok(self)
     }
 
     fn serialize_struct_variant(
         self,
-        _name: &'static str,
-        _variant_index: u32,
+        name: &'static str,
+        variant_index: u32,
         _variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        unimplemented!("serialize_struct_variant")
+        self.begin_variant(name, variant_index)?;
+
+        Ok(self)
     }
 }
 
@@ -390,7 +549,7 @@ impl<'a, 'b> serde::ser::SerializeSeq for &'a mut DocBufSerializer<'b> {
     }
 
     fn end(self) -> Result<Self::Ok> {
-        Ok(())
+        self.end_tuple()
     }
 }
 
@@ -406,7 +565,7 @@ impl<'a, 'b> serde::ser::SerializeTuple for &'a mut DocBufSerializer<'b> {
     }
 
     fn end(self) -> Result<Self::Ok> {
-        Ok(())
+        self.end_tuple()
     }
 }
 
@@ -414,15 +573,17 @@ impl<'a, 'b> serde::ser::SerializeTupleStruct for &'a mut DocBufSerializer<'b> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, _value: &T) -> Result<()>
+    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<()>
     where
         T: Serialize,
     {
-        unimplemented!("serialize_field")
+        self.next_field()?;
+
+        value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<Self::Ok> {
-        unimplemented!("end")
+        self.end_struct()
     }
 }
 
@@ -430,15 +591,17 @@ impl<'a, 'b> serde::ser::SerializeTupleVariant for &'a mut DocBufSerializer<'b>
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, _value: &T) -> Result<()>
+    fn serialize_field<T: ?Sized>(&mut self, value: &T) -> Result<()>
     where
         T: Serialize,
     {
-        unimplemented!("serialize_field")
+        self.next_field()?;
+
+        value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<Self::Ok> {
-        unimplemented!("end")
+        self.end_variant()
     }
 }
 
@@ -446,34 +609,22 @@ impl<'a, 'b> serde::ser::SerializeMap for &'a mut DocBufSerializer<'b> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_key<T: ?Sized>(&mut self, _key: &T) -> Result<()>
+    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<()>
     where
         T: Serialize,
     {
-        Ok(())
+        key.serialize(&mut **self)
     }
 
-    fn serialize_value<T: ?Sized>(&mut self, _value: &T) -> Result<()>
+    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<()>
     where
         T: Serialize,
     {
-        Ok(())
-    }
-
-    fn serialize_entry<K: ?Sized, V: ?Sized>(&mut self, key: &K, value: &V) -> Result<()>
-    where
-        K: Serialize,
-        V: Serialize,
-    {
-        key.serialize(&mut **self)?;
-
-        value.serialize(&mut **self)?;
-
-        Ok(())
+        value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<Self::Ok> {
-        Ok(())
+        self.end_tuple()
     }
 }
 
@@ -491,9 +642,15 @@ impl<'a, 'b> serde::ser::SerializeStruct for &'a mut DocBufSerializer<'b> {
         value.serialize(&mut **self)
     }
 
+    fn skip_field(&mut self, name: &'static str) -> Result<()> {
+        self.set_field(name)?;
+
+        // Fields are positional in the document buffer, encode skipped fields as empty values.
+        self.encode_empty()
+    }
+
     fn end(self) -> Result<Self::Ok> {
-        // Update the current index
-        Ok(())
+        self.end_struct()
     }
 }
 
@@ -501,99 +658,71 @@ impl<'a, 'b> serde::ser::SerializeStructVariant for &'a mut DocBufSerializer<'b>
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(&mut self, _key: &'static str, _value: &T) -> Result<()>
+    fn serialize_field<T: ?Sized>(&mut self, name: &'static str, value: &T) -> Result<()>
     where
         T: Serialize,
     {
-        unimplemented!("serialize_field")
+        self.set_field(name)?;
+
+        value.serialize(&mut **self)
+    }
+
+    fn skip_field(&mut self, name: &'static str) -> Result<()> {
+        self.set_field(name)?;
+
+        self.encode_empty()
     }
 
     fn end(self) -> Result<Self::Ok> {
-        unimplemented!("end")
+        self.end_variant()
     }
 }
 
-impl<'a, 'b> DocBufSerializer<'b> {
-    pub fn set_field(&'a mut self, field_name: &'static str) -> Result<()> {
-        // println!("Current Item: {:?}", self.current_item);
-
-        match self.current_item {
-            Some(VTableItem::Struct(field_struct)) => {
-                match field_struct.field_by_name(field_name) {
-                    Ok(field) => {
-                        self.current_field_index = field.index;
-                        self.current_field = Some(field);
-
-                        return Ok(());
-                    }
-                    Err(_) => {
-                        // println!("Previous Items: {:?}", self.previous_items.len());
-
-                        // Attempt to find the field in the previous items
-                        // If the field is found, set the current item to the previous item
-                        // and set the previous item to None
-                        while let Some(previous_item) = self.previous_items.pop_front() {
-                            // println!("Previous Item: {:?}", previous_item);
-                            match previous_item {
-                                VTableItem::Struct(field_struct) => {
-                                    match field_struct.field_by_name(field_name) {
-                                        Ok(field) => {
-                                            self.current_item = Some(previous_item);
-                                            self.current_item_index = self.previous_item_index;
-                                            self.previous_item = None;
-
-                                            self.current_field_index = field.index;
-                                            self.current_field = Some(field);
-
-                                            return Ok(());
-                                        }
-                                        Err(_) => {
-                                            self.previous_items.push_back(previous_item);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-
-                        return Err(Error::Serde(format!("Field not found: {}", field_name)));
-                    }
-                }
+impl<'b> DocBufSerializer<'b> {
+    /// Set the current field by name, from the current struct or enum variant.
+    pub fn set_field(&mut self, field_name: &'static str) -> Result<()> {
+        let item = self.current_item()?;
+
+        let field = match (item.item, item.variant) {
+            (_, Some(variant)) => variant.field_by_name(field_name),
+            (VTableItem::Struct(vtable_struct), None) => vtable_struct.field_by_name(field_name),
+            _ => Err(crate::vtable::Error::FieldNotFound),
+        }
+        .map_err(|_| Error::Serde(format!("Field not found: {}", field_name)))?;
+
+        self.current_field = Some(field);
+
+        Ok(())
+    }
+
+    /// Encode an empty value for a skipped field, i.e. an empty string, byte array,
+    /// list or map, or a `None` option.
+    pub fn encode_empty(&mut self) -> Result<()> {
+        let field = self.current_field()?;
+
+        match field.r#type {
+            VTableFieldType::Option(_) => {
+                let offset = field.encode_none(self.buffer)?;
+                self.push_offset(offset);
             }
-            _ => match self
-                .vtable
-                .struct_by_index(self.current_item_index)?
-                .field_by_name(field_name)
-            {
-                Ok(field) => {
-                    self.current_field_index = field.index;
-                    self.current_field = Some(field);
-
-                    return Ok(());
-                }
-                Err(_) => {
-                    match self
-                        .vtable
-                        .struct_by_index(self.previous_item_index)?
-                        .field_by_name(field_name)
-                    {
-                        Ok(field) => {
-                            // Reset the current item to the parent struct
-                            self.current_item = self.previous_item;
-                            self.current_item_index = self.previous_item_index;
-                            self.previous_item = None;
-
-                            self.current_field_index = field.index;
-                            self.current_field = Some(field);
-
-                            // Return the field
-                            return Ok(());
-                        }
-                        Err(_) => {
-                            return Err(Error::Serde(format!("Field not found: {}", field_name)));
-                        }
-                    }
-                }
-            },
-        };
+            VTableFieldType::String
+            | VTableFieldType::Str
+            | VTableFieldType::Bytes
+            | VTableFieldType::Vec(_)
+            | VTableFieldType::HashMap { .. } => {
+                field.encode_array_start(0, self.buffer)?;
+
+                let offset_start = self.buffer.len();
+                self.push_offset(field.as_offset(offset_start..offset_start));
+            }
+            _ => {
+                return Err(Error::Serde(format!(
+                    "Unable to skip field {} of type {}",
+                    field.name, field.r#type
+                )))
+            }
+        }
+
+        Ok(())
     }
 }
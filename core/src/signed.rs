@@ -0,0 +1,248 @@
+//! Signed document buffers.
+//!
+//! A signed document buffer bundles a document buffer with its signature, the
+//! public key of the signer and the ids of the signature and hash algorithms,
+//! so that receivers can verify the document before decoding it:
+//!
+//! | bytes | field                                  |
+//! |-------|----------------------------------------|
+//! | 4     | magic bytes, `DBSG`                    |
+//! | 1     | signed format version                  |
+//! | 1     | signature algorithm id                 |
+//! | 1     | hash algorithm id                      |
+//! | 8     | vtable id of the document              |
+//! | 2     | public key length, little endian       |
+//! | n     | public key                             |
+//! | 2     | signature length, little endian        |
+//! | n     | signature                              |
+//! | 4     | body length, little endian             |
+//! | n     | document buffer body                   |
+//!
+//! The signed message is the hash of the `SIGNED_MESSAGE_TAG`, the vtable id
+//! and the canonical form of the fields covered by the signature, see
+//! `VTable::canonical_signed_docbuf`. Fields with the `sign` rule are covered
+//! if there are any, otherwise the whole document is covered.
+
+use crate::crypto::{self, DocBufSigner, HashAlgorithm, SignatureAlgorithm};
+use crate::vtable::{DocBufReader, VTable, VTableId};
+
+/// Magic bytes at the start of every signed document buffer.
+pub const SIGNED_MAGIC: [u8; 4] = *b"DBSG";
+
+/// Version of the signed document buffer format.
+pub const SIGNED_VERSION: u8 = 1;
+
+/// Domain separation tag of the message signed for documents, so that it is
+/// never the message signed for a merkle tree, see `merkle::MERKLE_MESSAGE_TAG`.
+pub const SIGNED_MESSAGE_TAG: &[u8] = b"docbuf-signed-v1";
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Invalid signed document magic bytes: {0:?}")]
+    InvalidMagic([u8; 4]),
+    #[error("Unsupported signed document version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("Signature algorithm mismatch. Expected: {expected}; found: {found}")]
+    SignatureAlgorithmMismatch {
+        expected: SignatureAlgorithm,
+        found: SignatureAlgorithm,
+    },
+    #[error("Hash algorithm mismatch. Expected: {expected}; found: {found}")]
+    HashAlgorithmMismatch {
+        expected: HashAlgorithm,
+        found: HashAlgorithm,
+    },
+    #[error("VTable id mismatch. Expected: {expected}; found: {found}")]
+    VTableIdMismatch { expected: VTableId, found: VTableId },
+    #[error("Signed document body length mismatch. Expected: {expected}; found: {found}")]
+    BodyLengthMismatch { expected: usize, found: usize },
+    #[error("Signed document body length exceeds max: {0}")]
+    BodyTooLarge(usize),
+    #[error("Signed document key or signature length exceeds max: {0}")]
+    FieldTooLarge(usize),
+    #[error("Signed document header truncated")]
+    Truncated,
+    #[error(transparent)]
+    Crypto(#[from] crypto::Error),
+}
+
+/// The header of a signed document buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocBufSignature {
+    pub version: u8,
+    pub signature_algorithm: SignatureAlgorithm,
+    pub hash_algorithm: HashAlgorithm,
+    pub vtable_id: VTableId,
+    /// The encoded public key of the signer.
+    pub public_key: Vec<u8>,
+    /// The encoded signature of the signed message.
+    pub signature: Vec<u8>,
+    pub body_len: u32,
+}
+
+impl DocBufSignature {
+    /// Sign the document buffer of the vtable.
+    pub fn sign(
+        vtable: &VTable,
+        hash_algorithm: HashAlgorithm,
+        signer: &impl DocBufSigner,
+        body: &[u8],
+    ) -> crate::Result<Self> {
+        let body_len = u32::try_from(body.len()).map_err(|_| Error::BodyTooLarge(body.len()))?;
+
+        let message = Self::message(vtable, hash_algorithm, body)?;
+        let signature = signer.sign(&message).map_err(Error::from)?;
+
+        Ok(Self {
+            version: SIGNED_VERSION,
+            signature_algorithm: signer.algorithm(),
+            hash_algorithm,
+            vtable_id: vtable.id().to_owned(),
+            public_key: signer.public_key(),
+            signature,
+            body_len,
+        })
+    }
+
+    /// Return the message signed for the document buffer of the vtable.
+    pub fn message(
+        vtable: &VTable,
+        hash_algorithm: HashAlgorithm,
+        body: &[u8],
+    ) -> crate::Result<Vec<u8>> {
+        let mut canonical = Vec::with_capacity(body.len());
+        vtable.canonical_signed_docbuf(body, &mut canonical)?;
+
+        Ok(hash_algorithm.hash(&[SIGNED_MESSAGE_TAG, vtable.id().as_ref(), &canonical]))
+    }
+
+    /// Verify the signature of the document buffer of the vtable, with the
+    /// public key of the signature.
+    ///
+    /// The public key is only checked to match the signature. Whether the
+    /// signer is trusted is left to the caller.
+    pub fn verify(&self, vtable: &VTable, body: &[u8]) -> crate::Result<()> {
+        self.check_vtable_id(vtable.id())?;
+
+        let message = Self::message(vtable, self.hash_algorithm, body)?;
+
+        self.signature_algorithm
+            .verify(&self.public_key, &message, &self.signature)
+            .map_err(Error::from)?;
+
+        Ok(())
+    }
+
+    /// Check the document was signed with the expected algorithms.
+    pub fn check_algorithms(
+        &self,
+        signature_algorithm: SignatureAlgorithm,
+        hash_algorithm: HashAlgorithm,
+    ) -> Result<(), Error> {
+        if self.signature_algorithm != signature_algorithm {
+            return Err(Error::SignatureAlgorithmMismatch {
+                expected: signature_algorithm,
+                found: self.signature_algorithm,
+            });
+        }
+
+        if self.hash_algorithm != hash_algorithm {
+            return Err(Error::HashAlgorithmMismatch {
+                expected: hash_algorithm,
+                found: self.hash_algorithm,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Check the document was produced by the expected vtable.
+    #[inline]
+    pub fn check_vtable_id(&self, expected: &VTableId) -> Result<(), Error> {
+        match &self.vtable_id == expected {
+            true => Ok(()),
+            false => Err(Error::VTableIdMismatch {
+                expected: expected.to_owned(),
+                found: self.vtable_id.to_owned(),
+            }),
+        }
+    }
+
+    /// Serialize the signed document header into the buffer.
+    pub fn write_to_buffer(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
+        buffer.extend_from_slice(&SIGNED_MAGIC);
+        buffer.push(self.version);
+        buffer.push(self.signature_algorithm.id());
+        buffer.push(self.hash_algorithm.id());
+        buffer.extend_from_slice(self.vtable_id.as_ref());
+
+        for field in [&self.public_key, &self.signature] {
+            let len = u16::try_from(field.len()).map_err(|_| Error::FieldTooLarge(field.len()))?;
+
+            buffer.extend_from_slice(&len.to_le_bytes());
+            buffer.extend_from_slice(field);
+        }
+
+        buffer.extend_from_slice(&self.body_len.to_le_bytes());
+
+        Ok(())
+    }
+
+    /// Read the signed document header from the front of the buffer, returning
+    /// the header and the document buffer body. The signature is not verified.
+    pub fn read_from_buffer(buffer: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let mut reader = DocBufReader::new(buffer);
+
+        let magic: [u8; 4] = reader.read_array().map_err(|_| Error::Truncated)?;
+        if magic != SIGNED_MAGIC {
+            return Err(Error::InvalidMagic(magic));
+        }
+
+        let version = reader.read_u8().map_err(|_| Error::Truncated)?;
+        if version != SIGNED_VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        let signature_algorithm =
+            SignatureAlgorithm::from_id(reader.read_u8().map_err(|_| Error::Truncated)?)?;
+        let hash_algorithm =
+            HashAlgorithm::from_id(reader.read_u8().map_err(|_| Error::Truncated)?)?;
+
+        let vtable_id = VTableId::new(reader.read_array().map_err(|_| Error::Truncated)?);
+
+        let mut read_field = || -> Result<Vec<u8>, Error> {
+            let len = u16::from_le_bytes(reader.read_array().map_err(|_| Error::Truncated)?);
+
+            Ok(reader
+                .read_bytes(len as usize)
+                .map_err(|_| Error::Truncated)?
+                .to_vec())
+        };
+
+        let public_key = read_field()?;
+        let signature = read_field()?;
+
+        let body_len = u32::from_le_bytes(reader.read_array().map_err(|_| Error::Truncated)?);
+
+        let body = reader.remaining();
+        if body.len() != body_len as usize {
+            return Err(Error::BodyLengthMismatch {
+                expected: body_len as usize,
+                found: body.len(),
+            });
+        }
+
+        Ok((
+            Self {
+                version,
+                signature_algorithm,
+                hash_algorithm,
+                vtable_id,
+                public_key,
+                signature,
+                body_len,
+            },
+            body,
+        ))
+    }
+}
@@ -1,12 +1,16 @@
 use crate::{
-    error,
-    vtable::{VTable, VTableFieldOffset, VTableFieldOffsets},
+    envelope, error,
+    vtable::{DocBufReader, VTable, VTableFieldOffset, VTableFieldOffsets},
 };
 
 #[cfg(feature = "crypto")]
-use crate::crypto::{
-    digest::{self, Digest, FixedOutput, FixedOutputReset, HashMarker},
-    ed25519,
+use crate::{
+    crypto::{
+        digest::{self, Digest, FixedOutput, FixedOutputReset, HashMarker},
+        ed25519, DocBufSigner, HashAlgorithm, Keyring, SignatureAlgorithm,
+    },
+    merkle::DocBufMerkleTree,
+    signed::{self, DocBufSignature},
 };
 
 pub trait DocBuf {
@@ -39,8 +43,116 @@ pub trait DocBuf {
     /// Convert the document to a document buffer
     fn to_docbuf<'a>(&self, buffer: &'a mut Vec<u8>) -> Result<VTableFieldOffsets, error::Error>;
 
+    /// Convert the document to its canonical form, the same bytes for the same
+    /// logical document regardless of e.g. the iteration order of its maps.
+    ///
+    /// The canonical form is hashed and signed by `DocBufCrypto`, it is not a
+    /// document buffer and cannot be converted back to a document.
+    fn to_canonical_docbuf(&self, buffer: &mut Vec<u8>) -> Result<(), error::Error>
+    where
+        Self: ::serde::Serialize + std::fmt::Debug + Sized,
+    {
+        let mut bytes = Vec::with_capacity(1024);
+        self.to_docbuf(&mut bytes)?;
+
+        buffer.clear();
+        Self::vtable()?.canonical_docbuf(&bytes, buffer)?;
+
+        self.validate_with()
+    }
+
     /// Convert the document buffer to a document
-    fn from_docbuf<'a>(buffer: &'a mut Vec<u8>) -> Result<Self::Doc, error::Error>;
+    ///
+    /// The buffer is left intact, and the document may borrow string and
+    /// byte fields from it.
+    fn from_docbuf<'de>(buffer: &'de [u8]) -> Result<Self::Doc, error::Error>
+    where
+        Self::Doc: ::serde::Deserialize<'de>;
+
+    /// Convert a document buffer written with a different version of the
+    /// document's vtable to a document, e.g. a buffer stored before fields were
+    /// added, removed or reordered, read with the vtable from `VTable::from_file`.
+    ///
+    /// Returns `vtable::Error::IncompatibleVTable` if the changes between the
+    /// writer's and the document's vtable cannot be reconciled.
+    fn from_docbuf_compat<'de>(
+        writer: &VTable,
+        buffer: &'de [u8],
+    ) -> Result<Self::Doc, error::Error>
+    where
+        Self::Doc: ::serde::Deserialize<'de>,
+    {
+        let doc: Self::Doc = crate::serde::de::from_docbuf_compat(writer, buffer)?;
+
+        doc.validate_with()?;
+
+        Ok(doc)
+    }
+
+    /// Run the `validate_with` hooks of the document, its fields and nested
+    /// documents, e.g. to check invariants that span several fields.
+    ///
+    /// ```ignore
+    /// #[docbuf {
+    ///    validate_with = "check_range";
+    /// }]
+    /// struct Range {
+    ///    pub start: u64,
+    ///    pub end: u64,
+    /// }
+    ///
+    /// fn check_range(range: &Range) -> Result<(), String> {
+    ///    // ...
+    /// }
+    /// ```
+    ///
+    /// Called by `to_docbuf` and `from_docbuf` once the field rules have passed.
+    fn validate_with(&self) -> Result<(), error::Error> {
+        Ok(())
+    }
+
+    /// Validate the document against the field rules of its vtable, walking
+    /// nested documents, lists and maps.
+    ///
+    /// Unlike `to_docbuf`, which fails on the first violation, every violated
+    /// rule is collected in the report with the path of the offending value.
+    #[cfg(feature = "validate")]
+    fn validate(&self) -> Result<(), crate::validate::ValidationReport>
+    where
+        Self: ::serde::Serialize + Sized,
+    {
+        crate::validate::validate(self)
+    }
+
+    /// Convert the document to an enveloped document buffer, prefixing the
+    /// document buffer with the envelope header, e.g. the vtable id.
+    fn to_enveloped(&self, buffer: &mut Vec<u8>) -> Result<(), error::Error> {
+        self.to_docbuf(buffer)?;
+
+        let body_len = u32::try_from(buffer.len())
+            .map_err(|_| envelope::Error::BodyTooLarge(buffer.len()))?;
+
+        let mut header = Vec::with_capacity(envelope::ENVELOPE_HEADER_SIZE);
+        envelope::DocBufEnvelope::new(Self::vtable()?.id().to_owned(), body_len)
+            .write_to_buffer(&mut header);
+
+        buffer.splice(0..0, header);
+
+        Ok(())
+    }
+
+    /// Convert the enveloped document buffer to a document, checking the
+    /// envelope was produced by the vtable of the document.
+    fn from_enveloped<'de>(buffer: &'de [u8]) -> Result<Self::Doc, error::Error>
+    where
+        Self::Doc: ::serde::Deserialize<'de>,
+    {
+        let (envelope, body) = envelope::DocBufEnvelope::read_from_buffer(buffer)?;
+
+        envelope.check_vtable_id(Self::vtable()?.id())?;
+
+        Self::from_docbuf(body)
+    }
 
     /// Write the document buffer to a file
     #[cfg(feature = "std")]
@@ -56,10 +168,55 @@ pub trait DocBuf {
 
         Ok(())
     }
+
+    /// Write the document buffer to the writer as a length framed document,
+    /// allowing several documents to be written back to back to the same stream.
+    #[cfg(feature = "std")]
+    fn to_writer(&self, writer: impl std::io::Write) -> Result<(), error::Error> {
+        let mut buffer = Self::vtable()?.alloc_buf();
+        self.to_docbuf(&mut buffer)?;
+
+        crate::frame::write_frame(writer, &buffer)?;
+
+        Ok(())
+    }
+
+    /// Read a length framed document from the reader, written by `to_writer`.
+    ///
+    /// Only the bytes of a single document are consumed from the reader, so
+    /// documents written back to back can be read one at a time.
+    #[cfg(feature = "std")]
+    fn from_reader(reader: impl std::io::Read) -> Result<Self::Doc, error::Error>
+    where
+        Self::Doc: ::serde::de::DeserializeOwned,
+    {
+        let buffer = crate::frame::read_frame(reader)?;
+
+        Self::from_docbuf(&buffer)
+    }
 }
 
+/// Hash, sign and verify documents, implemented for documents with the docbuf
+/// `sign = true` attribute. The canonical form of the document is hashed, see
+/// `DocBuf::to_canonical_docbuf`, prefixed with the vtable id so documents of
+/// different vtables with the same encoding hash differently.
+///
+/// Signatures cover the fields with the `sign` rule, and the values nested in
+/// them, if the document has any. Otherwise, they cover the whole document.
+///
+/// The `crypto` and `hash` attributes select the algorithms of the document,
+/// e.g. `crypto = "p256"` and `hash = "sha3-256"`, see `crypto` for the
+/// supported algorithms and their features.
 #[cfg(feature = "crypto")]
 pub trait DocBufCrypto: DocBuf {
+    /// The signature algorithm of signed document buffers, set with the docbuf
+    /// `crypto` attribute.
+    const SIGNATURE_ALGORITHM: SignatureAlgorithm = SignatureAlgorithm::Ed25519;
+
+    /// The hash algorithm of the message signed in signed document buffers,
+    /// set with the docbuf `hash` attribute.
+    const HASH_ALGORITHM: HashAlgorithm = HashAlgorithm::Sha256;
+
     #[cfg(feature = "ed25519")]
     fn sign<D>(
         &self,
@@ -68,8 +225,9 @@ pub trait DocBufCrypto: DocBuf {
     ) -> Result<ed25519::Signature, error::Error>
     where
         D: Default + Digest + Clone + FixedOutput + FixedOutputReset + HashMarker + 'static,
+        Self: ::serde::Serialize + std::fmt::Debug + Sized,
     {
-        let data = self.hash(digest)?;
+        let data = self.hash_signed_fields(digest)?;
         let signature = signer.try_sign(&data)?;
         Ok(signature)
     }
@@ -83,9 +241,10 @@ pub trait DocBufCrypto: DocBuf {
     ) -> Result<(), error::Error>
     where
         D: Default + Digest + Clone + FixedOutput + FixedOutputReset + HashMarker + 'static,
+        Self: ::serde::Serialize + std::fmt::Debug + Sized,
     {
         // Re-compute the data hash message that was signed.
-        let data = self.hash(digest)?;
+        let data = self.hash_signed_fields(digest)?;
 
         // Verify the signature against the hashed payload.
         verifier.verify(&data, signature)?;
@@ -97,15 +256,18 @@ pub trait DocBufCrypto: DocBuf {
     fn hash<D>(&self, digest: &mut D) -> Result<Vec<u8>, error::Error>
     where
         D: Default + Digest + Clone + FixedOutput + FixedOutputReset + HashMarker + 'static,
+        Self: ::serde::Serialize + std::fmt::Debug + Sized,
     {
-        // Hash the document buffer contents
+        // Hash the canonical document contents
         use digest::DynDigest;
         let mut bytes = Vec::with_capacity(1024);
-        self.to_docbuf(&mut bytes)?;
+        self.to_canonical_docbuf(&mut bytes)?;
 
         let output_size = digest.output_size();
         let mut result = vec![0u8; output_size];
 
+        // Separate the hashes of documents of different vtables
+        Digest::update(digest, Self::vtable()?.id().as_ref());
         Digest::update(digest, &bytes);
 
         // Reset the digest after finalizing the hash
@@ -115,6 +277,153 @@ pub trait DocBufCrypto: DocBuf {
         // Return the hash result
         Ok(result)
     }
+
+    /// Hash the canonical form of the fields covered by signatures, see
+    /// `VTable::canonical_signed_docbuf`, prefixed with the signed message tag
+    /// and the vtable id.
+    #[cfg(feature = "digest")]
+    fn hash_signed_fields<D>(&self, digest: &mut D) -> Result<Vec<u8>, error::Error>
+    where
+        D: Default + Digest + Clone + FixedOutput + FixedOutputReset + HashMarker + 'static,
+    {
+        let vtable = Self::vtable()?;
+
+        let mut bytes = Vec::with_capacity(1024);
+        self.to_docbuf(&mut bytes)?;
+
+        let mut canonical = Vec::with_capacity(bytes.len());
+        vtable.canonical_signed_docbuf(&bytes, &mut canonical)?;
+
+        Digest::update(digest, signed::SIGNED_MESSAGE_TAG);
+        Digest::update(digest, vtable.id().as_ref());
+        Digest::update(digest, &canonical);
+
+        Ok(Digest::finalize_reset(digest).to_vec())
+    }
+
+    /// Return the message signed for the document, the hash of its fields
+    /// covered by signatures with the hash algorithm of the document.
+    fn signed_message(&self) -> Result<Vec<u8>, error::Error> {
+        let mut buffer = Vec::with_capacity(1024);
+        self.to_docbuf(&mut buffer)?;
+
+        DocBufSignature::message(Self::vtable()?, Self::HASH_ALGORITHM, &buffer)
+    }
+
+    /// Sign the document with the algorithms of the document, returning the
+    /// detached signature, the same signature as in signed document buffers.
+    fn sign_detached(&self, signer: &impl DocBufSigner) -> Result<Vec<u8>, error::Error> {
+        check_signer(Self::SIGNATURE_ALGORITHM, signer)?;
+
+        let message = self.signed_message()?;
+
+        Ok(signer.sign(&message)?)
+    }
+
+    /// Verify the detached signature of the document with the encoded public
+    /// key of the signer.
+    fn verify_detached(&self, public_key: &[u8], signature: &[u8]) -> Result<(), error::Error> {
+        let message = self.signed_message()?;
+
+        Self::SIGNATURE_ALGORITHM.verify(public_key, &message, signature)?;
+
+        Ok(())
+    }
+
+    /// Convert the document to a signed document buffer, bundling the document
+    /// buffer with its signature, the public key of the signer and the ids of
+    /// the signature and hash algorithms, see `signed::DocBufSignature`.
+    fn to_signed_docbuf(
+        &self,
+        signer: &impl DocBufSigner,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), error::Error> {
+        check_signer(Self::SIGNATURE_ALGORITHM, signer)?;
+
+        self.to_docbuf(buffer)?;
+
+        let signature =
+            DocBufSignature::sign(Self::vtable()?, Self::HASH_ALGORITHM, signer, buffer)?;
+
+        let mut header = Vec::with_capacity(64 + signature.signature.len());
+        signature.write_to_buffer(&mut header)?;
+
+        buffer.splice(0..0, header);
+
+        Ok(())
+    }
+
+    /// Convert the signed document buffer to a document, verifying the
+    /// signature with the public key of the signer before decoding the document
+    /// buffer. The algorithms must be the algorithms of the document.
+    ///
+    /// The public key is only checked to match the signature. Read it with
+    /// `DocBufSignature::read_from_buffer` to check whether the signer is trusted,
+    /// or use `from_trusted_signed_docbuf`.
+    fn from_signed_docbuf<'de>(buffer: &'de [u8]) -> Result<Self::Doc, error::Error>
+    where
+        Self::Doc: ::serde::Deserialize<'de>,
+    {
+        let (signature, body) = DocBufSignature::read_from_buffer(buffer)?;
+
+        signature.check_algorithms(Self::SIGNATURE_ALGORITHM, Self::HASH_ALGORITHM)?;
+        signature.verify(Self::vtable()?, body)?;
+
+        Self::from_docbuf(body)
+    }
+
+    /// Convert the signed document buffer to a document, checking the signer
+    /// is a trusted key of the keyring before verifying the signature.
+    fn from_trusted_signed_docbuf<'de>(
+        buffer: &'de [u8],
+        keyring: &Keyring,
+    ) -> Result<Self::Doc, error::Error>
+    where
+        Self::Doc: ::serde::Deserialize<'de>,
+    {
+        let (signature, body) = DocBufSignature::read_from_buffer(buffer)?;
+
+        signature.check_algorithms(Self::SIGNATURE_ALGORITHM, Self::HASH_ALGORITHM)?;
+        keyring
+            .trust(signature.signature_algorithm, &signature.public_key)
+            .map_err(signed::Error::from)?;
+        signature.verify(Self::vtable()?, body)?;
+
+        Self::from_docbuf(body)
+    }
+
+    /// Commit to the fields of the document in a merkle tree, with a random
+    /// salt per field, and sign its root. The tree discloses a subset of the
+    /// fields with their inclusion proofs, see `merkle::DocBufMerkleTree`.
+    fn commit_fields(&self, signer: &impl DocBufSigner) -> Result<DocBufMerkleTree, error::Error> {
+        check_signer(Self::SIGNATURE_ALGORITHM, signer)?;
+
+        let mut buffer = Vec::new();
+        let offsets = self.to_docbuf(&mut buffer)?;
+
+        DocBufMerkleTree::new(
+            Self::vtable()?,
+            Self::HASH_ALGORITHM,
+            signer,
+            &buffer,
+            &offsets,
+        )
+    }
+}
+
+/// Check the signer uses the signature algorithm of the document.
+#[cfg(feature = "crypto")]
+fn check_signer(
+    expected: SignatureAlgorithm,
+    signer: &impl DocBufSigner,
+) -> Result<(), signed::Error> {
+    match signer.algorithm() == expected {
+        true => Ok(()),
+        false => Err(signed::Error::SignatureAlgorithmMismatch {
+            expected,
+            found: signer.algorithm(),
+        }),
+    }
 }
 
 /// This trait is used by the vtable to read a field from the
@@ -150,7 +459,7 @@ pub trait DocBufEncodeField<T> {
 
 /// DocBufDecodeField is a trait used to deserialize a field from the document buffer.
 pub trait DocBufDecodeField<T> {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<T, crate::vtable::Error>;
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<T, crate::vtable::Error>;
 }
 
 /// DocBufValidateField is a trait used to validate a field from the document buffer.
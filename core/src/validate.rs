@@ -0,0 +1,209 @@
+mod validator;
+
+pub use validator::*;
+
+// Re-export the necessary validation libraries for validating fields
+pub use regex;
+
+use serde::Serialize;
+
+use crate::{error::Error, traits::DocBuf};
+
+/// A field rule that a document value can violate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationRule {
+    /// The `length` rule.
+    Length,
+    /// The `min_length` rule.
+    MinLength,
+    /// The `max_length` rule.
+    MaxLength,
+    /// The `min_value` rule.
+    MinValue,
+    /// The `max_value` rule.
+    MaxValue,
+    /// The `regex` rule.
+    Regex,
+    /// The `one_of` rule.
+    OneOf,
+    /// The `format` rule.
+    Format,
+    /// The `total_digits` rule.
+    TotalDigits,
+    /// The `fraction_digits` rule.
+    FractionDigits,
+    /// The `whitespace` rule.
+    Whitespace,
+    /// The `not_empty` rule.
+    NotEmpty,
+    /// A `validate_with` hook of the document or a field.
+    ValidateWith,
+    /// The value does not match the vtable of the document, e.g. a field or
+    /// item is missing, so the rest of the document could not be validated.
+    Schema,
+}
+
+impl std::fmt::Display for ValidationRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ValidationRule::Length => "length",
+            ValidationRule::MinLength => "min_length",
+            ValidationRule::MaxLength => "max_length",
+            ValidationRule::MinValue => "min_value",
+            ValidationRule::MaxValue => "max_value",
+            ValidationRule::Regex => "regex",
+            ValidationRule::OneOf => "one_of",
+            ValidationRule::Format => "format",
+            ValidationRule::TotalDigits => "total_digits",
+            ValidationRule::FractionDigits => "fraction_digits",
+            ValidationRule::Whitespace => "whitespace",
+            ValidationRule::NotEmpty => "not_empty",
+            ValidationRule::ValidateWith => "validate_with",
+            ValidationRule::Schema => "schema",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl ValidationRule {
+    /// Return the vtable error for a violation of the rule.
+    pub fn error(&self, message: String) -> crate::vtable::Error {
+        use crate::vtable::Error;
+
+        match self {
+            ValidationRule::Length | ValidationRule::MinLength | ValidationRule::MaxLength => {
+                Error::FieldRulesLength(message)
+            }
+            ValidationRule::MinValue | ValidationRule::MaxValue => Error::FieldRulesValue(message),
+            ValidationRule::Regex => Error::FieldRulesRegex(message),
+            ValidationRule::OneOf => Error::FieldRulesOneOf(message),
+            ValidationRule::Format => Error::FieldRulesFormat(message),
+            ValidationRule::TotalDigits | ValidationRule::FractionDigits => {
+                Error::FieldRulesDigits(message)
+            }
+            ValidationRule::Whitespace => Error::FieldRulesWhitespace(message),
+            ValidationRule::NotEmpty => Error::FieldRulesNotEmpty(message),
+            ValidationRule::ValidateWith => Error::DocumentValidation(message),
+            ValidationRule::Schema => Error::InvalidValidationType(message),
+        }
+    }
+}
+
+/// A field rule violated by a value of the document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// The path of the value in the document, e.g. `metadata.tags[3]`.
+    pub path: String,
+    /// The rule the value violates.
+    pub rule: ValidationRule,
+    /// The offending value.
+    pub value: String,
+    /// A description of the violation.
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} rule violated by {:?}: {}",
+            self.path, self.rule, self.value, self.message
+        )
+    }
+}
+
+/// Every field rule violated by a document, returned by `DocBuf::validate`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    pub errors: Vec<ValidationError>,
+}
+
+impl ValidationReport {
+    /// Returns true if the document violates no field rules.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Return the violations of the value at the path.
+    pub fn errors_at<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a ValidationError> {
+        self.errors.iter().filter(move |error| error.path == path)
+    }
+
+    /// Return the report as an error, if the document violates any field rules.
+    pub fn into_result(self) -> Result<(), ValidationReport> {
+        if self.is_valid() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors = self
+            .errors
+            .iter()
+            .map(|error| error.to_string())
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", errors.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationReport {}
+
+/// Validate the document against the field rules of its vtable, collecting
+/// every violation rather than stopping at the first.
+pub fn validate<T>(value: &T) -> Result<(), ValidationReport>
+where
+    T: Serialize + DocBuf,
+{
+    let vtable = match T::vtable() {
+        Ok(vtable) => vtable,
+        Err(error) => {
+            return Err(ValidationReport {
+                errors: vec![ValidationError {
+                    path: String::new(),
+                    rule: ValidationRule::Schema,
+                    value: String::new(),
+                    message: error.to_string(),
+                }],
+            })
+        }
+    };
+
+    let mut validator = DocBufValidator::new(vtable);
+
+    // The walk stops at values that do not match the vtable, the violations
+    // found before are still reported.
+    if let Err(error) = value.serialize(&mut validator) {
+        validator.report(ValidationRule::Schema, String::new(), error.to_string());
+    }
+
+    // The `validate_with` hooks may rely on the field rules, and are only run
+    // once the field rules have passed.
+    if validator.report.is_valid() {
+        if let Err(error) = value.validate_with() {
+            let (path, message) = match error {
+                Error::VTable(crate::vtable::Error::FieldValidation { field, message }) => {
+                    (field, message)
+                }
+                Error::VTable(crate::vtable::Error::DocumentValidation(message)) => {
+                    (String::new(), message)
+                }
+                error => (String::new(), error.to_string()),
+            };
+
+            validator.report.errors.push(ValidationError {
+                path,
+                rule: ValidationRule::ValidateWith,
+                value: String::new(),
+                message,
+            });
+        }
+    }
+
+    validator.report.into_result()
+}
@@ -0,0 +1,719 @@
+use serde::Serialize;
+
+use super::{ValidationError, ValidationReport, ValidationRule};
+use crate::vtable::*;
+use crate::{error::Error, Result};
+
+/// A segment of the path of a value in the document.
+#[derive(Debug, Clone)]
+pub enum ValidationPathSegment {
+    /// A struct field or enum variant.
+    Field(&'static str),
+    /// An element of a list, array or tuple.
+    Index(usize),
+    /// The value of a map entry.
+    Key(String),
+}
+
+/// A struct or enum variant that is currently being validated.
+#[derive(Debug)]
+pub struct DocBufValidatorItem {
+    pub item: &'static VTableItem,
+    /// The enum variant being validated, if the item is an enum.
+    pub variant: Option<&'static VTableVariant>,
+    /// The field containing the item, restored once the item is validated.
+    pub parent_field: Option<&'static VTableField>,
+    /// The index of the next positional field, used by tuple structs and variants.
+    pub next_field_index: VTableFieldIndex,
+}
+
+/// Walks a document with its vtable, checking each value against the rules
+/// of its field and recording every violation with the path of the value.
+#[derive(Debug)]
+pub struct DocBufValidator {
+    pub vtable: &'static VTable,
+    pub current_field: Option<&'static VTableField>,
+    /// Stack of items being validated, the last item is the current item.
+    pub items: Vec<DocBufValidatorItem>,
+    /// Stack of the fields of the arrays and tuples being validated.
+    pub tuples: Vec<&'static VTableField>,
+    /// Stack of the next element index of the lists, arrays and tuples being validated.
+    pub indexes: Vec<usize>,
+    /// The path of the current value.
+    pub path: Vec<ValidationPathSegment>,
+    /// The current map key, set while the key of a map entry is validated.
+    pub map_key: Option<String>,
+    pub report: ValidationReport,
+}
+
+impl DocBufValidator {
+    pub fn new(vtable: &'static VTable) -> Self {
+        Self {
+            vtable,
+            current_field: None,
+            items: Vec::with_capacity(vtable.num_items as usize),
+            tuples: Vec::new(),
+            indexes: Vec::new(),
+            path: Vec::new(),
+            map_key: None,
+            report: ValidationReport::default(),
+        }
+    }
+
+    // Return the current field being validated
+    pub fn current_field(&self) -> Result<&'static VTableField> {
+        self.current_field
+            .ok_or(Error::VTable(crate::vtable::Error::FieldNotFound))
+    }
+
+    /// Return the path of the current value, e.g. `metadata.tags[3]`.
+    pub fn path(&self) -> String {
+        let mut path = String::new();
+
+        for segment in self.path.iter() {
+            match segment {
+                ValidationPathSegment::Field(name) if path.is_empty() => path.push_str(name),
+                ValidationPathSegment::Field(name) => {
+                    path.push('.');
+                    path.push_str(name);
+                }
+                ValidationPathSegment::Index(index) => path.push_str(&format!("[{}]", index)),
+                ValidationPathSegment::Key(key) => path.push_str(&format!("[{}]", key)),
+            }
+        }
+
+        path
+    }
+
+    /// Record a violation of the value at the current path.
+    pub fn report(&mut self, rule: ValidationRule, value: String, message: String) {
+        self.report.errors.push(ValidationError {
+            path: self.path(),
+            rule,
+            value,
+            message,
+        });
+    }
+
+    /// Record the violations of the value at the current path. The value is
+    /// only formatted if a rule is violated.
+    fn report_all(
+        &mut self,
+        violations: Vec<(ValidationRule, String)>,
+        value: impl FnOnce() -> String,
+    ) {
+        if violations.is_empty() {
+            return;
+        }
+
+        let value = value();
+
+        for (rule, message) in violations {
+            self.report(rule, value.clone(), message);
+        }
+    }
+
+    /// Record a scalar map key, used as the path segment of the map entry.
+    fn record_key(&mut self, key: impl FnOnce() -> String) {
+        if self.map_key.is_some() {
+            self.map_key = Some(key());
+        }
+    }
+
+    pub fn check_str(&mut self, value: &str) -> Result<()> {
+        let rules = &self.current_field()?.rules;
+
+        self.record_key(|| format!("{:?}", value));
+
+        if rules.is_none() {
+            return Ok(());
+        }
+
+        let violations = rules.string_violations(value);
+
+        self.report_all(violations, || value.to_string());
+
+        Ok(())
+    }
+
+    pub fn check_bytes(&mut self, value: &[u8]) -> Result<()> {
+        let rules = &self.current_field()?.rules;
+
+        self.record_key(|| hex::encode(value));
+
+        if rules.is_none() {
+            return Ok(());
+        }
+
+        let violations = rules.bytes_violations(value);
+
+        self.report_all(violations, || hex::encode(value));
+
+        Ok(())
+    }
+
+    pub fn check_bool(&mut self, value: bool) -> Result<()> {
+        let rules = &self.current_field()?.rules;
+
+        self.record_key(|| value.to_string());
+
+        if rules.is_none() {
+            return Ok(());
+        }
+
+        let violations = rules.bool_violations(value);
+
+        self.report_all(violations, || value.to_string());
+
+        Ok(())
+    }
+
+    /// Check the number of elements of a list or map.
+    pub fn check_collection(&mut self, num_elements: usize) -> Result<()> {
+        let rules = &self.current_field()?.rules;
+
+        if rules.is_none() {
+            return Ok(());
+        }
+
+        let violations = rules.collection_violations(num_elements);
+
+        self.report_all(violations, || format!("{} elements", num_elements));
+
+        Ok(())
+    }
+
+    pub fn check_numeric(&mut self, value: NumericValue) -> Result<()> {
+        let rules = &self.current_field()?.rules;
+
+        self.record_key(|| value.to_string());
+
+        if rules.is_none() {
+            return Ok(());
+        }
+
+        let violations = rules.numeric_violations(&value);
+
+        self.report_all(violations, || value.to_string());
+
+        Ok(())
+    }
+
+    /// Begin validating a struct, given its name.
+    pub fn begin_struct(&mut self, name: &str) -> Result<()> {
+        let item = self
+            .vtable
+            .item_by_name(name)
+            .map_err(|_| Error::Serde(format!("Struct {} not found in the vtable", name)))?;
+
+        self.items.push(DocBufValidatorItem {
+            item,
+            variant: None,
+            parent_field: self.current_field,
+            next_field_index: 0,
+        });
+
+        Ok(())
+    }
+
+    /// Finish validating the current struct or enum variant, restoring the parent field.
+    pub fn end_item(&mut self) -> Result<()> {
+        let item = self
+            .items
+            .pop()
+            .ok_or(Error::VTable(crate::vtable::Error::ItemNotFound))?;
+
+        self.current_field = item.parent_field;
+
+        Ok(())
+    }
+
+    /// Begin validating an enum variant, adding the variant to the path.
+    pub fn begin_variant(
+        &mut self,
+        name: &str,
+        variant_index: u32,
+        variant_name: &'static str,
+    ) -> Result<()> {
+        let item = self
+            .vtable
+            .item_by_name(name)
+            .map_err(|_| Error::Serde(format!("Enum {} not found in the vtable", name)))?;
+
+        let variant = match item {
+            VTableItem::Enum(vtable_enum) => {
+                let index = VTableVariantIndex::try_from(variant_index)
+                    .map_err(|_| crate::vtable::Error::VariantIndexExceedsMax(variant_index))?;
+
+                vtable_enum.variant_by_index(index)?
+            }
+            _ => return Err(Error::VTable(crate::vtable::Error::EnumNotFound)),
+        };
+
+        self.items.push(DocBufValidatorItem {
+            item,
+            variant: Some(variant),
+            parent_field: self.current_field,
+            next_field_index: 0,
+        });
+
+        self.path.push(ValidationPathSegment::Field(variant_name));
+
+        Ok(())
+    }
+
+    /// Finish validating the current enum variant.
+    pub fn end_variant(&mut self) -> Result<()> {
+        self.path.pop();
+        self.end_item()
+    }
+
+    /// Set the current field by name, from the current struct or enum variant.
+    pub fn set_field(&mut self, field_name: &'static str) -> Result<()> {
+        let item = self
+            .items
+            .last()
+            .ok_or(Error::VTable(crate::vtable::Error::ItemNotFound))?;
+
+        let field = match (item.item, item.variant) {
+            (_, Some(variant)) => variant.field_by_name(field_name),
+            (VTableItem::Struct(vtable_struct), None) => vtable_struct.field_by_name(field_name),
+            _ => Err(crate::vtable::Error::FieldNotFound),
+        }
+        .map_err(|_| Error::Serde(format!("Field not found: {}", field_name)))?;
+
+        self.current_field = Some(field);
+
+        Ok(())
+    }
+
+    /// Set the next positional field of the current tuple struct or enum variant.
+    pub fn next_field(&mut self) -> Result<&'static VTableField> {
+        let item = self
+            .items
+            .last_mut()
+            .ok_or(Error::VTable(crate::vtable::Error::ItemNotFound))?;
+
+        let field = match (item.item, item.variant) {
+            (_, Some(variant)) => variant.field_by_index(&item.next_field_index)?,
+            (VTableItem::Struct(vtable_struct), None) => {
+                vtable_struct.field_by_index(&item.next_field_index)?
+            }
+            _ => return Err(Error::VTable(crate::vtable::Error::FieldNotFound)),
+        };
+
+        item.next_field_index += 1;
+        self.current_field = Some(field);
+
+        Ok(field)
+    }
+
+    /// Validate the next element of the current list, array or tuple.
+    fn validate_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let index = self
+            .indexes
+            .last_mut()
+            .ok_or(Error::Serde("List not found".to_string()))?;
+
+        self.path.push(ValidationPathSegment::Index(*index));
+        *index += 1;
+
+        value.serialize(&mut *self)?;
+
+        self.path.pop();
+
+        Ok(())
+    }
+
+    /// Validate a struct or variant field, adding the field name to the path.
+    fn validate_field<T>(&mut self, name: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.path.push(ValidationPathSegment::Field(name));
+
+        value.serialize(&mut *self)?;
+
+        self.path.pop();
+
+        Ok(())
+    }
+}
+
+impl serde::ser::Serializer for &mut DocBufValidator {
+    type Ok = ();
+
+    type Error = Error;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Self> {
+        self.begin_struct(name)?;
+
+        Ok(self)
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
+        self.check_bool(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
+        self.check_bytes(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+        self.record_key(|| format!("{:?}", v));
+
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
+        self.check_numeric(NumericValue::F32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
+        self.check_numeric(NumericValue::F64(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
+        self.check_numeric(NumericValue::I8(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
+        self.check_numeric(NumericValue::I16(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
+        self.check_numeric(NumericValue::I32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
+        self.check_numeric(NumericValue::I64(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok> {
+        self.check_numeric(NumericValue::I128(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
+        self.check_str(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
+        self.check_numeric(NumericValue::U8(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
+        self.check_numeric(NumericValue::U16(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
+        self.check_numeric(NumericValue::U32(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
+        self.check_numeric(NumericValue::U64(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok> {
+        self.check_numeric(NumericValue::U128(v))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok> {
+        self.begin_variant(name, variant_index, variant)?;
+        self.end_variant()
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        // Newtypes of other crates, which are not in the vtable, are transparent.
+        if self.vtable.struct_by_name(name).is_err() {
+            return value.serialize(self);
+        }
+
+        // The inner value has the path of the newtype.
+        self.begin_struct(name)?;
+        self.next_field()?;
+
+        value.serialize(&mut *self)?;
+
+        self.end_item()
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.begin_variant(name, variant_index, variant)?;
+        self.next_field()?;
+
+        value.serialize(&mut *self)?;
+
+        self.end_variant()
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        self.check_collection(len.unwrap_or_default())?;
+
+        self.indexes.push(0);
+
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        let field = self.current_field()?;
+
+        let r#type = match &field.r#type {
+            VTableFieldType::Option(inner) => inner.as_ref(),
+            r#type => r#type,
+        };
+
+        // The length rule applies to the number of array elements of the field
+        // value, rather than the arrays nested in it.
+        if self.tuples.is_empty() {
+            if let VTableFieldType::Array { .. } = r#type {
+                let violations = field.rules.length_violations(len);
+                self.report_all(violations, || format!("{} elements", len));
+            }
+        }
+
+        self.tuples.push(field);
+        self.indexes.push(0);
+
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.begin_struct(name)?;
+
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        self.begin_variant(name, variant_index, variant)?;
+
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
+        self.check_collection(len.unwrap_or_default())?;
+
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        self.begin_variant(name, variant_index, variant)?;
+
+        Ok(self)
+    }
+}
+
+impl serde::ser::SerializeSeq for &mut DocBufValidator {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.validate_element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.indexes.pop();
+
+        Ok(())
+    }
+}
+
+impl serde::ser::SerializeTuple for &mut DocBufValidator {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.validate_element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.indexes.pop();
+        self.current_field = self.tuples.pop();
+
+        Ok(())
+    }
+}
+
+impl serde::ser::SerializeTupleStruct for &mut DocBufValidator {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let field = self.next_field()?;
+
+        self.validate_field(field.name.as_str(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.end_item()
+    }
+}
+
+impl serde::ser::SerializeTupleVariant for &mut DocBufValidator {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let field = self.next_field()?;
+
+        self.validate_field(field.name.as_str(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.end_variant()
+    }
+}
+
+impl serde::ser::SerializeMap for &mut DocBufValidator {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        // Violations of the key are reported at the path of the map, scalar
+        // keys are recorded as the path of the entry value.
+        self.map_key = Some(String::new());
+
+        key.serialize(&mut **self)?;
+
+        let key = self.map_key.take().unwrap_or_default();
+        self.path.push(ValidationPathSegment::Key(key));
+
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(&mut **self)?;
+
+        self.path.pop();
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        Ok(())
+    }
+}
+
+impl serde::ser::SerializeStruct for &mut DocBufValidator {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, name: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.set_field(name)?;
+
+        self.validate_field(name, value)
+    }
+
+    fn skip_field(&mut self, _name: &'static str) -> Result<()> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.end_item()
+    }
+}
+
+impl serde::ser::SerializeStructVariant for &mut DocBufValidator {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, name: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.set_field(name)?;
+
+        self.validate_field(name, value)
+    }
+
+    fn skip_field(&mut self, _name: &'static str) -> Result<()> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.end_variant()
+    }
+}
@@ -0,0 +1,387 @@
+mod codec;
+
+use crate::vtable::{Error, NumericValue, VTableFieldType};
+
+/// An untyped document value, decoded from a document buffer with its vtable
+/// alone, e.g. a vtable read with `VTable::from_file`, without the Rust type of
+/// the document.
+///
+/// Values are decoded with `VTable::decode_value` and encoded with
+/// `VTable::encode_value`. Struct fields, enum variant fields and map entries
+/// keep the order of the document buffer, so a decoded value encodes to the
+/// same buffer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A unit value, which is not encoded.
+    Unit,
+    Bool(bool),
+    /// An integer or float, of the field type, e.g. `NumericValue::U16` for `u16` fields.
+    Numeric(NumericValue),
+    Char(char),
+    /// A `String` or `&str` field.
+    String(String),
+    /// A byte array, e.g. a `Vec<u8>` field.
+    Bytes(Vec<u8>),
+    Uuid([u8; 16]),
+    Option(Option<Box<Value>>),
+    /// The elements of a `Vec` field.
+    List(Vec<Value>),
+    /// The elements of a fixed size array field.
+    Array(Vec<Value>),
+    /// The elements of a tuple field.
+    Tuple(Vec<Value>),
+    /// The entries of a `HashMap` field, in the order of the document buffer.
+    Map(Vec<(Value, Value)>),
+    /// A struct, with its fields in vtable order. Tuple and newtype struct
+    /// fields are named by their position, e.g. `0`, `1`, etc.
+    Struct {
+        name: String,
+        fields: Vec<(String, Value)>,
+    },
+    /// An enum variant, with its payload fields in vtable order.
+    Enum {
+        name: String,
+        variant: String,
+        fields: Vec<(String, Value)>,
+    },
+}
+
+impl Value {
+    /// Return the value of a struct or enum variant field by name.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.fields()?
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Return the mutable value of a struct or enum variant field by name.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Value> {
+        match self {
+            Value::Struct { fields, .. } | Value::Enum { fields, .. } => fields
+                .iter_mut()
+                .find(|(field, _)| field == name)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Return the fields of a struct or enum variant.
+    pub fn fields(&self) -> Option<&[(String, Value)]> {
+        match self {
+            Value::Struct { fields, .. } | Value::Enum { fields, .. } => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// Return the string of a `String` value.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Return the numeric value of an integer or float value.
+    pub fn as_numeric(&self) -> Option<&NumericValue> {
+        match self {
+            Value::Numeric(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Return the name of the kind of value, used in error messages.
+    pub fn kind(&self) -> String {
+        match self {
+            Value::Unit => "()".to_string(),
+            Value::Bool(_) => "bool".to_string(),
+            Value::Numeric(value) => value.field_type().to_string(),
+            Value::Char(_) => "char".to_string(),
+            Value::String(_) => "String".to_string(),
+            Value::Bytes(_) => "Bytes".to_string(),
+            Value::Uuid(_) => "Uuid".to_string(),
+            Value::Option(_) => "Option".to_string(),
+            Value::List(_) => "Vec".to_string(),
+            Value::Array(values) => format!("array of {} elements", values.len()),
+            Value::Tuple(values) => format!("tuple of {} elements", values.len()),
+            Value::Map(_) => "HashMap".to_string(),
+            Value::Struct { name, .. } => format!("struct {}", name),
+            Value::Enum { name, variant, .. } => format!("enum {}::{}", name, variant),
+        }
+    }
+
+    /// Returns true if the value is of the field type, disregarding the
+    /// values nested in the value.
+    pub fn is_type(&self, r#type: &VTableFieldType) -> bool {
+        match (r#type, self) {
+            (VTableFieldType::Unit, Value::Unit)
+            | (VTableFieldType::Bool, Value::Bool(_))
+            | (VTableFieldType::Char, Value::Char(_))
+            | (VTableFieldType::String | VTableFieldType::Str, Value::String(_))
+            | (VTableFieldType::Bytes, Value::Bytes(_))
+            | (VTableFieldType::Uuid, Value::Uuid(_))
+            | (VTableFieldType::Option(_), Value::Option(_))
+            | (VTableFieldType::Vec(_), Value::List(_))
+            | (VTableFieldType::HashMap { .. }, Value::Map(_)) => true,
+            (VTableFieldType::Array { len, .. }, Value::Array(values)) => values.len() == *len,
+            (VTableFieldType::Tuple(types), Value::Tuple(values)) => values.len() == types.len(),
+            (VTableFieldType::Struct(name), Value::Struct { name: value, .. })
+            | (VTableFieldType::Struct(name), Value::Enum { name: value, .. })
+            | (VTableFieldType::Enum(name), Value::Enum { name: value, .. }) => name == value,
+            (r#type, Value::Numeric(value)) => &value.field_type() == r#type,
+            _ => false,
+        }
+    }
+}
+
+/// Conversion of an untyped value into a Rust value, e.g. the value of a
+/// field path read with `VTable::get`.
+pub trait FromValue: Sized {
+    fn from_value(value: Value) -> Result<Self, Error>;
+}
+
+/// Return the error for a value that is not of the expected type.
+fn invalid_value(expected: &str, value: &Value) -> Error {
+    Error::InvalidValueType(expected.to_owned(), value.kind())
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<char> for Value {
+    fn from(value: char) -> Self {
+        Value::Char(value)
+    }
+}
+
+impl From<NumericValue> for Value {
+    fn from(value: NumericValue) -> Self {
+        Value::Numeric(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_owned())
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(value: Vec<u8>) -> Self {
+        Value::Bytes(value)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        Value::Option(value.map(|value| Box::new(value.into())))
+    }
+}
+
+macro_rules! numeric_value {
+    ($($type:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$type> for Value {
+                fn from(value: $type) -> Self {
+                    Value::Numeric(NumericValue::$variant(value))
+                }
+            }
+
+            impl FromValue for $type {
+                fn from_value(value: Value) -> Result<Self, Error> {
+                    match value {
+                        Value::Numeric(NumericValue::$variant(value)) => Ok(value),
+                        value => Err(invalid_value(stringify!($type), &value)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+numeric_value! {
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    u128 => U128,
+    usize => USIZE,
+    i8 => I8,
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    i128 => I128,
+    isize => ISIZE,
+    f32 => F32,
+    f64 => F64,
+}
+
+impl FromValue for Value {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        Ok(value)
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Bool(value) => Ok(value),
+            value => Err(invalid_value("bool", &value)),
+        }
+    }
+}
+
+impl FromValue for char {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Char(value) => Ok(value),
+            value => Err(invalid_value("char", &value)),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::String(value) => Ok(value),
+            value => Err(invalid_value("String", &value)),
+        }
+    }
+}
+
+impl FromValue for Vec<u8> {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Bytes(value) => Ok(value),
+            value => Err(invalid_value("Bytes", &value)),
+        }
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl FromValue for uuid::Uuid {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Uuid(value) => Ok(uuid::Uuid::from_bytes(value)),
+            value => Err(invalid_value("Uuid", &value)),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: Value) -> Result<Self, Error> {
+        match value {
+            Value::Option(None) => Ok(None),
+            Value::Option(Some(value)) => Ok(Some(T::from_value(*value)?)),
+            value => Err(invalid_value("Option", &value)),
+        }
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<uuid::Uuid> for Value {
+    fn from(value: uuid::Uuid) -> Self {
+        Value::Uuid(value.into_bytes())
+    }
+}
+
+/// Write the values separated by commas.
+fn write_list<T: std::fmt::Display>(
+    f: &mut std::fmt::Formatter<'_>,
+    values: impl IntoIterator<Item = T>,
+) -> std::fmt::Result {
+    for (index, value) in values.into_iter().enumerate() {
+        if index > 0 {
+            write!(f, ", ")?;
+        }
+
+        write!(f, "{}", value)?;
+    }
+
+    Ok(())
+}
+
+/// Write the fields of a struct or enum variant, e.g. ` { a: 1, b: "b" }`.
+fn write_fields(f: &mut std::fmt::Formatter<'_>, fields: &[(String, Value)]) -> std::fmt::Result {
+    if fields.is_empty() {
+        return Ok(());
+    }
+
+    write!(f, " {{ ")?;
+    write_list(
+        f,
+        fields
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value)),
+    )?;
+    write!(f, " }}")
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Unit => write!(f, "()"),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Numeric(value) => write!(f, "{}", value),
+            Value::Char(value) => write!(f, "{:?}", value),
+            Value::String(value) => write!(f, "{:?}", value),
+            Value::Bytes(value) => write!(f, "0x{}", hex::encode(value)),
+            Value::Uuid(value) => {
+                let hex = hex::encode(value);
+
+                write!(
+                    f,
+                    "{}-{}-{}-{}-{}",
+                    &hex[..8],
+                    &hex[8..12],
+                    &hex[12..16],
+                    &hex[16..20],
+                    &hex[20..]
+                )
+            }
+            Value::Option(None) => write!(f, "None"),
+            Value::Option(Some(value)) => write!(f, "Some({})", value),
+            Value::List(values) | Value::Array(values) => {
+                write!(f, "[")?;
+                write_list(f, values)?;
+                write!(f, "]")
+            }
+            Value::Tuple(values) => {
+                write!(f, "(")?;
+                write_list(f, values)?;
+                write!(f, ")")
+            }
+            Value::Map(entries) => {
+                write!(f, "{{")?;
+                write_list(
+                    f,
+                    entries
+                        .iter()
+                        .map(|(key, value)| format!("{}: {}", key, value)),
+                )?;
+                write!(f, "}}")
+            }
+            Value::Struct { name, fields } => {
+                write!(f, "{}", name)?;
+                write_fields(f, fields)
+            }
+            Value::Enum {
+                name,
+                variant,
+                fields,
+            } => {
+                write!(f, "{}::{}", name, variant)?;
+                write_fields(f, fields)
+            }
+        }
+    }
+}
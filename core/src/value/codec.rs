@@ -0,0 +1,402 @@
+use super::Value;
+
+#[cfg(feature = "validate")]
+use crate::traits::DocBufValidateField;
+use crate::vtable::*;
+use crate::{
+    error::Error,
+    traits::{DocBufDecodeField, DocBufEncodeField},
+    Result,
+};
+
+/// Returns true if the type is the type of the field value, rather than a type
+/// nested in it, e.g. the element type of a list. Options are disregarded.
+#[inline]
+fn is_field_type(field: &VTableField, r#type: &VTableFieldType) -> bool {
+    match &field.r#type {
+        VTableFieldType::Option(inner) => {
+            std::ptr::eq(inner.as_ref(), r#type) || std::ptr::eq(&field.r#type, r#type)
+        }
+        field_type => std::ptr::eq(field_type, r#type),
+    }
+}
+
+/// Return the error for a value that is not of the field type.
+#[inline]
+fn invalid_value(r#type: &VTableFieldType, value: &Value) -> Error {
+    Error::VTable(crate::vtable::Error::InvalidValueType(
+        r#type.to_string(),
+        value.kind(),
+    ))
+}
+
+/// Decode an integer or float of the type, with the encoding of the field.
+#[inline]
+fn decode_numeric(
+    field: &VTableField,
+    r#type: &VTableFieldType,
+    reader: &mut DocBufReader<'_>,
+) -> Result<NumericValue> {
+    let value = match r#type {
+        VTableFieldType::U8 => NumericValue::U8(field.decode(reader)?),
+        VTableFieldType::U16 => NumericValue::U16(field.decode(reader)?),
+        VTableFieldType::U32 => NumericValue::U32(field.decode(reader)?),
+        VTableFieldType::U64 => NumericValue::U64(field.decode(reader)?),
+        VTableFieldType::U128 => NumericValue::U128(field.decode(reader)?),
+        VTableFieldType::USIZE => NumericValue::USIZE(field.decode(reader)?),
+        VTableFieldType::I8 => NumericValue::I8(field.decode(reader)?),
+        VTableFieldType::I16 => NumericValue::I16(field.decode(reader)?),
+        VTableFieldType::I32 => NumericValue::I32(field.decode(reader)?),
+        VTableFieldType::I64 => NumericValue::I64(field.decode(reader)?),
+        VTableFieldType::I128 => NumericValue::I128(field.decode(reader)?),
+        VTableFieldType::ISIZE => NumericValue::ISIZE(field.decode(reader)?),
+        VTableFieldType::F32 => NumericValue::F32(field.decode(reader)?),
+        VTableFieldType::F64 => NumericValue::F64(field.decode(reader)?),
+        r#type => {
+            return Err(Error::VTable(crate::vtable::Error::DocBufDecodeFieldType(
+                Box::new(field.with_type(r#type.to_owned())),
+            )))
+        }
+    };
+
+    Ok(value)
+}
+
+impl VTable {
+    /// Decode a document buffer into an untyped value, with the vtable alone,
+    /// e.g. a vtable read with `VTable::from_file`.
+    ///
+    /// The field rules of the vtable are checked for each decoded value, and
+    /// the buffer must be consumed entirely.
+    pub fn decode_value(&self, buffer: &[u8]) -> Result<Value> {
+        let mut reader = DocBufReader::new(buffer);
+
+        let value = self.decode_item_value(self.root_item()?, &mut reader)?;
+
+        match reader.is_empty() {
+            true => Ok(value),
+            false => Err(Error::Serde("Unhandled trailing bytes".to_string())),
+        }
+    }
+
+    /// Encode an untyped value of the root item of the vtable into a document
+    /// buffer, checking the field rules of the vtable as `DocBuf::to_docbuf` does.
+    ///
+    /// Every field of the vtable must have a value of the field type, e.g. a
+    /// `Value::Numeric(NumericValue::U16(_))` for `u16` fields.
+    pub fn encode_value(&self, value: &Value) -> Result<Vec<u8>> {
+        let mut buffer = Vec::with_capacity(self.avg_size());
+
+        self.encode_item_value(self.root_item()?, value, &mut buffer)?;
+
+        Ok(buffer)
+    }
+
+    /// Decode a struct, or an enum variant and its payload fields.
+    fn decode_item_value(&self, item: &VTableItem, reader: &mut DocBufReader<'_>) -> Result<Value> {
+        match item {
+            VTableItem::Struct(vtable_struct) => Ok(Value::Struct {
+                name: vtable_struct.name.to_owned(),
+                fields: self.decode_fields_value(&vtable_struct.fields, reader)?,
+            }),
+            VTableItem::Enum(vtable_enum) => {
+                let variant = vtable_enum.variant_by_index(reader.read_u8()?)?;
+
+                Ok(Value::Enum {
+                    name: vtable_enum.name.to_owned(),
+                    variant: variant.name.to_owned(),
+                    fields: self.decode_fields_value(&variant.fields, reader)?,
+                })
+            }
+        }
+    }
+
+    /// Decode the fields of a struct or enum variant, in vtable order.
+    fn decode_fields_value(
+        &self,
+        fields: &VTableFields,
+        reader: &mut DocBufReader<'_>,
+    ) -> Result<Vec<(String, Value)>> {
+        fields
+            .iter()
+            .map(|field| {
+                let value = self.decode_field_value(field, &field.r#type, reader)?;
+
+                Ok((field.name.to_owned(), value))
+            })
+            .collect()
+    }
+
+    /// Decode a value of the type, which is the field type or a type nested
+    /// in it, e.g. the element type of a list.
+    pub(crate) fn decode_field_value(
+        &self,
+        field: &VTableField,
+        r#type: &VTableFieldType,
+        reader: &mut DocBufReader<'_>,
+    ) -> Result<Value> {
+        let value = match r#type {
+            VTableFieldType::Struct(name) | VTableFieldType::Enum(name) => {
+                self.decode_item_value(self.item_by_name(name)?, reader)?
+            }
+            VTableFieldType::Option(inner) => match field.decode_option(reader)? {
+                None => Value::Option(None),
+                Some(()) => Value::Option(Some(Box::new(
+                    self.decode_field_value(field, inner, reader)?,
+                ))),
+            },
+            VTableFieldType::Vec(elem) => {
+                let len = field.decode_len(reader)?;
+
+                #[cfg(feature = "validate")]
+                field.rules.check_collection(len)?;
+
+                Value::List(
+                    (0..len)
+                        .map(|_| self.decode_field_value(field, elem, reader))
+                        .collect::<Result<_>>()?,
+                )
+            }
+            VTableFieldType::HashMap { key, value } => {
+                let len = field.decode_len(reader)?;
+
+                #[cfg(feature = "validate")]
+                field.rules.check_collection(len)?;
+
+                Value::Map(
+                    (0..len)
+                        .map(|_| {
+                            Ok((
+                                self.decode_field_value(field, key, reader)?,
+                                self.decode_field_value(field, value, reader)?,
+                            ))
+                        })
+                        .collect::<Result<_>>()?,
+                )
+            }
+            VTableFieldType::Array { elem, len } => {
+                // The length rule applies to the number of array elements
+                #[cfg(feature = "validate")]
+                if is_field_type(field, r#type) {
+                    field.rules.check_length(*len)?;
+                }
+
+                Value::Array(
+                    (0..*len)
+                        .map(|_| self.decode_field_value(field, elem, reader))
+                        .collect::<Result<_>>()?,
+                )
+            }
+            VTableFieldType::Tuple(types) => Value::Tuple(
+                types
+                    .iter()
+                    .map(|r#type| self.decode_field_value(field, r#type, reader))
+                    .collect::<Result<_>>()?,
+            ),
+            VTableFieldType::String | VTableFieldType::Str => {
+                let len = field.decode_bytes_len(reader)?;
+                let data = reader.read_str(len)?;
+
+                #[cfg(feature = "validate")]
+                field.rules.validate(&data)?;
+
+                Value::String(data.to_owned())
+            }
+            VTableFieldType::Bytes => {
+                let len = field.decode_bytes_len(reader)?;
+                let data = reader.read_bytes(len)?;
+
+                #[cfg(feature = "validate")]
+                field.rules.validate(&data)?;
+
+                Value::Bytes(data.to_vec())
+            }
+            VTableFieldType::Uuid => Value::Uuid(reader.read_array()?),
+            VTableFieldType::Bool => {
+                let data = field.decode(reader)?;
+
+                #[cfg(feature = "validate")]
+                field.rules.validate(&data)?;
+
+                Value::Bool(data)
+            }
+            VTableFieldType::Char => Value::Char(field.decode(reader)?),
+            VTableFieldType::Unit => Value::Unit,
+            r#type => {
+                let data = decode_numeric(field, r#type, reader)?;
+
+                #[cfg(feature = "validate")]
+                field.rules.validate(&data)?;
+
+                Value::Numeric(data)
+            }
+        };
+
+        Ok(value)
+    }
+
+    /// Encode a struct, or an enum variant discriminant and its payload fields.
+    fn encode_item_value(
+        &self,
+        item: &VTableItem,
+        value: &Value,
+        buffer: &mut Vec<u8>,
+    ) -> Result<()> {
+        match (item, value) {
+            (VTableItem::Struct(vtable_struct), Value::Struct { name, fields })
+                if *name == vtable_struct.name =>
+            {
+                self.encode_fields_value(&vtable_struct.fields, fields, buffer)
+            }
+            (
+                VTableItem::Enum(vtable_enum),
+                Value::Enum {
+                    name,
+                    variant,
+                    fields,
+                },
+            ) if *name == vtable_enum.name => {
+                let variant = vtable_enum.variant_by_name(variant)?;
+
+                // Encode the variant discriminant
+                buffer.push(variant.index);
+
+                self.encode_fields_value(&variant.fields, fields, buffer)
+            }
+            (item, value) => Err(Error::VTable(crate::vtable::Error::InvalidValueType(
+                item.name().to_owned(),
+                value.kind(),
+            ))),
+        }
+    }
+
+    /// Encode the fields of a struct or enum variant, in vtable order.
+    fn encode_fields_value(
+        &self,
+        fields: &VTableFields,
+        values: &[(String, Value)],
+        buffer: &mut Vec<u8>,
+    ) -> Result<()> {
+        if let Some((name, _)) = values
+            .iter()
+            .find(|(name, _)| fields.find_field_by_name(name).is_none())
+        {
+            return Err(Error::VTable(crate::vtable::Error::UnknownFieldValue(
+                name.to_owned(),
+            )));
+        }
+
+        for field in fields.iter() {
+            let value = values
+                .iter()
+                .find(|(name, _)| *name == field.name)
+                .map(|(_, value)| value)
+                .ok_or_else(|| crate::vtable::Error::MissingFieldValue(field.name.to_owned()))?;
+
+            self.encode_field_value(field, &field.r#type, value, buffer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Encode a value of the type, which is the field type or a type nested
+    /// in it, e.g. the element type of a list.
+    pub(crate) fn encode_field_value(
+        &self,
+        field: &VTableField,
+        r#type: &VTableFieldType,
+        value: &Value,
+        buffer: &mut Vec<u8>,
+    ) -> Result<()> {
+        match (r#type, value) {
+            (VTableFieldType::Struct(name) | VTableFieldType::Enum(name), value) => {
+                self.encode_item_value(self.item_by_name(name)?, value, buffer)?;
+            }
+            (VTableFieldType::Option(_), Value::Option(None)) => {
+                field.encode_none(buffer)?;
+            }
+            (VTableFieldType::Option(inner), Value::Option(Some(value))) => {
+                self.encode_field_value(field, inner, value, buffer)?;
+            }
+            (VTableFieldType::Vec(elem), Value::List(values)) => {
+                // Check for the not empty rule of lists
+                #[cfg(feature = "validate")]
+                field.rules.check_collection(values.len())?;
+
+                field.encode_array_start(values.len(), buffer)?;
+
+                for value in values.iter() {
+                    self.encode_field_value(field, elem, value, buffer)?;
+                }
+            }
+            (
+                VTableFieldType::HashMap {
+                    key,
+                    value: value_type,
+                },
+                Value::Map(entries),
+            ) => {
+                // Check for the not empty rule of maps
+                #[cfg(feature = "validate")]
+                field.rules.check_collection(entries.len())?;
+
+                field.encode_map_start(entries.len(), buffer)?;
+
+                for (entry_key, entry_value) in entries.iter() {
+                    self.encode_field_value(field, key, entry_key, buffer)?;
+                    self.encode_field_value(field, value_type, entry_value, buffer)?;
+                }
+            }
+            (VTableFieldType::Array { elem, len }, Value::Array(values))
+                if values.len() == *len =>
+            {
+                // The length rule applies to the number of array elements
+                #[cfg(feature = "validate")]
+                if is_field_type(field, r#type) {
+                    field.rules.check_length(*len)?;
+                }
+
+                for value in values.iter() {
+                    self.encode_field_value(field, elem, value, buffer)?;
+                }
+            }
+            (VTableFieldType::Tuple(types), Value::Tuple(values))
+                if values.len() == types.len() =>
+            {
+                for (r#type, value) in types.iter().zip(values.iter()) {
+                    self.encode_field_value(field, r#type, value, buffer)?;
+                }
+            }
+            (VTableFieldType::String | VTableFieldType::Str, Value::String(data)) => {
+                field.encode(&data.as_str(), buffer)?;
+            }
+            (VTableFieldType::Bytes, Value::Bytes(data)) => {
+                #[cfg(feature = "validate")]
+                field.rules.validate(&data.as_slice())?;
+
+                field.encode_array_start(data.len(), buffer)?;
+                buffer.extend_from_slice(data);
+            }
+            (VTableFieldType::Uuid, Value::Uuid(data)) => {
+                #[cfg(feature = "validate")]
+                field.rules.validate(&data.as_slice())?;
+
+                buffer.extend_from_slice(data);
+            }
+            (VTableFieldType::Bool, Value::Bool(data)) => {
+                field.encode(data, buffer)?;
+            }
+            (VTableFieldType::Char, Value::Char(data)) => {
+                field.encode(data, buffer)?;
+            }
+            // Unit values are not encoded
+            (VTableFieldType::Unit, Value::Unit) => (),
+            (r#type, Value::Numeric(data)) if data.field_type() == *r#type => {
+                field.encode(data, buffer)?;
+            }
+            (r#type, value) => return Err(invalid_value(r#type, value)),
+        }
+
+        Ok(())
+    }
+}
@@ -1,11 +1,23 @@
 mod bufmap;
+mod canonical;
+mod compat;
+mod encoding;
 mod field;
 mod item;
+mod legacy;
+mod patch;
+mod path;
+mod reader;
 mod table;
 
 // pub use bufmap::*;
+pub use compat::*;
+pub use encoding::*;
 pub use field::*;
 pub use item::*;
+pub use patch::*;
+pub use path::*;
+pub use reader::*;
 pub use table::*;
 
 #[derive(thiserror::Error, Debug)]
@@ -18,6 +30,12 @@ pub enum Error {
     ItemNotFound,
     #[error("Struct Not Found")]
     StructNotFound,
+    #[error("Enum Not Found")]
+    EnumNotFound,
+    #[error("Enum Variant Not Found")]
+    VariantNotFound,
+    #[error("Enum variant index exceeds max: {0}")]
+    VariantIndexExceedsMax(u32),
     #[error("Field Not Found")]
     FieldNotFound,
     #[error(transparent)]
@@ -34,6 +52,20 @@ pub enum Error {
     FieldRulesRegex(String),
     #[error("Field Rules Invalid Value: {0}")]
     FieldRulesValue(String),
+    #[error("Field Rules One Of: {0}")]
+    FieldRulesOneOf(String),
+    #[error("Field Rules Invalid Format: {0}")]
+    FieldRulesFormat(String),
+    #[error("Field Rules Digits: {0}")]
+    FieldRulesDigits(String),
+    #[error("Field Rules Whitespace: {0}")]
+    FieldRulesWhitespace(String),
+    #[error("Field Rules Not Empty: {0}")]
+    FieldRulesNotEmpty(String),
+    #[error("Document Validation: {0}")]
+    DocumentValidation(String),
+    #[error("Field Validation: {field}: {message}")]
+    FieldValidation { field: String, message: String },
     #[error("Invalid field type for validation: {0}")]
     InvalidValidationType(String),
     #[error("Invalid vtable ID: {0}")]
@@ -42,16 +74,24 @@ pub enum Error {
     VTableBorrowMut,
     #[error("Unable to parse encoded data")]
     FailedToParseData,
+    #[error("Length prefix {0} exceeds the remaining {1} bytes of the buffer")]
+    LengthExceedsBuffer(usize, usize),
     #[error("Map entries exceeds max: {0}")]
     MapEntriesExceedsMax(usize),
     #[error("Array length exceeds max: {0}")]
     ArrayElementsExceedsMax(usize),
     #[error("Invalid docbuf map field type, expected: {0}")]
     DocBufMapInvalidFieldType(String),
+    #[error("Failed to map docbuf field value: {0}")]
+    DocBufMapValue(String),
+    #[error("Docbuf patch fields {0} and {1} overlap")]
+    DocBufPatchOverlap(String, String),
+    #[error("Field {0} is NaN, which has no canonical encoding")]
+    CanonicalNaN(String),
     #[error("Failed to encode docbuf map field type: {0}")]
     DocBufEncodeFieldType(String),
     #[error("Failed to decode docbuf map field type: {0}")]
-    DocBufDecodeFieldType(VTableField),
+    DocBufDecodeFieldType(Box<VTableField>),
     #[error(transparent)]
     Hex(#[from] hex::FromHexError),
     #[error(transparent)]
@@ -63,10 +103,50 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("Failed to read vtable from file: {0}")]
     ReadVTableBuffer(String),
+    #[error("Unsupported vtable format: missing magic bytes, written before the format was versioned")]
+    UnversionedVTableFormat,
+    #[error("Unsupported vtable format version: {0}")]
+    UnsupportedVTableFormat(u8),
+    #[error("Unknown Field Format: {0}")]
+    UnknownFieldFormat(u8),
+    #[error("Unknown Field Whitespace: {0}")]
+    UnknownWhitespace(u8),
     #[error("Unknown VTable Field Type: {0}")]
     UnknownFieldType(u8),
+    #[error("Failed to parse VTable Field Type: {0}")]
+    InvalidFieldType(String),
     #[error("Invalid Numeric Value Type: {0}")]
     InvalidNumericValueType(u8),
+    #[error("Unknown VTable Item Kind: {0}")]
+    UnknownItemKind(u8),
+    #[error("Unknown Enum Variant Kind: {0}")]
+    UnknownVariantKind(u8),
+    #[error("Unknown VTable Encoding: {0}")]
+    UnknownEncoding(u8),
+    #[error("Invalid value for field type, expected: {0}; found: {1}")]
+    InvalidValueType(String, String),
+    #[error("Missing value for field: {0}")]
+    MissingFieldValue(String),
+    #[error("Unknown field in value: {0}")]
+    UnknownFieldValue(String),
+    #[error("Invalid field path: {0}")]
+    FieldPathInvalid(String),
+    #[error("Unknown field in field path {path}: {field}")]
+    FieldPathUnknownField { path: String, field: String },
+    #[error("Index out of bounds in field path {path}: {index}; length: {len}")]
+    FieldPathIndexOutOfBounds {
+        path: String,
+        index: String,
+        len: usize,
+    },
+    #[error("Map key not found in field path {path}: {key}")]
+    FieldPathKeyNotFound { path: String, key: String },
+    #[error("Optional value is none in field path: {0}")]
+    FieldPathNone(String),
+    #[error("Field path {path} does not match the field type: {found}")]
+    FieldPathInvalidType { path: String, found: String },
+    #[error("Incompatible vtable changes: {0}")]
+    IncompatibleVTable(VTableCompatibility),
 }
 
 #[cfg(test)]
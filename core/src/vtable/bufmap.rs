@@ -1,64 +1,252 @@
 use super::*;
 
-use crate::traits::{DocBufMap, DocBufValidateField};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Range;
+
+use ::serde::{de::DeserializeOwned, Serialize};
+
+use crate::serde::{de::DocBufDeserializer, ser::DocBufSerializer};
+use crate::traits::{DocBuf, DocBufDecodeField, DocBufEncodeField, DocBufMap};
+
+/// Replace a length prefixed value, i.e. a string or byte array, and its length
+/// prefix. The size of a compact length prefix may change with the length,
+/// shifting the start of the value.
+#[inline]
+fn replace_len_prefixed(
+    field: &VTableField,
+    data: &[u8],
+    offset: VTableFieldOffset,
+    buffer: &mut Vec<u8>,
+    offsets: &mut VTableFieldOffsets,
+) -> Result<VTableFieldOffset, Error> {
+    let prefix_len = field.encoding.len_size(offset.len());
+    let prefix_start = offset.1.start - prefix_len;
+
+    let mut prefix = Vec::with_capacity(prefix_len);
+    field.encoding.write_len(data.len(), &mut prefix);
+
+    let start = prefix_start + prefix.len();
+
+    buffer.splice(
+        prefix_start..offset.1.end,
+        prefix.iter().chain(data.iter()).cloned(),
+    );
+
+    offsets.resize(
+        offset.range().start,
+        VTableFieldOffsetDiff::new(prefix_len + offset.len(), prefix.len() + data.len()),
+    );
+
+    let new_offset = VTableFieldOffset(offset.0, start..(start + data.len()));
+
+    if let Some(existing_offset) = offsets
+        .as_mut()
+        .iter_mut()
+        .find(|existing_offset| existing_offset.0 == offset.0)
+    {
+        existing_offset.1 = new_offset.range();
+    }
 
-impl DocBufMap<String> for &'static VTable {
-    #[inline]
-    fn docbuf_map(&self, buffer: &[u8], offset: &VTableFieldOffset) -> Result<String, Error> {
-        let field = self.get_field_by_offset_index(offset.0)?;
+    Ok(new_offset)
+}
 
-        match field.r#type {
-            VTableFieldType::String => {
-                let data = String::from_utf8(buffer[offset.range()].to_vec())?;
+/// Replace a value that is not length prefixed, i.e. a fixed size array or tuple.
+/// The size of the value may change with compact encoded elements.
+#[inline]
+fn replace_unprefixed(
+    data: &[u8],
+    offset: VTableFieldOffset,
+    buffer: &mut Vec<u8>,
+    offsets: &mut VTableFieldOffsets,
+) -> Result<VTableFieldOffset, Error> {
+    let start = offset.1.start;
+
+    buffer.splice(offset.range(), data.iter().cloned());
+
+    offsets.resize(start, VTableFieldOffsetDiff::new(offset.len(), data.len()));
+
+    let new_offset = VTableFieldOffset(offset.0, start..(start + data.len()));
+
+    if let Some(existing_offset) = offsets
+        .as_mut()
+        .iter_mut()
+        .find(|existing_offset| existing_offset.0 == offset.0)
+    {
+        existing_offset.1 = new_offset.range();
+    }
 
-                Ok(data)
+    Ok(new_offset)
+}
+
+/// Return the vtable error of a failure to serialize or deserialize a field value.
+#[inline]
+pub(crate) fn map_error(error: crate::error::Error) -> Error {
+    match error {
+        crate::error::Error::VTable(error) => error,
+        error => Error::DocBufMapValue(error.to_string()),
+    }
+}
+
+impl VTable {
+    /// Return the range of the encoded value of a field, given the offset of the
+    /// field. The offsets of strings, byte arrays, lists and maps start after
+    /// their length prefix, and the offsets of `None` values are empty, after
+    /// the null value.
+    pub(crate) fn encoded_range(
+        &self,
+        field: &VTableField,
+        r#type: &VTableFieldType,
+        buffer: &[u8],
+        range: Range<usize>,
+    ) -> Result<Range<usize>, Error> {
+        match r#type {
+            VTableFieldType::Option(inner) => {
+                let null_start = range.start.saturating_sub(NULL_FIELD.len());
+
+                match range.is_empty() && buffer.get(null_start..range.start) == Some(&NULL_FIELD) {
+                    true => Ok(null_start..range.end),
+                    false => self.encoded_range(field, inner, buffer, range),
+                }
             }
-            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+            VTableFieldType::String | VTableFieldType::Str | VTableFieldType::Bytes => {
+                Ok((range.start - field.encoding.len_size(range.len()))..range.end)
+            }
+            VTableFieldType::Vec(elem) => {
+                let len = self.num_elements(field, &[elem], &buffer[range.clone()])?;
+
+                Ok((range.start - field.encoding.len_size(len))..range.end)
+            }
+            VTableFieldType::HashMap { key, value } => {
+                let len = self.num_elements(field, &[key, value], &buffer[range.clone()])?;
+
+                Ok((range.start - field.encoding.len_size(len))..range.end)
+            }
+            _ => Ok(range),
         }
     }
 
-    #[inline]
-    fn docbuf_map_replace(
+    /// Count the elements of an encoded list, or the entries of an encoded map,
+    /// since their length prefix is the number of elements rather than bytes.
+    fn num_elements(
         &self,
-        new_value: &String,
-        offset: VTableFieldOffset,
-        buffer: &mut Vec<u8>,
-        offsets: &mut VTableFieldOffsets,
-    ) -> Result<VTableFieldOffset, Error> {
-        let field = self.get_field_by_offset_index(offset.0)?;
+        field: &VTableField,
+        types: &[&VTableFieldType],
+        data: &[u8],
+    ) -> Result<usize, Error> {
+        let mut reader = DocBufReader::new(data);
+        let mut len = 0;
+
+        while !reader.is_empty() {
+            for r#type in types {
+                self.skip_value(field, r#type, &mut reader)
+                    .map_err(map_error)?;
+            }
 
-        match field.r#type {
-            VTableFieldType::String => {
-                let data = new_value.as_bytes();
+            len += 1;
+        }
+
+        Ok(len)
+    }
 
-                // Update the length of the data in the buffer
-                let data_length = (data.len() as u32).to_le_bytes();
-                buffer.splice(offset.1.start - 4..offset.1.start, data_length.into_iter());
+    /// Return the indexes of the structs nested in a field of the type, whose
+    /// fields have their own offsets.
+    pub(crate) fn nested_items(&self, r#type: &VTableFieldType, items: &mut Vec<VTableItemIndex>) {
+        match r#type {
+            VTableFieldType::Struct(name) => {
+                if let Ok(vtable_struct) = self.struct_by_name(name) {
+                    items.push(vtable_struct.item_index);
+
+                    for field in vtable_struct.fields.iter() {
+                        self.nested_items(&field.r#type, items);
+                    }
+                }
+            }
+            VTableFieldType::Option(inner) => self.nested_items(inner, items),
+            _ => (),
+        }
+    }
+}
 
-                buffer.splice(offset.range(), data.iter().cloned());
+/// Deserialize the value of a field, from its encoded range in the buffer.
+#[inline]
+fn map_value<T: DeserializeOwned>(
+    vtable: &VTable,
+    field: &VTableField,
+    buffer: &[u8],
+    offset: &VTableFieldOffset,
+) -> Result<T, Error> {
+    let range = vtable.encoded_range(field, &field.r#type, buffer, offset.range())?;
 
-                offsets.resize(
-                    offset.range().start,
-                    VTableFieldOffsetDiff::new(offset.len(), data.len()),
-                );
+    let mut deserializer = DocBufDeserializer::new(vtable, &buffer[range]).map_err(map_error)?;
+    deserializer.set_field(field);
 
-                Ok(VTableFieldOffset(
-                    offset.0,
-                    offset.1.start..(offset.1.start + data.len()),
-                ))
-            }
-            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+    T::deserialize(&mut deserializer).map_err(map_error)
+}
+
+/// Serialize the new value of a field, checking the field rules, and replace
+/// the encoded value in the buffer. The offsets following the value are moved,
+/// and the offsets of the value and of the struct fields nested in it are
+/// replaced with the offsets of the new value.
+#[inline]
+fn replace_value<T: Serialize>(
+    vtable: &'static VTable,
+    field: &'static VTableField,
+    new_value: &T,
+    offset: VTableFieldOffset,
+    buffer: &mut Vec<u8>,
+    offsets: &mut VTableFieldOffsets,
+) -> Result<VTableFieldOffset, Error> {
+    let range = vtable.encoded_range(field, &field.r#type, buffer, offset.range())?;
+
+    let mut data = Vec::with_capacity(range.len());
+
+    let mut serializer = DocBufSerializer::new(vtable, &mut data);
+    serializer.current_field = Some(field);
+    new_value.serialize(&mut serializer).map_err(map_error)?;
+
+    let new_offsets = serializer.offsets;
+
+    buffer.splice(range.clone(), data.iter().cloned());
+
+    offsets.resize(
+        range.start,
+        VTableFieldOffsetDiff::new(range.len(), data.len()),
+    );
+
+    let mut nested_items = Vec::new();
+    vtable.nested_items(&field.r#type, &mut nested_items);
+
+    offsets.as_mut().retain(|existing_offset| {
+        existing_offset.0 != offset.0 && !nested_items.contains(&existing_offset.0 .0)
+    });
+
+    let mut new_offset = field.as_offset(range.start..(range.start + data.len()));
+
+    for value_offset in new_offsets.as_ref().iter() {
+        let value_offset = VTableFieldOffset(
+            value_offset.0,
+            (range.start + value_offset.1.start)..(range.start + value_offset.1.end),
+        );
+
+        if value_offset.0 == offset.0 {
+            new_offset = value_offset.clone();
         }
+
+        offsets.push(value_offset);
     }
+
+    Ok(new_offset)
 }
 
-impl DocBufMap<Vec<u8>> for &'static VTable {
+impl DocBufMap<String> for &'static VTable {
     #[inline]
-    fn docbuf_map(&self, buffer: &[u8], offset: &VTableFieldOffset) -> Result<Vec<u8>, Error> {
+    fn docbuf_map(&self, buffer: &[u8], offset: &VTableFieldOffset) -> Result<String, Error> {
         let field = self.get_field_by_offset_index(offset.0)?;
+
         match field.r#type {
-            VTableFieldType::Bytes => {
-                let data = buffer[offset.range()].to_vec();
+            VTableFieldType::String => {
+                let data = String::from_utf8(buffer[offset.range()].to_vec())?;
 
                 Ok(data)
             }
@@ -69,32 +257,16 @@ impl DocBufMap<Vec<u8>> for &'static VTable {
     #[inline]
     fn docbuf_map_replace(
         &self,
-        new_value: &Vec<u8>,
+        new_value: &String,
         offset: VTableFieldOffset,
         buffer: &mut Vec<u8>,
         offsets: &mut VTableFieldOffsets,
     ) -> Result<VTableFieldOffset, Error> {
         let field = self.get_field_by_offset_index(offset.0)?;
 
-        field.rules.validate(new_value)?;
-
         match field.r#type {
-            VTableFieldType::Bytes => {
-                // Update the length of the data in the buffer
-                let data_length = (new_value.len() as u32).to_le_bytes();
-                buffer.splice(offset.1.start - 4..offset.1.start, data_length.into_iter());
-
-                buffer.splice(offset.range(), new_value.iter().cloned());
-
-                offsets.resize(
-                    offset.range().start,
-                    VTableFieldOffsetDiff::new(offset.len(), new_value.len()),
-                );
-
-                Ok(VTableFieldOffset(
-                    offset.0,
-                    offset.1.start..(offset.1.start + new_value.len()),
-                ))
+            VTableFieldType::String => {
+                replace_len_prefixed(field, new_value.as_bytes(), offset, buffer, offsets)
             }
             _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
         }
@@ -119,12 +291,17 @@ impl DocBufMap<u8> for &'static VTable {
     #[inline]
     fn docbuf_map_replace(
         &self,
-        _new_value: &u8,
-        _offset: VTableFieldOffset,
-        _buffer: &mut Vec<u8>,
-        _offsets: &mut VTableFieldOffsets,
+        new_value: &u8,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
     ) -> Result<VTableFieldOffset, Error> {
-        unimplemented!("docbuf_map_replace")
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::U8 => replace_value(self, field, new_value, offset, buffer, offsets),
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
     }
 }
 
@@ -134,13 +311,7 @@ impl DocBufMap<u16> for &'static VTable {
         let field = self.get_field_by_offset_index(offset.0)?;
 
         match field.r#type {
-            VTableFieldType::U16 => {
-                let bytes = &buffer[offset.range()];
-
-                let data = u16::from_le_bytes([bytes[0], bytes[1]]);
-
-                Ok(data)
-            }
+            VTableFieldType::U16 => field.decode(&mut DocBufReader::new(&buffer[offset.range()])),
             _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
         }
     }
@@ -148,12 +319,17 @@ impl DocBufMap<u16> for &'static VTable {
     #[inline]
     fn docbuf_map_replace(
         &self,
-        _new_value: &u16,
-        _offset: VTableFieldOffset,
-        _buffer: &mut Vec<u8>,
-        _offsets: &mut VTableFieldOffsets,
+        new_value: &u16,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
     ) -> Result<VTableFieldOffset, Error> {
-        unimplemented!("docbuf_map_replace")
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::U16 => replace_value(self, field, new_value, offset, buffer, offsets),
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
     }
 }
 
@@ -163,13 +339,7 @@ impl DocBufMap<u32> for &'static VTable {
         let field = self.get_field_by_offset_index(offset.0)?;
 
         match field.r#type {
-            VTableFieldType::U32 => {
-                let bytes = &buffer[offset.range()];
-
-                let data = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
-
-                Ok(data)
-            }
+            VTableFieldType::U32 => field.decode(&mut DocBufReader::new(&buffer[offset.range()])),
             _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
         }
     }
@@ -177,12 +347,17 @@ impl DocBufMap<u32> for &'static VTable {
     #[inline]
     fn docbuf_map_replace(
         &self,
-        _new_value: &u32,
-        _offset: VTableFieldOffset,
-        _buffer: &mut Vec<u8>,
-        _offsets: &mut VTableFieldOffsets,
+        new_value: &u32,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
     ) -> Result<VTableFieldOffset, Error> {
-        unimplemented!("docbuf_map_replace")
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::U32 => replace_value(self, field, new_value, offset, buffer, offsets),
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
     }
 }
 
@@ -192,15 +367,7 @@ impl DocBufMap<u64> for &'static VTable {
         let field = self.get_field_by_offset_index(offset.0)?;
 
         match field.r#type {
-            VTableFieldType::U64 => {
-                let bytes = &buffer[offset.range()];
-
-                let data = u64::from_le_bytes([
-                    bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
-                ]);
-
-                Ok(data)
-            }
+            VTableFieldType::U64 => field.decode(&mut DocBufReader::new(&buffer[offset.range()])),
             _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
         }
     }
@@ -208,12 +375,17 @@ impl DocBufMap<u64> for &'static VTable {
     #[inline]
     fn docbuf_map_replace(
         &self,
-        _new_value: &u64,
-        _offset: VTableFieldOffset,
-        _buffer: &mut Vec<u8>,
-        _offsets: &mut VTableFieldOffsets,
+        new_value: &u64,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
     ) -> Result<VTableFieldOffset, Error> {
-        unimplemented!("docbuf_map_replace")
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::U64 => replace_value(self, field, new_value, offset, buffer, offsets),
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
     }
 }
 
@@ -223,15 +395,7 @@ impl DocBufMap<usize> for &'static VTable {
         let field = self.get_field_by_offset_index(offset.0)?;
 
         match field.r#type {
-            VTableFieldType::USIZE => {
-                let bytes = &buffer[offset.range()];
-
-                let data = usize::from_le_bytes([
-                    bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
-                ]);
-
-                Ok(data)
-            }
+            VTableFieldType::USIZE => field.decode(&mut DocBufReader::new(&buffer[offset.range()])),
             _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
         }
     }
@@ -239,12 +403,19 @@ impl DocBufMap<usize> for &'static VTable {
     #[inline]
     fn docbuf_map_replace(
         &self,
-        _new_value: &usize,
-        _offset: VTableFieldOffset,
-        _buffer: &mut Vec<u8>,
-        _offsets: &mut VTableFieldOffsets,
+        new_value: &usize,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
     ) -> Result<VTableFieldOffset, Error> {
-        unimplemented!("docbuf_map_replace")
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::USIZE => {
+                replace_value(self, field, new_value, offset, buffer, offsets)
+            }
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
     }
 }
 
@@ -266,12 +437,17 @@ impl DocBufMap<i8> for &'static VTable {
     #[inline]
     fn docbuf_map_replace(
         &self,
-        _new_value: &i8,
-        _offset: VTableFieldOffset,
-        _buffer: &mut Vec<u8>,
-        _offsets: &mut VTableFieldOffsets,
+        new_value: &i8,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
     ) -> Result<VTableFieldOffset, Error> {
-        unimplemented!("docbuf_map_replace")
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::I8 => replace_value(self, field, new_value, offset, buffer, offsets),
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
     }
 }
 
@@ -281,13 +457,7 @@ impl DocBufMap<i16> for &'static VTable {
         let field = self.get_field_by_offset_index(offset.0)?;
 
         match field.r#type {
-            VTableFieldType::I16 => {
-                let bytes = &buffer[offset.range()];
-
-                let data = i16::from_le_bytes([bytes[0], bytes[1]]);
-
-                Ok(data)
-            }
+            VTableFieldType::I16 => field.decode(&mut DocBufReader::new(&buffer[offset.range()])),
             _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
         }
     }
@@ -295,12 +465,17 @@ impl DocBufMap<i16> for &'static VTable {
     #[inline]
     fn docbuf_map_replace(
         &self,
-        _new_value: &i16,
-        _offset: VTableFieldOffset,
-        _buffer: &mut Vec<u8>,
-        _offsets: &mut VTableFieldOffsets,
+        new_value: &i16,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
     ) -> Result<VTableFieldOffset, Error> {
-        unimplemented!("docbuf_map_replace")
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::I16 => replace_value(self, field, new_value, offset, buffer, offsets),
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
     }
 }
 
@@ -310,13 +485,7 @@ impl DocBufMap<i32> for &'static VTable {
         let field = self.get_field_by_offset_index(offset.0)?;
 
         match field.r#type {
-            VTableFieldType::I32 => {
-                let bytes = &buffer[offset.range()];
-
-                let data = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
-
-                Ok(data)
-            }
+            VTableFieldType::I32 => field.decode(&mut DocBufReader::new(&buffer[offset.range()])),
             _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
         }
     }
@@ -324,12 +493,17 @@ impl DocBufMap<i32> for &'static VTable {
     #[inline]
     fn docbuf_map_replace(
         &self,
-        _new_value: &i32,
-        _offset: VTableFieldOffset,
-        _buffer: &mut Vec<u8>,
-        _offsets: &mut VTableFieldOffsets,
+        new_value: &i32,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
     ) -> Result<VTableFieldOffset, Error> {
-        unimplemented!("docbuf_map_replace")
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::I32 => replace_value(self, field, new_value, offset, buffer, offsets),
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
     }
 }
 
@@ -339,15 +513,7 @@ impl DocBufMap<i64> for &'static VTable {
         let field = self.get_field_by_offset_index(offset.0)?;
 
         match field.r#type {
-            VTableFieldType::I64 => {
-                let bytes = &buffer[offset.range()];
-
-                let data = i64::from_le_bytes([
-                    bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
-                ]);
-
-                Ok(data)
-            }
+            VTableFieldType::I64 => field.decode(&mut DocBufReader::new(&buffer[offset.range()])),
             _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
         }
     }
@@ -355,12 +521,17 @@ impl DocBufMap<i64> for &'static VTable {
     #[inline]
     fn docbuf_map_replace(
         &self,
-        _new_value: &i64,
-        _offset: VTableFieldOffset,
-        _buffer: &mut Vec<u8>,
-        _offsets: &mut VTableFieldOffsets,
+        new_value: &i64,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
     ) -> Result<VTableFieldOffset, Error> {
-        unimplemented!("docbuf_map_replace")
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::I64 => replace_value(self, field, new_value, offset, buffer, offsets),
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
     }
 }
 
@@ -370,15 +541,7 @@ impl DocBufMap<isize> for &'static VTable {
         let field = self.get_field_by_offset_index(offset.0)?;
 
         match field.r#type {
-            VTableFieldType::ISIZE => {
-                let bytes = &buffer[offset.range()];
-
-                let data = isize::from_le_bytes([
-                    bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
-                ]);
-
-                Ok(data)
-            }
+            VTableFieldType::ISIZE => field.decode(&mut DocBufReader::new(&buffer[offset.range()])),
             _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
         }
     }
@@ -386,12 +549,19 @@ impl DocBufMap<isize> for &'static VTable {
     #[inline]
     fn docbuf_map_replace(
         &self,
-        _new_value: &isize,
-        _offset: VTableFieldOffset,
-        _buffer: &mut Vec<u8>,
-        _offsets: &mut VTableFieldOffsets,
+        new_value: &isize,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
     ) -> Result<VTableFieldOffset, Error> {
-        unimplemented!("docbuf_map_replace")
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::ISIZE => {
+                replace_value(self, field, new_value, offset, buffer, offsets)
+            }
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
     }
 }
 
@@ -415,12 +585,17 @@ impl DocBufMap<f32> for &'static VTable {
     #[inline]
     fn docbuf_map_replace(
         &self,
-        _new_value: &f32,
-        _offset: VTableFieldOffset,
-        _buffer: &mut Vec<u8>,
-        _offsets: &mut VTableFieldOffsets,
+        new_value: &f32,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
     ) -> Result<VTableFieldOffset, Error> {
-        unimplemented!("docbuf_map_replace")
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::F32 => replace_value(self, field, new_value, offset, buffer, offsets),
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
     }
 }
 
@@ -446,12 +621,17 @@ impl DocBufMap<f64> for &'static VTable {
     #[inline]
     fn docbuf_map_replace(
         &self,
-        _new_value: &f64,
-        _offset: VTableFieldOffset,
-        _buffer: &mut Vec<u8>,
-        _offsets: &mut VTableFieldOffsets,
+        new_value: &f64,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
     ) -> Result<VTableFieldOffset, Error> {
-        unimplemented!("docbuf_map_replace")
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::F64 => replace_value(self, field, new_value, offset, buffer, offsets),
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
     }
 }
 
@@ -473,12 +653,17 @@ impl DocBufMap<bool> for &'static VTable {
     #[inline]
     fn docbuf_map_replace(
         &self,
-        _new_value: &bool,
-        _offset: VTableFieldOffset,
-        _buffer: &mut Vec<u8>,
-        _offsets: &mut VTableFieldOffsets,
+        new_value: &bool,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
     ) -> Result<VTableFieldOffset, Error> {
-        unimplemented!("docbuf_map_replace")
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::Bool => replace_value(self, field, new_value, offset, buffer, offsets),
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
     }
 }
 
@@ -517,3 +702,292 @@ impl DocBufMap<bool> for &'static VTable {
 //         }
 //     }
 // }
+
+impl<T, const N: usize> DocBufMap<[T; N]> for &'static VTable
+where
+    T: Copy + Into<NumericValue>,
+    VTableField: DocBufDecodeField<T>,
+{
+    #[inline]
+    fn docbuf_map(&self, buffer: &[u8], offset: &VTableFieldOffset) -> Result<[T; N], Error> {
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::Array { len, .. } if len == N => {
+                let mut reader = DocBufReader::new(&buffer[offset.range()]);
+
+                let elements = (0..N)
+                    .map(|_| field.decode(&mut reader))
+                    .collect::<Result<Vec<T>, Error>>()?;
+
+                elements.try_into().map_err(|_| Error::FailedToParseData)
+            }
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
+    }
+
+    #[inline]
+    fn docbuf_map_replace(
+        &self,
+        new_value: &[T; N],
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
+    ) -> Result<VTableFieldOffset, Error> {
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::Array { len, .. } if len == N => {
+                field.rules.check_length(N)?;
+
+                let mut data = Vec::with_capacity(offset.len());
+
+                for element in new_value.iter() {
+                    field.encode(&(*element).into(), &mut data)?;
+                }
+
+                replace_unprefixed(&data, offset, buffer, offsets)
+            }
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
+    }
+}
+
+impl<A, B> DocBufMap<(A, B)> for &'static VTable
+where
+    A: Copy + Into<NumericValue>,
+    B: Copy + Into<NumericValue>,
+    VTableField: DocBufDecodeField<A> + DocBufDecodeField<B>,
+{
+    #[inline]
+    fn docbuf_map(&self, buffer: &[u8], offset: &VTableFieldOffset) -> Result<(A, B), Error> {
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match &field.r#type {
+            VTableFieldType::Tuple(types) if types.len() == 2 => {
+                let mut reader = DocBufReader::new(&buffer[offset.range()]);
+
+                // Decode each element with its own type
+                let a = field.with_type(types[0].clone()).decode(&mut reader)?;
+                let b = field.with_type(types[1].clone()).decode(&mut reader)?;
+
+                Ok((a, b))
+            }
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
+    }
+
+    #[inline]
+    fn docbuf_map_replace(
+        &self,
+        new_value: &(A, B),
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
+    ) -> Result<VTableFieldOffset, Error> {
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match &field.r#type {
+            VTableFieldType::Tuple(types) if types.len() == 2 => {
+                let mut data = Vec::with_capacity(offset.len());
+
+                field
+                    .with_type(types[0].clone())
+                    .encode(&new_value.0.into(), &mut data)?;
+                field
+                    .with_type(types[1].clone())
+                    .encode(&new_value.1.into(), &mut data)?;
+
+                replace_unprefixed(&data, offset, buffer, offsets)
+            }
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
+    }
+}
+
+/// Generate the `DocBufMap` implementation of a value decoded with the serde
+/// deserializer, for fields of the given types.
+macro_rules! docbuf_map_value {
+    ($t:ty, $($pattern:pat_param)|+) => {
+        impl DocBufMap<$t> for &'static VTable {
+            #[inline]
+            fn docbuf_map(&self, buffer: &[u8], offset: &VTableFieldOffset) -> Result<$t, Error> {
+                let field = self.get_field_by_offset_index(offset.0)?;
+
+                match field.r#type {
+                    $($pattern)|+ => map_value(self, field, buffer, offset),
+                    _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+                }
+            }
+
+            #[inline]
+            fn docbuf_map_replace(
+                &self,
+                new_value: &$t,
+                offset: VTableFieldOffset,
+                buffer: &mut Vec<u8>,
+                offsets: &mut VTableFieldOffsets,
+            ) -> Result<VTableFieldOffset, Error> {
+                let field = self.get_field_by_offset_index(offset.0)?;
+
+                match field.r#type {
+                    $($pattern)|+ => replace_value(self, field, new_value, offset, buffer, offsets),
+                    _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+                }
+            }
+        }
+    };
+}
+
+docbuf_map_value!(u128, VTableFieldType::U128);
+docbuf_map_value!(i128, VTableFieldType::I128);
+docbuf_map_value!(char, VTableFieldType::Char);
+#[cfg(feature = "uuid")]
+docbuf_map_value!(uuid::Uuid, VTableFieldType::Uuid);
+
+impl<T> DocBufMap<Option<T>> for &'static VTable
+where
+    T: Serialize + DeserializeOwned,
+{
+    #[inline]
+    fn docbuf_map(&self, buffer: &[u8], offset: &VTableFieldOffset) -> Result<Option<T>, Error> {
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::Option(_) => map_value(self, field, buffer, offset),
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
+    }
+
+    #[inline]
+    fn docbuf_map_replace(
+        &self,
+        new_value: &Option<T>,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
+    ) -> Result<VTableFieldOffset, Error> {
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::Option(_) => {
+                replace_value(self, field, new_value, offset, buffer, offsets)
+            }
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
+    }
+}
+
+/// Lists, and byte arrays of `Bytes` fields, e.g. `Vec<u8>`.
+impl<T> DocBufMap<Vec<T>> for &'static VTable
+where
+    T: Serialize + DeserializeOwned,
+{
+    #[inline]
+    fn docbuf_map(&self, buffer: &[u8], offset: &VTableFieldOffset) -> Result<Vec<T>, Error> {
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::Vec(_) | VTableFieldType::Bytes => {
+                map_value(self, field, buffer, offset)
+            }
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
+    }
+
+    #[inline]
+    fn docbuf_map_replace(
+        &self,
+        new_value: &Vec<T>,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
+    ) -> Result<VTableFieldOffset, Error> {
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::Vec(_) | VTableFieldType::Bytes => {
+                replace_value(self, field, new_value, offset, buffer, offsets)
+            }
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
+    }
+}
+
+impl<K, V> DocBufMap<HashMap<K, V>> for &'static VTable
+where
+    K: Serialize + DeserializeOwned + Eq + Hash,
+    V: Serialize + DeserializeOwned,
+{
+    #[inline]
+    fn docbuf_map(
+        &self,
+        buffer: &[u8],
+        offset: &VTableFieldOffset,
+    ) -> Result<HashMap<K, V>, Error> {
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::HashMap { .. } => map_value(self, field, buffer, offset),
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
+    }
+
+    #[inline]
+    fn docbuf_map_replace(
+        &self,
+        new_value: &HashMap<K, V>,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
+    ) -> Result<VTableFieldOffset, Error> {
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match field.r#type {
+            VTableFieldType::HashMap { .. } => {
+                replace_value(self, field, new_value, offset, buffer, offsets)
+            }
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
+    }
+}
+
+/// Nested structs and enums, which are documents of the same namespace.
+impl<T> DocBufMap<T> for &'static VTable
+where
+    T: DocBuf + Serialize + DeserializeOwned,
+{
+    #[inline]
+    fn docbuf_map(&self, buffer: &[u8], offset: &VTableFieldOffset) -> Result<T, Error> {
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match &field.r#type {
+            VTableFieldType::Struct(name) | VTableFieldType::Enum(name)
+                if *name == T::vtable().map_err(map_error)?.root =>
+            {
+                map_value(self, field, buffer, offset)
+            }
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
+    }
+
+    #[inline]
+    fn docbuf_map_replace(
+        &self,
+        new_value: &T,
+        offset: VTableFieldOffset,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
+    ) -> Result<VTableFieldOffset, Error> {
+        let field = self.get_field_by_offset_index(offset.0)?;
+
+        match &field.r#type {
+            VTableFieldType::Struct(name) | VTableFieldType::Enum(name)
+                if *name == T::vtable().map_err(map_error)?.root =>
+            {
+                replace_value(self, field, new_value, offset, buffer, offsets)
+            }
+            _ => Err(Error::DocBufMapInvalidFieldType(field.r#type.to_string())),
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use super::*;
+
+use crate::value::Value;
+
+/// Writes the canonical form of a document buffer, walking the encoded values
+/// with the vtable rather than decoding the document.
+struct DocBufCanonicalizer<'a, 'de> {
+    vtable: &'a VTable,
+    buffer: &'de [u8],
+    reader: DocBufReader<'de>,
+}
+
+impl<'a, 'de> DocBufCanonicalizer<'a, 'de> {
+    /// Write the fields of a struct, or the discriminant and fields of the enum
+    /// variant at the reader. Only the fields covered by the signature are
+    /// written if `signed_only` is set.
+    fn write_item(
+        &mut self,
+        item: &VTableItem,
+        signed_only: bool,
+        canonical: &mut Vec<u8>,
+    ) -> crate::Result<()> {
+        let fields = match item {
+            VTableItem::Struct(vtable_struct) => &vtable_struct.fields,
+            VTableItem::Enum(vtable_enum) => {
+                let index = self.reader.read_u8()?;
+                canonical.push(index);
+
+                &vtable_enum.variant_by_index(index)?.fields
+            }
+        };
+
+        for field in fields.iter() {
+            match signed_only && !field.rules.sign() {
+                true => self.write_unsigned_value(field, &field.r#type, canonical)?,
+                false => self.write_value(field, &field.r#type, canonical)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the signed fields nested in a value of a field without the
+    /// `sign` rule, skipping the value if it holds no signed fields. The
+    /// option tags, enum discriminants and list lengths of the values holding
+    /// signed fields are written, and map entries are ordered by their key.
+    fn write_unsigned_value(
+        &mut self,
+        field: &VTableField,
+        r#type: &VTableFieldType,
+        canonical: &mut Vec<u8>,
+    ) -> crate::Result<()> {
+        if !self.vtable.holds_signed_fields(r#type, &mut Vec::new()) {
+            return self.vtable.skip_value(field, r#type, &mut self.reader);
+        }
+
+        let start = self.reader.position();
+
+        match r#type {
+            VTableFieldType::Struct(name) | VTableFieldType::Enum(name) => {
+                self.write_item(self.vtable.item_by_name(name)?, true, canonical)?;
+            }
+            VTableFieldType::Option(inner) => match field.decode_option(&mut self.reader)? {
+                Some(()) => {
+                    canonical.push(1);
+                    self.write_unsigned_value(field, inner, canonical)?;
+                }
+                None => canonical.push(0),
+            },
+            VTableFieldType::Vec(elem) => {
+                let len = field.decode_len(&mut self.reader)?;
+                canonical.extend_from_slice(&self.buffer[start..self.reader.position()]);
+
+                for _ in 0..len {
+                    self.write_unsigned_value(field, elem, canonical)?;
+                }
+            }
+            VTableFieldType::HashMap { key, value } => {
+                let len = field.decode_len(&mut self.reader)?;
+                canonical.extend_from_slice(&self.buffer[start..self.reader.position()]);
+
+                let mut entries = Vec::new();
+
+                for _ in 0..len {
+                    let key_start = self.reader.position();
+                    let mut entry_key = Vec::new();
+                    self.write_value(field, key, &mut entry_key)?;
+
+                    // The signed fields of the key, if any, are written from
+                    // the encoded key.
+                    let mut entry = Vec::new();
+                    let key_end = self.reader.position();
+                    self.reader.seek(key_start)?;
+                    self.write_unsigned_value(field, key, &mut entry)?;
+                    self.reader.seek(key_end)?;
+
+                    self.write_unsigned_value(field, value, &mut entry)?;
+
+                    entries.push((entry_key, entry));
+                }
+
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+                for (_, entry) in entries {
+                    canonical.extend_from_slice(&entry);
+                }
+            }
+            VTableFieldType::Array { elem, len } => {
+                for _ in 0..*len {
+                    self.write_unsigned_value(field, elem, canonical)?;
+                }
+            }
+            VTableFieldType::Tuple(types) => {
+                for r#type in types.iter() {
+                    self.write_unsigned_value(field, r#type, canonical)?;
+                }
+            }
+            r#type => self.vtable.skip_value(field, r#type, &mut self.reader)?,
+        }
+
+        Ok(())
+    }
+
+    /// Write the canonical form of the encoded value of the type at the reader.
+    fn write_value(
+        &mut self,
+        field: &VTableField,
+        r#type: &VTableFieldType,
+        canonical: &mut Vec<u8>,
+    ) -> crate::Result<()> {
+        let start = self.reader.position();
+
+        match r#type {
+            VTableFieldType::Struct(name) | VTableFieldType::Enum(name) => {
+                self.write_item(self.vtable.item_by_name(name)?, false, canonical)?;
+            }
+            // Options are tagged, rather than prefixed with the null field marker.
+            VTableFieldType::Option(inner) => match field.decode_option(&mut self.reader)? {
+                Some(()) => {
+                    canonical.push(1);
+                    self.write_value(field, inner, canonical)?;
+                }
+                None => canonical.push(0),
+            },
+            VTableFieldType::Vec(elem) => {
+                let len = field.decode_len(&mut self.reader)?;
+                canonical.extend_from_slice(&self.buffer[start..self.reader.position()]);
+
+                for _ in 0..len {
+                    self.write_value(field, elem, canonical)?;
+                }
+            }
+            // Map entries are sorted by their canonical key.
+            VTableFieldType::HashMap { key, value } => {
+                let len = field.decode_len(&mut self.reader)?;
+                canonical.extend_from_slice(&self.buffer[start..self.reader.position()]);
+
+                let mut entries = Vec::new();
+
+                for _ in 0..len {
+                    let mut entry_key = Vec::new();
+                    self.write_value(field, key, &mut entry_key)?;
+
+                    let mut entry_value = Vec::new();
+                    self.write_value(field, value, &mut entry_value)?;
+
+                    entries.push((entry_key, entry_value));
+                }
+
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+                for (entry_key, entry_value) in entries {
+                    canonical.extend_from_slice(&entry_key);
+                    canonical.extend_from_slice(&entry_value);
+                }
+            }
+            VTableFieldType::Array { elem, len } => {
+                for _ in 0..*len {
+                    self.write_value(field, elem, canonical)?;
+                }
+            }
+            VTableFieldType::Tuple(types) => {
+                for r#type in types.iter() {
+                    self.write_value(field, r#type, canonical)?;
+                }
+            }
+            VTableFieldType::F32 | VTableFieldType::F64 => {
+                let is_nan =
+                    match self
+                        .vtable
+                        .decode_field_value(field, r#type, &mut self.reader)?
+                    {
+                        Value::Numeric(NumericValue::F32(value)) => value.is_nan(),
+                        Value::Numeric(NumericValue::F64(value)) => value.is_nan(),
+                        _ => false,
+                    };
+
+                if is_nan {
+                    return Err(Error::CanonicalNaN(field.name.to_string()).into());
+                }
+
+                canonical.extend_from_slice(&self.buffer[start..self.reader.position()]);
+            }
+            r#type => {
+                self.vtable.skip_value(field, r#type, &mut self.reader)?;
+                canonical.extend_from_slice(&self.buffer[start..self.reader.position()]);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl VTable {
+    /// Write the canonical form of a document buffer of the vtable, without
+    /// decoding the document, see `DocBuf::to_canonical_docbuf`. The canonical
+    /// form differs from the document buffer and is not meant to be decoded:
+    ///
+    /// - map entries are sorted by their encoded key, rather than in iteration order,
+    /// - `NaN` floats are rejected,
+    /// - options are prefixed with a `0` (`None`) or `1` (`Some`) tag byte.
+    pub fn canonical_docbuf(&self, buffer: &[u8], canonical: &mut Vec<u8>) -> crate::Result<()> {
+        self.write_canonical(buffer, false, canonical)
+    }
+
+    /// Write the canonical form of the fields of a document buffer covered by
+    /// its signature. If fields of the vtable have the `sign` rule, only these
+    /// fields, and the values nested in them, are covered, including signed
+    /// fields nested in the options, lists, maps, arrays and tuples of
+    /// unsigned fields. Otherwise, the whole document is covered.
+    pub fn canonical_signed_docbuf(
+        &self,
+        buffer: &[u8],
+        canonical: &mut Vec<u8>,
+    ) -> crate::Result<()> {
+        self.write_canonical(buffer, self.has_signed_fields(), canonical)
+    }
+
+    /// Returns true if a struct or enum variant field of the vtable has the
+    /// `sign` rule.
+    pub fn has_signed_fields(&self) -> bool {
+        self.items
+            .iter()
+            .any(|item| item_fields(item).any(|field| field.rules.sign()))
+    }
+
+    /// Returns true if values of the type hold fields with the `sign` rule,
+    /// e.g. a list of structs with a signed field. The names of the items
+    /// visited are recorded, for recursive items.
+    fn holds_signed_fields<'a>(
+        &'a self,
+        r#type: &VTableFieldType,
+        visited: &mut Vec<&'a str>,
+    ) -> bool {
+        match r#type {
+            VTableFieldType::Struct(name) | VTableFieldType::Enum(name) => {
+                if visited.contains(&name.as_str()) {
+                    return false;
+                }
+
+                match self.item_by_name(name) {
+                    Ok(item) => {
+                        visited.push(item.name());
+
+                        item_fields(item).any(|field| {
+                            field.rules.sign() || self.holds_signed_fields(&field.r#type, visited)
+                        })
+                    }
+                    Err(_) => false,
+                }
+            }
+            VTableFieldType::Option(inner)
+            | VTableFieldType::Vec(inner)
+            | VTableFieldType::Array { elem: inner, .. } => {
+                self.holds_signed_fields(inner, visited)
+            }
+            VTableFieldType::HashMap { key, value } => {
+                self.holds_signed_fields(key, visited) || self.holds_signed_fields(value, visited)
+            }
+            VTableFieldType::Tuple(types) => types
+                .iter()
+                .any(|r#type| self.holds_signed_fields(r#type, visited)),
+            _ => false,
+        }
+    }
+
+    fn write_canonical(
+        &self,
+        buffer: &[u8],
+        signed_only: bool,
+        canonical: &mut Vec<u8>,
+    ) -> crate::Result<()> {
+        let mut canonicalizer = DocBufCanonicalizer {
+            vtable: self,
+            buffer,
+            reader: DocBufReader::new(buffer),
+        };
+
+        canonicalizer.write_item(self.root_item()?, signed_only, canonical)
+    }
+}
+
+/// Return the fields of a struct, or the payload fields of all variants of an enum.
+fn item_fields(item: &VTableItem) -> Box<dyn Iterator<Item = &VTableField> + '_> {
+    match item {
+        VTableItem::Struct(vtable_struct) => Box::new(vtable_struct.fields.iter()),
+        VTableItem::Enum(vtable_enum) => Box::new(vtable_enum.fields()),
+    }
+}
@@ -0,0 +1,264 @@
+use super::*;
+
+/// A change between the vtable a document buffer was written with and the
+/// vtable it is read with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VTableChange {
+    /// The root item of the document was renamed.
+    RootChanged { writer: String, reader: String },
+    /// An item changed between a struct and an enum.
+    ItemKindChanged { name: String },
+    /// A field is in the reader's vtable, but not the writer's. Optional fields
+    /// are read as `None`, other fields require a `#[serde(default)]`, recorded
+    /// as the `default` field rule.
+    FieldAdded {
+        path: String,
+        r#type: VTableFieldType,
+        default: bool,
+    },
+    /// A field is in the writer's vtable, but not the reader's, and is skipped.
+    FieldRemoved {
+        path: String,
+        r#type: VTableFieldType,
+    },
+    /// A field moved to a different position in its item.
+    FieldMoved {
+        path: String,
+        writer: VTableFieldIndex,
+        reader: VTableFieldIndex,
+    },
+    /// The type of a field changed.
+    FieldTypeChanged {
+        path: String,
+        writer: VTableFieldType,
+        reader: VTableFieldType,
+    },
+    /// A variant is in the reader's vtable, but not the writer's.
+    VariantAdded { path: String },
+    /// A variant is in the writer's vtable, but not the reader's.
+    VariantRemoved { path: String },
+    /// A variant changed between a unit, newtype, tuple and struct variant.
+    VariantKindChanged {
+        path: String,
+        writer: VTableVariantKind,
+        reader: VTableVariantKind,
+    },
+}
+
+impl VTableChange {
+    /// Returns true if document buffers written before the change can be read
+    /// after the change.
+    pub fn is_compatible(&self) -> bool {
+        match self {
+            VTableChange::FieldAdded {
+                r#type, default, ..
+            } => *default || matches!(r#type, VTableFieldType::Option(_)),
+            VTableChange::FieldRemoved { .. }
+            | VTableChange::FieldMoved { .. }
+            | VTableChange::VariantAdded { .. } => true,
+            VTableChange::FieldTypeChanged { writer, reader, .. } => {
+                writer.is_compatible_with(reader)
+            }
+            VTableChange::RootChanged { .. }
+            | VTableChange::ItemKindChanged { .. }
+            | VTableChange::VariantRemoved { .. }
+            | VTableChange::VariantKindChanged { .. } => false,
+        }
+    }
+}
+
+impl std::fmt::Display for VTableChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VTableChange::RootChanged { writer, reader } => {
+                write!(f, "root changed from {} to {}", writer, reader)
+            }
+            VTableChange::ItemKindChanged { name } => {
+                write!(f, "{} changed between a struct and an enum", name)
+            }
+            VTableChange::FieldAdded {
+                path,
+                r#type,
+                default,
+            } => match default {
+                true => write!(f, "{}: {} added with a default", path, r#type),
+                false => write!(f, "{}: {} added", path, r#type),
+            },
+            VTableChange::FieldRemoved { path, r#type } => {
+                write!(f, "{}: {} removed", path, r#type)
+            }
+            VTableChange::FieldMoved {
+                path,
+                writer,
+                reader,
+            } => write!(f, "{} moved from index {} to {}", path, writer, reader),
+            VTableChange::FieldTypeChanged {
+                path,
+                writer,
+                reader,
+            } => write!(f, "{} changed type from {} to {}", path, writer, reader),
+            VTableChange::VariantAdded { path } => write!(f, "{} added", path),
+            VTableChange::VariantRemoved { path } => write!(f, "{} removed", path),
+            VTableChange::VariantKindChanged {
+                path,
+                writer,
+                reader,
+            } => write!(
+                f,
+                "{} changed from a {:?} to a {:?} variant",
+                path, writer, reader
+            ),
+        }
+    }
+}
+
+/// The changes between the vtable a document buffer was written with and the
+/// vtable it is read with, returned by `VTable::compatibility`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VTableCompatibility {
+    pub changes: Vec<VTableChange>,
+}
+
+impl VTableCompatibility {
+    /// Returns true if the vtables have no changes.
+    pub fn is_identical(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns true if all changes can be reconciled when decoding.
+    pub fn is_compatible(&self) -> bool {
+        self.changes.iter().all(VTableChange::is_compatible)
+    }
+
+    /// Return the changes that cannot be reconciled when decoding.
+    pub fn incompatible(&self) -> impl Iterator<Item = &VTableChange> {
+        self.changes.iter().filter(|change| !change.is_compatible())
+    }
+
+    /// Compare the fields of a struct or enum variant by name.
+    fn compare_fields(&mut self, path: &str, writer: &VTableFields, reader: &VTableFields) {
+        for reader_field in reader.iter() {
+            let field_path = format!("{}.{}", path, reader_field.name);
+
+            let Some(writer_field) = writer.find_field_by_name(&reader_field.name) else {
+                self.changes.push(VTableChange::FieldAdded {
+                    path: field_path,
+                    r#type: reader_field.r#type.to_owned(),
+                    default: reader_field.rules.default,
+                });
+
+                continue;
+            };
+
+            if writer_field.r#type != reader_field.r#type {
+                self.changes.push(VTableChange::FieldTypeChanged {
+                    path: field_path.clone(),
+                    writer: writer_field.r#type.to_owned(),
+                    reader: reader_field.r#type.to_owned(),
+                });
+            }
+
+            if writer_field.index != reader_field.index {
+                self.changes.push(VTableChange::FieldMoved {
+                    path: field_path,
+                    writer: writer_field.index,
+                    reader: reader_field.index,
+                });
+            }
+        }
+
+        for writer_field in writer.iter() {
+            if reader.find_field_by_name(&writer_field.name).is_none() {
+                self.changes.push(VTableChange::FieldRemoved {
+                    path: format!("{}.{}", path, writer_field.name),
+                    r#type: writer_field.r#type.to_owned(),
+                });
+            }
+        }
+    }
+
+    /// Compare the variants of an enum by name.
+    fn compare_variants(&mut self, writer: &VTableEnum, reader: &VTableEnum) {
+        for reader_variant in reader.variants.iter() {
+            let path = format!("{}::{}", reader.name, reader_variant.name);
+
+            let Ok(writer_variant) = writer.variant_by_name(&reader_variant.name) else {
+                self.changes.push(VTableChange::VariantAdded { path });
+
+                continue;
+            };
+
+            if writer_variant.kind != reader_variant.kind {
+                self.changes.push(VTableChange::VariantKindChanged {
+                    path,
+                    writer: writer_variant.kind,
+                    reader: reader_variant.kind,
+                });
+
+                continue;
+            }
+
+            self.compare_fields(&path, &writer_variant.fields, &reader_variant.fields);
+        }
+
+        for writer_variant in writer.variants.iter() {
+            if reader.variant_by_name(&writer_variant.name).is_err() {
+                self.changes.push(VTableChange::VariantRemoved {
+                    path: format!("{}::{}", writer.name, writer_variant.name),
+                });
+            }
+        }
+    }
+}
+
+impl VTable {
+    /// Compare the vtable a document buffer was written with, i.e. `self`,
+    /// with the vtable it is read with. Items, fields and variants are matched
+    /// by name, so reordering fields is a compatible change.
+    pub fn compatibility(&self, reader: &VTable) -> VTableCompatibility {
+        let mut compatibility = VTableCompatibility::default();
+
+        if self.root != reader.root {
+            compatibility.changes.push(VTableChange::RootChanged {
+                writer: self.root.to_owned(),
+                reader: reader.root.to_owned(),
+            });
+        }
+
+        for reader_item in reader.items.iter() {
+            // Items new to the reader are compared through the fields that use them.
+            let Ok(writer_item) = self.item_by_name(reader_item.name()) else {
+                continue;
+            };
+
+            match (writer_item, reader_item) {
+                (VTableItem::Struct(writer_struct), VTableItem::Struct(reader_struct)) => {
+                    compatibility.compare_fields(
+                        &reader_struct.name,
+                        &writer_struct.fields,
+                        &reader_struct.fields,
+                    );
+                }
+                (VTableItem::Enum(writer_enum), VTableItem::Enum(reader_enum)) => {
+                    compatibility.compare_variants(writer_enum, reader_enum);
+                }
+                _ => compatibility.changes.push(VTableChange::ItemKindChanged {
+                    name: reader_item.name().to_owned(),
+                }),
+            }
+        }
+
+        compatibility
+    }
+}
+
+impl std::fmt::Display for VTableCompatibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let changes = self
+            .incompatible()
+            .map(|change| change.to_string())
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", changes.join("; "))
+    }
+}
@@ -0,0 +1,111 @@
+use super::*;
+
+use serde_derive::{Deserialize, Serialize};
+
+/// Maximum number of bytes of a LEB128 encoded `u128`.
+pub const MAX_VARINT_LEN: usize = 19;
+
+/// The encoding of integers and length prefixes in a document buffer.
+///
+/// The encoding is selected per document with `#[docbuf { encoding = "compact" }]`,
+/// and recorded in the vtable, so buffers are decoded with the encoding they
+/// were written with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum VTableEncoding {
+    /// Integers are written at their full width, and lengths as 4 little endian bytes.
+    #[default]
+    Fixed = 0,
+    /// Integers and lengths are written as LEB128 varints, with signed integers
+    /// zigzag encoded. Single byte integers and floats are written as is.
+    Compact = 1,
+}
+
+impl From<VTableEncoding> for u8 {
+    fn from(encoding: VTableEncoding) -> u8 {
+        encoding as u8
+    }
+}
+
+impl TryFrom<u8> for VTableEncoding {
+    type Error = Error;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte {
+            0 => Ok(VTableEncoding::Fixed),
+            1 => Ok(VTableEncoding::Compact),
+            _ => Err(Error::UnknownEncoding(byte)),
+        }
+    }
+}
+
+impl std::fmt::Display for VTableEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VTableEncoding::Fixed => write!(f, "fixed"),
+            VTableEncoding::Compact => write!(f, "compact"),
+        }
+    }
+}
+
+impl VTableEncoding {
+    /// Write the length prefix of a string, byte array, list or map.
+    #[inline]
+    pub fn write_len(&self, len: usize, buffer: &mut Vec<u8>) {
+        match self {
+            VTableEncoding::Fixed => buffer.extend_from_slice(&(len as u32).to_le_bytes()),
+            VTableEncoding::Compact => write_uvarint(len as u128, buffer),
+        }
+    }
+
+    /// Read the length prefix of a string, byte array, list or map.
+    #[inline]
+    pub fn read_len(&self, buffer: &mut DocBufReader<'_>) -> Result<usize, Error> {
+        match self {
+            VTableEncoding::Fixed => buffer.read_len(),
+            VTableEncoding::Compact => buffer.read_uvarint(),
+        }
+    }
+
+    /// Return the number of bytes of the length prefix for the length.
+    #[inline]
+    pub fn len_size(&self, len: usize) -> usize {
+        match self {
+            VTableEncoding::Fixed => DEFAULT_FIELD_LENGTH_LE_BYTES,
+            VTableEncoding::Compact => uvarint_len(len as u128),
+        }
+    }
+}
+
+/// Write an unsigned integer as a LEB128 varint, seven bits per byte with the
+/// high bit set on all but the last byte.
+#[inline]
+pub fn write_uvarint(mut value: u128, buffer: &mut Vec<u8>) {
+    while value >= 0x80 {
+        buffer.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+
+    buffer.push(value as u8);
+}
+
+/// Return the number of bytes of the LEB128 varint for the value.
+#[inline]
+pub fn uvarint_len(value: u128) -> usize {
+    let bits = (u128::BITS - value.leading_zeros()).max(1) as usize;
+
+    bits.div_ceil(7)
+}
+
+/// Map a signed integer to an unsigned integer, so that values of small
+/// magnitude have a short varint, i.e. 0, -1, 1, -2, 2 map to 0, 1, 2, 3, 4.
+#[inline]
+pub fn zigzag_encode(value: i128) -> u128 {
+    ((value << 1) ^ (value >> 127)) as u128
+}
+
+/// Reverse of `zigzag_encode`.
+#[inline]
+pub fn zigzag_decode(value: u128) -> i128 {
+    ((value >> 1) as i128) ^ -((value & 1) as i128)
+}
@@ -2,6 +2,7 @@
 
 mod decode;
 mod encode;
+mod facet;
 mod numeric;
 mod offset;
 mod rules;
@@ -13,6 +14,7 @@ use super::*;
 // Re-export Field Implementations
 pub use decode::*;
 pub use encode::*;
+pub use facet::*;
 pub use numeric::*;
 pub use offset::*;
 pub use rules::*;
@@ -50,6 +52,9 @@ pub struct VTableField {
     pub index: VTableFieldIndex,
     pub name: VTableFieldName,
     pub rules: VTableFieldRules,
+    /// The encoding of the document, set from the vtable the field belongs to.
+    #[serde(default)]
+    pub encoding: VTableEncoding,
 }
 
 impl VTableField {
@@ -66,6 +71,7 @@ impl VTableField {
             index,
             name,
             rules,
+            encoding: VTableEncoding::default(),
         }
     }
 
@@ -79,8 +85,7 @@ impl VTableField {
             return Err(Error::ArrayElementsExceedsMax(num_elements));
         }
 
-        // Only encode the first three bytes
-        output.extend_from_slice(&(num_elements as u32).to_le_bytes());
+        self.encoding.write_len(num_elements, output);
 
         Ok(())
     }
@@ -91,32 +96,43 @@ impl VTableField {
             return Err(Error::MapEntriesExceedsMax(num_entries));
         }
 
-        output.extend_from_slice(&(num_entries as u32).to_le_bytes());
+        self.encoding.write_len(num_entries, output);
 
         Ok(())
     }
 
+    /// Return a copy of the field with a different type, e.g. to decode an
+    /// element of a tuple field with the element type.
+    #[inline]
+    pub fn with_type(&self, r#type: VTableFieldType) -> Self {
+        Self {
+            r#type,
+            ..self.clone()
+        }
+    }
+
+    /// Consume the length prefix of a string, byte array, list or map.
+    #[inline]
+    pub fn decode_len(&self, buffer: &mut DocBufReader<'_>) -> Result<usize, Error> {
+        self.encoding.read_len(buffer)
+    }
+
+    /// Consume the length prefix of a string or byte array, returning an error
+    /// if the length is larger than the remaining bytes.
+    #[inline]
+    pub fn decode_bytes_len(&self, buffer: &mut DocBufReader<'_>) -> Result<usize, Error> {
+        let len = self.decode_len(buffer)?;
+
+        buffer.check_len(len)
+    }
+
     #[inline]
     pub fn write_to_buffer(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
         // Push the item index
         buffer.push(self.item_index);
 
         // Write the field type
-        buffer.push(self.r#type.clone().into());
-
-        // If the field type is a struct, write the struct name.
-        match &self.r#type {
-            VTableFieldType::Struct(name) => {
-                let name_bytes = name.as_bytes();
-                buffer.push(name_bytes.len() as u8);
-                buffer.extend_from_slice(name_bytes);
-            }
-            VTableFieldType::HashMap { key, value } => {
-                buffer.push(key.deref().to_owned().into());
-                buffer.push(value.deref().to_owned().into());
-            }
-            _ => (),
-        }
+        self.r#type.write_to_buffer(buffer)?;
 
         // Write the field index
         buffer.push(self.index);
@@ -134,50 +150,23 @@ impl VTableField {
     }
 
     #[inline]
-    pub fn read_from_buffer(buffer: &mut Vec<u8>) -> Result<Self, Error> {
+    pub fn read_from_buffer(buffer: &mut DocBufReader<'_>) -> Result<Self, Error> {
         // Read the item index
-        let item_index = buffer.remove(0);
+        let item_index = buffer.read_u8()?;
 
         // Read the field type
-        let mut r#type = VTableFieldType::try_from(buffer.remove(0))?;
-
-        // If the type is a struct, read the struct name
-        match r#type {
-            VTableFieldType::Struct(_) => {
-                let name_len = buffer.remove(0);
-                let name = buffer.drain(0..name_len as usize).collect::<Vec<u8>>();
-                let name = String::from_utf8(name)?;
-
-                r#type = VTableFieldType::Struct(name);
-            }
-            VTableFieldType::HashMap { .. } => {
-                let key = Box::new(VTableFieldType::try_from(buffer.remove(0))?);
-                let value = Box::new(VTableFieldType::try_from(buffer.remove(0))?);
-
-                r#type = VTableFieldType::HashMap { key, value };
-            }
-            _ => (),
-        }
+        let r#type = VTableFieldType::read_from_buffer(buffer)?;
 
         // Read the field index
-        let index = buffer.remove(0);
+        let index = buffer.read_u8()?;
 
         // Read the field name
-        let name_len = buffer.remove(0);
-        let name = buffer.drain(0..name_len as usize).collect::<Vec<u8>>();
-
-        let name = String::from_utf8(name)?;
+        let name = buffer.read_name()?;
 
         // Read the field rules
         let rules = VTableFieldRules::read_from_buffer(buffer)?;
 
-        Ok(Self {
-            item_index,
-            r#type,
-            index,
-            name,
-            rules,
-        })
+        Ok(Self::new(item_index, r#type, index, name, rules))
     }
 
     #[inline]
@@ -303,6 +292,7 @@ pub enum VTableFieldType {
     Bytes,
     Bool,
     Struct(StructName),
+    Enum(EnumName),
     Option(Box<VTableFieldType>),
     Vec(Box<VTableFieldType>),
     HashMap {
@@ -310,6 +300,15 @@ pub enum VTableFieldType {
         value: Box<VTableFieldType>,
     },
     Uuid,
+    Char,
+    Unit,
+    /// A fixed size array, encoded as its elements without a length prefix.
+    Array {
+        elem: Box<VTableFieldType>,
+        len: usize,
+    },
+    /// A tuple, encoded as its elements in order without a length prefix.
+    Tuple(Vec<VTableFieldType>),
 }
 
 impl std::fmt::Display for VTableFieldType {
@@ -335,11 +334,20 @@ impl std::fmt::Display for VTableFieldType {
             VTableFieldType::Bytes => write!(f, "Bytes"),
             VTableFieldType::Bool => write!(f, "bool"),
             VTableFieldType::Struct(s) => write!(f, "{}", s),
+            VTableFieldType::Enum(e) => write!(f, "{}", e),
             VTableFieldType::HashMap { key, value } => {
                 write!(f, "HashMap<{}, {}>", key, value)
             }
             VTableFieldType::Uuid => write!(f, "Uuid"),
             VTableFieldType::Option(t) => write!(f, "Option<{}>", t),
+            VTableFieldType::Char => write!(f, "char"),
+            VTableFieldType::Unit => write!(f, "()"),
+            VTableFieldType::Array { elem, len } => write!(f, "[{}; {}]", elem, len),
+            VTableFieldType::Tuple(types) => {
+                let types = types.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+                write!(f, "({})", types.join(", "))
+            }
         }
     }
 }
@@ -350,38 +358,227 @@ impl VTableFieldType {
             Ok(VTableFieldType::Struct(name)) => Some(name),
             Ok(VTableFieldType::Option(opt)) => Self::is_struct(*opt),
             Ok(VTableFieldType::Vec(t)) => Self::is_struct(*t),
+            Ok(VTableFieldType::Array { elem, .. }) => Self::is_struct(*elem),
+            Ok(VTableFieldType::Tuple(types)) => types.into_iter().find_map(Self::is_struct),
             _ => None,
         }
     }
 
-    pub(crate) fn parse_hashmap_types(input: &str) -> VTableFieldType {
-        let mut types = input.split('<');
-        types = types
+    /// Returns true if values of the `other` type are encoded by this field type,
+    /// either directly or as the inner type of an option, list or map.
+    pub fn holds(&self, other: &VTableFieldType) -> bool {
+        if self == other {
+            return true;
+        }
+
+        match self {
+            VTableFieldType::Option(t) | VTableFieldType::Vec(t) => t.holds(other),
+            VTableFieldType::HashMap { key, value } => key.holds(other) || value.holds(other),
+            VTableFieldType::Array { elem, .. } => elem.holds(other),
+            VTableFieldType::Tuple(types) => types.iter().any(|t| t.holds(other)),
+            // Byte arrays may be encoded element-wise as a sequence of u8.
+            VTableFieldType::Bytes => other == &VTableFieldType::U8,
+            _ => false,
+        }
+    }
+
+    /// Returns true if values encoded with this field type can be read as the
+    /// `reader` type, e.g. a widened integer, or a value read into an option.
+    pub fn is_compatible_with(&self, reader: &VTableFieldType) -> bool {
+        use VTableFieldType::*;
+
+        if self == reader {
+            return true;
+        }
+
+        match (self, reader) {
+            (String | Str, String | Str) => true,
+            (U8, U16 | U32 | U64 | U128 | USIZE | I16 | I32 | I64 | I128 | ISIZE) => true,
+            (U16, U32 | U64 | U128 | USIZE | I32 | I64 | I128 | ISIZE) => true,
+            (U32, U64 | U128 | USIZE | I64 | I128 | ISIZE) => true,
+            (U64 | USIZE, U64 | U128 | USIZE | I128) => true,
+            (I8, I16 | I32 | I64 | I128 | ISIZE) => true,
+            (I16, I32 | I64 | I128 | ISIZE) => true,
+            (I32, I64 | I128 | ISIZE) => true,
+            (I64 | ISIZE, I64 | I128 | ISIZE) => true,
+            (F32, F64) => true,
+            (Option(writer), Option(reader)) | (Vec(writer), Vec(reader)) => {
+                writer.is_compatible_with(reader)
+            }
+            (
+                HashMap { key, value },
+                HashMap {
+                    key: reader_key,
+                    value: reader_value,
+                },
+            ) => key.is_compatible_with(reader_key) && value.is_compatible_with(reader_value),
+            (
+                Array { elem, len },
+                Array {
+                    elem: reader_elem,
+                    len: reader_len,
+                },
+            ) => len == reader_len && elem.is_compatible_with(reader_elem),
+            (Tuple(types), Tuple(reader_types)) => {
+                types.len() == reader_types.len()
+                    && types
+                        .iter()
+                        .zip(reader_types.iter())
+                        .all(|(writer, reader)| writer.is_compatible_with(reader))
+            }
+            (writer, Option(reader)) => writer.is_compatible_with(reader),
+            _ => false,
+        }
+    }
+
+    /// Write the field type byte, followed by the name of struct and enum types,
+    /// the inner types of options, lists and maps, the element type and length
+    /// of arrays, or the number of elements and element types of tuples.
+    #[inline]
+    pub fn write_to_buffer(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
+        buffer.push(self.clone().into());
+
+        match self {
+            VTableFieldType::Struct(name) | VTableFieldType::Enum(name) => {
+                let name_bytes = name.as_bytes();
+                buffer.push(name_bytes.len() as u8);
+                buffer.extend_from_slice(name_bytes);
+            }
+            VTableFieldType::Option(t) | VTableFieldType::Vec(t) => {
+                t.write_to_buffer(buffer)?;
+            }
+            VTableFieldType::HashMap { key, value } => {
+                key.write_to_buffer(buffer)?;
+                value.write_to_buffer(buffer)?;
+            }
+            VTableFieldType::Array { elem, len } => {
+                elem.write_to_buffer(buffer)?;
+                buffer.extend_from_slice(&(*len as u32).to_le_bytes());
+            }
+            VTableFieldType::Tuple(types) => {
+                buffer.push(types.len() as u8);
+
+                for t in types.iter() {
+                    t.write_to_buffer(buffer)?;
+                }
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn read_from_buffer(buffer: &mut DocBufReader<'_>) -> Result<Self, Error> {
+        let r#type = match VTableFieldType::try_from(buffer.read_u8()?)? {
+            VTableFieldType::Struct(_) => VTableFieldType::Struct(buffer.read_name()?),
+            VTableFieldType::Enum(_) => VTableFieldType::Enum(buffer.read_name()?),
+            VTableFieldType::Option(_) => {
+                VTableFieldType::Option(Box::new(Self::read_from_buffer(buffer)?))
+            }
+            VTableFieldType::Vec(_) => VTableFieldType::Vec(Box::new(Self::read_from_buffer(buffer)?)),
+            VTableFieldType::HashMap { .. } => {
+                let key = Box::new(Self::read_from_buffer(buffer)?);
+                let value = Box::new(Self::read_from_buffer(buffer)?);
+
+                VTableFieldType::HashMap { key, value }
+            }
+            VTableFieldType::Array { .. } => {
+                let elem = Box::new(Self::read_from_buffer(buffer)?);
+                let len = u32::from_le_bytes(buffer.read_array()?) as usize;
+
+                VTableFieldType::Array { elem, len }
+            }
+            VTableFieldType::Tuple(_) => {
+                let num_types = buffer.read_u8()?;
+
+                VTableFieldType::Tuple(
+                    (0..num_types)
+                        .map(|_| Self::read_from_buffer(buffer))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            r#type => r#type,
+        };
+
+        Ok(r#type)
+    }
+
+    /// Parse a fixed size array type, e.g. `[f64 ; 3]`. The length must be an
+    /// integer literal, const lengths are only known to the compiler.
+    pub(crate) fn parse_array_type(input: &str) -> Result<VTableFieldType, Error> {
+        let invalid = || Error::InvalidFieldType(input.to_owned());
+
+        let (elem, len) = input[1..input.len() - 1]
+            .rsplit_once(';')
+            .ok_or_else(invalid)?;
+
+        Ok(VTableFieldType::Array {
+            elem: Box::new(VTableFieldType::try_from(elem.trim())?),
+            len: len.trim().parse().map_err(|_| invalid())?,
+        })
+    }
+
+    /// Parse a tuple type, e.g. `(u32 , [u8 ; 2] , String)`, splitting the element
+    /// types on the commas that are not nested in another type.
+    pub(crate) fn parse_tuple_types(input: &str) -> Result<VTableFieldType, Error> {
+        let inner = &input[1..input.len() - 1];
+
+        let mut types = Vec::new();
+        let mut depth = 0;
+        let mut start = 0;
+
+        for (index, c) in inner.char_indices() {
+            match c {
+                '<' | '(' | '[' => depth += 1,
+                '>' | ')' | ']' => depth -= 1,
+                ',' if depth == 0 => {
+                    types.push(&inner[start..index]);
+                    start = index + 1;
+                }
+                _ => (),
+            }
+        }
+
+        types.push(&inner[start..]);
+
+        Ok(VTableFieldType::Tuple(
+            types
+                .into_iter()
+                .map(str::trim)
+                // Single element tuples have a trailing comma
+                .filter(|t| !t.is_empty())
+                .map(VTableFieldType::try_from)
+                .collect::<Result<_, _>>()?,
+        ))
+    }
+
+    pub(crate) fn parse_hashmap_types(input: &str) -> Result<VTableFieldType, Error> {
+        let invalid = || Error::InvalidFieldType(input.to_owned());
+
+        let mut types = input
+            .split('<')
             .nth(1)
             .map(|s| s.trim_end_matches('>'))
-            .expect("failed to parse hash map key,pair types")
+            .ok_or_else(invalid)?
             .split(',');
-        let key = types
-            .nth(0)
-            .map(|k| k.trim())
-            .expect("failed to parse hash map key type");
-        let value = types
-            .nth(0)
-            .map(|s| s.trim())
-            .expect("failed to parse hash map value type");
-
-        VTableFieldType::HashMap {
-            key: Box::new(VTableFieldType::from(key)),
-            value: Box::new(VTableFieldType::from(value)),
-        }
+        let key = types.next().map(|k| k.trim()).ok_or_else(invalid)?;
+        let value = types.next().map(|s| s.trim()).ok_or_else(invalid)?;
+
+        Ok(VTableFieldType::HashMap {
+            key: Box::new(VTableFieldType::try_from(key)?),
+            value: Box::new(VTableFieldType::try_from(value)?),
+        })
     }
 }
 
-impl From<&str> for VTableFieldType {
-    fn from(s: &str) -> Self {
+impl TryFrom<&str> for VTableFieldType {
+    type Error = Error;
+
+    fn try_from(s: &str) -> Result<Self, Error> {
         // println!("field type: {s}");
 
-        match s {
+        let r#type = match s {
             "u8" => VTableFieldType::U8,
             "u16" => VTableFieldType::U16,
             "u32" => VTableFieldType::U32,
@@ -397,12 +594,18 @@ impl From<&str> for VTableFieldType {
             "f32" => VTableFieldType::F32,
             "f64" => VTableFieldType::F64,
             "String" => VTableFieldType::String,
+            "char" => VTableFieldType::Char,
+            "()" | "( )" => VTableFieldType::Unit,
             // "[u8; 32]" => VTableFieldType::Bytes,
             "Vec < u8 >" => VTableFieldType::Bytes,
+            s if s.starts_with('[') && s.ends_with(']') && s.contains(';') => {
+                VTableFieldType::parse_array_type(s)?
+            }
+            s if s.starts_with('(') && s.ends_with(')') => VTableFieldType::parse_tuple_types(s)?,
             s if s.contains("str") => VTableFieldType::Str,
             // s if s.contains("[u8]") => VTableFieldType::Bytes,
             // s if s.contains("[u8; ") => VTableFieldType::Bytes,
-            s if s.contains("[u8") => VTableFieldType::Bytes,
+            s if s.contains("[u8") && !s.contains(';') => VTableFieldType::Bytes,
             s if s.contains("Uuid") => VTableFieldType::Uuid,
             s if s.contains("Option < Vec < u8 ") => {
                 VTableFieldType::Option(Box::new(VTableFieldType::Bytes))
@@ -412,21 +615,23 @@ impl From<&str> for VTableFieldType {
                 let t = s.trim_start_matches("Option < ").trim_end_matches(" >");
                 let t = t.trim_start_matches("Vec < ").trim_end_matches(" >");
                 VTableFieldType::Option(Box::new(VTableFieldType::Vec(Box::new(
-                    VTableFieldType::from(t),
+                    VTableFieldType::try_from(t)?,
                 ))))
             }
             s if s.contains("Vec") => {
                 let t = s.trim_start_matches("Vec < ").trim_end_matches(" >");
-                VTableFieldType::Vec(Box::new(VTableFieldType::from(t)))
+                VTableFieldType::Vec(Box::new(VTableFieldType::try_from(t)?))
             }
             s if s.contains("Option") => {
                 let t = s.trim_start_matches("Option < ").trim_end_matches(" >");
-                VTableFieldType::Option(Box::new(VTableFieldType::from(t)))
+                VTableFieldType::Option(Box::new(VTableFieldType::try_from(t)?))
             }
             "bool" => VTableFieldType::Bool,
-            s if s.contains("HashMap") => VTableFieldType::parse_hashmap_types(s),
+            s if s.contains("HashMap") => VTableFieldType::parse_hashmap_types(s)?,
             s => VTableFieldType::Struct(s.to_owned()),
-        }
+        };
+
+        Ok(r#type)
     }
 }
 
@@ -461,6 +666,14 @@ impl TryFrom<u8> for VTableFieldType {
             },
             21 => VTableFieldType::Uuid,
             22 => VTableFieldType::Option(Box::new(VTableFieldType::U8)),
+            23 => VTableFieldType::Enum(String::new()),
+            24 => VTableFieldType::Char,
+            25 => VTableFieldType::Unit,
+            26 => VTableFieldType::Array {
+                elem: Box::new(VTableFieldType::U8),
+                len: 0,
+            },
+            27 => VTableFieldType::Tuple(Vec::new()),
             _ => return Err(Error::UnknownFieldType(byte)),
         };
 
@@ -494,6 +707,11 @@ impl Into<u8> for VTableFieldType {
             VTableFieldType::HashMap { .. } => 20, //
             VTableFieldType::Uuid => 21,
             VTableFieldType::Option(_) => 22,
+            VTableFieldType::Enum(_) => 23,
+            VTableFieldType::Char => 24,
+            VTableFieldType::Unit => 25,
+            VTableFieldType::Array { .. } => 26,
+            VTableFieldType::Tuple(_) => 27,
         }
     }
 }
@@ -519,14 +737,22 @@ impl PartialEq for VTableFieldType {
             | (VTableFieldType::Str, VTableFieldType::Str)
             | (VTableFieldType::Bytes, VTableFieldType::Bytes)
             | (VTableFieldType::Bool, VTableFieldType::Bool)
-            | (VTableFieldType::Uuid, VTableFieldType::Uuid) => true,
+            | (VTableFieldType::Uuid, VTableFieldType::Uuid)
+            | (VTableFieldType::Char, VTableFieldType::Char)
+            | (VTableFieldType::Unit, VTableFieldType::Unit) => true,
             (VTableFieldType::Struct(s1), VTableFieldType::Struct(s2)) => s1 == s2,
+            (VTableFieldType::Enum(e1), VTableFieldType::Enum(e2)) => e1 == e2,
             (
                 VTableFieldType::HashMap { key: k1, value: v1 },
                 VTableFieldType::HashMap { key: k2, value: v2 },
             ) => k1 == k2 && v1 == v2,
             (VTableFieldType::Option(o1), VTableFieldType::Option(o2)) => o1 == o2,
             (VTableFieldType::Vec(v1), VTableFieldType::Vec(v2)) => v1 == v2,
+            (
+                VTableFieldType::Array { elem: e1, len: l1 },
+                VTableFieldType::Array { elem: e2, len: l2 },
+            ) => e1 == e2 && l1 == l2,
+            (VTableFieldType::Tuple(t1), VTableFieldType::Tuple(t2)) => t1 == t2,
             _ => false,
         }
     }
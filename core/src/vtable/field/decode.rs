@@ -1,379 +1,282 @@
 use super::*;
 
 impl VTableField {
-    pub fn decode_option(&self, buffer: &mut Vec<u8>) -> Result<Option<()>, Error> {
-        // println!("Decode Option Buffer: {buffer:?}");
-        if buffer.len() < NULL_FIELD.len() {
-            return Ok(Some(()));
-        }
-
-        let bytes = &buffer[..16];
-
-        if bytes == &NULL_FIELD {
-            // println!("Decoding Option: {bytes:?}");
-            buffer.drain(0..16);
-            Ok(None)
-        } else {
-            Ok(Some(()))
+    /// Consume the null marker of an optional field, returning `None` if the field is null.
+    pub fn decode_option(&self, buffer: &mut DocBufReader<'_>) -> Result<Option<()>, Error> {
+        match buffer.consume_if(&NULL_FIELD) {
+            true => Ok(None),
+            false => Ok(Some(())),
         }
     }
 }
 
 impl DocBufDecodeField<String> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<String, Error> {
-        match self.r#type {
-            // VTableFieldType::Uuid => {},
-            VTableFieldType::String | VTableFieldType::HashMap { .. } => {
-                let length =
-                    u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as usize;
-
-                // Consume the data from the buffer
-                buffer.drain(0..DEFAULT_FIELD_LENGTH_LE_BYTES);
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<String, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::String) || t.holds(&VTableFieldType::Str) => {
+                let length = self.decode_bytes_len(buffer)?;
 
-                let data = String::from_utf8(buffer.drain(0..length).collect())?;
-
-                Ok(data)
+                Ok(buffer.read_str(length)?.to_owned())
             }
             _ => {
                 dbg!("Failed to Decode Type: String");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
 impl DocBufDecodeField<bool> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<bool, Error> {
-        match self.r#type {
-            VTableFieldType::Bool => {
-                let data = buffer[0] == 1;
-
-                // Consume the data from the buffer
-                buffer.drain(0..1);
-
-                Ok(data)
-            }
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<bool, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::Bool) => Ok(buffer.read_u8()? == 1),
             _ => {
                 dbg!("Failed to Decode Type: bool");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
 impl DocBufDecodeField<u8> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<u8, Error> {
-        // println!("Decoding: {:?}", self.r#type);
-
-        match self.r#type {
-            VTableFieldType::U8 | VTableFieldType::Uuid | VTableFieldType::Bytes => {
-                let data = buffer[0];
-
-                // Consume the data from the buffer
-                buffer.drain(0..1);
-
-                Ok(data)
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<u8, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::U8) || t.holds(&VTableFieldType::Uuid) => {
+                buffer.read_u8()
             }
             _ => {
                 dbg!("Failed to Decode Type: u8");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
 impl DocBufDecodeField<u16> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<u16, Error> {
-        // println!("Field Type: {:?}", self.r#type);
-
-        match self.r#type {
-            VTableFieldType::U16 | VTableFieldType::Option(_) => {
-                let data = u16::from_le_bytes([buffer[0], buffer[1]]);
-
-                // Consume the data from the buffer
-                buffer.drain(0..2);
-
-                Ok(data)
-            }
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<u16, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::U16) => match self.encoding {
+                VTableEncoding::Fixed => Ok(u16::from_le_bytes(buffer.read_array()?)),
+                VTableEncoding::Compact => buffer.read_uvarint(),
+            },
             _ => {
                 dbg!("Failed to Decode Type: u16");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
 impl DocBufDecodeField<u32> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<u32, Error> {
-        match self.r#type {
-            VTableFieldType::U32 => {
-                let data = u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]);
-
-                // Consume the data from the buffer
-                buffer.drain(0..4);
-
-                Ok(data)
-            }
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<u32, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::U32) => match self.encoding {
+                VTableEncoding::Fixed => Ok(u32::from_le_bytes(buffer.read_array()?)),
+                VTableEncoding::Compact => buffer.read_uvarint(),
+            },
             _ => {
                 dbg!("Failed to Decode Type: u32");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
 impl DocBufDecodeField<u64> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<u64, Error> {
-        match self.r#type {
-            VTableFieldType::U64 | VTableFieldType::USIZE => {
-                let data = u64::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
-                    buffer[7],
-                ]);
-
-                // Consume the data from the buffer
-                buffer.drain(0..8);
-
-                Ok(data)
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<u64, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::U64) || t.holds(&VTableFieldType::USIZE) => {
+                match self.encoding {
+                    VTableEncoding::Fixed => Ok(u64::from_le_bytes(buffer.read_array()?)),
+                    VTableEncoding::Compact => buffer.read_uvarint(),
+                }
             }
             _ => {
                 dbg!("Failed to Decode Type: u64");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
 impl DocBufDecodeField<u128> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<u128, Error> {
-        match self.r#type {
-            VTableFieldType::Uuid => {
-                unimplemented!("VTableFieldType::Uuid")
-            }
-
-            VTableFieldType::U128 => {
-                let data = u128::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
-                    buffer[7], buffer[8], buffer[9], buffer[10], buffer[11], buffer[12],
-                    buffer[13], buffer[14], buffer[15],
-                ]);
-
-                // Consume the data from the buffer
-                buffer.drain(0..16);
-
-                Ok(data)
-            }
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<u128, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::U128) => match self.encoding {
+                VTableEncoding::Fixed => Ok(u128::from_le_bytes(buffer.read_array()?)),
+                VTableEncoding::Compact => buffer.read_uvarint(),
+            },
             _ => {
                 dbg!("Failed to Decode Type: u128");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
 impl DocBufDecodeField<usize> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<usize, Error> {
-        match self.r#type {
-            VTableFieldType::USIZE => {
-                let data = usize::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
-                    buffer[7],
-                ]);
-
-                // Consume the data from the buffer
-                buffer.drain(0..8);
-
-                Ok(data)
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<usize, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::USIZE) => {
+                match self.encoding {
+                    VTableEncoding::Fixed => Ok(usize::from_le_bytes(buffer.read_array()?)),
+                    VTableEncoding::Compact => buffer.read_uvarint(),
+                }
             }
             _ => {
                 dbg!("Failed to Decode Type: usize");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
 impl DocBufDecodeField<i8> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<i8, Error> {
-        match self.r#type {
-            VTableFieldType::I8 => {
-                let data = i8::from_le_bytes([buffer[0]]);
-
-                // Consume the data from the buffer
-                buffer.drain(0..1);
-
-                Ok(data)
-            }
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<i8, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::I8) => Ok(i8::from_le_bytes(buffer.read_array()?)),
             _ => {
                 dbg!("Failed to Decode Type: i8");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
 impl DocBufDecodeField<i16> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<i16, Error> {
-        match self.r#type {
-            VTableFieldType::I16 => {
-                let data = i16::from_le_bytes([buffer[0], buffer[1]]);
-
-                // Consume the data from the buffer
-                buffer.drain(0..2);
-
-                Ok(data)
-            }
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<i16, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::I16) => match self.encoding {
+                VTableEncoding::Fixed => Ok(i16::from_le_bytes(buffer.read_array()?)),
+                VTableEncoding::Compact => buffer.read_ivarint(),
+            },
             _ => {
                 dbg!("Failed to Decode Type: i16");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
 impl DocBufDecodeField<i32> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<i32, Error> {
-        match self.r#type {
-            VTableFieldType::I32 => {
-                let data = i32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]);
-
-                // Consume the data from the buffer
-                buffer.drain(0..4);
-
-                Ok(data)
-            }
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<i32, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::I32) => match self.encoding {
+                VTableEncoding::Fixed => Ok(i32::from_le_bytes(buffer.read_array()?)),
+                VTableEncoding::Compact => buffer.read_ivarint(),
+            },
             _ => {
                 dbg!("Failed to Decode Type: i32");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
 impl DocBufDecodeField<i64> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<i64, Error> {
-        match self.r#type {
-            VTableFieldType::I64 | VTableFieldType::ISIZE => {
-                let data = i64::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
-                    buffer[7],
-                ]);
-
-                // Consume the data from the buffer
-                buffer.drain(0..8);
-
-                Ok(data)
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<i64, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::I64) || t.holds(&VTableFieldType::ISIZE) => {
+                match self.encoding {
+                    VTableEncoding::Fixed => Ok(i64::from_le_bytes(buffer.read_array()?)),
+                    VTableEncoding::Compact => buffer.read_ivarint(),
+                }
             }
             _ => {
                 dbg!("Failed to Decode Type: i64");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
 impl DocBufDecodeField<i128> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<i128, Error> {
-        match self.r#type {
-            VTableFieldType::I128 => {
-                let data = i128::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
-                    buffer[7], buffer[8], buffer[9], buffer[10], buffer[11], buffer[12],
-                    buffer[13], buffer[14], buffer[15],
-                ]);
-
-                // Consume the data from the buffer
-                buffer.drain(0..16);
-
-                Ok(data)
-            }
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<i128, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::I128) => match self.encoding {
+                VTableEncoding::Fixed => Ok(i128::from_le_bytes(buffer.read_array()?)),
+                VTableEncoding::Compact => buffer.read_ivarint(),
+            },
             _ => {
                 dbg!("Failed to Decode Type: i128");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
 impl DocBufDecodeField<isize> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<isize, Error> {
-        match self.r#type {
-            VTableFieldType::ISIZE => {
-                let data = isize::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
-                    buffer[7],
-                ]);
-
-                // Consume the data from the buffer
-                buffer.drain(0..8);
-
-                Ok(data)
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<isize, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::ISIZE) => {
+                match self.encoding {
+                    VTableEncoding::Fixed => Ok(isize::from_le_bytes(buffer.read_array()?)),
+                    VTableEncoding::Compact => buffer.read_ivarint(),
+                }
             }
             _ => {
                 dbg!("Failed to Decode Type: isize");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
 impl DocBufDecodeField<f32> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<f32, Error> {
-        match self.r#type {
-            VTableFieldType::F32 => {
-                let data = f32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]);
-
-                // Consume the data from the buffer
-                buffer.drain(0..4);
-
-                Ok(data)
-            }
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<f32, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::F32) => Ok(f32::from_le_bytes(buffer.read_array()?)),
             _ => {
                 dbg!("Failed to Decode Type: f32");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
 impl DocBufDecodeField<f64> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<f64, Error> {
-        match self.r#type {
-            VTableFieldType::F64 => {
-                let data = f64::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
-                    buffer[7],
-                ]);
-
-                // Consume the data from the buffer
-                buffer.drain(0..8);
-
-                Ok(data)
-            }
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<f64, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::F64) => Ok(f64::from_le_bytes(buffer.read_array()?)),
             _ => {
                 dbg!("Failed to Decode Type: f64");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
 }
 
-impl DocBufDecodeField<Vec<u8>> for VTableField {
-    fn decode(&self, buffer: &mut Vec<u8>) -> Result<Vec<u8>, Error> {
-        match self.r#type {
-            VTableFieldType::Uuid => Ok(buffer.drain(0..16).collect()),
-            VTableFieldType::Bytes => {
-                let length =
-                    u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as usize;
+impl DocBufDecodeField<char> for VTableField {
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<char, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::Char) => {
+                let value = match self.encoding {
+                    VTableEncoding::Fixed => u32::from_le_bytes(buffer.read_array()?),
+                    VTableEncoding::Compact => buffer.read_uvarint()?,
+                };
 
-                // Consume the data from the buffer
-                buffer.drain(0..DEFAULT_FIELD_LENGTH_LE_BYTES);
+                char::from_u32(value).ok_or(Error::FailedToParseData)
+            }
+            _ => Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned()))),
+        }
+    }
+}
+
+impl DocBufDecodeField<Vec<u8>> for VTableField {
+    fn decode(&self, buffer: &mut DocBufReader<'_>) -> Result<Vec<u8>, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::Uuid) => Ok(buffer.read_bytes(16)?.to_vec()),
+            VTableFieldType::Array { elem, len } if **elem == VTableFieldType::U8 => {
+                Ok(buffer.read_bytes(*len)?.to_vec())
+            }
+            t if t.holds(&VTableFieldType::Bytes) => {
+                let length = self.decode_bytes_len(buffer)?;
 
-                Ok(buffer.drain(0..length).collect())
+                Ok(buffer.read_bytes(length)?.to_vec())
             }
             _ => {
                 dbg!("Failed to Decode Type: Vec<u8>");
-                Err(Error::DocBufDecodeFieldType(self.to_owned()))
+                Err(Error::DocBufDecodeFieldType(Box::new(self.to_owned())))
             }
         }
     }
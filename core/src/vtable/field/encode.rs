@@ -22,12 +22,9 @@ impl DocBufEncodeField<String> for VTableField {
         self.rules.validate(data)?;
 
         match &self.r#type {
-            VTableFieldType::String | VTableFieldType::HashMap { .. } => {
-                // prepend length to the field data
-                let data_length = (data.len() as u32).to_le_bytes();
-
+            t if t.holds(&VTableFieldType::String) || t.holds(&VTableFieldType::Str) => {
                 // Encode the data length
-                buffer.extend_from_slice(&data_length);
+                self.encoding.write_len(data.len(), buffer);
 
                 let offset_start = buffer.len();
 
@@ -54,23 +51,23 @@ impl DocBufEncodeField<&str> for VTableField {
         self.rules.validate(data)?;
 
         match &self.r#type {
-            VTableFieldType::Uuid => {
+            t if t.holds(&VTableFieldType::Uuid)
+                && !(t.holds(&VTableFieldType::String) || t.holds(&VTableFieldType::Str)) =>
+            {
                 let offset_start = buffer.len();
 
-                // Encode the field data
-                buffer.extend_from_slice(data.as_bytes());
+                // Uuids are serialized as strings by human readable formats,
+                // encode the raw 16 bytes rather than the string.
+                buffer.extend_from_slice(uuid::Uuid::parse_str(data)?.as_bytes());
 
                 let offset_end = buffer.len();
 
                 // Return the offset of the field data, disregarding the data length
                 Ok(self.as_offset(offset_start..offset_end))
             }
-            VTableFieldType::String | VTableFieldType::HashMap { .. } => {
-                // prepend length to the field data
-                let data_length = (data.len() as u32).to_le_bytes();
-
+            t if t.holds(&VTableFieldType::String) || t.holds(&VTableFieldType::Str) => {
                 // Encode the data length
-                buffer.extend_from_slice(&data_length);
+                self.encoding.write_len(data.len(), buffer);
 
                 let offset_start = buffer.len();
 
@@ -97,7 +94,7 @@ impl DocBufEncodeField<&[u8]> for VTableField {
         self.rules.validate(data)?;
 
         match &self.r#type {
-            VTableFieldType::Uuid => {
+            t if t.holds(&VTableFieldType::Uuid) => {
                 let offset_start = buffer.len();
 
                 // Encode the field data
@@ -108,12 +105,25 @@ impl DocBufEncodeField<&[u8]> for VTableField {
                 // Return the offset of the field data, disregarding the data length
                 Ok(self.as_offset(offset_start..offset_end))
             }
-            VTableFieldType::Bytes | VTableFieldType::Vec(_) => {
-                // prepend length to the field data
-                let data_length = (data.len() as u32).to_le_bytes();
+            VTableFieldType::Array { elem, len } if **elem == VTableFieldType::U8 => {
+                // Byte arrays have a fixed length, and are encoded without a length prefix
+                if data.len() != *len {
+                    return Err(Error::FieldRulesLength(format!(
+                        "data size does not match array length: {len}"
+                    )));
+                }
 
+                let offset_start = buffer.len();
+
+                buffer.extend_from_slice(data);
+
+                let offset_end = buffer.len();
+
+                Ok(self.as_offset(offset_start..offset_end))
+            }
+            t if t.holds(&VTableFieldType::Bytes) || matches!(t, VTableFieldType::Vec(_)) => {
                 // Encode the data length
-                buffer.extend_from_slice(&data_length);
+                self.encoding.write_len(data.len(), buffer);
 
                 let offset_start = buffer.len();
 
@@ -140,7 +150,7 @@ impl DocBufEncodeField<bool> for VTableField {
         self.rules.validate(data)?;
 
         match &self.r#type {
-            VTableFieldType::Bool => {
+            t if t.holds(&VTableFieldType::Bool) => {
                 let offset_start = buffer.len();
 
                 // Encode the field data
@@ -159,6 +169,30 @@ impl DocBufEncodeField<bool> for VTableField {
     }
 }
 
+impl DocBufEncodeField<char> for VTableField {
+    fn encode(&self, data: &char, buffer: &mut Vec<u8>) -> Result<VTableFieldOffset, Error> {
+        match &self.r#type {
+            t if t.holds(&VTableFieldType::Char) => {
+                let offset_start = buffer.len();
+
+                // Encode the unicode scalar value as a u32
+                match self.encoding {
+                    VTableEncoding::Fixed => {
+                        buffer.extend_from_slice(&(*data as u32).to_le_bytes())
+                    }
+                    VTableEncoding::Compact => write_uvarint(*data as u128, buffer),
+                }
+
+                let offset_end = buffer.len();
+
+                // Return the offset of the field data
+                Ok(self.as_offset(offset_start..offset_end))
+            }
+            _ => Err(Error::DocBufEncodeFieldType(self.r#type.to_string())),
+        }
+    }
+}
+
 impl DocBufEncodeField<NumericValue> for VTableField {
     fn encode(
         &self,
@@ -170,21 +204,49 @@ impl DocBufEncodeField<NumericValue> for VTableField {
 
         let offset_start = buffer.len();
 
-        match data {
-            NumericValue::U8(value) => buffer.push(*value),
-            NumericValue::U16(value) => buffer.extend_from_slice(&value.to_le_bytes()),
-            NumericValue::U32(value) => buffer.extend_from_slice(&value.to_le_bytes()),
-            NumericValue::U64(value) => buffer.extend_from_slice(&value.to_le_bytes()),
-            NumericValue::U128(value) => buffer.extend_from_slice(&value.to_le_bytes()),
-            NumericValue::USIZE(value) => buffer.extend_from_slice(&value.to_le_bytes()),
-            NumericValue::F32(value) => buffer.extend_from_slice(&value.to_le_bytes()),
-            NumericValue::F64(value) => buffer.extend_from_slice(&value.to_le_bytes()),
-            NumericValue::I8(value) => buffer.extend_from_slice(&value.to_le_bytes()),
-            NumericValue::I16(value) => buffer.extend_from_slice(&value.to_le_bytes()),
-            NumericValue::I32(value) => buffer.extend_from_slice(&value.to_le_bytes()),
-            NumericValue::I64(value) => buffer.extend_from_slice(&value.to_le_bytes()),
-            NumericValue::I128(value) => buffer.extend_from_slice(&value.to_le_bytes()),
-            NumericValue::ISIZE(value) => buffer.extend_from_slice(&value.to_le_bytes()),
+        match (self.encoding, data) {
+            (VTableEncoding::Compact, NumericValue::U16(value)) => {
+                write_uvarint(*value as u128, buffer)
+            }
+            (VTableEncoding::Compact, NumericValue::U32(value)) => {
+                write_uvarint(*value as u128, buffer)
+            }
+            (VTableEncoding::Compact, NumericValue::U64(value)) => {
+                write_uvarint(*value as u128, buffer)
+            }
+            (VTableEncoding::Compact, NumericValue::U128(value)) => write_uvarint(*value, buffer),
+            (VTableEncoding::Compact, NumericValue::USIZE(value)) => {
+                write_uvarint(*value as u128, buffer)
+            }
+            (VTableEncoding::Compact, NumericValue::I16(value)) => {
+                write_uvarint(zigzag_encode(*value as i128), buffer)
+            }
+            (VTableEncoding::Compact, NumericValue::I32(value)) => {
+                write_uvarint(zigzag_encode(*value as i128), buffer)
+            }
+            (VTableEncoding::Compact, NumericValue::I64(value)) => {
+                write_uvarint(zigzag_encode(*value as i128), buffer)
+            }
+            (VTableEncoding::Compact, NumericValue::I128(value)) => {
+                write_uvarint(zigzag_encode(*value), buffer)
+            }
+            (VTableEncoding::Compact, NumericValue::ISIZE(value)) => {
+                write_uvarint(zigzag_encode(*value as i128), buffer)
+            }
+            (_, NumericValue::U8(value)) => buffer.push(*value),
+            (_, NumericValue::U16(value)) => buffer.extend_from_slice(&value.to_le_bytes()),
+            (_, NumericValue::U32(value)) => buffer.extend_from_slice(&value.to_le_bytes()),
+            (_, NumericValue::U64(value)) => buffer.extend_from_slice(&value.to_le_bytes()),
+            (_, NumericValue::U128(value)) => buffer.extend_from_slice(&value.to_le_bytes()),
+            (_, NumericValue::USIZE(value)) => buffer.extend_from_slice(&value.to_le_bytes()),
+            (_, NumericValue::F32(value)) => buffer.extend_from_slice(&value.to_le_bytes()),
+            (_, NumericValue::F64(value)) => buffer.extend_from_slice(&value.to_le_bytes()),
+            (_, NumericValue::I8(value)) => buffer.extend_from_slice(&value.to_le_bytes()),
+            (_, NumericValue::I16(value)) => buffer.extend_from_slice(&value.to_le_bytes()),
+            (_, NumericValue::I32(value)) => buffer.extend_from_slice(&value.to_le_bytes()),
+            (_, NumericValue::I64(value)) => buffer.extend_from_slice(&value.to_le_bytes()),
+            (_, NumericValue::I128(value)) => buffer.extend_from_slice(&value.to_le_bytes()),
+            (_, NumericValue::ISIZE(value)) => buffer.extend_from_slice(&value.to_le_bytes()),
         };
 
         let offset_end = buffer.len();
@@ -0,0 +1,260 @@
+use super::*;
+
+/// A built-in string format of a field, set with `#[docbuf { format = "email"; }]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum VTableFieldFormat {
+    /// An email address, e.g. `user@example.com`.
+    Email = 0,
+    /// An absolute URI with a scheme, e.g. `https://example.com/path`.
+    Uri = 1,
+    /// A hyphenated UUID, e.g. `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+    Uuid = 2,
+    /// An RFC 3339 date and time, e.g. `2024-03-01T12:30:00Z`.
+    Rfc3339 = 3,
+    /// An IPv4 address, e.g. `127.0.0.1`.
+    Ipv4 = 4,
+    /// An IPv6 address, e.g. `::1`.
+    Ipv6 = 5,
+}
+
+impl From<VTableFieldFormat> for u8 {
+    fn from(format: VTableFieldFormat) -> u8 {
+        format as u8
+    }
+}
+
+impl TryFrom<u8> for VTableFieldFormat {
+    type Error = Error;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte {
+            0 => Ok(VTableFieldFormat::Email),
+            1 => Ok(VTableFieldFormat::Uri),
+            2 => Ok(VTableFieldFormat::Uuid),
+            3 => Ok(VTableFieldFormat::Rfc3339),
+            4 => Ok(VTableFieldFormat::Ipv4),
+            5 => Ok(VTableFieldFormat::Ipv6),
+            _ => Err(Error::UnknownFieldFormat(byte)),
+        }
+    }
+}
+
+impl std::fmt::Display for VTableFieldFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VTableFieldFormat::Email => write!(f, "email"),
+            VTableFieldFormat::Uri => write!(f, "uri"),
+            VTableFieldFormat::Uuid => write!(f, "uuid"),
+            VTableFieldFormat::Rfc3339 => write!(f, "rfc3339"),
+            VTableFieldFormat::Ipv4 => write!(f, "ipv4"),
+            VTableFieldFormat::Ipv6 => write!(f, "ipv6"),
+        }
+    }
+}
+
+impl VTableFieldFormat {
+    /// Returns true if the data is in the format.
+    pub fn is_valid(&self, data: &str) -> bool {
+        match self {
+            VTableFieldFormat::Email => is_email(data),
+            VTableFieldFormat::Uri => is_uri(data),
+            VTableFieldFormat::Uuid => is_uuid(data),
+            VTableFieldFormat::Rfc3339 => is_rfc3339(data),
+            VTableFieldFormat::Ipv4 => data.parse::<std::net::Ipv4Addr>().is_ok(),
+            VTableFieldFormat::Ipv6 => data.parse::<std::net::Ipv6Addr>().is_ok(),
+        }
+    }
+}
+
+/// The whitespace normalization of a string field, following the XSD
+/// `whitespace` facet, set with `#[docbuf { whitespace = "collapse"; }]`.
+///
+/// Values are not normalized when encoded, values that are not already
+/// normalized are rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum VTableFieldWhitespace {
+    /// Whitespace is kept as is.
+    Preserve = 0,
+    /// Tabs, line feeds and carriage returns are not allowed.
+    Replace = 1,
+    /// As `Replace`, and leading, trailing and consecutive spaces are not allowed.
+    Collapse = 2,
+}
+
+impl From<VTableFieldWhitespace> for u8 {
+    fn from(whitespace: VTableFieldWhitespace) -> u8 {
+        whitespace as u8
+    }
+}
+
+impl TryFrom<u8> for VTableFieldWhitespace {
+    type Error = Error;
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte {
+            0 => Ok(VTableFieldWhitespace::Preserve),
+            1 => Ok(VTableFieldWhitespace::Replace),
+            2 => Ok(VTableFieldWhitespace::Collapse),
+            _ => Err(Error::UnknownWhitespace(byte)),
+        }
+    }
+}
+
+impl std::fmt::Display for VTableFieldWhitespace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VTableFieldWhitespace::Preserve => write!(f, "preserve"),
+            VTableFieldWhitespace::Replace => write!(f, "replace"),
+            VTableFieldWhitespace::Collapse => write!(f, "collapse"),
+        }
+    }
+}
+
+impl VTableFieldWhitespace {
+    /// Returns true if the data is already normalized.
+    pub fn is_normalized(&self, data: &str) -> bool {
+        let replaced = !data.contains(['\t', '\n', '\r']);
+
+        match self {
+            VTableFieldWhitespace::Preserve => true,
+            VTableFieldWhitespace::Replace => replaced,
+            VTableFieldWhitespace::Collapse => {
+                replaced && !data.starts_with(' ') && !data.ends_with(' ') && !data.contains("  ")
+            }
+        }
+    }
+}
+
+fn is_email(data: &str) -> bool {
+    let Some((local, domain)) = data.rsplit_once('@') else {
+        return false;
+    };
+
+    let valid_local = !local.is_empty()
+        && local.len() <= 64
+        && !local.starts_with('.')
+        && !local.ends_with('.')
+        && !local.contains("..")
+        && local.chars().all(|c| {
+            c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~.".contains(c) || !c.is_ascii()
+        });
+
+    let labels = domain.split('.').collect::<Vec<_>>();
+
+    let valid_domain = labels.len() > 1
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        });
+
+    valid_local && valid_domain
+}
+
+fn is_uri(data: &str) -> bool {
+    let Some((scheme, rest)) = data.split_once(':') else {
+        return false;
+    };
+
+    let mut scheme_chars = scheme.chars();
+
+    let valid_scheme = scheme_chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && scheme_chars.all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+
+    // Percent encodings must be followed by two hex digits.
+    let valid_escapes = rest.split('%').skip(1).all(|escaped| {
+        escaped.len() >= 2 && escaped.as_bytes()[..2].iter().all(u8::is_ascii_hexdigit)
+    });
+
+    valid_scheme
+        && valid_escapes
+        && !rest.is_empty()
+        && !rest
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || "<>\"{}|\\^`".contains(c))
+}
+
+fn is_uuid(data: &str) -> bool {
+    data.len() == 36
+        && data.bytes().enumerate().all(|(index, byte)| match index {
+            8 | 13 | 18 | 23 => byte == b'-',
+            _ => byte.is_ascii_hexdigit(),
+        })
+}
+
+/// Parse the ASCII digits in the range of the data.
+fn parse_digits(data: &str, range: Range<usize>) -> Option<u32> {
+    let digits = data.get(range)?;
+
+    if digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        digits.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn is_rfc3339(data: &str) -> bool {
+    let number = |range: Range<usize>| parse_digits(data, range);
+
+    let (Some(year), Some(month), Some(day), Some(hour), Some(minute), Some(second)) = (
+        number(0..4),
+        number(5..7),
+        number(8..10),
+        number(11..13),
+        number(14..16),
+        number(17..19),
+    ) else {
+        return false;
+    };
+
+    let bytes = data.as_bytes();
+
+    if bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || !matches!(bytes[10], b'T' | b't')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return false;
+    }
+
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        _ => return false,
+    };
+
+    // A leap second is allowed as the 60th second.
+    if day == 0 || day > days_in_month || hour > 23 || minute > 59 || second > 60 {
+        return false;
+    }
+
+    let mut offset = &data[19..];
+
+    if let Some(fraction) = offset.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+
+        if digits == 0 {
+            return false;
+        }
+
+        offset = &fraction[digits..];
+    }
+
+    match offset {
+        "Z" | "z" => true,
+        _ => {
+            offset.len() == 6
+                && (offset.starts_with('+') || offset.starts_with('-'))
+                && offset.as_bytes()[3] == b':'
+                && parse_digits(offset, 1..3).is_some_and(|hours| hours <= 23)
+                && parse_digits(offset, 4..6).is_some_and(|minutes| minutes <= 59)
+        }
+    }
+}
@@ -23,23 +23,71 @@ pub enum NumericValue {
 impl NumericValue {
     fn as_u8_type(&self) -> u8 {
         match self {
-            NumericValue::U8(_) => NumericValue::U8 as u8,
-            NumericValue::U16(_) => NumericValue::U16 as u8,
-            NumericValue::U32(_) => NumericValue::U32 as u8,
-            NumericValue::U64(_) => NumericValue::U64 as u8,
-            NumericValue::U128(_) => NumericValue::U128 as u8,
-            NumericValue::USIZE(_) => NumericValue::USIZE as u8,
-            NumericValue::F32(_) => NumericValue::F32 as u8,
-            NumericValue::F64(_) => NumericValue::F64 as u8,
-            NumericValue::I8(_) => NumericValue::I8 as u8,
-            NumericValue::I16(_) => NumericValue::I16 as u8,
-            NumericValue::I32(_) => NumericValue::I32 as u8,
-            NumericValue::I64(_) => NumericValue::I64 as u8,
-            NumericValue::I128(_) => NumericValue::I128 as u8,
-            NumericValue::ISIZE(_) => NumericValue::ISIZE as u8,
+            NumericValue::U8(_) => 0,
+            NumericValue::U16(_) => 1,
+            NumericValue::U32(_) => 2,
+            NumericValue::U64(_) => 3,
+            NumericValue::U128(_) => 4,
+            NumericValue::USIZE(_) => 5,
+            NumericValue::F32(_) => 6,
+            NumericValue::F64(_) => 7,
+            NumericValue::I8(_) => 8,
+            NumericValue::I16(_) => 9,
+            NumericValue::I32(_) => 10,
+            NumericValue::I64(_) => 11,
+            NumericValue::I128(_) => 12,
+            NumericValue::ISIZE(_) => 13,
         }
     }
 
+    /// Return the field type of the value, e.g. `VTableFieldType::U16` for `NumericValue::U16`.
+    pub fn field_type(&self) -> VTableFieldType {
+        match self {
+            NumericValue::U8(_) => VTableFieldType::U8,
+            NumericValue::U16(_) => VTableFieldType::U16,
+            NumericValue::U32(_) => VTableFieldType::U32,
+            NumericValue::U64(_) => VTableFieldType::U64,
+            NumericValue::U128(_) => VTableFieldType::U128,
+            NumericValue::USIZE(_) => VTableFieldType::USIZE,
+            NumericValue::F32(_) => VTableFieldType::F32,
+            NumericValue::F64(_) => VTableFieldType::F64,
+            NumericValue::I8(_) => VTableFieldType::I8,
+            NumericValue::I16(_) => VTableFieldType::I16,
+            NumericValue::I32(_) => VTableFieldType::I32,
+            NumericValue::I64(_) => VTableFieldType::I64,
+            NumericValue::I128(_) => VTableFieldType::I128,
+            NumericValue::ISIZE(_) => VTableFieldType::ISIZE,
+        }
+    }
+
+    /// Return the number of total and fraction digits of the decimal
+    /// representation of the value, ignoring leading and trailing zeros.
+    /// Returns `None` for infinite and NaN floats.
+    pub fn decimal_digits(&self) -> Option<(usize, usize)> {
+        let decimal = self.to_string();
+
+        if !decimal
+            .bytes()
+            .all(|byte| byte.is_ascii_digit() || byte == b'-' || byte == b'.')
+        {
+            return None;
+        }
+
+        let decimal = decimal.trim_start_matches('-');
+        let (integer, fraction) = decimal.split_once('.').unwrap_or((decimal, ""));
+
+        let integer = integer.trim_start_matches('0');
+        let fraction = fraction.trim_end_matches('0');
+
+        let total_digits = if integer.is_empty() {
+            fraction.trim_start_matches('0').len()
+        } else {
+            integer.len() + fraction.len()
+        };
+
+        Some((total_digits, fraction.len()))
+    }
+
     pub fn from_u8_type(value: u8) -> Result<Self, Error> {
         let value = match value {
             0 => NumericValue::U8(0),
@@ -88,107 +136,31 @@ impl NumericValue {
         Ok(())
     }
 
-    pub fn read_from_buffer(buffer: &mut Vec<u8>) -> Result<Self, Error> {
-        let value_type = Self::from_u8_type(buffer.remove(0))?;
+    pub fn read_from_buffer(buffer: &mut DocBufReader<'_>) -> Result<Self, Error> {
+        let value_type = Self::from_u8_type(buffer.read_u8()?)?;
 
         let value = match value_type {
-            NumericValue::U8(_) => {
-                let value = buffer.remove(0);
-                NumericValue::U8(value)
-            }
-            NumericValue::U16(_) => {
-                let value = NumericValue::U16(u16::from_le_bytes([buffer[0], buffer[1]]));
-                buffer.drain(0..2);
-                value
-            }
-            NumericValue::U32(_) => {
-                let value = NumericValue::U32(u32::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3],
-                ]));
-                buffer.drain(0..4);
-                value
-            }
-            NumericValue::U64(_) => {
-                let value = NumericValue::U64(u64::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
-                    buffer[7],
-                ]));
-                buffer.drain(0..8);
-                value
-            }
+            NumericValue::U8(_) => NumericValue::U8(buffer.read_u8()?),
+            NumericValue::U16(_) => NumericValue::U16(u16::from_le_bytes(buffer.read_array()?)),
+            NumericValue::U32(_) => NumericValue::U32(u32::from_le_bytes(buffer.read_array()?)),
+            NumericValue::U64(_) => NumericValue::U64(u64::from_le_bytes(buffer.read_array()?)),
             NumericValue::U128(_) => {
-                let value = NumericValue::U128(u128::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
-                    buffer[7], buffer[8], buffer[9], buffer[10], buffer[11], buffer[12],
-                    buffer[13], buffer[14], buffer[15],
-                ]));
-                buffer.drain(0..16);
-                value
+                NumericValue::U128(u128::from_le_bytes(buffer.read_array()?))
             }
             NumericValue::USIZE(_) => {
-                let value = NumericValue::USIZE(usize::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
-                    buffer[7],
-                ]));
-                buffer.drain(0..8);
-                value
-            }
-            NumericValue::F32(_) => {
-                let value = NumericValue::F32(f32::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3],
-                ]));
-                buffer.drain(0..4);
-                value
-            }
-            NumericValue::F64(_) => {
-                let value = NumericValue::F64(f64::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
-                    buffer[7],
-                ]));
-                buffer.drain(0..8);
-                value
-            }
-            NumericValue::I8(_) => {
-                let value = NumericValue::I8(buffer.remove(0) as i8);
-                value
-            }
-            NumericValue::I16(_) => {
-                let value = NumericValue::I16(i16::from_le_bytes([buffer[0], buffer[1]]));
-                buffer.drain(0..2);
-                value
-            }
-
-            NumericValue::I32(_) => {
-                let value = NumericValue::I32(i32::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3],
-                ]));
-                buffer.drain(0..4);
-                value
-            }
-            NumericValue::I64(_) => {
-                let value = NumericValue::I64(i64::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
-                    buffer[7],
-                ]));
-                buffer.drain(0..8);
-                value
+                NumericValue::USIZE(usize::from_le_bytes(buffer.read_array()?))
             }
+            NumericValue::F32(_) => NumericValue::F32(f32::from_le_bytes(buffer.read_array()?)),
+            NumericValue::F64(_) => NumericValue::F64(f64::from_le_bytes(buffer.read_array()?)),
+            NumericValue::I8(_) => NumericValue::I8(buffer.read_u8()? as i8),
+            NumericValue::I16(_) => NumericValue::I16(i16::from_le_bytes(buffer.read_array()?)),
+            NumericValue::I32(_) => NumericValue::I32(i32::from_le_bytes(buffer.read_array()?)),
+            NumericValue::I64(_) => NumericValue::I64(i64::from_le_bytes(buffer.read_array()?)),
             NumericValue::I128(_) => {
-                let value = NumericValue::I128(i128::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
-                    buffer[7], buffer[8], buffer[9], buffer[10], buffer[11], buffer[12],
-                    buffer[13], buffer[14], buffer[15],
-                ]));
-                buffer.drain(0..16);
-                value
+                NumericValue::I128(i128::from_le_bytes(buffer.read_array()?))
             }
             NumericValue::ISIZE(_) => {
-                let value = NumericValue::ISIZE(isize::from_le_bytes([
-                    buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
-                    buffer[7],
-                ]));
-                buffer.drain(0..8);
-                value
+                NumericValue::ISIZE(isize::from_le_bytes(buffer.read_array()?))
             }
         };
 
@@ -328,3 +300,24 @@ impl PartialOrd for NumericValue {
         }
     }
 }
+
+impl std::fmt::Display for NumericValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumericValue::U8(value) => write!(f, "{}", value),
+            NumericValue::U16(value) => write!(f, "{}", value),
+            NumericValue::U32(value) => write!(f, "{}", value),
+            NumericValue::U64(value) => write!(f, "{}", value),
+            NumericValue::U128(value) => write!(f, "{}", value),
+            NumericValue::USIZE(value) => write!(f, "{}", value),
+            NumericValue::F32(value) => write!(f, "{}", value),
+            NumericValue::F64(value) => write!(f, "{}", value),
+            NumericValue::I8(value) => write!(f, "{}", value),
+            NumericValue::I16(value) => write!(f, "{}", value),
+            NumericValue::I32(value) => write!(f, "{}", value),
+            NumericValue::I64(value) => write!(f, "{}", value),
+            NumericValue::I128(value) => write!(f, "{}", value),
+            NumericValue::ISIZE(value) => write!(f, "{}", value),
+        }
+    }
+}
@@ -86,6 +86,16 @@ impl VTableFieldOffsetDiff {
             Self::Decrease(old_len - new_len)
         }
     }
+
+    /// Shift a buffer position by the difference.
+    #[inline]
+    pub fn apply(&self, position: usize) -> usize {
+        match self {
+            Self::Increase(increase) => position + increase,
+            Self::Decrease(decrease) => position - decrease,
+            Self::None => position,
+        }
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -162,33 +172,43 @@ impl VTableFieldOffsets {
             }
         }
 
-        // Ensure the offsets are sorted by the start range
+        // Ensure the offsets are sorted by the start range, with empty and
+        // nested offsets before the offsets of the structs containing them.
         // TODO: Optimize this
-        self.0.sort_by(|a, b| a.1.start.cmp(&b.1.start));
+        self.0.sort_by_key(|offset| (offset.1.start, offset.1.end));
     }
 
+    /// Shift the offsets for a value at `from_index` of the buffer that grew or
+    /// shrank by `diff`. Offsets after the value are moved, while offsets
+    /// containing the value, e.g. of the struct, list or map containing it, are
+    /// resized. Empty offsets ending at `from_index` are left as is.
     #[inline]
     pub fn resize(&mut self, from_index: usize, diff: VTableFieldOffsetDiff) {
-        self.0
-            .iter_mut()
-            .filter(|offset| offset.1.start >= from_index)
-            .for_each(|offset| match diff {
-                VTableFieldOffsetDiff::Increase(increase) => {
-                    if offset.1.start != from_index {
-                        offset.1.start += increase;
-                    }
-
-                    offset.1.end += increase;
-                }
-                VTableFieldOffsetDiff::Decrease(decrease) => {
-                    if offset.1.start != from_index {
-                        offset.1.start -= decrease;
-                    }
-
-                    offset.1.end -= decrease;
-                }
-                VTableFieldOffsetDiff::None => {}
-            });
+        self.0.iter_mut().for_each(|offset| {
+            if offset.1.start > from_index {
+                offset.1.start = diff.apply(offset.1.start);
+                offset.1.end = diff.apply(offset.1.end);
+            } else if offset.1.end > from_index {
+                offset.1.end = diff.apply(offset.1.end);
+            }
+        });
+    }
+
+    /// Shift the offsets for a value at `range` of the buffer replaced by
+    /// `new_len` bytes, e.g. a value nested in a field. Offsets after the value
+    /// are moved, and offsets containing or ending with the value, e.g. of the
+    /// list containing it, are resized.
+    pub fn splice(&mut self, range: Range<usize>, new_len: usize) {
+        let (start, end) = (range.start, range.end);
+
+        self.0.iter_mut().for_each(|offset| {
+            if offset.1.start >= end && offset.1.start > start {
+                offset.1.start = offset.1.start - end + start + new_len;
+                offset.1.end = offset.1.end - end + start + new_len;
+            } else if offset.1.end >= end {
+                offset.1.end = offset.1.end - end + start + new_len;
+            }
+        });
     }
 
     pub fn offset(&self, index: VTableFieldOffsetIndex) -> Option<&VTableFieldOffset> {
@@ -1,6 +1,6 @@
 use super::*;
 
-use crate::validate::regex::Regex;
+use crate::validate::{regex::Regex, ValidationRule};
 
 use serde_derive::{Deserialize, Serialize};
 
@@ -24,6 +24,31 @@ pub struct VTableFieldRules {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub regex: Option<String>,
     pub sign: bool,
+    /// The allowed values of a string or boolean field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub one_of: Option<Vec<String>>,
+    /// The allowed values of a numeric field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub one_of_values: Option<Vec<NumericValue>>,
+    /// A built-in format of a string field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<VTableFieldFormat>,
+    /// The max number of significant digits of a numeric field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_digits: Option<usize>,
+    /// The max number of fraction digits of a numeric field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fraction_digits: Option<usize>,
+    /// The whitespace normalization of a string field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub whitespace: Option<VTableFieldWhitespace>,
+    /// Strings, byte arrays, lists and maps must not be empty
+    #[serde(default)]
+    pub not_empty: bool,
+    /// The field has a default value when missing, e.g. `#[serde(default)]`.
+    /// Not a validation rule, used to check the compatibility of vtables.
+    #[serde(default)]
+    pub default: bool,
 }
 
 impl VTableFieldRules {
@@ -38,6 +63,14 @@ impl VTableFieldRules {
             length: None,
             regex: None,
             sign: false,
+            one_of: None,
+            one_of_values: None,
+            format: None,
+            total_digits: None,
+            fraction_digits: None,
+            whitespace: None,
+            not_empty: false,
+            default: false,
         }
     }
 
@@ -53,6 +86,13 @@ impl VTableFieldRules {
             && self.length.is_none()
             && self.regex.is_none()
             && !self.sign
+            && self.one_of.is_none()
+            && self.one_of_values.is_none()
+            && self.format.is_none()
+            && self.total_digits.is_none()
+            && self.fraction_digits.is_none()
+            && self.whitespace.is_none()
+            && !self.not_empty
     }
 
     #[inline]
@@ -126,72 +166,213 @@ impl VTableFieldRules {
     }
 
     #[inline]
-    pub fn check_bool(&self, _data: &bool) -> Result<(), Error> {
-        unimplemented!("Boolean value checking is not yet implemented")
+    pub fn check_bool(&self, data: &bool) -> Result<(), Error> {
+        check(self.bool_violations(*data))
     }
 
     #[cfg(feature = "regex")]
     #[inline]
     pub fn check_regex(&self, data: &str) -> Result<(), Error> {
+        check(self.regex_violations(data))
+    }
+
+    #[inline]
+    pub fn check_length(&self, length: usize) -> Result<(), Error> {
+        check(self.length_violations(length))
+    }
+
+    #[inline]
+    pub fn check_numeric(&self, value: &NumericValue) -> Result<(), Error> {
+        check(self.numeric_violations(value))
+    }
+
+    #[inline]
+    pub fn check_string(&self, data: &str) -> Result<(), Error> {
+        check(self.string_violations(data))
+    }
+
+    #[inline]
+    pub fn check_bytes(&self, data: &[u8]) -> Result<(), Error> {
+        check(self.bytes_violations(data))
+    }
+
+    #[inline]
+    pub fn check_collection(&self, num_elements: usize) -> Result<(), Error> {
+        check(self.collection_violations(num_elements))
+    }
+
+    /// Return the rules violated by a string value.
+    pub fn string_violations(&self, data: &str) -> Vec<(ValidationRule, String)> {
+        let mut violations = self.length_violations(data.len());
+
+        #[cfg(feature = "regex")]
+        violations.extend(self.regex_violations(data));
+
+        violations.extend(self.collection_violations(data.len()));
+
+        if let Some(one_of) = &self.one_of {
+            if !one_of.iter().any(|value| value == data) {
+                let msg = format!("data is not one of: {}", one_of.join(", "));
+                violations.push((ValidationRule::OneOf, msg));
+            }
+        }
+
+        if let Some(format) = self.format {
+            if !format.is_valid(data) {
+                let msg = format!("data is not a valid {format}");
+                violations.push((ValidationRule::Format, msg));
+            }
+        }
+
+        if let Some(whitespace) = self.whitespace {
+            if !whitespace.is_normalized(data) {
+                let msg = format!("data whitespace is not normalized: {whitespace}");
+                violations.push((ValidationRule::Whitespace, msg));
+            }
+        }
+
+        violations
+    }
+
+    /// Return the rules violated by a byte array value.
+    pub fn bytes_violations(&self, data: &[u8]) -> Vec<(ValidationRule, String)> {
+        let mut violations = self.length_violations(data.len());
+
+        violations.extend(self.collection_violations(data.len()));
+
+        violations
+    }
+
+    /// Return the rules violated by a boolean value.
+    pub fn bool_violations(&self, data: bool) -> Vec<(ValidationRule, String)> {
+        let mut violations = Vec::new();
+
+        if let Some(one_of) = &self.one_of {
+            if !one_of.iter().any(|value| value == &data.to_string()) {
+                let msg = format!("data is not one of: {}", one_of.join(", "));
+                violations.push((ValidationRule::OneOf, msg));
+            }
+        }
+
+        violations
+    }
+
+    /// Return the rules violated by a string, byte array, list or map of
+    /// `num_elements` elements.
+    pub fn collection_violations(&self, num_elements: usize) -> Vec<(ValidationRule, String)> {
+        let mut violations = Vec::new();
+
+        if self.not_empty && num_elements == 0 {
+            let msg = "data is empty".to_string();
+            violations.push((ValidationRule::NotEmpty, msg));
+        }
+
+        violations
+    }
+
+    /// Return the regex rule violated by the data, if any.
+    #[cfg(feature = "regex")]
+    pub fn regex_violations(&self, data: &str) -> Vec<(ValidationRule, String)> {
+        let mut violations = Vec::new();
+
         if let Some(regex_pattern) = self.regex() {
             if !regex_pattern.is_match(data) {
                 let msg = format!("data does not match regex: {regex_pattern}");
-                return Err(Error::FieldRulesRegex(msg));
+                violations.push((ValidationRule::Regex, msg));
             }
         }
 
-        Ok(())
+        violations
     }
 
-    #[inline]
-    pub fn check_length(&self, length: usize) -> Result<(), Error> {
+    /// Return the length rules violated by data of the length.
+    pub fn length_violations(&self, length: usize) -> Vec<(ValidationRule, String)> {
+        let mut violations = Vec::new();
+
         if length > MAX_FIELD_SIZE {
             let msg = format!("data size exceeds 1 gigabyte");
-            return Err(Error::FieldRulesLength(msg));
+            violations.push((ValidationRule::MaxLength, msg));
         }
 
         if let Some(length_rule) = self.length {
             if length != length_rule {
-                let msg = format!("data size does not match required length: {length}");
-                return Err(Error::FieldRulesLength(msg));
+                let msg = format!("data size does not match required length: {length_rule}");
+                violations.push((ValidationRule::Length, msg));
             }
         } else {
             // If exact length is not set, check the min and max length values.
             if let Some(max_length) = self.max_length {
                 if length > max_length {
                     let msg = format!("data size exceeds field max length: {max_length}");
-                    return Err(Error::FieldRulesLength(msg));
+                    violations.push((ValidationRule::MaxLength, msg));
                 }
             }
 
             if let Some(min_length) = self.min_length {
                 if length < min_length {
                     let msg = format!("data size is less than min length: {min_length}");
-                    return Err(Error::FieldRulesLength(msg));
+                    violations.push((ValidationRule::MinLength, msg));
                 }
             };
         }
 
-        Ok(())
+        violations
     }
 
-    #[inline]
-    pub fn check_numeric(&self, value: &NumericValue) -> Result<(), Error> {
+    /// Return the value rules violated by the numeric value.
+    pub fn numeric_violations(&self, value: &NumericValue) -> Vec<(ValidationRule, String)> {
+        let mut violations = Vec::new();
+
         if let Some(max_value) = &self.max_value {
             if value > max_value {
                 let msg = format!("data value exceeds field max value: {:?}", max_value);
-                return Err(Error::FieldRulesValue(msg));
+                violations.push((ValidationRule::MaxValue, msg));
             }
         }
 
         if let Some(min_value) = &self.min_value {
             if value < min_value {
                 let msg = format!("data value is less than min value: {:?}", min_value);
-                return Err(Error::FieldRulesValue(msg));
+                violations.push((ValidationRule::MinValue, msg));
             }
         };
 
-        Ok(())
+        if let Some(one_of) = &self.one_of_values {
+            if !one_of.contains(value) {
+                let values = one_of
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<_>>();
+                let msg = format!("data value is not one of: {}", values.join(", "));
+                violations.push((ValidationRule::OneOf, msg));
+            }
+        }
+
+        if self.total_digits.is_some() || self.fraction_digits.is_some() {
+            match value.decimal_digits() {
+                None => {
+                    let msg = "data value is not a finite number".to_string();
+                    violations.push((ValidationRule::TotalDigits, msg));
+                }
+                Some((total_digits, fraction_digits)) => {
+                    if let Some(max_digits) = self.total_digits {
+                        if total_digits > max_digits {
+                            let msg = format!("data value exceeds total digits: {max_digits}");
+                            violations.push((ValidationRule::TotalDigits, msg));
+                        }
+                    }
+
+                    if let Some(max_digits) = self.fraction_digits {
+                        if fraction_digits > max_digits {
+                            let msg = format!("data value exceeds fraction digits: {max_digits}");
+                            violations.push((ValidationRule::FractionDigits, msg));
+                        }
+                    }
+                }
+            }
+        }
+
+        violations
     }
 
     #[inline]
@@ -245,64 +426,163 @@ impl VTableFieldRules {
             buffer.push(0);
         }
 
+        if let Some(one_of) = &self.one_of {
+            buffer.push(1);
+            buffer.extend_from_slice(&(one_of.len() as u16).to_le_bytes());
+
+            for value in one_of.iter() {
+                buffer.extend_from_slice(&(value.len() as u16).to_le_bytes());
+                buffer.extend_from_slice(value.as_bytes());
+            }
+        } else {
+            buffer.push(0);
+        }
+
+        if let Some(one_of_values) = &self.one_of_values {
+            buffer.push(1);
+            buffer.extend_from_slice(&(one_of_values.len() as u16).to_le_bytes());
+
+            for value in one_of_values.iter() {
+                value.write_to_buffer(buffer)?;
+            }
+        } else {
+            buffer.push(0);
+        }
+
+        if let Some(format) = self.format {
+            buffer.push(1);
+            buffer.push(format.into());
+        } else {
+            buffer.push(0);
+        }
+
+        if let Some(total_digits) = self.total_digits {
+            buffer.push(1);
+            buffer.extend_from_slice(&total_digits.to_le_bytes());
+        } else {
+            buffer.push(0);
+        }
+
+        if let Some(fraction_digits) = self.fraction_digits {
+            buffer.push(1);
+            buffer.extend_from_slice(&fraction_digits.to_le_bytes());
+        } else {
+            buffer.push(0);
+        }
+
+        if let Some(whitespace) = self.whitespace {
+            buffer.push(1);
+            buffer.push(whitespace.into());
+        } else {
+            buffer.push(0);
+        }
+
+        buffer.push(self.not_empty as u8);
+        buffer.push(self.default as u8);
+
         Ok(())
     }
 
     #[inline]
-    pub fn read_from_buffer(buffer: &mut Vec<u8>) -> Result<Self, Error> {
-        let ignore = buffer.remove(0) == 1;
-        let sign = buffer.remove(0) == 1;
+    pub fn read_from_buffer(buffer: &mut DocBufReader<'_>) -> Result<Self, Error> {
+        let ignore = buffer.read_u8()? == 1;
+        let sign = buffer.read_u8()? == 1;
 
-        let read_value = |buffer: &mut Vec<u8>| -> usize {
-            let value = usize::from_le_bytes([
-                buffer[0], buffer[1], buffer[2], buffer[3], buffer[4], buffer[5], buffer[6],
-                buffer[7],
-            ]);
-
-            buffer.drain(0..8);
-
-            value
+        let read_value = |buffer: &mut DocBufReader<'_>| -> Result<usize, Error> {
+            Ok(usize::from_le_bytes(buffer.read_array()?))
         };
 
-        let max_value = if buffer.remove(0) == 1 {
+        let max_value = if buffer.read_u8()? == 1 {
             Some(NumericValue::read_from_buffer(buffer)?)
         } else {
             None
         };
 
-        let min_value = if buffer.remove(0) == 1 {
+        let min_value = if buffer.read_u8()? == 1 {
             Some(NumericValue::read_from_buffer(buffer)?)
         } else {
             None
         };
 
-        let max_length = if buffer.remove(0) == 1 {
-            Some(read_value(buffer))
+        let max_length = if buffer.read_u8()? == 1 {
+            Some(read_value(buffer)?)
+        } else {
+            None
+        };
+
+        let min_length = if buffer.read_u8()? == 1 {
+            Some(read_value(buffer)?)
+        } else {
+            None
+        };
+
+        let length = if buffer.read_u8()? == 1 {
+            Some(read_value(buffer)?)
+        } else {
+            None
+        };
+
+        let regex = if buffer.read_u8()? == 1 {
+            let len = u16::from_le_bytes(buffer.read_array()?) as usize;
+            Some(buffer.read_str(len)?.to_owned())
+        } else {
+            None
+        };
+
+        let one_of = if buffer.read_u8()? == 1 {
+            let count = u16::from_le_bytes(buffer.read_array()?) as usize;
+
+            let mut one_of = Vec::with_capacity(count);
+            for _ in 0..count {
+                let len = u16::from_le_bytes(buffer.read_array()?) as usize;
+                one_of.push(buffer.read_str(len)?.to_owned());
+            }
+
+            Some(one_of)
+        } else {
+            None
+        };
+
+        let one_of_values = if buffer.read_u8()? == 1 {
+            let count = u16::from_le_bytes(buffer.read_array()?) as usize;
+
+            let mut one_of_values = Vec::with_capacity(count);
+            for _ in 0..count {
+                one_of_values.push(NumericValue::read_from_buffer(buffer)?);
+            }
+
+            Some(one_of_values)
+        } else {
+            None
+        };
+
+        let format = if buffer.read_u8()? == 1 {
+            Some(VTableFieldFormat::try_from(buffer.read_u8()?)?)
         } else {
             None
         };
 
-        let min_length = if buffer.remove(0) == 1 {
-            Some(read_value(buffer))
+        let total_digits = if buffer.read_u8()? == 1 {
+            Some(read_value(buffer)?)
         } else {
             None
         };
 
-        let length = if buffer.remove(0) == 1 {
-            Some(read_value(buffer))
+        let fraction_digits = if buffer.read_u8()? == 1 {
+            Some(read_value(buffer)?)
         } else {
             None
         };
 
-        let regex = if buffer.remove(0) == 1 {
-            let len = u16::from_be_bytes([buffer[0], buffer[1]]) as usize;
-            buffer.drain(0..2);
-            let regex = String::from_utf8(buffer.drain(0..len).collect())?;
-            Some(regex)
+        let whitespace = if buffer.read_u8()? == 1 {
+            Some(VTableFieldWhitespace::try_from(buffer.read_u8()?)?)
         } else {
             None
         };
 
+        let not_empty = buffer.read_u8()? == 1;
+        let default = buffer.read_u8()? == 1;
+
         Ok(Self {
             ignore,
             sign,
@@ -312,6 +592,14 @@ impl VTableFieldRules {
             min_length,
             length,
             regex,
+            one_of,
+            one_of_values,
+            format,
+            total_digits,
+            fraction_digits,
+            whitespace,
+            not_empty,
+            default,
         })
     }
 }
@@ -326,7 +614,24 @@ impl PartialEq for VTableFieldRules {
             && self.min_length == other.min_length
             && self.length == other.length
             && self.regex == other.regex
+            && self.one_of == other.one_of
+            && self.one_of_values == other.one_of_values
+            && self.format == other.format
+            && self.total_digits == other.total_digits
+            && self.fraction_digits == other.fraction_digits
+            && self.whitespace == other.whitespace
+            && self.not_empty == other.not_empty
+            && self.default == other.default
     }
 }
 
 impl Eq for VTableFieldRules {}
+
+/// Return the first violated rule as an error.
+#[inline]
+fn check(violations: Vec<(ValidationRule, String)>) -> Result<(), Error> {
+    match violations.into_iter().next() {
+        Some((rule, msg)) => Err(rule.error(msg)),
+        None => Ok(()),
+    }
+}
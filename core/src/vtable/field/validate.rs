@@ -10,12 +10,8 @@ impl<'a> DocBufValidateField<String> for VTableFieldRules {
             return Ok(());
         }
 
-        // Check for string length rules
-        self.check_length(data.len())?;
-
-        // Check for regex rules
-        #[cfg(feature = "regex")]
-        self.check_regex(data)?;
+        // Check for string length, regex, one of, format and whitespace rules
+        self.check_string(data)?;
 
         Ok(())
     }
@@ -29,12 +25,8 @@ impl<'a> DocBufValidateField<&str> for VTableFieldRules {
             return Ok(());
         }
 
-        // Check for string length rules
-        self.check_length(data.len())?;
-
-        // Check for regex rules
-        #[cfg(feature = "regex")]
-        self.check_regex(data)?;
+        // Check for string length, regex, one of, format and whitespace rules
+        self.check_string(data)?;
 
         Ok(())
     }
@@ -49,7 +41,7 @@ impl<'a> DocBufValidateField<Vec<u8>> for VTableFieldRules {
         }
 
         // Check for byte length rules
-        self.check_length(data.len())?;
+        self.check_bytes(data)?;
 
         Ok(())
     }
@@ -64,7 +56,7 @@ impl<'a> DocBufValidateField<&[u8]> for VTableFieldRules {
         }
 
         // Check for byte length rules
-        self.check_length(data.len())?;
+        self.check_bytes(data)?;
 
         Ok(())
     }
@@ -77,7 +69,7 @@ impl<'a> DocBufValidateField<NumericValue> for VTableFieldRules {
             return Ok(());
         }
 
-        // Check for numeric value rules
+        // Check for numeric value, one of and digits rules
         self.check_numeric(value)?;
 
         Ok(())
@@ -1,6 +1,8 @@
+pub mod enums;
 pub mod structs;
 
 use super::*;
+pub use enums::*;
 pub use structs::*;
 
 use serde_derive::{Deserialize, Serialize};
@@ -10,12 +12,14 @@ pub type VTableItemIndex = u8;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum VTableItem {
     Struct(VTableStruct),
+    Enum(VTableEnum),
 }
 
 impl Into<u8> for &VTableItem {
     fn into(self) -> u8 {
         match self {
             VTableItem::Struct(_) => 0,
+            VTableItem::Enum(_) => 1,
         }
     }
 }
@@ -24,12 +28,31 @@ impl From<u8> for VTableItem {
     fn from(value: u8) -> Self {
         match value {
             0 => VTableItem::Struct(VTableStruct::default()),
+            1 => VTableItem::Enum(VTableEnum::default()),
             _ => unimplemented!("VTableItem::from"),
         }
     }
 }
 
 impl VTableItem {
+    /// Return the name of the item
+    #[inline]
+    pub fn name(&self) -> &str {
+        match self {
+            VTableItem::Struct(vtable_struct) => &vtable_struct.name,
+            VTableItem::Enum(vtable_enum) => &vtable_enum.name,
+        }
+    }
+
+    /// Return the index of the item in the vtable
+    #[inline]
+    pub fn item_index(&self) -> VTableItemIndex {
+        match self {
+            VTableItem::Struct(vtable_struct) => vtable_struct.item_index,
+            VTableItem::Enum(vtable_enum) => vtable_enum.item_index,
+        }
+    }
+
     #[inline]
     pub fn write_to_buffer(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
         buffer.push(self.into());
@@ -38,20 +61,27 @@ impl VTableItem {
             VTableItem::Struct(vtable_struct) => {
                 vtable_struct.write_to_buffer(buffer)?;
             }
+            VTableItem::Enum(vtable_enum) => {
+                vtable_enum.write_to_buffer(buffer)?;
+            }
         }
 
         Ok(())
     }
 
     #[inline]
-    pub fn read_from_buffer(buffer: &mut Vec<u8>) -> Result<Self, Error> {
-        let value: Self = buffer.remove(0).into();
+    pub fn read_from_buffer(buffer: &mut DocBufReader<'_>) -> Result<Self, Error> {
+        let value: Self = buffer.read_u8()?.into();
 
         let item = match value {
             VTableItem::Struct(_) => {
                 let s = VTableStruct::read_from_buffer(buffer)?;
                 Self::Struct(s)
             }
+            VTableItem::Enum(_) => {
+                let e = VTableEnum::read_from_buffer(buffer)?;
+                Self::Enum(e)
+            }
         };
 
         Ok(item)
@@ -70,6 +100,10 @@ impl VTableItems {
         self.0.push(VTableItem::Struct(vtable_struct));
     }
 
+    pub fn add_enum(&mut self, vtable_enum: VTableEnum) {
+        self.0.push(VTableItem::Enum(vtable_enum));
+    }
+
     pub fn iter(&self) -> std::slice::Iter<'_, VTableItem> {
         self.0.iter()
     }
@@ -115,6 +149,8 @@ impl PartialEq for VTableItem {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (VTableItem::Struct(a), VTableItem::Struct(b)) => a == b,
+            (VTableItem::Enum(a), VTableItem::Enum(b)) => a == b,
+            _ => false,
         }
     }
 }
@@ -0,0 +1,332 @@
+use super::*;
+
+use serde_derive::{Deserialize, Serialize};
+
+pub type EnumName = String; //  = &'a str;
+
+pub type VariantName = String; //  = &'a str;
+
+/// Enum variants are encoded with a single byte discriminant,
+/// allowing up to 256 variants per enum.
+pub type VTableVariantIndex = u8;
+
+/// The shape of an enum variant's payload.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum VTableVariantKind {
+    /// `Variant`
+    #[default]
+    Unit,
+    /// `Variant(T)`
+    Newtype,
+    /// `Variant(T, U, ...)`
+    Tuple,
+    /// `Variant { a: T, b: U, ... }`
+    Struct,
+}
+
+impl From<VTableVariantKind> for u8 {
+    fn from(kind: VTableVariantKind) -> u8 {
+        match kind {
+            VTableVariantKind::Unit => 0,
+            VTableVariantKind::Newtype => 1,
+            VTableVariantKind::Tuple => 2,
+            VTableVariantKind::Struct => 3,
+        }
+    }
+}
+
+impl TryFrom<u8> for VTableVariantKind {
+    type Error = Error;
+
+    fn try_from(byte: u8) -> Result<Self, Error> {
+        match byte {
+            0 => Ok(VTableVariantKind::Unit),
+            1 => Ok(VTableVariantKind::Newtype),
+            2 => Ok(VTableVariantKind::Tuple),
+            3 => Ok(VTableVariantKind::Struct),
+            _ => Err(Error::UnknownVariantKind(byte)),
+        }
+    }
+}
+
+/// A variant of an enum item. The payload fields of the variant belong to
+/// the enum item, e.g. `item_index` is the index of the enum, and are indexed
+/// by their position in the variant. Tuple and newtype fields are named by
+/// their position, e.g. `0`, `1`, etc.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct VTableVariant {
+    pub index: VTableVariantIndex,
+    pub name: VariantName,
+    pub kind: VTableVariantKind,
+    pub fields: VTableFields,
+    pub num_fields: VTableFieldIndex,
+}
+
+impl VTableVariant {
+    pub fn new(index: VTableVariantIndex, name: &str, kind: VTableVariantKind) -> Self {
+        Self {
+            index,
+            name: name.to_owned(),
+            kind,
+            fields: VTableFields::new(),
+            num_fields: 0,
+        }
+    }
+
+    #[inline]
+    pub fn add_field(
+        &mut self,
+        item_index: VTableItemIndex,
+        field_type: impl Into<VTableFieldType>,
+        field_name: &str,
+        field_rules: VTableFieldRules,
+    ) {
+        let field = VTableField::new(
+            item_index,
+            field_type.into(),
+            self.num_fields,
+            field_name.to_owned(),
+            field_rules,
+        );
+
+        self.fields.add_field(field);
+
+        self.num_fields += 1;
+    }
+
+    // Return the field by name from the variant
+    #[inline]
+    pub fn field_by_name(&self, name: &str) -> Result<&VTableField, Error> {
+        self.fields
+            .find_field_by_name(name)
+            .ok_or(Error::FieldNotFound)
+    }
+
+    // Return the field by index from the variant
+    #[inline]
+    pub fn field_by_index(&self, index: &VTableFieldIndex) -> Result<&VTableField, Error> {
+        self.fields
+            .iter()
+            .find(|field| field.index == *index)
+            .ok_or(Error::FieldNotFound)
+    }
+
+    #[inline]
+    pub fn write_to_buffer(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
+        // Discriminant of the variant
+        buffer.push(self.index);
+
+        // Payload shape of the variant
+        buffer.push(self.kind.into());
+
+        // Name of the variant
+        let name_bytes = self.name.as_bytes();
+        buffer.push(name_bytes.len() as u8);
+        buffer.extend_from_slice(name_bytes);
+
+        // Number of payload fields in the variant
+        buffer.push(self.num_fields);
+
+        // Serialize the payload fields
+        self.fields.write_to_buffer(buffer)
+    }
+
+    #[inline]
+    pub fn read_from_buffer(buffer: &mut DocBufReader<'_>) -> Result<Self, Error> {
+        let index = buffer.read_u8()?;
+        let kind = VTableVariantKind::try_from(buffer.read_u8()?)?;
+
+        let name = buffer.read_name()?;
+
+        let num_fields = buffer.read_u8()?;
+
+        let mut fields = VTableFields::new();
+        for _ in 0..num_fields {
+            let field = VTableField::read_from_buffer(buffer)?;
+            fields.add_field(field);
+        }
+
+        Ok(Self {
+            index,
+            name,
+            kind,
+            fields,
+            num_fields,
+        })
+    }
+}
+
+impl PartialEq for VTableVariant {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+            && self.name == other.name
+            && self.kind == other.kind
+            && self.fields == other.fields
+            && self.num_fields == other.num_fields
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct VTableEnum {
+    pub item_index: VTableItemIndex,
+    pub name: EnumName,
+    pub variants: Vec<VTableVariant>,
+    pub num_variants: VTableVariantIndex,
+}
+
+impl VTableEnum {
+    pub fn new(name: &str, index: Option<u8>) -> Self {
+        Self {
+            item_index: index.unwrap_or_default(),
+            name: name.to_owned(),
+            variants: Vec::new(),
+            num_variants: 0,
+        }
+    }
+
+    /// Add a variant to the enum, returning the variant index for adding its payload fields.
+    /// Variants are indexed in declaration order, which must match the serde variant index.
+    ///
+    /// The number of variants is encoded as a single byte, so an enum has at most 255 variants.
+    #[inline]
+    pub fn add_variant(
+        &mut self,
+        name: &str,
+        kind: VTableVariantKind,
+    ) -> Result<VTableVariantIndex, Error> {
+        let index = self.num_variants;
+
+        if index == VTableVariantIndex::MAX {
+            return Err(Error::VariantIndexExceedsMax(index as u32));
+        }
+
+        self.variants.push(VTableVariant::new(index, name, kind));
+        self.num_variants += 1;
+
+        Ok(index)
+    }
+
+    /// Add a payload field to a variant of the enum.
+    #[inline]
+    pub fn add_variant_field(
+        &mut self,
+        variant_index: VTableVariantIndex,
+        field_type: impl Into<VTableFieldType>,
+        field_name: &str,
+        field_rules: VTableFieldRules,
+    ) -> Result<(), Error> {
+        let item_index = self.item_index;
+
+        self.variants
+            .get_mut(variant_index as usize)
+            .ok_or(Error::VariantNotFound)?
+            .add_field(item_index, field_type, field_name, field_rules);
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_item_index(&mut self, index: VTableItemIndex) {
+        self.item_index = index;
+
+        for variant in self.variants.iter_mut() {
+            for field in variant.fields.inner_mut() {
+                field.item_index = self.item_index;
+            }
+        }
+    }
+
+    #[inline]
+    pub fn set_encoding(&mut self, encoding: VTableEncoding) {
+        for variant in self.variants.iter_mut() {
+            for field in variant.fields.inner_mut() {
+                field.encoding = encoding;
+            }
+        }
+    }
+
+    // Return the variant by its discriminant
+    #[inline]
+    pub fn variant_by_index(&self, index: VTableVariantIndex) -> Result<&VTableVariant, Error> {
+        self.variants
+            .get(index as usize)
+            .filter(|variant| variant.index == index)
+            .ok_or(Error::VariantNotFound)
+    }
+
+    // Return the variant by name
+    #[inline]
+    pub fn variant_by_name(&self, name: &str) -> Result<&VTableVariant, Error> {
+        self.variants
+            .iter()
+            .find(|variant| variant.name == name)
+            .ok_or(Error::VariantNotFound)
+    }
+
+    /// Total number of payload fields across all variants.
+    #[inline]
+    pub fn num_fields(&self) -> usize {
+        self.variants
+            .iter()
+            .map(|variant| variant.num_fields as usize)
+            .sum()
+    }
+
+    /// Return the payload fields of all variants.
+    #[inline]
+    pub fn fields(&self) -> impl Iterator<Item = &VTableField> {
+        self.variants.iter().flat_map(|variant| variant.fields.iter())
+    }
+
+    #[inline]
+    pub fn write_to_buffer(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
+        // Item index it belongs to
+        buffer.push(self.item_index);
+
+        // Name of the enum
+        let name_bytes = self.name.as_bytes();
+        let name_len = name_bytes.len() as u8;
+        buffer.push(name_len);
+        buffer.extend_from_slice(name_bytes);
+
+        // Number of variants in the enum
+        buffer.push(self.num_variants);
+
+        // Serialize the variants
+        for variant in self.variants.iter() {
+            variant.write_to_buffer(buffer)?;
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn read_from_buffer(buffer: &mut DocBufReader<'_>) -> Result<Self, Error> {
+        let item_index = buffer.read_u8()?;
+        let name = buffer.read_name()?;
+
+        let num_variants = buffer.read_u8()?;
+
+        let mut variants = Vec::with_capacity(num_variants as usize);
+        for _ in 0..num_variants {
+            variants.push(VTableVariant::read_from_buffer(buffer)?);
+        }
+
+        Ok(Self {
+            item_index,
+            name,
+            variants,
+            num_variants,
+        })
+    }
+}
+
+impl PartialEq for VTableEnum {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.variants == other.variants
+            && self.num_variants == other.num_variants
+            && self.item_index == other.item_index
+    }
+}
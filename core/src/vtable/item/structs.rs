@@ -60,6 +60,13 @@ impl VTableStruct {
         }
     }
 
+    #[inline]
+    pub fn set_encoding(&mut self, encoding: VTableEncoding) {
+        for field in self.fields.inner_mut() {
+            field.encoding = encoding;
+        }
+    }
+
     // Return the field index from the struct
     #[inline]
     pub fn field_index_from_name(&self, name: &str) -> Result<VTableFieldIndex, Error> {
@@ -120,12 +127,11 @@ impl VTableStruct {
     }
 
     #[inline]
-    pub fn read_from_buffer(buffer: &mut Vec<u8>) -> Result<Self, Error> {
-        let item_index = buffer.remove(0);
-        let name_len = buffer.remove(0) as usize;
-        let name = String::from_utf8(buffer.drain(0..name_len).collect())?;
+    pub fn read_from_buffer(buffer: &mut DocBufReader<'_>) -> Result<Self, Error> {
+        let item_index = buffer.read_u8()?;
+        let name = buffer.read_name()?;
 
-        let num_fields = buffer.remove(0);
+        let num_fields = buffer.read_u8()?;
 
         let mut fields = VTableFields::new();
         for _ in 0..num_fields {
@@ -0,0 +1,189 @@
+use super::*;
+
+impl VTable {
+    /// Deserialize a vtable written before the format was versioned, i.e. without the
+    /// `VTABLE_MAGIC` bytes, for migrating vtable files.
+    ///
+    /// The unversioned format has no encoding, documents were written with the fixed
+    /// encoding. It does not record the inner types of optional and list fields, the key
+    /// and value names of map fields, nor the field rules added since, so the vtable only
+    /// identifies the document type it was written for. Its id is not the id of the
+    /// document type, see `VTable::matches_legacy`.
+    pub fn read_legacy_from_buffer(buffer: &mut DocBufReader<'_>) -> Result<Self, Error> {
+        let namespace = buffer.read_name()?;
+        let root = buffer.read_name()?;
+
+        let num_items = buffer.read_u8()?;
+        let num_fields = u16::from_le_bytes(buffer.read_array()?);
+
+        let mut vtable = Self::new(namespace, root);
+
+        vtable.num_items = num_items;
+        vtable.num_fields = num_fields;
+
+        for _ in 0..num_items {
+            // Structs are the only items of the unversioned format
+            let kind = buffer.read_u8()?;
+            if kind != 0 {
+                return Err(Error::UnknownItemKind(kind));
+            }
+
+            let item_index = buffer.read_u8()?;
+            let name = buffer.read_name()?;
+
+            let num_fields = buffer.read_u8()?;
+
+            let mut fields = VTableFields::new();
+            for _ in 0..num_fields {
+                fields.add_field(read_legacy_field(buffer)?);
+            }
+
+            vtable.items.0.push(VTableItem::Struct(VTableStruct {
+                item_index,
+                name,
+                fields,
+                num_fields,
+            }));
+        }
+
+        vtable.set_encoding(VTableEncoding::Fixed);
+
+        Ok(vtable)
+    }
+
+    /// Returns true if the unversioned vtable, read by `VTable::read_legacy_from_buffer`,
+    /// was written for the document type of this vtable, and its documents are read by
+    /// this vtable. The namespace, root and encoding match, and every struct of the
+    /// unversioned vtable has the same fields, of the same kind of type.
+    pub fn matches_legacy(&self, legacy: &VTable) -> bool {
+        self.namespace == legacy.namespace
+            && self.root == legacy.root
+            && self.encoding == VTableEncoding::Fixed
+            && legacy.items.iter().all(|item| match item {
+                VTableItem::Struct(legacy_struct) => self
+                    .struct_by_name(&legacy_struct.name)
+                    .map(|vtable_struct| {
+                        vtable_struct.fields.len() == legacy_struct.fields.len()
+                            && vtable_struct
+                                .fields
+                                .iter()
+                                .zip(legacy_struct.fields.iter())
+                                .all(|(field, legacy_field)| {
+                                    field.name == legacy_field.name
+                                        && matches_legacy_type(&field.r#type, &legacy_field.r#type)
+                                })
+                    })
+                    .unwrap_or(false),
+                VTableItem::Enum(_) => false,
+            })
+    }
+}
+
+/// Returns true if the field type is the type recorded by the unversioned format, which
+/// records the kind of the type, and the name of struct types.
+fn matches_legacy_type(r#type: &VTableFieldType, legacy: &VTableFieldType) -> bool {
+    match (r#type, legacy) {
+        (VTableFieldType::Struct(name), VTableFieldType::Struct(legacy_name)) => {
+            name == legacy_name
+        }
+        (r#type, legacy) => {
+            let byte: u8 = r#type.to_owned().into();
+            let legacy_byte: u8 = legacy.to_owned().into();
+
+            byte == legacy_byte
+        }
+    }
+}
+
+fn read_legacy_field(buffer: &mut DocBufReader<'_>) -> Result<VTableField, Error> {
+    let item_index = buffer.read_u8()?;
+
+    let r#type = match buffer.read_u8()? {
+        // Enums, chars, units, arrays and tuples were added with the versioned format
+        byte if byte > 22 => return Err(Error::UnknownFieldType(byte)),
+        byte => match VTableFieldType::try_from(byte)? {
+            VTableFieldType::Struct(_) => VTableFieldType::Struct(buffer.read_name()?),
+            VTableFieldType::HashMap { .. } => VTableFieldType::HashMap {
+                key: Box::new(VTableFieldType::try_from(buffer.read_u8()?)?),
+                value: Box::new(VTableFieldType::try_from(buffer.read_u8()?)?),
+            },
+            r#type => r#type,
+        },
+    };
+
+    let index = buffer.read_u8()?;
+    let name = buffer.read_name()?;
+
+    let rules = read_legacy_rules(buffer, &r#type)?;
+
+    Ok(VTableField::new(item_index, r#type, index, name, rules))
+}
+
+fn read_legacy_rules(
+    buffer: &mut DocBufReader<'_>,
+    r#type: &VTableFieldType,
+) -> Result<VTableFieldRules, Error> {
+    let mut rules = VTableFieldRules::new();
+
+    rules.ignore = buffer.read_u8()? == 1;
+    rules.sign = buffer.read_u8()? == 1;
+
+    if buffer.read_u8()? == 1 {
+        rules.max_value = Some(read_legacy_numeric_value(buffer, r#type)?);
+    }
+
+    if buffer.read_u8()? == 1 {
+        rules.min_value = Some(read_legacy_numeric_value(buffer, r#type)?);
+    }
+
+    if buffer.read_u8()? == 1 {
+        rules.max_length = Some(usize::from_le_bytes(buffer.read_array()?));
+    }
+
+    if buffer.read_u8()? == 1 {
+        rules.min_length = Some(usize::from_le_bytes(buffer.read_array()?));
+    }
+
+    if buffer.read_u8()? == 1 {
+        rules.length = Some(usize::from_le_bytes(buffer.read_array()?));
+    }
+
+    if buffer.read_u8()? == 1 {
+        // The length of the regex is written little endian
+        let len = u16::from_le_bytes(buffer.read_array()?) as usize;
+        let regex = String::from_utf8(buffer.read_bytes(len)?.to_vec())?;
+
+        rules.regex = Some(regex);
+    }
+
+    Ok(rules)
+}
+
+/// Read a min or max value rule. The unversioned format wrote an invalid value type,
+/// the value has the type of the field.
+fn read_legacy_numeric_value(
+    buffer: &mut DocBufReader<'_>,
+    r#type: &VTableFieldType,
+) -> Result<NumericValue, Error> {
+    let value_type = buffer.read_u8()?;
+
+    let value = match r#type {
+        VTableFieldType::U8 => NumericValue::U8(buffer.read_u8()?),
+        VTableFieldType::U16 => NumericValue::U16(u16::from_le_bytes(buffer.read_array()?)),
+        VTableFieldType::U32 => NumericValue::U32(u32::from_le_bytes(buffer.read_array()?)),
+        VTableFieldType::U64 => NumericValue::U64(u64::from_le_bytes(buffer.read_array()?)),
+        VTableFieldType::U128 => NumericValue::U128(u128::from_le_bytes(buffer.read_array()?)),
+        VTableFieldType::USIZE => NumericValue::USIZE(usize::from_le_bytes(buffer.read_array()?)),
+        VTableFieldType::I8 => NumericValue::I8(buffer.read_u8()? as i8),
+        VTableFieldType::I16 => NumericValue::I16(i16::from_le_bytes(buffer.read_array()?)),
+        VTableFieldType::I32 => NumericValue::I32(i32::from_le_bytes(buffer.read_array()?)),
+        VTableFieldType::I64 => NumericValue::I64(i64::from_le_bytes(buffer.read_array()?)),
+        VTableFieldType::I128 => NumericValue::I128(i128::from_le_bytes(buffer.read_array()?)),
+        VTableFieldType::ISIZE => NumericValue::ISIZE(isize::from_le_bytes(buffer.read_array()?)),
+        VTableFieldType::F32 => NumericValue::F32(f32::from_le_bytes(buffer.read_array()?)),
+        VTableFieldType::F64 => NumericValue::F64(f64::from_le_bytes(buffer.read_array()?)),
+        _ => return Err(Error::InvalidNumericValueType(value_type)),
+    };
+
+    Ok(value)
+}
@@ -0,0 +1,197 @@
+use std::cmp::Reverse;
+
+use ::serde::Serialize;
+
+use super::bufmap::map_error;
+use super::*;
+
+use crate::serde::ser::DocBufSerializer;
+
+/// The new value of a field, encoded when the field is added to a patch.
+#[derive(Debug)]
+struct DocBufPatchField {
+    field: &'static VTableField,
+    /// The encoded value of the field.
+    data: Vec<u8>,
+    /// The offsets of the value, relative to the start of the encoded value.
+    offsets: VTableFieldOffsets,
+}
+
+/// A set of field replacements, applied to a document buffer in one pass.
+///
+/// Replacing fields one at a time with `DocBufMap::docbuf_map_replace` splices
+/// the buffer and shifts the offsets that follow each field. A patch encodes
+/// each new value as it is added, checking the field rules, then rebuilds the
+/// buffer and its offsets once:
+///
+/// ```ignore
+/// let offsets = DocBufPatch::new(Order::vtable()?)
+///     .replace(status.offset_index(), &Status::Shipped)?
+///     .replace(tracking.offset_index(), &Some("1Z999".to_string()))?
+///     .apply(&mut buffer, &offsets)?;
+/// ```
+#[derive(Debug)]
+pub struct DocBufPatch {
+    vtable: &'static VTable,
+    fields: Vec<DocBufPatchField>,
+}
+
+impl DocBufPatch {
+    pub fn new(vtable: &'static VTable) -> Self {
+        Self {
+            vtable,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Add the new value of the field at the offset index, checking the field
+    /// rules. A field replaced again keeps the last value.
+    pub fn replace<T: Serialize>(
+        mut self,
+        index: VTableFieldOffsetIndex,
+        value: &T,
+    ) -> Result<Self, Error> {
+        let field = self.vtable.get_field_by_offset_index(index)?;
+
+        let mut data = Vec::new();
+
+        let mut serializer = DocBufSerializer::new(self.vtable, &mut data);
+        serializer.current_field = Some(field);
+        value.serialize(&mut serializer).map_err(map_error)?;
+
+        let offsets = serializer.offsets;
+
+        self.fields
+            .retain(|patch_field| patch_field.field.offset_index() != index);
+        self.fields.push(DocBufPatchField {
+            field,
+            data,
+            offsets,
+        });
+
+        Ok(self)
+    }
+
+    /// Return the number of fields replaced by the patch.
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Apply the replacements to the document buffer and return the offsets
+    /// of the patched buffer.
+    ///
+    /// The buffer is left intact if a replaced field has no offset, e.g. a
+    /// field of a `None` struct, or if replaced values overlap, e.g. a struct
+    /// and one of its fields.
+    pub fn apply(
+        &self,
+        buffer: &mut Vec<u8>,
+        offsets: &VTableFieldOffsets,
+    ) -> Result<VTableFieldOffsets, Error> {
+        // Locate the encoded values being replaced, in buffer order with
+        // containing values first.
+        let mut replacements = self
+            .fields
+            .iter()
+            .map(|patch_field| {
+                let field = patch_field.field;
+
+                let offset = offsets
+                    .offset(field.offset_index())
+                    .ok_or(Error::FieldNotFound)?;
+
+                let range =
+                    self.vtable
+                        .encoded_range(field, &field.r#type, buffer, offset.range())?;
+
+                Ok((range, patch_field))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        replacements.sort_by_key(|(range, _)| (range.start, Reverse(range.end)));
+
+        for pair in replacements.windows(2) {
+            if pair[0].0.end > pair[1].0.start {
+                return Err(Error::DocBufPatchOverlap(
+                    pair[0].1.field.name.to_string(),
+                    pair[1].1.field.name.to_string(),
+                ));
+            }
+        }
+
+        // The size difference of the buffer following the end of each value.
+        let mut diff = 0isize;
+        let shifts = replacements
+            .iter()
+            .map(|(range, patch_field)| {
+                diff += patch_field.data.len() as isize - range.len() as isize;
+
+                (range.end, diff)
+            })
+            .collect::<Vec<_>>();
+
+        // Move a buffer position past the values replaced before it.
+        let shift = |position: usize| match shifts.partition_point(|(end, _)| *end <= position) {
+            0 => position,
+            n => (position as isize + shifts[n - 1].1) as usize,
+        };
+
+        // The offsets of the replaced fields, and of the struct fields nested
+        // in them, are replaced with the offsets of the new values.
+        let mut nested_items = Vec::new();
+
+        for (_, patch_field) in replacements.iter() {
+            self.vtable
+                .nested_items(&patch_field.field.r#type, &mut nested_items);
+        }
+
+        let mut patched = VTableFieldOffsets::with_capacity(offsets.len());
+
+        patched.as_mut().extend(
+            offsets
+                .as_ref()
+                .iter()
+                .filter(|offset| {
+                    !nested_items.contains(&offset.0 .0)
+                        && !replacements
+                            .iter()
+                            .any(|(_, patch_field)| patch_field.field.offset_index() == offset.0)
+                })
+                .map(|offset| {
+                    VTableFieldOffset(offset.0, shift(offset.1.start)..shift(offset.1.end))
+                }),
+        );
+
+        let mut new_buffer = Vec::with_capacity((buffer.len() as isize + diff) as usize);
+        let mut position = 0;
+
+        for (range, patch_field) in replacements.iter() {
+            new_buffer.extend_from_slice(&buffer[position..range.start]);
+
+            let start = new_buffer.len();
+            new_buffer.extend_from_slice(&patch_field.data);
+
+            patched
+                .as_mut()
+                .extend(patch_field.offsets.as_ref().iter().map(|offset| {
+                    VTableFieldOffset(offset.0, (start + offset.1.start)..(start + offset.1.end))
+                }));
+
+            position = range.end;
+        }
+
+        new_buffer.extend_from_slice(&buffer[position..]);
+
+        *buffer = new_buffer;
+
+        patched
+            .as_mut()
+            .sort_by_key(|offset| (offset.1.start, offset.1.end));
+
+        Ok(patched)
+    }
+}
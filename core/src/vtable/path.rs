@@ -0,0 +1,546 @@
+use std::ops::Range;
+
+use super::*;
+
+use crate::value::{FromValue, Value};
+
+/// A segment of a field path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum VTableFieldPathSegment {
+    /// A field of a struct or enum variant, e.g. `.author`.
+    Field(String),
+    /// An element of a list, array or tuple, or the value of a map entry,
+    /// e.g. `[2]`, `[room]` or `["room"]`.
+    Index(String),
+}
+
+/// The path of a value nested in a document, e.g. `metadata.tags[0]` or
+/// `labels["room"]`.
+///
+/// Paths start with a field of the root item, and select the fields of
+/// nested structs and enum variants by name, and the elements of lists,
+/// arrays and tuples, and the values of map entries, by index or key. Keys
+/// containing `.`, `[` or `]` are quoted, e.g. `labels["a.b"]`. Optional
+/// values are followed when they are some.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct VTableFieldPath(Vec<VTableFieldPathSegment>);
+
+impl VTableFieldPath {
+    /// Parse a field path, e.g. `metadata.author.name`.
+    pub fn parse(path: &str) -> Result<Self, Error> {
+        let invalid = || Error::FieldPathInvalid(path.to_owned());
+
+        let mut segments = Vec::new();
+        let mut chars = path.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '[' => {
+                    let mut index = String::new();
+
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+
+                        loop {
+                            match chars.next().ok_or_else(invalid)? {
+                                '"' => break,
+                                '\\' => index.push(chars.next().ok_or_else(invalid)?),
+                                c => index.push(c),
+                            }
+                        }
+
+                        if chars.next() != Some(']') {
+                            return Err(invalid());
+                        }
+                    } else {
+                        loop {
+                            match chars.next().ok_or_else(invalid)? {
+                                ']' => break,
+                                '[' | '"' => return Err(invalid()),
+                                c => index.push(c),
+                            }
+                        }
+
+                        if index.is_empty() {
+                            return Err(invalid());
+                        }
+                    }
+
+                    segments.push(VTableFieldPathSegment::Index(index));
+
+                    // An index is followed by a field or another index.
+                    match chars.peek() {
+                        Some('.') => {
+                            chars.next();
+                        }
+                        Some('[') | None => (),
+                        Some(_) => return Err(invalid()),
+                    }
+                }
+                ']' | '"' | '.' => return Err(invalid()),
+                c => {
+                    let mut name = String::from(c);
+
+                    while let Some(c) = chars.next_if(|c| !matches!(c, '.' | '[' | ']' | '"')) {
+                        name.push(c);
+                    }
+
+                    segments.push(VTableFieldPathSegment::Field(name));
+
+                    match chars.peek() {
+                        Some('.') => {
+                            chars.next();
+                        }
+                        Some('[') | None => (),
+                        Some(_) => return Err(invalid()),
+                    }
+                }
+            }
+        }
+
+        // A path must not be empty, or end with a separator.
+        if segments.is_empty() || path.ends_with('.') {
+            return Err(invalid());
+        }
+
+        Ok(Self(segments))
+    }
+
+    /// Return the segments of the path.
+    pub fn segments(&self) -> &[VTableFieldPathSegment] {
+        &self.0
+    }
+
+    /// Return the path of the first `len` segments, used in error messages.
+    fn prefix(&self, len: usize) -> String {
+        Self(self.0[..len.min(self.0.len())].to_vec()).to_string()
+    }
+}
+
+impl std::str::FromStr for VTableFieldPath {
+    type Err = Error;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        Self::parse(path)
+    }
+}
+
+impl std::fmt::Display for VTableFieldPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (position, segment) in self.0.iter().enumerate() {
+            match segment {
+                VTableFieldPathSegment::Field(name) if position == 0 => write!(f, "{}", name)?,
+                VTableFieldPathSegment::Field(name) => write!(f, ".{}", name)?,
+                VTableFieldPathSegment::Index(index)
+                    if index.contains(['.', '[', ']', '"', '\\']) =>
+                {
+                    write!(f, "[{:?}]", index)?
+                }
+                VTableFieldPathSegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The encoded value of a field path in a document buffer.
+#[derive(Debug, Clone)]
+pub(crate) struct VTableFieldLocation<'a> {
+    /// The field containing the value.
+    pub field: &'a VTableField,
+    /// The type of the value, which is the field type or a type nested in it.
+    pub r#type: &'a VTableFieldType,
+    /// The range of the encoded value, including its length prefix.
+    pub range: Range<usize>,
+}
+
+/// The position of a field path in the vtable, as the path is resolved.
+#[derive(Debug, Clone, Copy)]
+enum Cursor<'a> {
+    Item(&'a VTableItem),
+    Value(&'a VTableField, &'a VTableFieldType),
+}
+
+/// Returns true if the map key is the key of a field path index, e.g. the
+/// `u8` key `3` for the index `[3]`.
+fn is_key(key: &Value, index: &str) -> bool {
+    match key {
+        Value::String(key) => key == index,
+        Value::Char(key) => index.chars().eq([*key]),
+        Value::Numeric(_) | Value::Bool(_) | Value::Uuid(_) => key.to_string() == index,
+        _ => false,
+    }
+}
+
+impl VTable {
+    /// Read the value of a field path from a document buffer, without
+    /// decoding the rest of the document, e.g. `metadata.author.name`.
+    ///
+    /// The offsets of the document, returned by `DocBuf::to_docbuf`, are used
+    /// to seek to the fields of the root item, and the values nested in them
+    /// are found by skipping the values preceding them.
+    pub fn get<T: FromValue>(
+        &self,
+        buffer: &[u8],
+        offsets: &VTableFieldOffsets,
+        path: &str,
+    ) -> crate::Result<T> {
+        Ok(T::from_value(self.get_value(buffer, offsets, path)?)?)
+    }
+
+    /// Read the untyped value of a field path from a document buffer.
+    pub fn get_value(
+        &self,
+        buffer: &[u8],
+        offsets: &VTableFieldOffsets,
+        path: &str,
+    ) -> crate::Result<Value> {
+        let location = self.locate(buffer, offsets, &VTableFieldPath::parse(path)?)?;
+
+        let mut reader = DocBufReader::new(&buffer[location.range]);
+
+        self.decode_field_value(location.field, location.r#type, &mut reader)
+    }
+
+    /// Replace the value of a field path in a document buffer, checking the
+    /// field rules of the new value, and return the new offset of the value.
+    ///
+    /// The buffer is shifted if the new value is larger or smaller than the
+    /// old one, and the offsets are shifted with it. Optional values are
+    /// replaced by some new value, unless the new value is itself an option.
+    pub fn replace(
+        &self,
+        buffer: &mut Vec<u8>,
+        offsets: &mut VTableFieldOffsets,
+        path: &str,
+        value: impl Into<Value>,
+    ) -> crate::Result<VTableFieldOffset> {
+        let location = self.locate(buffer, offsets, &VTableFieldPath::parse(path)?)?;
+
+        let value = match (location.r#type, value.into()) {
+            (VTableFieldType::Option(_), value @ Value::Option(_)) => value,
+            (VTableFieldType::Option(_), value) => Value::Option(Some(Box::new(value))),
+            (_, value) => value,
+        };
+
+        let mut data = Vec::with_capacity(location.range.len());
+        self.encode_field_value(location.field, location.r#type, &value, &mut data)?;
+
+        let range = location.range;
+        let start = range.start;
+
+        // The offsets of strings, byte arrays, lists and maps start after the
+        // length prefix, which may change size with compact encoding.
+        let old_offset_start =
+            self.offset_start(location.field, location.r#type, &buffer[range.clone()])?;
+        let new_offset_start = self.offset_start(location.field, location.r#type, &data)?;
+
+        // The offsets of the value, rather than of the struct or list containing it.
+        let value_offsets = offsets
+            .as_ref()
+            .iter()
+            .map(|offset| {
+                offset.0 == location.field.offset_index()
+                    && offset.1.start == start + old_offset_start
+            })
+            .collect::<Vec<_>>();
+
+        buffer.splice(range.clone(), data.iter().cloned());
+        offsets.splice(range, data.len());
+
+        offsets
+            .as_mut()
+            .iter_mut()
+            .zip(value_offsets)
+            .filter(|(_, is_value_offset)| *is_value_offset)
+            .for_each(|(offset, _)| offset.1.start = start + new_offset_start);
+
+        Ok(location
+            .field
+            .as_offset((start + new_offset_start)..(start + data.len())))
+    }
+
+    /// Return the start of the offset of an encoded value of the type,
+    /// relative to the start of the value. The offsets of strings, byte arrays,
+    /// lists and maps start after their length prefix, and the offsets of
+    /// `None` values are empty, after the null value.
+    fn offset_start(
+        &self,
+        field: &VTableField,
+        r#type: &VTableFieldType,
+        data: &[u8],
+    ) -> crate::Result<usize> {
+        let mut reader = DocBufReader::new(data);
+
+        match r#type {
+            VTableFieldType::Option(inner) => match field.decode_option(&mut reader)? {
+                Some(()) => self.offset_start(field, inner, data),
+                None => Ok(reader.position()),
+            },
+            VTableFieldType::String
+            | VTableFieldType::Str
+            | VTableFieldType::Bytes
+            | VTableFieldType::Vec(_)
+            | VTableFieldType::HashMap { .. } => {
+                field.decode_len(&mut reader)?;
+
+                Ok(reader.position())
+            }
+            _ => Ok(0),
+        }
+    }
+
+    /// Resolve a field path to the encoded value in the document buffer.
+    pub(crate) fn locate<'a>(
+        &'a self,
+        buffer: &[u8],
+        offsets: &VTableFieldOffsets,
+        path: &VTableFieldPath,
+    ) -> crate::Result<VTableFieldLocation<'a>> {
+        let mut reader = DocBufReader::new(buffer);
+        let mut cursor = Cursor::Item(self.root_item()?);
+
+        for (position, segment) in path.segments().iter().enumerate() {
+            // Follow the optional values and items containing the segment.
+            loop {
+                cursor = match cursor {
+                    Cursor::Value(field, VTableFieldType::Option(inner)) => {
+                        match field.decode_option(&mut reader)? {
+                            Some(()) => Cursor::Value(field, inner),
+                            None => return Err(Error::FieldPathNone(path.prefix(position)).into()),
+                        }
+                    }
+                    Cursor::Value(
+                        _,
+                        VTableFieldType::Struct(name) | VTableFieldType::Enum(name),
+                    ) => Cursor::Item(self.item_by_name(name)?),
+                    _ => break,
+                };
+            }
+
+            cursor = match (cursor, segment) {
+                (Cursor::Item(item), VTableFieldPathSegment::Field(name)) => {
+                    // Only the offsets of the root item are seeked to.
+                    let offsets = (position == 0).then_some(offsets);
+
+                    let field = self
+                        .seek_field(item, name, offsets, &mut reader)?
+                        .ok_or_else(|| Error::FieldPathUnknownField {
+                            path: path.prefix(position + 1),
+                            field: name.to_owned(),
+                        })?;
+
+                    Cursor::Value(field, &field.r#type)
+                }
+                (Cursor::Value(field, r#type), VTableFieldPathSegment::Index(index)) => {
+                    let r#type = self.seek_element(field, r#type, index, &mut reader, || {
+                        path.prefix(position + 1)
+                    })?;
+
+                    Cursor::Value(field, r#type)
+                }
+                (cursor, _) => {
+                    let found = match cursor {
+                        Cursor::Item(item) => item.name().to_owned(),
+                        Cursor::Value(_, r#type) => r#type.to_string(),
+                    };
+
+                    return Err(Error::FieldPathInvalidType {
+                        path: path.prefix(position + 1),
+                        found,
+                    }
+                    .into());
+                }
+            };
+        }
+
+        match cursor {
+            Cursor::Value(field, r#type) => {
+                let start = reader.position();
+                self.skip_value(field, r#type, &mut reader)?;
+
+                Ok(VTableFieldLocation {
+                    field,
+                    r#type,
+                    range: start..reader.position(),
+                })
+            }
+            Cursor::Item(_) => Err(Error::FieldPathInvalid(path.to_string()).into()),
+        }
+    }
+
+    /// Move the reader to the named field of a struct, or of the enum variant
+    /// at the reader, returning `None` if there is no such field.
+    fn seek_field<'a>(
+        &'a self,
+        item: &'a VTableItem,
+        name: &str,
+        offsets: Option<&VTableFieldOffsets>,
+        reader: &mut DocBufReader<'_>,
+    ) -> crate::Result<Option<&'a VTableField>> {
+        // Enum variant fields are not recorded in the offsets.
+        let (fields, offsets) = match item {
+            VTableItem::Struct(vtable_struct) => (&vtable_struct.fields, offsets),
+            VTableItem::Enum(vtable_enum) => (
+                &vtable_enum.variant_by_index(reader.read_u8()?)?.fields,
+                None,
+            ),
+        };
+
+        let Some(position) = fields.iter().position(|field| field.name == name) else {
+            return Ok(None);
+        };
+
+        let mut skip = 0;
+
+        // Seek to the end of the closest preceding field with an offset.
+        if let Some((index, offset)) = offsets.and_then(|offsets| {
+            fields.inner()[..position]
+                .iter()
+                .enumerate()
+                .rev()
+                .find_map(|(index, field)| offsets.offset(field.offset_index()).map(|o| (index, o)))
+        }) {
+            reader.seek(offset.1.end)?;
+            skip = index + 1;
+        }
+
+        for field in fields.inner()[skip..position].iter() {
+            self.skip_value(field, &field.r#type, reader)?;
+        }
+
+        Ok(fields.inner().get(position))
+    }
+
+    /// Move the reader to an element of a list, array or tuple, or to the
+    /// value of a map entry, returning the type of the element.
+    fn seek_element<'a>(
+        &'a self,
+        field: &'a VTableField,
+        r#type: &'a VTableFieldType,
+        index: &str,
+        reader: &mut DocBufReader<'_>,
+        path: impl Fn() -> String,
+    ) -> crate::Result<&'a VTableFieldType> {
+        let position = |len: usize| match index.parse::<usize>() {
+            Ok(position) if position < len => Ok(position),
+            _ => Err(Error::FieldPathIndexOutOfBounds {
+                path: path(),
+                index: index.to_owned(),
+                len,
+            }),
+        };
+
+        match r#type {
+            VTableFieldType::Vec(elem) => {
+                let position = position(field.decode_len(reader)?)?;
+
+                for _ in 0..position {
+                    self.skip_value(field, elem, reader)?;
+                }
+
+                Ok(elem)
+            }
+            VTableFieldType::Array { elem, len } => {
+                for _ in 0..position(*len)? {
+                    self.skip_value(field, elem, reader)?;
+                }
+
+                Ok(elem)
+            }
+            VTableFieldType::Tuple(types) => {
+                let position = position(types.len())?;
+
+                for r#type in types[..position].iter() {
+                    self.skip_value(field, r#type, reader)?;
+                }
+
+                Ok(&types[position])
+            }
+            VTableFieldType::HashMap { key, value } => {
+                for _ in 0..field.decode_len(reader)? {
+                    if is_key(&self.decode_field_value(field, key, reader)?, index) {
+                        return Ok(value);
+                    }
+
+                    self.skip_value(field, value, reader)?;
+                }
+
+                Err(Error::FieldPathKeyNotFound {
+                    path: path(),
+                    key: index.to_owned(),
+                }
+                .into())
+            }
+            r#type => Err(Error::FieldPathInvalidType {
+                path: path(),
+                found: r#type.to_string(),
+            }
+            .into()),
+        }
+    }
+
+    /// Move the reader past an encoded value of the type, without decoding
+    /// strings and byte arrays.
+    pub(crate) fn skip_value(
+        &self,
+        field: &VTableField,
+        r#type: &VTableFieldType,
+        reader: &mut DocBufReader<'_>,
+    ) -> crate::Result<()> {
+        match r#type {
+            VTableFieldType::Struct(name) | VTableFieldType::Enum(name) => {
+                let fields = match self.item_by_name(name)? {
+                    VTableItem::Struct(vtable_struct) => &vtable_struct.fields,
+                    VTableItem::Enum(vtable_enum) => {
+                        &vtable_enum.variant_by_index(reader.read_u8()?)?.fields
+                    }
+                };
+
+                for field in fields.iter() {
+                    self.skip_value(field, &field.r#type, reader)?;
+                }
+            }
+            VTableFieldType::Option(inner) => {
+                if field.decode_option(reader)?.is_some() {
+                    self.skip_value(field, inner, reader)?;
+                }
+            }
+            VTableFieldType::Vec(elem) => {
+                for _ in 0..field.decode_len(reader)? {
+                    self.skip_value(field, elem, reader)?;
+                }
+            }
+            VTableFieldType::HashMap { key, value } => {
+                for _ in 0..field.decode_len(reader)? {
+                    self.skip_value(field, key, reader)?;
+                    self.skip_value(field, value, reader)?;
+                }
+            }
+            VTableFieldType::Array { elem, len } => {
+                for _ in 0..*len {
+                    self.skip_value(field, elem, reader)?;
+                }
+            }
+            VTableFieldType::Tuple(types) => {
+                for r#type in types.iter() {
+                    self.skip_value(field, r#type, reader)?;
+                }
+            }
+            VTableFieldType::String | VTableFieldType::Str | VTableFieldType::Bytes => {
+                let len = field.decode_bytes_len(reader)?;
+                reader.read_bytes(len)?;
+            }
+            VTableFieldType::Uuid => {
+                reader.read_bytes(16)?;
+            }
+            VTableFieldType::Unit => (),
+            r#type => {
+                self.decode_field_value(field, r#type, reader)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use super::*;
+
+/// A position-tracking reader over a borrowed byte buffer.
+///
+/// The reader is shared by the vtable codec, the field decoders and the
+/// serde deserializer. Reads advance the cursor position rather than
+/// removing bytes from the front of the buffer, so decoding is linear in
+/// the size of the buffer and the buffer is left intact.
+#[derive(Debug, Clone)]
+pub struct DocBufReader<'de> {
+    buffer: &'de [u8],
+    position: usize,
+}
+
+impl<'de> DocBufReader<'de> {
+    pub fn new(buffer: &'de [u8]) -> Self {
+        Self {
+            buffer,
+            position: 0,
+        }
+    }
+
+    /// Return the cursor position in the buffer.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Move the cursor to a position in the buffer, e.g. the end of a field
+    /// offset, returning an error if the position is past the end of the buffer.
+    #[inline]
+    pub fn seek(&mut self, position: usize) -> Result<(), Error> {
+        if position > self.buffer.len() {
+            return Err(Error::FailedToParseData);
+        }
+
+        self.position = position;
+
+        Ok(())
+    }
+
+    /// Return the unread bytes of the buffer.
+    #[inline]
+    pub fn remaining(&self) -> &'de [u8] {
+        &self.buffer[self.position..]
+    }
+
+    /// Return true if all bytes of the buffer have been read.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.position >= self.buffer.len()
+    }
+
+    /// Consume `len` bytes, borrowing them from the buffer.
+    #[inline]
+    pub fn read_bytes(&mut self, len: usize) -> Result<&'de [u8], Error> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.buffer.len())
+            .ok_or(Error::FailedToParseData)?;
+
+        let bytes = &self.buffer[self.position..end];
+        self.position = end;
+
+        Ok(bytes)
+    }
+
+    /// Consume a fixed number of bytes.
+    #[inline]
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(self.read_bytes(N)?);
+
+        Ok(bytes)
+    }
+
+    /// Consume a single byte.
+    #[inline]
+    pub fn read_u8(&mut self) -> Result<u8, Error> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    /// Consume the 4-byte length prefix of a string, byte array, list or map.
+    ///
+    /// Elements of lists and maps may encode to zero bytes, e.g. unit structs,
+    /// so their length is not bounded by the buffer, and is not trusted for
+    /// allocations.
+    #[inline]
+    pub fn read_len(&mut self) -> Result<usize, Error> {
+        Ok(u32::from_le_bytes(self.read_array()?) as usize)
+    }
+
+    /// Return the length of a string or byte array, or an error if it is
+    /// larger than the remaining bytes.
+    #[inline]
+    pub fn check_len(&self, len: usize) -> Result<usize, Error> {
+        let remaining = self.remaining().len();
+
+        if len > remaining {
+            return Err(Error::LengthExceedsBuffer(len, remaining));
+        }
+
+        Ok(len)
+    }
+
+    /// Consume a LEB128 varint, returning an error if the value does not fit the type.
+    #[inline]
+    pub fn read_uvarint<T: TryFrom<u128>>(&mut self) -> Result<T, Error> {
+        let mut value = 0u128;
+
+        for index in 0..MAX_VARINT_LEN {
+            let byte = self.read_u8()?;
+            value |= ((byte & 0x7f) as u128) << (index * 7);
+
+            if byte & 0x80 == 0 {
+                return T::try_from(value).map_err(|_| Error::FailedToParseData);
+            }
+        }
+
+        Err(Error::FailedToParseData)
+    }
+
+    /// Consume a zigzag encoded LEB128 varint, returning an error if the value
+    /// does not fit the type.
+    #[inline]
+    pub fn read_ivarint<T: TryFrom<i128>>(&mut self) -> Result<T, Error> {
+        T::try_from(zigzag_decode(self.read_uvarint()?)).map_err(|_| Error::FailedToParseData)
+    }
+
+    /// Consume `len` bytes of UTF-8, borrowing them from the buffer.
+    #[inline]
+    pub fn read_str(&mut self, len: usize) -> Result<&'de str, Error> {
+        Ok(std::str::from_utf8(self.read_bytes(len)?)?)
+    }
+
+    /// Consume a string prefixed with its single byte length, e.g. vtable names.
+    #[inline]
+    pub fn read_name(&mut self) -> Result<String, Error> {
+        let len = self.read_u8()? as usize;
+
+        Ok(self.read_str(len)?.to_owned())
+    }
+
+    /// Consume the bytes if the unread buffer starts with them, returning
+    /// true if they were consumed.
+    #[inline]
+    pub fn consume_if(&mut self, bytes: &[u8]) -> bool {
+        match self.remaining().starts_with(bytes) {
+            true => {
+                self.position += bytes.len();
+                true
+            }
+            false => false,
+        }
+    }
+}
+
+impl<'de> From<&'de [u8]> for DocBufReader<'de> {
+    fn from(buffer: &'de [u8]) -> Self {
+        Self::new(buffer)
+    }
+}
+
+impl<'de> From<&'de Vec<u8>> for DocBufReader<'de> {
+    fn from(buffer: &'de Vec<u8>) -> Self {
+        Self::new(buffer)
+    }
+}
@@ -1,8 +1,10 @@
 use std::ops::Deref;
+use std::sync::OnceLock;
 
 use super::*;
 
 use serde_derive::{Deserialize, Serialize};
+use xxhash_rust::xxh3::xxh3_64;
 
 /// Average field size in bytes, used to estimate the size of the vtable.
 /// This can be used for pre-allocating memory or page sizes for the DocBuf
@@ -14,6 +16,13 @@ pub const DEFAULT_ENTRIES_PER_PAGE: u8 = u8::MAX;
 
 const HASH_PRIME_CONST: u16 = 5;
 
+/// Magic bytes at the start of every serialized vtable.
+pub const VTABLE_MAGIC: [u8; 4] = *b"DBVT";
+
+/// Version of the serialized vtable format. Vtables written before the format
+/// was versioned have no magic bytes, and are rejected by `read_from_buffer`.
+pub const VTABLE_FORMAT_VERSION: u8 = 1;
+
 /// Total number of items in the vtable.
 pub type VTableNumItems = VTableItemIndex;
 
@@ -128,15 +137,27 @@ impl VTableId {
     }
 }
 
+/// The virtual table of a document type, its items and their fields.
+///
+/// The fields are read with accessors, e.g. `VTable::items`, and changed with
+/// `VTable::items_mut`, `VTable::add_struct`, `VTable::add_enum` and
+/// `VTable::set_encoding`, which reset the cached `VTable::id`. The fields were
+/// public before the id was cached.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VTable {
-    pub namespace: VTableNamespace,
+    pub(crate) namespace: VTableNamespace,
     /// The root name of the vtable.
-    pub root: VTableRootItemName,
-    pub items: VTableItems,
-    pub num_items: VTableNumItems,
+    pub(crate) root: VTableRootItemName,
+    pub(crate) items: VTableItems,
+    pub(crate) num_items: VTableNumItems,
     /// Total number of fields in the vtable.
-    pub num_fields: VTableNumFields,
+    pub(crate) num_fields: VTableNumFields,
+    /// The encoding of integers and length prefixes in the document buffer.
+    pub(crate) encoding: VTableEncoding,
+    /// The fingerprint of the vtable, computed on first use by `VTable::id`.
+    /// The fields are only changed through methods clearing the fingerprint.
+    #[serde(skip)]
+    id: OnceLock<VTableId>,
 }
 
 impl std::fmt::Display for VTable {
@@ -153,6 +174,64 @@ impl VTable {
             items: VTableItems::new(),
             num_items: 0,
             num_fields: 0,
+            encoding: VTableEncoding::default(),
+            id: OnceLock::new(),
+        }
+    }
+
+    #[inline]
+    pub fn namespace(&self) -> &VTableNamespace {
+        &self.namespace
+    }
+
+    /// Return the root name of the vtable.
+    #[inline]
+    pub fn root(&self) -> &VTableRootItemName {
+        &self.root
+    }
+
+    #[inline]
+    pub fn items(&self) -> &VTableItems {
+        &self.items
+    }
+
+    /// Return the items for changing, e.g. sorting. The id of the vtable is
+    /// recomputed on next use.
+    #[inline]
+    pub fn items_mut(&mut self) -> &mut VTableItems {
+        self.id.take();
+
+        &mut self.items
+    }
+
+    #[inline]
+    pub fn num_items(&self) -> VTableNumItems {
+        self.num_items
+    }
+
+    /// Return the total number of fields in the vtable.
+    #[inline]
+    pub fn num_fields(&self) -> VTableNumFields {
+        self.num_fields
+    }
+
+    /// Return the encoding of integers and length prefixes in the document buffer.
+    #[inline]
+    pub fn encoding(&self) -> VTableEncoding {
+        self.encoding
+    }
+
+    /// Set the encoding of the document buffer, for all items of the vtable.
+    #[inline]
+    pub fn set_encoding(&mut self, encoding: VTableEncoding) {
+        self.encoding = encoding;
+        self.id.take();
+
+        for item in self.items.inner_mut() {
+            match item {
+                VTableItem::Struct(vtable_struct) => vtable_struct.set_encoding(encoding),
+                VTableItem::Enum(vtable_enum) => vtable_enum.set_encoding(encoding),
+            }
         }
     }
 
@@ -162,6 +241,9 @@ impl VTable {
         // Clear the buffer
         buffer.clear();
 
+        buffer.extend_from_slice(&VTABLE_MAGIC);
+        buffer.push(VTABLE_FORMAT_VERSION);
+
         // Serialize the vtable
         let namespace_bytes = self.namespace.as_bytes();
         let namespace_len = namespace_bytes.len() as u8;
@@ -175,6 +257,7 @@ impl VTable {
 
         buffer.push(self.num_items as u8);
         buffer.extend_from_slice(&self.num_fields.to_le_bytes());
+        buffer.push(self.encoding.into());
 
         for item in self.items.iter() {
             item.write_to_buffer(buffer)?;
@@ -184,16 +267,24 @@ impl VTable {
     }
 
     #[inline]
-    pub fn read_from_buffer(buffer: &mut Vec<u8>) -> Result<Self, Error> {
-        let namespace_len = buffer.remove(0);
-        let namespace = String::from_utf8(buffer.drain(0..namespace_len as usize).collect())?;
+    /// Deserialize the vtable from a byte buffer.
+    pub fn read_from_buffer(buffer: &mut DocBufReader<'_>) -> Result<Self, Error> {
+        let magic: [u8; 4] = buffer.read_array()?;
+        if magic != VTABLE_MAGIC {
+            return Err(Error::UnversionedVTableFormat);
+        }
+
+        let version = buffer.read_u8()?;
+        if version != VTABLE_FORMAT_VERSION {
+            return Err(Error::UnsupportedVTableFormat(version));
+        }
 
-        let root_len = buffer.remove(0);
-        let root = String::from_utf8(buffer.drain(0..root_len as usize).collect())?;
+        let namespace = buffer.read_name()?;
+        let root = buffer.read_name()?;
 
-        let num_items = buffer.remove(0);
-        let num_fields = u16::from_le_bytes([buffer[0], buffer[1]]);
-        buffer.drain(0..2);
+        let num_items = buffer.read_u8()?;
+        let num_fields = u16::from_le_bytes(buffer.read_array()?);
+        let encoding = VTableEncoding::try_from(buffer.read_u8()?)?;
 
         let mut vtable = Self::new(namespace, root);
 
@@ -206,6 +297,8 @@ impl VTable {
             vtable.items.0.push(item);
         }
 
+        vtable.set_encoding(encoding);
+
         Ok(vtable)
     }
 
@@ -219,7 +312,26 @@ impl VTable {
         let mut file = std::fs::File::open(&path)?;
         let mut buf = Vec::new();
         file.read_to_end(&mut buf)?;
-        Self::read_from_buffer(&mut buf)
+        Self::read_from_buffer(&mut DocBufReader::new(&buf))
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    /// Write the vtable to the writer as a length framed vtable,
+    /// allowing it to be followed by other vtables or documents on the same stream.
+    pub fn to_writer(&self, writer: impl std::io::Write) -> Result<(), Error> {
+        crate::frame::write_frame(writer, &self.to_vec()?)?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    /// Read a length framed vtable from the reader, written by `to_writer`.
+    pub fn from_reader(reader: impl std::io::Read) -> Result<Self, Error> {
+        let buf = crate::frame::read_frame(reader)?;
+
+        Self::read_from_buffer(&mut DocBufReader::new(&buf))
     }
 
     pub fn to_vec(&self) -> Result<Vec<u8>, Error> {
@@ -248,6 +360,9 @@ impl VTable {
             VTableItem::Struct(vtable_struct) => {
                 self.add_struct(vtable_struct);
             }
+            VTableItem::Enum(vtable_enum) => {
+                self.add_enum(vtable_enum);
+            }
         }
     }
 
@@ -255,20 +370,29 @@ impl VTable {
     pub fn add_struct(&mut self, vtable_struct: VTableStruct) {
         let mut vtable_struct = vtable_struct;
         vtable_struct.set_item_index(self.num_items);
+        vtable_struct.set_encoding(self.encoding);
         self.num_fields += vtable_struct.num_fields as u16;
         self.items.add_struct(vtable_struct);
         self.num_items += 1;
+        self.id.take();
+    }
+
+    #[inline]
+    pub fn add_enum(&mut self, vtable_enum: VTableEnum) {
+        let mut vtable_enum = vtable_enum;
+        vtable_enum.set_item_index(self.num_items);
+        vtable_enum.set_encoding(self.encoding);
+        self.num_fields += vtable_enum.num_fields() as u16;
+        self.items.add_enum(vtable_enum);
+        self.num_items += 1;
+        self.id.take();
     }
 
     #[inline]
     pub fn merge_vtable(&mut self, vtable: &'static VTable) {
         for vtable_item in vtable.items.iter() {
             if !self.items.0.contains(vtable_item) {
-                match vtable_item {
-                    VTableItem::Struct(vtable_struct) => {
-                        self.add_struct(vtable_struct.to_owned());
-                    }
-                }
+                self.add_item(vtable_item.to_owned());
             }
         }
     }
@@ -279,17 +403,25 @@ impl VTable {
             return Err(Error::ItemNotFound);
         }
 
-        for vtable_item in self.items.iter() {
-            match vtable_item {
-                VTableItem::Struct(vtable_struct) => {
-                    if vtable_struct.item_index == index {
-                        return Ok(vtable_item);
-                    }
-                }
-            }
-        }
+        self.items
+            .iter()
+            .find(|vtable_item| vtable_item.item_index() == index)
+            .ok_or(Error::ItemNotFound)
+    }
 
-        Err(Error::ItemNotFound)
+    // Return the item from the item name
+    #[inline]
+    pub fn item_by_name(&self, name: &str) -> Result<&VTableItem, Error> {
+        self.items
+            .iter()
+            .find(|vtable_item| vtable_item.name() == name)
+            .ok_or(Error::ItemNotFound)
+    }
+
+    /// Return the root item of the vtable.
+    #[inline]
+    pub fn root_item(&self) -> Result<&VTableItem, Error> {
+        self.item_by_name(&self.root)
     }
 
     #[inline]
@@ -297,6 +429,7 @@ impl VTable {
     pub fn num_fields_by_index(&self, index: VTableItemIndex) -> Result<u8, Error> {
         match self.item_by_index(index)? {
             VTableItem::Struct(vtable_struct) => Ok(vtable_struct.num_fields),
+            VTableItem::Enum(vtable_enum) => Ok(vtable_enum.num_fields() as u8),
         }
     }
 
@@ -308,11 +441,9 @@ impl VTable {
         }
 
         for vtable_item in self.items.iter() {
-            match vtable_item {
-                VTableItem::Struct(vtable_struct) => {
-                    if vtable_struct.item_index == index {
-                        return Ok(vtable_struct);
-                    }
+            if let VTableItem::Struct(vtable_struct) = vtable_item {
+                if vtable_struct.item_index == index {
+                    return Ok(vtable_struct);
                 }
             }
         }
@@ -324,11 +455,9 @@ impl VTable {
     #[inline]
     pub fn struct_by_name(&self, name: &str) -> Result<&VTableStruct, Error> {
         for vtable_item in self.items.iter() {
-            match vtable_item {
-                VTableItem::Struct(vtable_struct) => {
-                    if vtable_struct.name == name {
-                        return Ok(vtable_struct);
-                    }
+            if let VTableItem::Struct(vtable_struct) = vtable_item {
+                if vtable_struct.name == name {
+                    return Ok(vtable_struct);
                 }
             }
         }
@@ -336,6 +465,55 @@ impl VTable {
         Err(Error::StructNotFound)
     }
 
+    // Return the enum from the enum index
+    #[inline]
+    pub fn enum_by_index(&self, index: VTableItemIndex) -> Result<&VTableEnum, Error> {
+        match self.item_by_index(index) {
+            Ok(VTableItem::Enum(vtable_enum)) => Ok(vtable_enum),
+            _ => Err(Error::EnumNotFound),
+        }
+    }
+
+    // Return the enum from the enum name
+    #[inline]
+    pub fn enum_by_name(&self, name: &str) -> Result<&VTableEnum, Error> {
+        match self.item_by_name(name) {
+            Ok(VTableItem::Enum(vtable_enum)) => Ok(vtable_enum),
+            _ => Err(Error::EnumNotFound),
+        }
+    }
+
+    /// Resolve struct type names that refer to enum items in the vtable, e.g. a field
+    /// type parsed as `Struct("Status")` becomes `Enum("Status")` if `Status` is an enum.
+    #[inline]
+    pub fn resolve_field_type(&self, field_type: VTableFieldType) -> VTableFieldType {
+        match field_type {
+            VTableFieldType::Struct(name) => match self.enum_by_name(&name) {
+                Ok(_) => VTableFieldType::Enum(name),
+                Err(_) => VTableFieldType::Struct(name),
+            },
+            VTableFieldType::Option(t) => {
+                VTableFieldType::Option(Box::new(self.resolve_field_type(*t)))
+            }
+            VTableFieldType::Vec(t) => VTableFieldType::Vec(Box::new(self.resolve_field_type(*t))),
+            VTableFieldType::HashMap { key, value } => VTableFieldType::HashMap {
+                key: Box::new(self.resolve_field_type(*key)),
+                value: Box::new(self.resolve_field_type(*value)),
+            },
+            VTableFieldType::Array { elem, len } => VTableFieldType::Array {
+                elem: Box::new(self.resolve_field_type(*elem)),
+                len,
+            },
+            VTableFieldType::Tuple(types) => VTableFieldType::Tuple(
+                types
+                    .into_iter()
+                    .map(|t| self.resolve_field_type(t))
+                    .collect(),
+            ),
+            field_type => field_type,
+        }
+    }
+
     // Return the field from the vtable by struct index and field index
     #[inline]
     pub fn get_struct_field_by_index(
@@ -349,17 +527,9 @@ impl VTable {
 
     #[inline]
     pub fn get_struct_item_index_by_name(&self, name: &str) -> Option<u8> {
-        for vtable_item in self.items.iter() {
-            match vtable_item {
-                VTableItem::Struct(vtable_struct) => {
-                    if vtable_struct.name == name {
-                        return Some(vtable_struct.item_index);
-                    }
-                }
-            }
-        }
-
-        None
+        self.struct_by_name(name)
+            .ok()
+            .map(|vtable_struct| vtable_struct.item_index)
     }
 
     // Return the field from the current item index and field index
@@ -375,6 +545,7 @@ impl VTable {
                 VTableItem::Struct(vtable_struct) => vtable_struct
                     .field_by_index(&field_index)
                     .map_err(|_| Error::FieldNotFound),
+                VTableItem::Enum(_) => Err(Error::FieldNotFound),
             })
     }
 
@@ -415,6 +586,7 @@ impl VTable {
             .iter()
             .map(|item| match item {
                 VTableItem::Struct(vtable_struct) => vtable_struct.fields.0.clone(),
+                VTableItem::Enum(vtable_enum) => vtable_enum.fields().cloned().collect(),
             })
             .flatten()
             .collect::<Vec<VTableField>>();
@@ -432,20 +604,25 @@ impl VTable {
         Self::hash_tag(&self.root)
     }
 
+    /// Return the vtable identifier, the xxh3-64 fingerprint of the canonical
+    /// serialized vtable, as written by `VTable::write_to_buffer`.
+    ///
+    /// The fingerprint covers the namespace, item names, field names, field
+    /// types and field rules, so any change to the vtable changes its id. It
+    /// is computed once per vtable instance, and recomputed when the vtable
+    /// changes.
     #[inline]
-    /// Return the vtable identifier as a 8 byte array.
-    pub fn id(&self) -> &'static VTableId {
-        static VTABLE_ID: ::std::sync::OnceLock<VTableId> = ::std::sync::OnceLock::new();
-
-        VTABLE_ID.get_or_init(|| {
-            let mut id = [0u8; 8];
-            id[0..2].copy_from_slice(&self.namespace_tag());
-            id[2..4].copy_from_slice(&self.root_tag());
-            id[4] = (self.namespace.len() + self.root.len()) as u8;
-            id[5] = self.num_items;
-            id[6..8].copy_from_slice(&self.num_fields.to_le_bytes());
+    pub fn id(&self) -> &VTableId {
+        self.id.get_or_init(|| {
+            let mut buffer = Vec::new();
+
+            // A vtable that cannot be serialized cannot be persisted either,
+            // its debug representation still distinguishes it from others.
+            if self.write_to_buffer(&mut buffer).is_err() {
+                buffer = format!("{:?}", self).into_bytes();
+            }
 
-            VTableId::new(id)
+            VTableId::from(xxh3_64(&buffer))
         })
     }
 
@@ -470,11 +647,38 @@ impl VTable {
         vec![0; self.avg_size() * 2]
     }
 
+    /// Return the number of field offsets produced when encoding a document.
+    /// Nested struct fields have an offset spanning the struct, followed by the
+    /// offsets of their fields, while enum values, lists and maps are a single
+    /// offset spanning the discriminant and payload, or the elements.
     #[inline]
     pub fn num_offsets(&self) -> u16 {
-        // Add 1 to account for the root item offset
-        // Substract the number of items to account for the flat array of offsets
-        self.num_fields + 1 - self.num_items as u16
+        match self.root_item() {
+            Ok(VTableItem::Struct(vtable_struct)) => self.num_struct_offsets(vtable_struct),
+            Ok(VTableItem::Enum(_)) => 1,
+            // Add 1 to account for the root item offset
+            // Substract the number of items to account for the flat array of offsets
+            Err(_) => (self.num_fields + 1).saturating_sub(self.num_items as u16),
+        }
+    }
+
+    fn num_struct_offsets(&self, vtable_struct: &VTableStruct) -> u16 {
+        vtable_struct
+            .fields
+            .iter()
+            .map(|field| match &field.r#type {
+                VTableFieldType::Struct(name) => self.struct_by_name(name).ok(),
+                VTableFieldType::Option(t) => match t.as_ref() {
+                    VTableFieldType::Struct(name) => self.struct_by_name(name).ok(),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .map(|nested| match nested {
+                Some(nested) => 1 + self.num_struct_offsets(nested),
+                None => 1,
+            })
+            .sum()
     }
 
     #[inline]
@@ -502,5 +706,6 @@ impl PartialEq for VTable {
             && self.items == other.items
             && self.num_items == other.num_items
             && self.num_fields == other.num_fields
+            && self.encoding == other.encoding
     }
 }
@@ -1,4 +1,4 @@
-use crate::{Partition, PartitionId, PartitionPermission};
+use crate::{Partition, PartitionId, PartitionPermission, PARTITION_COUNT_OFFSET};
 
 use super::Error;
 
@@ -10,7 +10,7 @@ use std::{
     str::FromStr,
 };
 
-use docbuf_core::vtable::{VTable, VTableId};
+use docbuf_core::vtable::{self, DocBufReader, VTable, VTableFieldOffsets, VTableId};
 use serde::{Deserialize, Serialize};
 
 /// Default directory for the DocBuf database.
@@ -221,6 +221,152 @@ impl DocBufDbConfig {
         Ok(ids)
     }
 
+    /// Migrate the vtable directories named by a previous vtable id scheme to
+    /// the current `VTable::id` of the vtable file in each directory, returning
+    /// the previous and current id of every migrated directory.
+    ///
+    /// Vtable files written before the vtable format was versioned do not record
+    /// the full vtable, they are migrated to the matching vtable of the compiled
+    /// document types, e.g. `&[Document::vtable()?]`, see `VTable::matches_legacy`,
+    /// and rewritten in the current format.
+    ///
+    /// The vtable file and lock file are renamed with the directory. Partition
+    /// files are named by partition id and are kept as is. Directories already
+    /// named by the current id are left untouched, so the migration can be run
+    /// on every start.
+    ///
+    /// Previous ids may collide, so a directory may hold the documents of several
+    /// document types. Directories holding a document that does not decode with
+    /// the vtable are not migrated, see `Error::VTableMigrationMixed`.
+    ///
+    /// The database manager does not migrate the directories on start, callers
+    /// must run the migration with the vtables of their document types before
+    /// reading or writing documents, see `DocBufDbManager::migrate_vtable_directories`.
+    pub fn migrate_vtable_directories(
+        &self,
+        vtables: &[&VTable],
+    ) -> Result<Vec<(VTableId, VTableId)>, Error> {
+        let mut migrated = Vec::new();
+
+        for previous_id in self.vtable_ids()? {
+            let vtable_file = self.vtable_file(&previous_id)?;
+            let bytes = std::fs::read(&vtable_file)?;
+
+            let (vtable, legacy) = match VTable::read_from_buffer(&mut DocBufReader::new(&bytes)) {
+                Ok(vtable) => (vtable, false),
+                Err(vtable::Error::UnversionedVTableFormat) => {
+                    let legacy = VTable::read_legacy_from_buffer(&mut DocBufReader::new(&bytes))?;
+
+                    let vtable = vtables
+                        .iter()
+                        .find(|vtable| vtable.matches_legacy(&legacy))
+                        .ok_or_else(|| {
+                            Error::VTableMigrationNotFound(
+                                previous_id.as_hex(),
+                                format!("{}::{}", legacy.namespace(), legacy.root()),
+                            )
+                        })?;
+
+                    ((*vtable).clone(), true)
+                }
+                Err(error) => return Err(error.into()),
+            };
+
+            let id = vtable.id().to_owned();
+
+            if id == previous_id {
+                continue;
+            }
+
+            let previous_dir = self.vtable_directory(&previous_id)?;
+            let dir = self.vtable_directory(&id)?;
+
+            // The previous ids collide, a directory may already hold the vtable.
+            if dir.exists() {
+                return Err(Error::VTableMigrationConflict(
+                    previous_id.as_hex(),
+                    id.as_hex(),
+                ));
+            }
+
+            if !self.holds_vtable_documents(&previous_id, &vtable)? {
+                return Err(Error::VTableMigrationMixed(
+                    previous_id.as_hex(),
+                    format!("{}::{}", vtable.namespace(), vtable.root()),
+                ));
+            }
+
+            let migrated_file = previous_dir.join(format!("{}.vtable", id.as_hex()));
+
+            if legacy {
+                vtable.to_file(migrated_file)?;
+                std::fs::remove_file(vtable_file)?;
+            } else {
+                std::fs::rename(vtable_file, migrated_file)?;
+            }
+
+            let lock_file = self.vtable_lock_file(&previous_id)?;
+            if lock_file.exists() {
+                std::fs::rename(
+                    lock_file,
+                    previous_dir.join(format!("{}.lock", id.as_hex())),
+                )?;
+            }
+
+            std::fs::rename(previous_dir, dir)?;
+
+            migrated.push((previous_id, id));
+        }
+
+        Ok(migrated)
+    }
+
+    /// Returns true if every document of the partition files of the vtable
+    /// directory decodes with the vtable. Tombstones are skipped.
+    fn holds_vtable_documents(&self, vtable_id: &VTableId, vtable: &VTable) -> Result<bool, Error> {
+        let offset_len = vtable.offset_len();
+
+        for entry in self.vtable_directory(vtable_id)?.read_dir()? {
+            let path = entry?.path();
+
+            if path.extension().and_then(|ext| ext.to_str()) != Some("dbp") {
+                continue;
+            }
+
+            let bytes = std::fs::read(&path)?;
+
+            // The documents follow the count of the partition, each prefixed by its offsets.
+            let mut position = PARTITION_COUNT_OFFSET as usize;
+
+            while position < bytes.len() {
+                let offsets = match bytes.get(position..position + offset_len) {
+                    Some(offsets) => VTableFieldOffsets::from_bytes(offsets),
+                    None => return Ok(false),
+                };
+
+                let start = position + offset_len;
+
+                let document = match start
+                    .checked_add(offsets.doc_buffer_len())
+                    .and_then(|end| bytes.get(start..end))
+                {
+                    Some(document) if start + document.len() > position => document,
+                    _ => return Ok(false),
+                };
+
+                let is_tombstone = document.iter().all(|byte| *byte == 0);
+
+                if !is_tombstone && vtable.decode_value(document).is_err() {
+                    return Ok(false);
+                }
+
+                position = start + document.len();
+            }
+        }
+
+        Ok(true)
+    }
+
     /// Save the configuration to the file system.
     pub fn save(&self, path: impl Into<PathBuf>) -> Result<(), Error> {
         let path: PathBuf = path.into();
@@ -243,4 +389,190 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_migrate_vtable_directories() -> Result<(), Box<dyn std::error::Error>> {
+        use super::*;
+        use docbuf_core::vtable::{
+            VTableFieldOffset, VTableFieldRules, VTableFieldType, VTableStruct,
+        };
+
+        let directory = std::env::temp_dir().join(format!("docbuf-migrate-{}", std::process::id()));
+        let mut config = DocBufDbConfig::default().set_directory(directory.clone());
+        config.setup_directory()?;
+
+        // A vtable directory named by a previous vtable id.
+        let mut vtable = VTable::new("migrate".to_string(), "Document".to_string());
+        let mut document = VTableStruct::new("Document", None);
+        document.add_field(VTableFieldType::U64, "id", VTableFieldRules::new());
+        vtable.add_struct(document);
+
+        // A partition file of the count, the offsets and the document, and a tombstone.
+        let offset = VTableFieldOffset((0, 0), 0..8).as_bytes();
+        let partition = [
+            &2u64.to_le_bytes()[..],
+            &offset,
+            &42u64.to_le_bytes(),
+            &offset,
+            &[0; 8],
+        ]
+        .concat();
+
+        let previous_id = VTableId::new([1, 2, 3, 4, 5, 6, 7, 8]);
+        let previous_dir = config.vtable_directory(&previous_id)?;
+        std::fs::create_dir_all(&previous_dir)?;
+        vtable.to_file(config.vtable_file(&previous_id)?)?;
+        std::fs::write(previous_dir.join("0x0001.dbp"), &partition)?;
+        std::fs::write(previous_dir.join("0x0002.dbp"), [])?;
+
+        assert_eq!(
+            config.migrate_vtable_directories(&[])?,
+            vec![(previous_id.clone(), vtable.id().to_owned())]
+        );
+
+        assert!(!previous_dir.exists());
+        assert!(config.vtable_file(vtable.id())?.exists());
+        assert!(config
+            .vtable_directory(vtable.id())?
+            .join("0x0001.dbp")
+            .exists());
+        assert_eq!(config.read_vtable(vtable.id())?.id(), vtable.id());
+
+        // Migrated directories are left untouched.
+        assert_eq!(config.migrate_vtable_directories(&[])?, vec![]);
+
+        // A directory holding the documents of another document type, here a
+        // string, is not migrated.
+        let string = [&5u32.to_le_bytes()[..], b"hello"].concat();
+        let offset = VTableFieldOffset((0, 0), 0..string.len()).as_bytes();
+        let mixed = [&partition[..], &offset, &string].concat();
+
+        let previous_id = VTableId::new([2, 3, 4, 5, 6, 7, 8, 9]);
+        let previous_dir = config.vtable_directory(&previous_id)?;
+        std::fs::create_dir_all(&previous_dir)?;
+        vtable.to_file(config.vtable_file(&previous_id)?)?;
+        std::fs::write(previous_dir.join("0x0001.dbp"), mixed)?;
+
+        std::fs::remove_dir_all(config.vtable_directory(vtable.id())?)?;
+
+        assert!(matches!(
+            config.migrate_vtable_directories(&[]),
+            Err(Error::VTableMigrationMixed(..))
+        ));
+        assert!(previous_dir.exists());
+        assert!(!config.vtable_directory(vtable.id())?.exists());
+
+        std::fs::remove_dir_all(directory)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_legacy_vtable_directories() -> Result<(), Box<dyn std::error::Error>> {
+        use super::*;
+        use docbuf_core::vtable::{NumericValue, VTableFieldRules, VTableFieldType, VTableStruct};
+
+        // The vtable of the `Archive` document, written before the vtable format was
+        // versioned:
+        //
+        // #[docbuf { namespace = "legacy"; }]
+        // pub struct Archive {
+        //     #[docbuf { max_length = 32; regex = r"^[a-z]+$"; }]
+        //     pub name: String,
+        //     pub tags: Vec<String>,
+        //     pub note: Option<String>,
+        //     #[docbuf { min_value = 1; }]
+        //     pub count: u32,
+        //     pub entry: Entry,
+        // }
+        //
+        // #[docbuf { namespace = "legacy"; }]
+        // pub struct Entry {
+        //     pub id: u64,
+        //     pub payload: Vec<u8>,
+        // }
+        const LEGACY_VTABLE: &[u8] = include_bytes!("../fixtures/legacy.vtable");
+
+        let legacy = VTable::read_legacy_from_buffer(&mut DocBufReader::new(LEGACY_VTABLE))?;
+        assert_eq!(legacy.root(), "Archive");
+
+        let archive = legacy.struct_by_name("Archive")?;
+        assert_eq!(
+            archive.field_by_name("name")?.rules.regex.as_deref(),
+            Some("^[a-z]+$")
+        );
+        assert_eq!(
+            archive.field_by_name("count")?.rules.min_value,
+            Some(NumericValue::U32(1))
+        );
+
+        // The vtable of the compiled `Archive` document.
+        let mut vtable = VTable::new("legacy".to_string(), "Archive".to_string());
+
+        let mut entry = VTableStruct::new("Entry", None);
+        entry.add_field(VTableFieldType::U64, "id", VTableFieldRules::new());
+        entry.add_field(VTableFieldType::Bytes, "payload", VTableFieldRules::new());
+        vtable.add_struct(entry);
+
+        let mut archive = VTableStruct::new("Archive", None);
+        archive.add_field(
+            VTableFieldType::String,
+            "name",
+            VTableFieldRules::new().set_max_length(32),
+        );
+        archive.add_field(
+            VTableFieldType::Vec(Box::new(VTableFieldType::String)),
+            "tags",
+            VTableFieldRules::new(),
+        );
+        archive.add_field(
+            VTableFieldType::Option(Box::new(VTableFieldType::String)),
+            "note",
+            VTableFieldRules::new(),
+        );
+        archive.add_field(VTableFieldType::U32, "count", VTableFieldRules::new());
+        archive.add_field(
+            VTableFieldType::Struct("Entry".to_string()),
+            "entry",
+            VTableFieldRules::new(),
+        );
+        vtable.add_struct(archive);
+
+        assert!(vtable.matches_legacy(&legacy));
+
+        // A vtable with different fields does not match.
+        let mut other = VTable::new("legacy".to_string(), "Archive".to_string());
+        let mut archive = VTableStruct::new("Archive", None);
+        archive.add_field(VTableFieldType::String, "name", VTableFieldRules::new());
+        other.add_struct(archive);
+
+        assert!(!other.matches_legacy(&legacy));
+
+        let directory =
+            std::env::temp_dir().join(format!("docbuf-migrate-legacy-{}", std::process::id()));
+        let mut config = DocBufDbConfig::default().set_directory(directory.clone());
+        config.setup_directory()?;
+
+        let previous_id = VTableId::new([8, 7, 6, 5, 4, 3, 2, 1]);
+        std::fs::create_dir_all(config.vtable_directory(&previous_id)?)?;
+        std::fs::write(config.vtable_file(&previous_id)?, LEGACY_VTABLE)?;
+
+        // The vtable of the document type is required to migrate the directory.
+        assert!(matches!(
+            config.migrate_vtable_directories(&[&other]),
+            Err(Error::VTableMigrationNotFound(..))
+        ));
+
+        assert_eq!(
+            config.migrate_vtable_directories(&[&other, &vtable])?,
+            vec![(previous_id, vtable.id().to_owned())]
+        );
+
+        // The vtable file is rewritten in the current format.
+        assert_eq!(&config.read_vtable(vtable.id())?, &vtable);
+
+        std::fs::remove_dir_all(directory)?;
+
+        Ok(())
+    }
 }
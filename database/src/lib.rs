@@ -28,6 +28,12 @@ pub enum Error {
     TomlSer(#[from] toml::ser::Error),
     #[error("VTable ID not found")]
     VTableIdNotFound,
+    #[error("Cannot migrate vtable directory {0}, the vtable directory {1} already exists")]
+    VTableMigrationConflict(String, String),
+    #[error("Cannot migrate vtable directory {0}, no vtable matches the unversioned vtable {1}")]
+    VTableMigrationNotFound(String, String),
+    #[error("Cannot migrate vtable directory {0}, it holds documents which are not documents of the vtable {1}")]
+    VTableMigrationMixed(String, String),
     #[error("Database directory not set")]
     DirectoryNotSet,
     #[error(transparent)]
@@ -6,6 +6,7 @@ use crate::Error;
 use docbuf_core::traits::DocBuf;
 use docbuf_core::vtable::*;
 use docbuf_rpc::RpcClient;
+use serde::de::DeserializeOwned;
 use tracing::debug;
 
 use std::io::ErrorKind;
@@ -21,6 +22,10 @@ pub struct DocBufDbManager {
 }
 
 impl DocBufDbManager {
+    /// Create a manager of the database of the configuration.
+    ///
+    /// Vtable directories named by a previous vtable id are not migrated on
+    /// start, run `DocBufDbManager::migrate_vtable_directories` first.
     pub fn from_config(config: DocBufDbConfig) -> Result<Self, Error> {
         let rpc_client = config
             .rpc()
@@ -90,6 +95,20 @@ impl DocBufDbManager {
         Ok(())
     }
 
+    /// Migrate the vtable directories named by a previous vtable id scheme,
+    /// see `DocBufDbConfig::migrate_vtable_directories`.
+    ///
+    /// The migration needs the vtables of the document types of the database,
+    /// e.g. `&[Document::vtable()?]`, so it is not run by the manager. Run it
+    /// before reading or writing documents, the documents of unmigrated
+    /// directories are not found.
+    pub fn migrate_vtable_directories(
+        &self,
+        vtables: &[&VTable],
+    ) -> Result<Vec<(VTableId, VTableId)>, Error> {
+        self.config.migrate_vtable_directories(vtables)
+    }
+
     pub fn read_docbuf(&self, request: ReadDocBufRequest) -> Result<Option<Vec<u8>>, Error> {
         match self
             .config
@@ -354,13 +373,16 @@ impl DocBufDbMngr for DocBufDbManager {
         &self,
         predicate: Self::Predicate,
         partition_key: Option<PartitionKey>,
-    ) -> Result<impl Iterator<Item = D::Doc>, Error> {
+    ) -> Result<impl Iterator<Item = D::Doc>, Error>
+    where
+        D::Doc: DeserializeOwned,
+    {
         let vtable_id = D::vtable()?.id().deref();
         let partition_id = partition_key.clone().map(PartitionId::from).map(u16::from);
 
         let iter = self
             .search_docbufs(vtable_id, partition_id, predicate)?
-            .map(|mut buf| D::from_docbuf(&mut buf))
+            .map(|buf| D::from_docbuf(&buf))
             .collect::<Result<Vec<_>, _>>()?
             .into_iter();
 
@@ -372,7 +394,10 @@ impl DocBufDbMngr for DocBufDbManager {
         &self,
         id: D::DocId,
         partition_key: Option<PartitionKey>,
-    ) -> Result<Option<<D as DocBuf>::Doc>, Error> {
+    ) -> Result<Option<<D as DocBuf>::Doc>, Error>
+    where
+        D::Doc: DeserializeOwned,
+    {
         let vtable_id = D::vtable()?.id().deref();
 
         let doc_id: [u8; 16] = id.into();
@@ -393,7 +418,7 @@ impl DocBufDbMngr for DocBufDbManager {
             // Write the document buffer to the database.
             self.read_docbuf(request)?
         }
-        .map(|mut buf| D::from_docbuf(&mut buf).map_err(Error::from))
+        .map(|buf| D::from_docbuf(&buf).map_err(Error::from))
         .transpose()
     }
 
@@ -422,7 +447,10 @@ impl DocBufDbMngr for DocBufDbManager {
     }
 
     /// Delete a document from the database.
-    fn delete<D: DocBuf>(&self, doc: D, partition_key: PartitionKey) -> Result<D::Doc, Error> {
+    fn delete<D: DocBuf>(&self, doc: D, partition_key: PartitionKey) -> Result<D::Doc, Error>
+    where
+        D::Doc: DeserializeOwned,
+    {
         debug!("Deleting Document in Database");
         let vtable_id = D::vtable()?.id().deref();
         let doc_id = doc.uuid()?;
@@ -440,7 +468,7 @@ impl DocBufDbMngr for DocBufDbManager {
             self.delete_docbuf(request)?
         };
 
-        Ok(D::from_docbuf(&mut docbuf)?)
+        Ok(D::from_docbuf(&docbuf)?)
     }
 
     /// Return the number of documents in the database.
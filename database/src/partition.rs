@@ -19,7 +19,7 @@ use docbuf_core::vtable::{VTable, VTableFieldOffsets};
 use file_lock::{FileLock, FileOptions};
 
 /// The offset of the partition count in the partition file.
-const PARTITION_COUNT_OFFSET: u64 = 8;
+pub(crate) const PARTITION_COUNT_OFFSET: u64 = 8;
 
 pub enum PartitionPermission {
     Write,
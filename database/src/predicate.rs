@@ -5,7 +5,10 @@ use crate::Error;
 use docbuf_core::serde::serde_bytes;
 use docbuf_core::{
     traits::DocBufDecodeField,
-    vtable::{VTable, VTableField, VTableFieldOffsetIndex, VTableFieldOffsets, VTableFieldType},
+    vtable::{
+        DocBufReader, VTable, VTableField, VTableFieldOffsetIndex, VTableFieldOffsets,
+        VTableFieldType,
+    },
 };
 use docbuf_macros::docbuf;
 
@@ -150,90 +153,96 @@ impl Predicate {
                         unimplemented!("Option type not implemented")
                     }
                     VTableFieldType::I8 => {
-                        let value: i8 = field.decode(&mut self.value().to_vec())?;
-                        let field_value: i8 = field.decode(&mut data.to_vec())?;
+                        let value: i8 = field.decode(&mut DocBufReader::new(self.value()))?;
+                        let field_value: i8 = field.decode(&mut DocBufReader::new(data))?;
 
                         return Ok(value.cmp(&field_value) == self.order());
                     }
                     VTableFieldType::I16 => {
-                        let value: i16 = field.decode(&mut self.value().to_vec())?;
-                        let field_value: i16 = field.decode(&mut data.to_vec())?;
+                        let value: i16 = field.decode(&mut DocBufReader::new(self.value()))?;
+                        let field_value: i16 = field.decode(&mut DocBufReader::new(data))?;
 
                         return Ok(value.cmp(&field_value) == self.order());
                     }
                     VTableFieldType::I32 => {
-                        let value: i32 = field.decode(&mut self.value().to_vec())?;
-                        let field_value: i32 = field.decode(&mut data.to_vec())?;
+                        let value: i32 = field.decode(&mut DocBufReader::new(self.value()))?;
+                        let field_value: i32 = field.decode(&mut DocBufReader::new(data))?;
 
                         return Ok(value.cmp(&field_value) == self.order());
                     }
                     VTableFieldType::I64 => {
-                        let value: i64 = field.decode(&mut self.value().to_vec())?;
-                        let field_value: i64 = field.decode(&mut data.to_vec())?;
+                        let value: i64 = field.decode(&mut DocBufReader::new(self.value()))?;
+                        let field_value: i64 = field.decode(&mut DocBufReader::new(data))?;
 
                         return Ok(value.cmp(&field_value) == self.order());
                     }
 
                     VTableFieldType::I128 => {
-                        let value: i128 = field.decode(&mut self.value().to_vec())?;
-                        let field_value: i128 = field.decode(&mut data.to_vec())?;
+                        let value: i128 = field.decode(&mut DocBufReader::new(self.value()))?;
+                        let field_value: i128 = field.decode(&mut DocBufReader::new(data))?;
 
                         return Ok(value.cmp(&field_value) == self.order());
                     }
                     VTableFieldType::ISIZE => {
-                        let value: isize = field.decode(&mut self.value().to_vec())?;
-                        let field_value: isize = field.decode(&mut data.to_vec())?;
+                        let value: isize = field.decode(&mut DocBufReader::new(self.value()))?;
+                        let field_value: isize = field.decode(&mut DocBufReader::new(data))?;
 
                         return Ok(value.cmp(&field_value) == self.order());
                     }
                     VTableFieldType::U8 => {
-                        let value: u8 = field.decode(&mut self.value().to_vec())?;
-                        let field_value: u8 = field.decode(&mut data.to_vec())?;
+                        let value: u8 = field.decode(&mut DocBufReader::new(self.value()))?;
+                        let field_value: u8 = field.decode(&mut DocBufReader::new(data))?;
 
                         return Ok(value.cmp(&field_value) == self.order());
                     }
                     VTableFieldType::U16 => {
-                        let value: u16 = field.decode(&mut self.value().to_vec())?;
-                        let field_value: u16 = field.decode(&mut data.to_vec())?;
+                        let value: u16 = field.decode(&mut DocBufReader::new(self.value()))?;
+                        let field_value: u16 = field.decode(&mut DocBufReader::new(data))?;
 
                         return Ok(value.cmp(&field_value) == self.order());
                     }
                     VTableFieldType::U32 => {
-                        let value: u32 = field.decode(&mut self.value().to_vec())?;
-                        let field_value: u32 = field.decode(&mut data.to_vec())?;
+                        let value: u32 = field.decode(&mut DocBufReader::new(self.value()))?;
+                        let field_value: u32 = field.decode(&mut DocBufReader::new(data))?;
 
                         return Ok(value.cmp(&field_value) == self.order());
                     }
                     VTableFieldType::U64 => {
-                        let value: u64 = field.decode(&mut self.value().to_vec())?;
-                        let field_value: u64 = field.decode(&mut data.to_vec())?;
+                        let value: u64 = field.decode(&mut DocBufReader::new(self.value()))?;
+                        let field_value: u64 = field.decode(&mut DocBufReader::new(data))?;
 
                         return Ok(value.cmp(&field_value) == self.order());
                     }
                     VTableFieldType::U128 => {
-                        let value: u128 = field.decode(&mut self.value().to_vec())?;
-                        let field_value: u128 = field.decode(&mut data.to_vec())?;
+                        let value: u128 = field.decode(&mut DocBufReader::new(self.value()))?;
+                        let field_value: u128 = field.decode(&mut DocBufReader::new(data))?;
 
                         return Ok(value.cmp(&field_value) == self.order());
                     }
                     VTableFieldType::USIZE => {
-                        let value: usize = field.decode(&mut self.value().to_vec())?;
-                        let field_value: usize = field.decode(&mut data.to_vec())?;
+                        let value: usize = field.decode(&mut DocBufReader::new(self.value()))?;
+                        let field_value: usize = field.decode(&mut DocBufReader::new(data))?;
 
                         return Ok(value.cmp(&field_value) == self.order());
                     }
                     VTableFieldType::F32 => {
-                        let value: f32 = field.decode(&mut self.value().to_vec())?;
-                        let field_value: f32 = field.decode(&mut data.to_vec())?;
+                        let value: f32 = field.decode(&mut DocBufReader::new(self.value()))?;
+                        let field_value: f32 = field.decode(&mut DocBufReader::new(data))?;
 
                         return Ok(value.partial_cmp(&field_value) == Some(self.order()));
                     }
                     VTableFieldType::F64 => {
-                        let value: f64 = field.decode(&mut self.value().to_vec())?;
-                        let field_value: f64 = field.decode(&mut data.to_vec())?;
+                        let value: f64 = field.decode(&mut DocBufReader::new(self.value()))?;
+                        let field_value: f64 = field.decode(&mut DocBufReader::new(data))?;
 
                         return Ok(value.partial_cmp(&field_value) == Some(self.order()));
                     }
+                    VTableFieldType::Char => {
+                        let value: char = field.decode(&mut DocBufReader::new(self.value()))?;
+                        let field_value: char = field.decode(&mut DocBufReader::new(data))?;
+
+                        return Ok(value.cmp(&field_value) == self.order());
+                    }
                     VTableFieldType::String
                     | VTableFieldType::Str
                     | VTableFieldType::Bytes
@@ -241,7 +250,11 @@ impl Predicate {
                     | VTableFieldType::Bool
                     | VTableFieldType::HashMap { .. }
                     | VTableFieldType::Uuid
-                    | VTableFieldType::Struct(_) => {
+                    | VTableFieldType::Unit
+                    | VTableFieldType::Array { .. }
+                    | VTableFieldType::Tuple(_)
+                    | VTableFieldType::Struct(_)
+                    | VTableFieldType::Enum(_) => {
                         return Ok(self.value().cmp(&data) == self.order());
                     }
                 }
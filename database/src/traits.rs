@@ -1,6 +1,7 @@
 use crate::{Error, PartitionKey};
 
 use docbuf_core::{traits::*, vtable::VTableId};
+use serde::de::DeserializeOwned;
 
 /// DocBufDb is a trait used to interact with the DocBufDataBase for DocBuf documents.
 pub trait DocBufDbMngr {
@@ -23,20 +24,26 @@ pub trait DocBufDbMngr {
         &self,
         predicate: Self::Predicate,
         partition_key: Option<PartitionKey>,
-    ) -> Result<impl Iterator<Item = D::Doc>, Error>;
+    ) -> Result<impl Iterator<Item = D::Doc>, Error>
+    where
+        D::Doc: DeserializeOwned;
 
     /// Get a document from the database.
     fn get<D: DocBuf>(
         &self,
         id: D::DocId,
         partition_key: Option<PartitionKey>,
-    ) -> Result<Option<<D as DocBuf>::Doc>, Error>;
+    ) -> Result<Option<<D as DocBuf>::Doc>, Error>
+    where
+        D::Doc: DeserializeOwned;
 
     /// Update a document in the database.
     fn update<D: DocBuf>(&self, doc: &D, partition_key: PartitionKey) -> Result<(), Error>;
 
     /// Delete a document from the database.
-    fn delete<D: DocBuf>(&self, doc: D, partition_key: PartitionKey) -> Result<D::Doc, Error>;
+    fn delete<D: DocBuf>(&self, doc: D, partition_key: PartitionKey) -> Result<D::Doc, Error>
+    where
+        D::Doc: DeserializeOwned;
 
     /// Return the number of documents in the database.
     fn count<D: DocBuf>(
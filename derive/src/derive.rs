@@ -4,7 +4,7 @@ use docbuf_core::vtable::*;
 
 use proc_macro2::{token_stream, Ident, Span, TokenStream, TokenTree};
 use quote::{quote, ToTokens};
-use syn::{DeriveInput, ItemStruct};
+use syn::{DeriveInput, ItemEnum, ItemStruct};
 
 pub const DEFAULT_NAMESPACE: &str = "default";
 
@@ -14,27 +14,101 @@ pub enum Error {}
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub enum DocBufCryptoAlgorithm {
     Ed25519,
+    // Requires the `p256` feature of docbuf-core
+    EcdsaP256,
+    // Requires the `k256` feature of docbuf-core
+    EcdsaSecp256k1,
 }
 
 impl From<&str> for DocBufCryptoAlgorithm {
     fn from(algo: &str) -> Self {
         match algo {
             "ed25519" => DocBufCryptoAlgorithm::Ed25519,
-            _ => unimplemented!("Unsupported crypto algorithm: {}", algo),
+            "p256" => DocBufCryptoAlgorithm::EcdsaP256,
+            "secp256k1" => DocBufCryptoAlgorithm::EcdsaSecp256k1,
+            _ => panic!(
+                "Unsupported crypto algorithm: {}; expected one of: ed25519, p256, secp256k1",
+                algo
+            ),
         }
     }
 }
 
+impl ToTokens for DocBufCryptoAlgorithm {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match self {
+            DocBufCryptoAlgorithm::Ed25519 => quote! {
+                ::docbuf_core::crypto::SignatureAlgorithm::Ed25519
+            },
+            DocBufCryptoAlgorithm::EcdsaP256 => quote! {
+                ::docbuf_core::crypto::SignatureAlgorithm::EcdsaP256
+            },
+            DocBufCryptoAlgorithm::EcdsaSecp256k1 => quote! {
+                ::docbuf_core::crypto::SignatureAlgorithm::EcdsaSecp256k1
+            },
+        });
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub enum HashAlgorithm {
     Sha256,
+    Sha512,
+    // Requires the `sha3` feature of docbuf-core
+    Sha3_256,
+    // Requires the `blake3` feature of docbuf-core
+    Blake3,
 }
 
 impl From<&str> for HashAlgorithm {
     fn from(algo: &str) -> Self {
         match algo {
             "sha256" => HashAlgorithm::Sha256,
-            _ => unimplemented!("Unsupported hash algorithm: {}", algo),
+            "sha512" => HashAlgorithm::Sha512,
+            "sha3-256" => HashAlgorithm::Sha3_256,
+            "blake3" => HashAlgorithm::Blake3,
+            _ => panic!(
+                "Unsupported hash algorithm: {}; expected one of: sha256, sha512, sha3-256, blake3",
+                algo
+            ),
+        }
+    }
+}
+
+impl ToTokens for HashAlgorithm {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match self {
+            HashAlgorithm::Sha256 => quote! {
+                ::docbuf_core::crypto::HashAlgorithm::Sha256
+            },
+            HashAlgorithm::Sha512 => quote! {
+                ::docbuf_core::crypto::HashAlgorithm::Sha512
+            },
+            HashAlgorithm::Sha3_256 => quote! {
+                ::docbuf_core::crypto::HashAlgorithm::Sha3_256
+            },
+            HashAlgorithm::Blake3 => quote! {
+                ::docbuf_core::crypto::HashAlgorithm::Blake3
+            },
+        });
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum DocBufEncoding {
+    Fixed,
+    Compact,
+}
+
+impl From<&str> for DocBufEncoding {
+    fn from(encoding: &str) -> Self {
+        match encoding {
+            "fixed" => DocBufEncoding::Fixed,
+            "compact" => DocBufEncoding::Compact,
+            _ => panic!(
+                "Unsupported encoding: {}; expected one of: fixed, compact",
+                encoding
+            ),
         }
     }
 }
@@ -53,6 +127,8 @@ pub enum DocBufOpt {
     UseUuid(bool),
     UseDb(bool),
     DbConfig(DbConfigPath),
+    Encoding(DocBufEncoding),
+    ValidateWith(String),
 }
 
 #[derive(Debug, Clone)]
@@ -69,6 +145,30 @@ impl DocBufOpts {
             .unwrap_or(DEFAULT_NAMESPACE)
     }
 
+    /// Returns the vtable encoding of the document buffer, defaulting to `fixed`.
+    pub fn encoding(&self) -> TokenStream {
+        let encoding = self.0.iter().find_map(|opt| match opt {
+            DocBufOpt::Encoding(encoding) => Some(encoding),
+            _ => None,
+        });
+
+        match encoding {
+            Some(DocBufEncoding::Compact) => quote! { ::docbuf_core::vtable::VTableEncoding::Compact },
+            _ => quote! { ::docbuf_core::vtable::VTableEncoding::Fixed },
+        }
+    }
+
+    /// Returns the path of the document level `validate_with` function, if set.
+    pub fn validate_with(&self) -> Option<syn::Path> {
+        self.0.iter().find_map(|opt| match opt {
+            DocBufOpt::ValidateWith(path) => Some(
+                syn::parse_str(path)
+                    .unwrap_or_else(|_| panic!("Invalid validate_with path: {}", path)),
+            ),
+            _ => None,
+        })
+    }
+
     /// Returns true if the `uuid` option is set to true,
     /// or if the `db_config` option is set.
     /// Otherwise, returns false.
@@ -122,6 +222,8 @@ where
             ("html", template) => DocBufOpt::Html(template.to_string()),
             ("uuid", v) => DocBufOpt::UseUuid(v == "true"),
             ("db", v) => DocBufOpt::UseDb(v == "true"),
+            ("encoding", encoding) => DocBufOpt::Encoding(DocBufEncoding::from(encoding)),
+            ("validate_with", path) => DocBufOpt::ValidateWith(path.to_string()),
             ("db_config", path) => {
                 // unimplemented!("Db Config Path: {:?}", path);
                 DocBufOpt::DbConfig(path.to_string())
@@ -181,17 +283,41 @@ pub fn docbuf_item(
     options: &DocBufOpts,
     item: &TokenStream,
 ) -> TokenStream {
+    let derivatives = parse_item_derivatives(item);
+
+    if is_enum_item(item) {
+        let variants = parse_item_variants(item);
+
+        return quote! {
+            #derivatives
+            pub enum #name #lifetimes {
+                #variants
+            }
+        };
+    }
+
     let fields = parse_item_fields(item, options);
 
     // panic!("Fields: {:?}", fields.to_string());
 
-    let derivatives = parse_item_derivatives(item);
-
-    let output = quote! {
-        #derivatives
-        pub struct #name #lifetimes {
-            #fields
-        }
+    // Retain the shape of tuple, newtype and unit structs
+    let output = match parse_item_struct_fields(item) {
+        syn::Fields::Named(_) => quote! {
+            #derivatives
+            pub struct #name #lifetimes {
+                #fields
+            }
+        },
+        syn::Fields::Unnamed(_) => quote! {
+            #derivatives
+            pub struct #name #lifetimes (
+                #fields
+            );
+        },
+        syn::Fields::Unit => quote! {
+            #derivatives
+            pub struct #name #lifetimes;
+        },
     };
 
     TokenStream::from(output)
@@ -204,7 +330,8 @@ pub fn docbuf_impl(
     item: &TokenStream,
 ) -> TokenStream {
     let serialization_methods = docbuf_impl_serialization();
-    let uuid_methods = docbuf_impl_uuid(options);
+    let uuid_methods = docbuf_impl_uuid(options, item);
+    let validate_with = docbuf_impl_validate_with(options, item);
     let vtable = docbuf_impl_vtable(name, options, item);
 
     let output = quote! {
@@ -219,6 +346,9 @@ pub fn docbuf_impl(
             // UUID Methods
             #uuid_methods
 
+            // Validation hooks
+            #validate_with
+
             // VTable
             #vtable
         }
@@ -238,8 +368,21 @@ pub fn docbuf_impl_crypto(
 
     // Check if the sign option is present
     if options.0.contains(&DocBufOpt::Sign(true)) {
+        // The algorithms of signed document buffers, if not the defaults
+        let algorithms = options.0.iter().filter_map(|option| match option {
+            DocBufOpt::Crypto(algorithm) => Some(quote! {
+                const SIGNATURE_ALGORITHM: ::docbuf_core::crypto::SignatureAlgorithm = #algorithm;
+            }),
+            DocBufOpt::Hash(algorithm) => Some(quote! {
+                const HASH_ALGORITHM: ::docbuf_core::crypto::HashAlgorithm = #algorithm;
+            }),
+            _ => None,
+        });
+
         output.push(quote! {
-            impl #lifetimes ::docbuf_core::traits::DocBufCrypto for #name #lifetimes {}
+            impl #lifetimes ::docbuf_core::traits::DocBufCrypto for #name #lifetimes {
+                #(#algorithms)*
+            }
         });
     }
 
@@ -403,64 +546,66 @@ pub fn docbuf_impl_vtable(
     item: &TokenStream,
 ) -> TokenStream {
     let namespace = options.namespace();
+    let encoding = options.encoding();
+
+    let ast: DeriveInput = syn::parse(item.to_owned().into()).expect("Failed to parse item");
+
+    let vtable_item = match ast.data {
+        syn::Data::Enum(data) => docbuf_impl_vtable_enum(name, &data),
+        syn::Data::Struct(data) => docbuf_impl_vtable_struct(
+            name,
+            options,
+            &data.fields,
+            has_serde_default(&ast.attrs),
+        ),
+        syn::Data::Union(_) => unimplemented!("DocBuf does not support unions"),
+    };
 
-    let ast: ItemStruct = syn::parse(item.to_owned().into()).expect("Failed to parse item");
-
-    let fields = ast.fields.into_iter().collect::<Vec<_>>();
-
-    let fields = fields.iter().map(|field| {
-        let field_name = field.ident.as_ref().map(|f| f.to_owned()).unwrap_or(Ident::new("__inner__", Span::call_site()));
-        let ty = field.ty.to_token_stream();
-        let rules = parse_field_rules(&field).expect("Failed to parse field rules");
+    let vtable = quote! {
+        fn vtable() -> Result<&'static ::docbuf_core::vtable::VTable, ::docbuf_core::error::Error> {
+            static VTABLE: ::std::sync::OnceLock<::docbuf_core::vtable::VTable> = ::std::sync::OnceLock::new();
 
-        match VTableFieldType::is_struct(ty.to_string().as_ref()) {
-            Some(name) => {
-                // panic!("Found struct name: {name:?}");
-                let table_name = format!("{}_vtable", name).to_lowercase();
-                let table_name_var = Ident::new(&table_name, Span::call_site());
+            if let Some(vtable) = VTABLE.get() {
+                return Ok(vtable);
+            }
 
-                let struct_name = format!("{}_struct", name).to_lowercase();
-                let struct_name_var = Ident::new(&struct_name, Span::call_site());
+            let mut vtable = ::docbuf_core::vtable::VTable::new(String::from(#namespace), String::from(stringify!(#name)));
 
-                let stype = Ident::new(&name, Span::call_site());
+            // Set the encoding before adding items, so all fields share the document encoding
+            vtable.set_encoding(#encoding);
 
-                let scope = quote! {
-                    // Lookup the vtable for the struct
-                    let #table_name_var = #stype::vtable().expect("Failed to lookup vtable for struct");
+            #vtable_item
 
-                    for vtable_item in #table_name_var.items.iter() {
-                        match vtable_item {
-                            ::docbuf_core::vtable::VTableItem::Struct(#struct_name_var) => {
-                                let name = #struct_name_var.name.clone();
-                                if name == stringify!(#stype) {
-                                    let field_type = ::docbuf_core::vtable::VTableFieldType::from(stringify!(#ty));
+            // Another thread may have built the vtable first, both are the same
+            Ok(VTABLE.get_or_init(|| vtable))
+        }
+    };
 
-                                    // Add the field rules to the vtable field
-                                    #rules
-                                    vtable_struct.add_field(field_type, stringify!(#field_name), field_rules);
-                                }
-                            }
-                            _ => {
-                                unimplemented!("Unimeplemented vtable item type");
-                            }
-                        }
-                    }
+    vtable
+}
 
-                    // Merge the vtable with the input vtable
-                    vtable.merge_vtable(#table_name_var);
-                };
+// Construct the vtable struct item for the input struct. All fields have a default
+// value if the struct has the `#[serde(default)]` attribute.
+pub fn docbuf_impl_vtable_struct(
+    name: &TokenStream,
+    options: &DocBufOpts,
+    fields: &syn::Fields,
+    default: bool,
+) -> TokenStream {
+    let set_default = default.then(|| quote! { field_rules.default = true; });
 
-                scope
-            },
-            None => {
-                quote! {
-                    // Add the field rules to the vtable field
-                    #rules
+    let fields = fields.iter().enumerate().map(|(index, field)| {
+        // Tuple and newtype struct fields are named by their position
+        let field_name = field
+            .ident
+            .as_ref()
+            .map(|ident| ident.to_string())
+            .unwrap_or(index.to_string());
 
-                    vtable_struct.add_field(stringify!(#ty), stringify!(#field_name), field_rules);
-                }
-            }
-        }
+        docbuf_impl_vtable_field(field, quote! {
+            #set_default
+            vtable_struct.add_field(field_type, #field_name, field_rules);
+        })
     });
 
     // Add the `_uuid` field to the vtable if the option is enabled
@@ -473,45 +618,247 @@ pub fn docbuf_impl_vtable(
         false => quote! {},
     };
 
-    let vtable = quote! {
-        fn vtable() -> Result<&'static ::docbuf_core::vtable::VTable, ::docbuf_core::error::Error> {
-            static VTABLE: ::std::sync::OnceLock<::docbuf_core::vtable::VTable> = ::std::sync::OnceLock::new();
+    quote! {
+        let mut vtable_struct = ::docbuf_core::vtable::VTableStruct::new(stringify!(#name), None);
 
-            let vtable = VTABLE.get_or_init(||  {
-                let mut vtable = ::docbuf_core::vtable::VTable::new(String::from(#namespace), String::from(stringify!(#name)));
+        // Add the _uuid field to the vtable
+        #uuid
 
-                let mut vtable_struct = ::docbuf_core::vtable::VTableStruct::new(stringify!(#name), None);
+        // Add the fields to the vtable
+        #(#fields)*
 
-                // Add the _uuid field to the vtable
-                #uuid
+        // Sorting is required to ensure the structs are added in a consistent order
+        vtable.items_mut().inner_mut().sort_by(|a, b| a.name().cmp(b.name()).then(a.item_index().cmp(&b.item_index())));
 
-                // Add the fields to the vtable
-                #(#fields)*
+        // Create a vtable_struct for the input struct
+        vtable.add_struct(vtable_struct);
+    }
+}
 
-                // Sorting is required to ensure the structs are added in a consistent order
-                vtable.items.inner_mut().sort_by(|a, b| match (a, b) {
-                    (::docbuf_core::vtable::VTableItem::Struct(a), ::docbuf_core::vtable::VTableItem::Struct(b)) => a.name
-                        .cmp(&b.name)
-                        .then(a.item_index.cmp(&b.item_index)),
-                    _ => std::cmp::Ordering::Equal,
-                });
+// Construct the vtable enum item for the input enum
+pub fn docbuf_impl_vtable_enum(name: &TokenStream, data: &syn::DataEnum) -> TokenStream {
+    let variants = data.variants.iter().map(|variant| {
+        let variant_name = &variant.ident;
 
-                // Create a vtable_struct for the input struct
-                vtable.add_struct(vtable_struct);
+        let kind = match &variant.fields {
+            syn::Fields::Unit => quote! { Unit },
+            syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => quote! { Newtype },
+            syn::Fields::Unnamed(_) => quote! { Tuple },
+            syn::Fields::Named(_) => quote! { Struct },
+        };
 
-                vtable
-            });
+        let fields = variant.fields.iter().enumerate().map(|(index, field)| {
+            // Tuple variant fields are named by their position
+            let field_name = field
+                .ident
+                .as_ref()
+                .map(|ident| ident.to_string())
+                .unwrap_or(index.to_string());
 
-            Ok(vtable)
+            docbuf_impl_vtable_field(field, quote! {
+                vtable_enum.add_variant_field(variant_index, field_type, #field_name, field_rules)?;
+            })
+        });
+
+        quote! {
+            let variant_index = vtable_enum.add_variant(
+                stringify!(#variant_name),
+                ::docbuf_core::vtable::VTableVariantKind::#kind,
+            )?;
+
+            #(#fields)*
         }
+    });
+
+    quote! {
+        let mut vtable_enum = ::docbuf_core::vtable::VTableEnum::new(stringify!(#name), None);
+
+        // Add the variants to the vtable, in declaration order
+        #(#variants)*
+
+        // Sorting is required to ensure the items are added in a consistent order
+        vtable.items_mut().inner_mut().sort_by(|a, b| a.name().cmp(b.name()).then(a.item_index().cmp(&b.item_index())));
+
+        // Create a vtable_enum for the input enum
+        vtable.add_enum(vtable_enum);
+    }
+}
+
+// Construct the vtable field for a struct field or enum variant field, merging the vtables
+// of nested docbuf types. The `add_field` statement adds the `field_type` and `field_rules`.
+pub fn docbuf_impl_vtable_field(field: &syn::Field, add_field: TokenStream) -> TokenStream {
+    let rules = parse_field_rules(field).expect("Failed to parse field rules");
+
+    let (r#type, field_type) = match parse_field_type(&field.ty) {
+        Ok(field_type) => field_type,
+        Err(error) => return error.to_compile_error(),
     };
 
-    vtable
+    match VTableFieldType::is_struct(r#type) {
+        Some(name) => {
+            let table_name = format!("{}_vtable", name).to_lowercase();
+            let table_name_var = Ident::new(&table_name, Span::call_site());
+
+            let stype = Ident::new(&name, Span::call_site());
+
+            quote! {
+                {
+                    // Lookup the vtable for the struct or enum
+                    let #table_name_var = #stype::vtable()?;
+
+                    // Resolve enum types from the nested vtable
+                    let field_type = #table_name_var.resolve_field_type(#field_type);
+
+                    // Add the field rules to the vtable field
+                    #rules
+                    #add_field
+
+                    // Merge the vtable with the input vtable
+                    vtable.merge_vtable(#table_name_var);
+                }
+            }
+        }
+        None => {
+            quote! {
+                {
+                    let field_type = #field_type;
+
+                    // Add the field rules to the vtable field
+                    #rules
+                    #add_field
+                }
+            }
+        }
+    }
+}
+
+// Parse the vtable field type of a field, returning the type and the expression constructing
+// it in the generated code. Fixed size arrays are constructed from their length expression, as
+// const lengths, e.g. `[u8; HASH_LEN]`, are only known to the compiler. The length of the
+// returned array type is zero for const lengths.
+pub fn parse_field_type(ty: &syn::Type) -> syn::Result<(VTableFieldType, TokenStream)> {
+    match ty {
+        syn::Type::Array(array) => {
+            let (elem, elem_type) = parse_field_type(&array.elem)?;
+            let len_expr = &array.len;
+
+            let len = match len_expr {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(len),
+                    ..
+                }) => len.base10_parse()?,
+                _ => 0,
+            };
+
+            Ok((
+                VTableFieldType::Array {
+                    elem: Box::new(elem),
+                    len,
+                },
+                quote! {
+                    ::docbuf_core::vtable::VTableFieldType::Array {
+                        elem: Box::new(#elem_type),
+                        len: #len_expr,
+                    }
+                },
+            ))
+        }
+        syn::Type::Paren(paren) => parse_field_type(&paren.elem),
+        syn::Type::Group(group) => parse_field_type(&group.elem),
+        syn::Type::Tuple(tuple) if contains_array_type(ty) => {
+            let (types, field_types): (Vec<_>, Vec<_>) = tuple
+                .elems
+                .iter()
+                .map(parse_field_type)
+                .collect::<syn::Result<Vec<_>>>()?
+                .into_iter()
+                .unzip();
+
+            Ok((
+                VTableFieldType::Tuple(types),
+                quote! {
+                    ::docbuf_core::vtable::VTableFieldType::Tuple(vec![#(#field_types),*])
+                },
+            ))
+        }
+        syn::Type::Path(path) if contains_array_type(ty) => {
+            let segment = path.path.segments.last();
+
+            let inner = segment.and_then(|segment| match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+                    match args.args.first() {
+                        Some(syn::GenericArgument::Type(inner)) => Some(inner),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            });
+
+            match (segment.map(|segment| segment.ident.to_string()), inner) {
+                (Some(ident), Some(inner)) if ident == "Option" => {
+                    let (inner, inner_type) = parse_field_type(inner)?;
+
+                    Ok((
+                        VTableFieldType::Option(Box::new(inner)),
+                        quote! {
+                            ::docbuf_core::vtable::VTableFieldType::Option(Box::new(#inner_type))
+                        },
+                    ))
+                }
+                (Some(ident), Some(inner)) if ident == "Vec" => {
+                    let (inner, inner_type) = parse_field_type(inner)?;
+
+                    Ok((
+                        VTableFieldType::Vec(Box::new(inner)),
+                        quote! {
+                            ::docbuf_core::vtable::VTableFieldType::Vec(Box::new(#inner_type))
+                        },
+                    ))
+                }
+                _ => Err(syn::Error::new_spanned(
+                    ty,
+                    "Unsupported docbuf field type: fixed size arrays are supported in tuples, `Option` and `Vec`",
+                )),
+            }
+        }
+        _ => {
+            let name = ty.to_token_stream().to_string();
+
+            let r#type = VTableFieldType::try_from(name.as_str())
+                .map_err(|error| syn::Error::new_spanned(ty, error))?;
+
+            Ok((
+                r#type,
+                quote! {
+                    ::docbuf_core::vtable::VTableFieldType::try_from(stringify!(#ty))?
+                },
+            ))
+        }
+    }
+}
+
+// Returns true if the type is or contains a fixed size array, e.g. `Option<[u8; 32]>`
+pub fn contains_array_type(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Array(_) => true,
+        syn::Type::Paren(paren) => contains_array_type(&paren.elem),
+        syn::Type::Group(group) => contains_array_type(&group.elem),
+        syn::Type::Tuple(tuple) => tuple.elems.iter().any(contains_array_type),
+        syn::Type::Path(path) => path.path.segments.iter().any(|segment| {
+            match &segment.arguments {
+                syn::PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| {
+                    matches!(arg, syn::GenericArgument::Type(inner) if contains_array_type(inner))
+                }),
+                _ => false,
+            }
+        }),
+        _ => false,
+    }
 }
 
 // Impl docbuf return uuid
-pub fn docbuf_impl_uuid(options: &DocBufOpts) -> TokenStream {
-    if options.uuid() {
+pub fn docbuf_impl_uuid(options: &DocBufOpts, item: &TokenStream) -> TokenStream {
+    if options.uuid() && !is_enum_item(item) {
         let output = quote! {
             fn uuid(&self) -> Result<Self::DocId, ::docbuf_core::error::Error> {
                 Ok(self._uuid)
@@ -524,19 +871,144 @@ pub fn docbuf_impl_uuid(options: &DocBufOpts) -> TokenStream {
     }
 }
 
+// Impl the docbuf validation hooks, running the `validate_with` functions of the
+// fields, the hooks of nested documents, and the `validate_with` function of the document.
+pub fn docbuf_impl_validate_with(options: &DocBufOpts, item: &TokenStream) -> TokenStream {
+    let ast: DeriveInput = syn::parse(item.to_owned().into()).expect("Failed to parse item");
+
+    let fields = match &ast.data {
+        syn::Data::Struct(data) => data
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                // Tuple and newtype struct fields are accessed by their position
+                let member = match &field.ident {
+                    Some(ident) => quote! { #ident },
+                    None => {
+                        let index = syn::Index::from(index);
+                        quote! { #index }
+                    }
+                };
+
+                let field_name = field
+                    .ident
+                    .as_ref()
+                    .map(|ident| ident.to_string())
+                    .unwrap_or(index.to_string());
+
+                let nested = docbuf_impl_validate_with_nested(&field.ty, &member);
+
+                let hook = parse_field_validate_with(field).map(|path| {
+                    quote! {
+                        #path(&self.#member).map_err(|error| {
+                            ::docbuf_core::vtable::Error::FieldValidation {
+                                field: String::from(#field_name),
+                                message: error.to_string(),
+                            }
+                        })?;
+                    }
+                });
+
+                quote! {
+                    #nested
+                    #hook
+                }
+            })
+            .collect::<Vec<_>>(),
+        _ => Vec::new(),
+    };
+
+    let document = options.validate_with().map(|path| {
+        quote! {
+            #path(self).map_err(|error| {
+                ::docbuf_core::vtable::Error::DocumentValidation(error.to_string())
+            })?;
+        }
+    });
+
+    if fields.is_empty() && document.is_none() {
+        return TokenStream::new();
+    }
+
+    quote! {
+        fn validate_with(&self) -> Result<(), ::docbuf_core::error::Error> {
+            #(#fields)*
+            #document
+
+            Ok(())
+        }
+    }
+}
+
+// Run the validation hooks of the nested documents of a field, i.e. a nested document,
+// or an optional, list or map of nested documents.
+pub fn docbuf_impl_validate_with_nested(ty: &syn::Type, member: &TokenStream) -> TokenStream {
+    let validate = quote! {
+        ::docbuf_core::traits::DocBuf::validate_with(value)?;
+    };
+
+    // Invalid field types are reported by the vtable
+    let r#type = match parse_field_type(ty) {
+        Ok((r#type, _)) => r#type,
+        Err(_) => return TokenStream::new(),
+    };
+
+    match r#type {
+        VTableFieldType::Struct(_) => quote! {
+            {
+                let value = &self.#member;
+                #validate
+            }
+        },
+        VTableFieldType::Option(inner) if matches!(*inner, VTableFieldType::Struct(_)) => quote! {
+            if let Some(value) = &self.#member {
+                #validate
+            }
+        },
+        VTableFieldType::Vec(elem) | VTableFieldType::Array { elem, .. }
+            if matches!(*elem, VTableFieldType::Struct(_)) =>
+        {
+            quote! {
+                for value in self.#member.iter() {
+                    #validate
+                }
+            }
+        }
+        VTableFieldType::HashMap { value, .. } if matches!(*value, VTableFieldType::Struct(_)) => {
+            quote! {
+                for value in self.#member.values() {
+                    #validate
+                }
+            }
+        }
+        _ => TokenStream::new(),
+    }
+}
+
 // Impl docbuf serialization and deserialization for the input struct
 pub fn docbuf_impl_serialization() -> TokenStream {
     let output = quote! {
         // Serialize the struct to a byte buffer
-        fn to_docbuf<'a>(&self, buffer: &'a mut Vec<u8>) -> Result<::docbuf_core::vtable::VTableFieldOffsets, ::docbuf_core::error::Error> {
+        fn to_docbuf(&self, buffer: &mut Vec<u8>) -> Result<::docbuf_core::vtable::VTableFieldOffsets, ::docbuf_core::error::Error> {
             let offsets = ::docbuf_core::serde::ser::to_docbuf(self, buffer)?;
 
+            // Run the validation hooks once the field rules have passed
+            ::docbuf_core::traits::DocBuf::validate_with(self)?;
+
             Ok(offsets)
         }
 
         // Deserialize the byte buffer to a struct
-        fn from_docbuf<'a>(buf: &'a mut Vec<u8>) -> Result<Self, ::docbuf_core::error::Error> {
-            Ok(::docbuf_core::serde::de::from_docbuf(buf)?)
+        fn from_docbuf<'de>(buf: &'de [u8]) -> Result<Self, ::docbuf_core::error::Error>
+        where
+            Self::Doc: ::docbuf_core::deps::serde::Deserialize<'de>,
+        {
+            let doc: Self = ::docbuf_core::serde::de::from_docbuf(buf)?;
+
+            ::docbuf_core::traits::DocBuf::validate_with(&doc)?;
+
+            Ok(doc)
         }
     };
 
@@ -598,15 +1070,43 @@ pub fn parse_item_derivatives(input: &TokenStream) -> TokenStream {
     }
 }
 
+// Returns true if the input item is an enum
+pub fn is_enum_item(input: &TokenStream) -> bool {
+    let ast: DeriveInput = syn::parse(input.to_owned().into()).expect("Failed to parse input");
+    matches!(ast.data, syn::Data::Enum(_))
+}
+
+// Parse the enum variants from the input stream, stripping the docbuf field attributes.
+pub fn parse_item_variants(item: &TokenStream) -> TokenStream {
+    let mut ast: ItemEnum =
+        syn::parse(item.to_owned().into()).expect("Failed to parse item variants.");
+
+    for variant in ast.variants.iter_mut() {
+        variant.attrs.retain(|attr| !attr.path().is_ident("docbuf"));
+
+        for field in variant.fields.iter_mut() {
+            field.attrs.retain(|attr| !attr.path().is_ident("docbuf"));
+        }
+    }
+
+    ast.variants.to_token_stream()
+}
+
+// Parse the struct fields from the input stream
+pub fn parse_item_struct_fields(item: &TokenStream) -> syn::Fields {
+    let ast: ItemStruct = syn::parse(item.to_owned().into()).expect("Failed to parse item fields.");
+    ast.fields
+}
+
 // Parse the item fields from the input stream
 pub fn parse_item_fields(item: &TokenStream, options: &DocBufOpts) -> TokenStream {
     let ast: ItemStruct = syn::parse(item.to_owned().into()).expect("Failed to parse item fields.");
+
+    if options.uuid() && !matches!(ast.fields, syn::Fields::Named(_)) {
+        panic!("The DocBuf uuid and db options require a struct with named fields");
+    }
+
     let fields = ast.fields.iter().map(|field| {
-        let name = field
-            .ident
-            .as_ref()
-            .map(|f| f.to_owned())
-            .unwrap_or(Ident::new("__inner__", Span::call_site()));
         let ty = field.ty.to_token_stream();
         let vis = &field.vis;
 
@@ -626,9 +1126,15 @@ pub fn parse_item_fields(item: &TokenStream, options: &DocBufOpts) -> TokenStrea
 
         // TODO: Parse comments from the field attributes
 
-        quote! {
-            #(#attr)*
-            #vis #name: #ty
+        match &field.ident {
+            Some(name) => quote! {
+                #(#attr)*
+                #vis #name: #ty
+            },
+            None => quote! {
+                #(#attr)*
+                #vis #ty
+            },
         }
     });
 
@@ -652,7 +1158,7 @@ pub fn parse_item_fields(item: &TokenStream, options: &DocBufOpts) -> TokenStrea
 }
 
 pub fn parse_item_lifetimes(input: &TokenStream) -> TokenStream {
-    let ast: ItemStruct = syn::parse(input.to_owned().into()).unwrap();
+    let ast: DeriveInput = syn::parse(input.to_owned().into()).unwrap();
 
     let lifetimes = ast.generics.lifetimes();
     let count = lifetimes.count();
@@ -688,15 +1194,45 @@ pub fn parse_field_rules(input: &syn::Field) -> Result<TokenStream, Error> {
             let rules = attributes
                 .iter()
                 .filter_map(|(_, (key, value))| match key.to_string().as_str() {
-                    "sign" | "ignore" => Some(quote! {
+                    "sign" | "ignore" | "not_empty" => Some(quote! {
                         field_rules.#key = #value;
                     }),
                     "min_value" | "max_value" => Some(quote! {
                         field_rules.#key = Some((#value as #field_type).into());
                     }),
-                    "min_length" | "max_length" | "length" => Some(quote! {
-                        field_rules.#key = Some(#value);
+                    "min_length" | "max_length" | "length" | "total_digits" | "fraction_digits" => {
+                        Some(quote! {
+                            field_rules.#key = Some(#value);
+                        })
+                    }
+                    // String and boolean values are compared as strings, numeric
+                    // values as the numeric type of the field.
+                    "one_of" if value.to_string().contains('"') => Some(quote! {
+                        field_rules.one_of = Some(
+                            #value.iter().map(|value| value.to_string()).collect()
+                        );
+                    }),
+                    "one_of" => Some(quote! {
+                        field_rules.one_of_values = Some(
+                            #value.iter().map(|value| (*value as #field_type).into()).collect()
+                        );
                     }),
+                    "format" => {
+                        let format = parse_field_format(value)
+                            .unwrap_or_else(|error| error.to_compile_error());
+
+                        Some(quote! {
+                            field_rules.format = Some(#format);
+                        })
+                    }
+                    "whitespace" => {
+                        let whitespace = parse_field_whitespace(value)
+                            .unwrap_or_else(|error| error.to_compile_error());
+
+                        Some(quote! {
+                            field_rules.whitespace = Some(#whitespace);
+                        })
+                    }
                     #[cfg(feature = "regex")]
                     "regex" => Some(quote! {
                         field_rules.#key = Some(
@@ -715,15 +1251,111 @@ pub fn parse_field_rules(input: &syn::Field) -> Result<TokenStream, Error> {
         })
         .collect::<Result<Vec<TokenStream>, Error>>()?;
 
+    // Record the serde default of the field, for the compatibility of vtables
+    let default = has_serde_default(&input.attrs).then(|| {
+        quote! {
+            field_rules.default = true;
+        }
+    });
+
     let rules = quote!(
         let mut field_rules = ::docbuf_core::vtable::VTableFieldRules::new();
 
         #(#fields)*
+        #default
     );
 
     Ok(rules)
 }
 
+/// Returns true if the attributes include `#[serde(default)]` or
+/// `#[serde(default = "path")]`.
+pub fn has_serde_default(attrs: &[syn::Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("serde"))
+        .any(|attr| {
+            let mut default = false;
+
+            // Errors are left to serde, the attribute is only searched for `default`
+            let _ = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    default = true;
+                }
+
+                // Skip the values of other attributes, e.g. `rename = "name"`
+                if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<syn::Expr>()?;
+                } else if meta.input.peek(syn::token::Paren) {
+                    let _nested;
+                    syn::parenthesized!(_nested in meta.input);
+                }
+
+                Ok(())
+            });
+
+            default
+        })
+}
+
+/// Return the path of the field level `validate_with` function, if set.
+pub fn parse_field_validate_with(input: &syn::Field) -> Option<syn::Path> {
+    input.attrs.iter().find_map(|attr| {
+        let attributes = parse_docbuf_field_attrs(attr.to_token_stream()).ok()?;
+        let (_, value) = attributes.get("validate_with")?;
+
+        let path = value.to_string().trim_matches('"').to_string();
+
+        Some(
+            syn::parse_str(&path)
+                .unwrap_or_else(|_| panic!("Invalid validate_with path: {}", path)),
+        )
+    })
+}
+
+/// Return the `VTableFieldFormat` of a `format` field rule.
+pub fn parse_field_format(value: &TokenTree) -> syn::Result<TokenStream> {
+    let format = match value.to_string().trim_matches('"') {
+        "email" => quote! { ::docbuf_core::vtable::VTableFieldFormat::Email },
+        "uri" => quote! { ::docbuf_core::vtable::VTableFieldFormat::Uri },
+        "uuid" => quote! { ::docbuf_core::vtable::VTableFieldFormat::Uuid },
+        "rfc3339" => quote! { ::docbuf_core::vtable::VTableFieldFormat::Rfc3339 },
+        "ipv4" => quote! { ::docbuf_core::vtable::VTableFieldFormat::Ipv4 },
+        "ipv6" => quote! { ::docbuf_core::vtable::VTableFieldFormat::Ipv6 },
+        format => {
+            return Err(syn::Error::new_spanned(
+                value,
+                format!(
+                    "Unsupported field format: {}; expected one of: email, uri, uuid, rfc3339, ipv4, ipv6",
+                    format
+                ),
+            ))
+        }
+    };
+
+    Ok(format)
+}
+
+/// Return the `VTableFieldWhitespace` of a `whitespace` field rule.
+pub fn parse_field_whitespace(value: &TokenTree) -> syn::Result<TokenStream> {
+    let whitespace = match value.to_string().trim_matches('"') {
+        "preserve" => quote! { ::docbuf_core::vtable::VTableFieldWhitespace::Preserve },
+        "replace" => quote! { ::docbuf_core::vtable::VTableFieldWhitespace::Replace },
+        "collapse" => quote! { ::docbuf_core::vtable::VTableFieldWhitespace::Collapse },
+        whitespace => {
+            return Err(syn::Error::new_spanned(
+                value,
+                format!(
+                    "Unsupported whitespace rule: {}; expected one of: preserve, replace, collapse",
+                    whitespace
+                ),
+            ))
+        }
+    };
+
+    Ok(whitespace)
+}
+
 pub fn parse_docbuf_field_attrs(
     input: TokenStream,
 ) -> Result<HashMap<String, (TokenTree, TokenTree)>, Error> {
@@ -750,7 +1382,8 @@ pub fn parse_docbuf_field_attrs(
                                             key = Some(group_token);
                                         }
                                     },
-                                    TokenTree::Literal(_) => {
+                                    // Literal values and lists of values, e.g. `one_of = ["a", "b"]`
+                                    TokenTree::Literal(_) | TokenTree::Group(_) => {
                                         value = Some(group_token);
                                     }
                                     _ => {}
@@ -350,9 +350,9 @@ pub fn gen_rpc_client(interface: ItemTrait, options: &mut RpcOptions) -> Result<
                         .add_headers(headers)
                         .add_body(buffer);
 
-                    let mut response = client.send(request)?;
+                    let response = client.send(request)?;
 
-                    let doc = #response_type::from_docbuf(&mut response.body)?;
+                    let doc = #response_type::from_docbuf(&response.body)?;
 
                     Ok(doc)
                 }
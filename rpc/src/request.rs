@@ -89,10 +89,13 @@ impl RpcRequest {
         }
     }
 
-    pub fn as_docbuf<Doc: DocBuf>(&mut self) -> Result<<Doc as DocBuf>::Doc, crate::Error> {
+    pub fn as_docbuf<'de, Doc: DocBuf>(&'de self) -> Result<<Doc as DocBuf>::Doc, crate::Error>
+    where
+        <Doc as DocBuf>::Doc: docbuf_core::deps::serde::Deserialize<'de>,
+    {
         Ok(self
             .body
-            .as_mut()
+            .as_deref()
             .map(Doc::from_docbuf)
             .ok_or(crate::Error::MissingRequestBody)??)
     }
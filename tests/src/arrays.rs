@@ -0,0 +1,320 @@
+use crate::{SetTestValues, TestHarness};
+
+use docbuf_core::{
+    error::Error,
+    traits::{DocBuf, DocBufMap},
+    vtable::{self, DocBufReader, VTable, VTableFieldType},
+};
+use docbuf_macros::*;
+use serde::{Deserialize, Serialize};
+
+#[docbuf {
+    namespace = "arrays";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Shape {
+    #[docbuf {
+        length = 32;
+    }]
+    pub hash: [u8; 32],
+    pub coords: [f64; 3],
+    pub pair: (u32, String),
+    pub points: Vec<[i16; 2]>,
+    pub key: Option<[u8; 4]>,
+    pub grid: [[u8; 2]; 2],
+    pub labelled: (u8, Vertex),
+    pub name: String,
+}
+
+#[docbuf {
+    namespace = "arrays";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Vertex {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[docbuf {
+    namespace = "arrays";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Key {
+    pub id: [u8; 4],
+    pub version: (u16, u8),
+}
+
+#[docbuf {
+    namespace = "arrays";
+    encoding = "compact";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Range {
+    pub bounds: [u32; 2],
+    pub step: (u16, u64),
+    pub name: String,
+}
+
+/// An array with a length rule that does not match the array length.
+#[docbuf {
+    namespace = "arrays";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Mismatch {
+    #[docbuf {
+        length = 2;
+    }]
+    pub key: [u8; 4],
+}
+
+pub const HASH_LEN: usize = 32;
+
+/// Arrays with const lengths, known only to the compiler.
+#[docbuf {
+    namespace = "arrays";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Digest {
+    pub hash: [u8; HASH_LEN],
+    pub parts: Option<[u16; HASH_LEN / 16]>,
+    pub vertices: ([Vertex; 2], u8),
+}
+
+impl Shape {
+    pub fn dummy() -> Self {
+        Self {
+            hash: [7; 32],
+            coords: [1.5, -2.0, 3.25],
+            pair: (42, "pair".to_string()),
+            points: vec![[1, -1], [i16::MAX, i16::MIN]],
+            key: Some([1, 2, 3, 4]),
+            grid: [[1, 2], [3, 4]],
+            labelled: (9, Vertex { x: -5, y: 5 }),
+            name: "shape".to_string(),
+        }
+    }
+}
+
+impl SetTestValues for Shape {}
+
+impl<'de> TestHarness<'de> for Shape {}
+
+#[test]
+fn test_arrays_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let mut buffer = Vec::new();
+    Shape::dummy().assert_serialization_round_trip(&mut buffer)?;
+
+    let shape = Shape {
+        points: vec![],
+        key: None,
+        ..Shape::dummy()
+    };
+
+    shape.to_docbuf(&mut buffer)?;
+    assert_eq!(Shape::from_docbuf(&buffer)?, shape);
+
+    Ok(())
+}
+
+#[test]
+fn test_arrays_wire_format() -> Result<(), Error> {
+    let key = Key {
+        id: [1, 2, 3, 4],
+        version: (0x0102, 3),
+    };
+
+    let mut buffer = Vec::new();
+    let offsets = key.to_docbuf(&mut buffer)?;
+
+    // Arrays and tuples are encoded without a length prefix.
+    assert_eq!(buffer, vec![1, 2, 3, 4, 0x02, 0x01, 3]);
+
+    // Each array and tuple is addressed by a single offset.
+    assert_eq!(offsets.len(), 2);
+    assert_eq!(offsets.as_ref()[0].range(), 0..4);
+    assert_eq!(offsets.as_ref()[1].range(), 4..7);
+
+    assert_eq!(Key::from_docbuf(&buffer)?, key);
+
+    Ok(())
+}
+
+#[test]
+fn test_arrays_vtable() -> Result<(), Error> {
+    let vtable = Shape::vtable()?;
+    let shape = vtable.struct_by_name("Shape")?;
+
+    assert_eq!(
+        shape.field_by_name("hash")?.r#type,
+        VTableFieldType::Array {
+            elem: Box::new(VTableFieldType::U8),
+            len: 32,
+        }
+    );
+    assert_eq!(
+        shape.field_by_name("pair")?.r#type,
+        VTableFieldType::Tuple(vec![VTableFieldType::U32, VTableFieldType::String])
+    );
+    assert_eq!(
+        shape.field_by_name("grid")?.r#type,
+        VTableFieldType::Array {
+            elem: Box::new(VTableFieldType::Array {
+                elem: Box::new(VTableFieldType::U8),
+                len: 2,
+            }),
+            len: 2,
+        }
+    );
+    assert_eq!(
+        shape.field_by_name("labelled")?.r#type,
+        VTableFieldType::Tuple(vec![
+            VTableFieldType::U8,
+            VTableFieldType::Struct("Vertex".to_string())
+        ])
+    );
+
+    // Nested documents in tuples are merged into the vtable.
+    assert!(vtable.struct_by_name("Vertex").is_ok());
+
+    // The array and tuple types survive the vtable round trip.
+    let bytes = vtable.to_vec()?;
+    let decoded = VTable::read_from_buffer(&mut DocBufReader::new(&bytes))?;
+
+    assert_eq!(&decoded, vtable);
+
+    Ok(())
+}
+
+#[test]
+fn test_arrays_length_rule() -> Result<(), Error> {
+    let mut buffer = Vec::new();
+
+    assert!(Mismatch { key: [0; 4] }.to_docbuf(&mut buffer).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_arrays_docbuf_map() -> Result<(), Error> {
+    let mut buffer = Vec::new();
+    let mut offsets = Key {
+        id: [1, 2, 3, 4],
+        version: (1, 2),
+    }
+    .to_docbuf(&mut buffer)?;
+
+    let vtable = Key::vtable()?;
+
+    let id_offset = offsets.as_ref()[0].clone();
+    let version_offset = offsets.as_ref()[1].clone();
+
+    let id: [u8; 4] = vtable.docbuf_map(&buffer, &id_offset)?;
+    let version: (u16, u8) = vtable.docbuf_map(&buffer, &version_offset)?;
+
+    assert_eq!(id, [1, 2, 3, 4]);
+    assert_eq!(version, (1, 2));
+
+    vtable.docbuf_map_replace(&[9u8, 8, 7, 6], id_offset, &mut buffer, &mut offsets)?;
+    vtable.docbuf_map_replace(&(3u16, 4u8), version_offset, &mut buffer, &mut offsets)?;
+
+    assert_eq!(
+        Key::from_docbuf(&buffer)?,
+        Key {
+            id: [9, 8, 7, 6],
+            version: (3, 4),
+        }
+    );
+
+    // The array length must match the field type.
+    let result: Result<[u8; 2], _> = vtable.docbuf_map(&buffer, &offsets.as_ref()[0]);
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_arrays_compact_docbuf_map() -> Result<(), Error> {
+    let range = Range {
+        bounds: [1, 2],
+        step: (3, 4),
+        name: "range".to_string(),
+    };
+
+    let mut buffer = Vec::new();
+    let mut offsets = range.to_docbuf(&mut buffer)?;
+
+    // Elements are varints, without a length prefix.
+    assert_eq!(&buffer[..4], &[1, 2, 3, 4]);
+
+    let vtable = Range::vtable()?;
+
+    let bounds_offset = offsets.as_ref()[0].clone();
+    let bounds: [u32; 2] = vtable.docbuf_map(&buffer, &bounds_offset)?;
+    assert_eq!(bounds, [1, 2]);
+
+    // Wider values shift the fields that follow.
+    let new_offset = vtable.docbuf_map_replace(
+        &[300u32, u32::MAX],
+        bounds_offset,
+        &mut buffer,
+        &mut offsets,
+    )?;
+
+    assert_eq!(new_offset.range(), 0..7);
+    assert_eq!(offsets.as_ref()[1].range(), 7..9);
+
+    let step: (u16, u64) = vtable.docbuf_map(&buffer, &offsets.as_ref()[1])?;
+    assert_eq!(step, (3, 4));
+
+    assert_eq!(
+        Range::from_docbuf(&buffer)?,
+        Range {
+            bounds: [300, u32::MAX],
+            ..range
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_arrays_const_length() -> Result<(), Error> {
+    let vtable = Digest::vtable()?;
+    let digest = vtable.struct_by_name("Digest")?;
+
+    assert_eq!(
+        digest.field_by_name("hash")?.r#type,
+        VTableFieldType::Array {
+            elem: Box::new(VTableFieldType::U8),
+            len: HASH_LEN,
+        }
+    );
+    assert_eq!(
+        digest.field_by_name("parts")?.r#type,
+        VTableFieldType::Option(Box::new(VTableFieldType::Array {
+            elem: Box::new(VTableFieldType::U16),
+            len: 2,
+        }))
+    );
+
+    // Nested documents in const length arrays are merged into the vtable.
+    assert!(vtable.struct_by_name("Vertex").is_ok());
+
+    let doc = Digest {
+        hash: [3; HASH_LEN],
+        parts: Some([1, 2]),
+        vertices: ([Vertex { x: 1, y: 2 }, Vertex { x: -3, y: -4 }], 5),
+    };
+
+    let mut buffer = Vec::new();
+    doc.to_docbuf(&mut buffer)?;
+    assert_eq!(Digest::from_docbuf(&buffer)?, doc);
+
+    // Type names with const lengths are not parsed.
+    assert!(matches!(
+        VTableFieldType::try_from("[u8 ; HASH_LEN]"),
+        Err(vtable::Error::InvalidFieldType(_))
+    ));
+
+    Ok(())
+}
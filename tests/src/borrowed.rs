@@ -0,0 +1,119 @@
+use std::borrow::Cow;
+
+use docbuf_core::{serde::de::DocBufDeserializer, traits::DocBuf, vtable::VTableFieldType};
+use docbuf_macros::*;
+use serde::{Deserialize, Serialize};
+
+#[docbuf {
+    namespace = "borrowed";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Message<'a> {
+    pub id: u32,
+    pub topic: &'a str,
+    #[serde(borrow)]
+    pub label: Cow<'a, str>,
+    #[docbuf {
+        max_length = 64;
+    }]
+    pub payload: &'a [u8],
+    pub owned: String,
+}
+
+impl<'a> Message<'a> {
+    pub fn dummy() -> Self {
+        Self {
+            id: 7,
+            topic: "events",
+            label: Cow::Borrowed("label"),
+            payload: &[1, 2, 3, 4],
+            owned: "owned".to_string(),
+        }
+    }
+}
+
+/// Returns true if the slice is borrowed from the input buffer.
+fn borrows_from(input: &[u8], slice: &[u8]) -> bool {
+    input.as_ptr_range().contains(&slice.as_ptr())
+}
+
+#[test]
+fn test_borrowed_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let message = Message::dummy();
+
+    let mut buffer = Vec::with_capacity(1024);
+    message.to_docbuf(&mut buffer)?;
+
+    let input = buffer.clone();
+    let decoded = Message::from_docbuf(&buffer)?;
+
+    assert_eq!(message, decoded);
+
+    // The input buffer is left intact.
+    assert_eq!(input, buffer);
+
+    // String and byte fields are borrowed from the input buffer.
+    assert!(borrows_from(&buffer, decoded.topic.as_bytes()));
+    assert!(borrows_from(&buffer, decoded.payload));
+    assert!(matches!(decoded.label, Cow::Borrowed(_)));
+    assert!(borrows_from(&buffer, decoded.label.as_bytes()));
+
+    Ok(())
+}
+
+#[test]
+fn test_borrowed_decode_twice() -> Result<(), Box<dyn std::error::Error>> {
+    let mut buffer = Vec::with_capacity(1024);
+    Message::dummy().to_docbuf(&mut buffer)?;
+
+    // The same input can be decoded multiple times.
+    let first = Message::from_docbuf(&buffer)?;
+    let second = Message::from_docbuf(&buffer)?;
+
+    assert_eq!(first, second);
+
+    Ok(())
+}
+
+#[test]
+fn test_borrowed_vtable() -> Result<(), Box<dyn std::error::Error>> {
+    let vtable = Message::vtable()?;
+    let message = vtable.struct_by_name("Message")?;
+
+    assert_eq!(message.field_by_name("topic")?.r#type, VTableFieldType::Str);
+    assert_eq!(message.field_by_name("label")?.r#type, VTableFieldType::Str);
+    assert_eq!(message.field_by_name("payload")?.r#type, VTableFieldType::Bytes);
+
+    Ok(())
+}
+
+#[test]
+fn test_borrowed_truncated_input() -> Result<(), Box<dyn std::error::Error>> {
+    let mut buffer = Vec::with_capacity(1024);
+    Message::dummy().to_docbuf(&mut buffer)?;
+
+    for len in 0..buffer.len() {
+        assert!(Message::from_docbuf(&buffer[..len]).is_err());
+    }
+
+    // Trailing bytes are rejected.
+    buffer.push(0);
+    assert!(Message::from_docbuf(&buffer).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_borrowed_deserializer_position() -> Result<(), Box<dyn std::error::Error>> {
+    let mut buffer = Vec::with_capacity(1024);
+    Message::dummy().to_docbuf(&mut buffer)?;
+
+    let mut deserializer = DocBufDeserializer::new(Message::vtable()?, &buffer)?;
+    let decoded = Message::deserialize(&mut deserializer)?;
+
+    assert_eq!(decoded, Message::dummy());
+    assert_eq!(deserializer.position(), buffer.len());
+    assert!(deserializer.remaining().is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+
+use docbuf_core::{
+    deps::uuid::Uuid,
+    error::Error,
+    traits::{DocBuf, DocBufMap},
+    vtable::{self, VTableFieldOffset, VTableFieldOffsets},
+};
+use docbuf_macros::*;
+use serde::{Deserialize, Serialize};
+
+use crate::value::Counter;
+
+#[docbuf {
+    namespace = "bufmap";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Device {
+    pub id: Uuid,
+    pub serial: u128,
+    pub drift: i128,
+    pub class: char,
+    pub alias: Option<String>,
+    #[docbuf {
+        not_empty = true;
+    }]
+    pub readings: Vec<u32>,
+    pub tags: HashMap<String, u16>,
+    pub position: Position,
+    pub owner: Option<Owner>,
+    pub enabled: bool,
+}
+
+#[docbuf {
+    namespace = "bufmap";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Position {
+    pub label: String,
+    pub x: i32,
+}
+
+#[docbuf {
+    namespace = "bufmap";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Owner {
+    pub name: String,
+}
+
+impl Device {
+    pub fn dummy() -> Self {
+        Self {
+            id: Uuid::from_bytes([3; 16]),
+            serial: u128::MAX - 1,
+            drift: i128::MIN + 1,
+            class: 'B',
+            alias: None,
+            readings: vec![10, 20],
+            tags: HashMap::from([("zone".to_string(), 4)]),
+            position: Position {
+                label: "rack".to_string(),
+                x: -7,
+            },
+            owner: Some(Owner {
+                name: "ops".to_string(),
+            }),
+            enabled: true,
+        }
+    }
+}
+
+/// Return the offset of a field of an item of the device vtable.
+fn offset(
+    offsets: &VTableFieldOffsets,
+    item: &str,
+    field: &str,
+) -> Result<VTableFieldOffset, Error> {
+    let field = Device::vtable()?
+        .struct_by_name(item)?
+        .field_by_name(field)?;
+
+    offsets
+        .offset(field.offset_index())
+        .cloned()
+        .ok_or(Error::FieldNotFound)
+}
+
+/// Assert the offsets match the offsets of a fresh serialization of the document.
+fn assert_offsets(buffer: &[u8], offsets: &VTableFieldOffsets) -> Result<(), Error> {
+    let mut new_buffer = Vec::new();
+    let new_offsets = Device::from_docbuf(buffer)?.to_docbuf(&mut new_buffer)?;
+
+    assert_eq!(buffer, new_buffer);
+    assert_eq!(offsets.to_vec(), new_offsets.to_vec());
+
+    Ok(())
+}
+
+#[test]
+fn test_bufmap_values() -> Result<(), Error> {
+    let device = Device::dummy();
+
+    let mut buffer = Vec::new();
+    let mut offsets = device.to_docbuf(&mut buffer)?;
+
+    let vtable = Device::vtable()?;
+
+    // Nested structs add an offset, in addition to the offsets of their fields.
+    assert_eq!(offsets.len(), vtable.num_offsets() as usize);
+
+    let id: Uuid = vtable.docbuf_map(&buffer, &offset(&offsets, "Device", "id")?)?;
+    let serial: u128 = vtable.docbuf_map(&buffer, &offset(&offsets, "Device", "serial")?)?;
+    let drift: i128 = vtable.docbuf_map(&buffer, &offset(&offsets, "Device", "drift")?)?;
+    let class: char = vtable.docbuf_map(&buffer, &offset(&offsets, "Device", "class")?)?;
+    let alias: Option<String> =
+        vtable.docbuf_map(&buffer, &offset(&offsets, "Device", "alias")?)?;
+    let readings: Vec<u32> =
+        vtable.docbuf_map(&buffer, &offset(&offsets, "Device", "readings")?)?;
+    let tags: HashMap<String, u16> =
+        vtable.docbuf_map(&buffer, &offset(&offsets, "Device", "tags")?)?;
+
+    assert_eq!(
+        (id, serial, drift, class, alias, readings, tags),
+        (
+            device.id,
+            device.serial,
+            device.drift,
+            device.class,
+            device.alias,
+            device.readings,
+            device.tags
+        )
+    );
+
+    let id = Uuid::from_bytes([9; 16]);
+    vtable.docbuf_map_replace(
+        &id,
+        offset(&offsets, "Device", "id")?,
+        &mut buffer,
+        &mut offsets,
+    )?;
+    vtable.docbuf_map_replace(
+        &7u128,
+        offset(&offsets, "Device", "serial")?,
+        &mut buffer,
+        &mut offsets,
+    )?;
+    vtable.docbuf_map_replace(
+        &'Z',
+        offset(&offsets, "Device", "class")?,
+        &mut buffer,
+        &mut offsets,
+    )?;
+    vtable.docbuf_map_replace(
+        &Some("edge".to_string()),
+        offset(&offsets, "Device", "alias")?,
+        &mut buffer,
+        &mut offsets,
+    )?;
+    let readings = vtable.docbuf_map_replace(
+        &vec![1u32, 2, 3, 4],
+        offset(&offsets, "Device", "readings")?,
+        &mut buffer,
+        &mut offsets,
+    )?;
+    vtable.docbuf_map_replace(
+        &HashMap::<String, u16>::new(),
+        offset(&offsets, "Device", "tags")?,
+        &mut buffer,
+        &mut offsets,
+    )?;
+    vtable.docbuf_map_replace(
+        &false,
+        offset(&offsets, "Device", "enabled")?,
+        &mut buffer,
+        &mut offsets,
+    )?;
+
+    assert_eq!(
+        Device::from_docbuf(&buffer)?,
+        Device {
+            id,
+            serial: 7,
+            class: 'Z',
+            alias: Some("edge".to_string()),
+            readings: vec![1, 2, 3, 4],
+            tags: HashMap::new(),
+            enabled: false,
+            ..Device::dummy()
+        }
+    );
+
+    // The offset of the list follows the length prefix.
+    let readings: Vec<u32> = vtable.docbuf_map(&buffer, &readings)?;
+    assert_eq!(readings, vec![1, 2, 3, 4]);
+
+    assert_offsets(&buffer, &offsets)?;
+
+    // The field rules are checked, and the type must match the field type.
+    assert!(matches!(
+        vtable.docbuf_map_replace(
+            &Vec::<u32>::new(),
+            offset(&offsets, "Device", "readings")?,
+            &mut buffer,
+            &mut offsets,
+        ),
+        Err(vtable::Error::FieldRulesNotEmpty(_))
+    ));
+
+    let result: Result<u128, _> = vtable.docbuf_map(&buffer, &offset(&offsets, "Device", "drift")?);
+    assert!(matches!(
+        result,
+        Err(vtable::Error::DocBufMapInvalidFieldType(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_bufmap_nested() -> Result<(), Error> {
+    let mut buffer = Vec::new();
+    let mut offsets = Device::dummy().to_docbuf(&mut buffer)?;
+
+    let vtable = Device::vtable()?;
+
+    let position: Position =
+        vtable.docbuf_map(&buffer, &offset(&offsets, "Device", "position")?)?;
+    assert_eq!(position, Device::dummy().position);
+
+    let position = Position {
+        label: "shelf-12".to_string(),
+        x: 300,
+    };
+
+    vtable.docbuf_map_replace(
+        &position,
+        offset(&offsets, "Device", "position")?,
+        &mut buffer,
+        &mut offsets,
+    )?;
+
+    // The offsets of the nested fields are updated with the struct.
+    let label: String = vtable.docbuf_map(&buffer, &offset(&offsets, "Position", "label")?)?;
+    assert_eq!(label, "shelf-12");
+
+    vtable.docbuf_map_replace(
+        &None::<Owner>,
+        offset(&offsets, "Device", "owner")?,
+        &mut buffer,
+        &mut offsets,
+    )?;
+
+    assert_eq!(
+        Device::from_docbuf(&buffer)?,
+        Device {
+            position: position.clone(),
+            owner: None,
+            ..Device::dummy()
+        }
+    );
+
+    // The offsets of the fields of a `None` struct are removed.
+    let owner: Option<Owner> = vtable.docbuf_map(&buffer, &offset(&offsets, "Device", "owner")?)?;
+    assert_eq!(owner, None);
+    assert!(offset(&offsets, "Owner", "name").is_err());
+
+    let owner = Owner {
+        name: "facilities".to_string(),
+    };
+
+    vtable.docbuf_map_replace(
+        &Some(owner.clone()),
+        offset(&offsets, "Device", "owner")?,
+        &mut buffer,
+        &mut offsets,
+    )?;
+
+    let name: String = vtable.docbuf_map(&buffer, &offset(&offsets, "Owner", "name")?)?;
+    assert_eq!(name, "facilities");
+
+    assert_eq!(Device::from_docbuf(&buffer)?.owner, Some(owner));
+    assert_offsets(&buffer, &offsets)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_bufmap_compact_list() -> Result<(), Error> {
+    let counter = Counter {
+        count: 1,
+        delta: -1,
+        names: vec!["a".to_string()],
+    };
+
+    let mut buffer = Vec::new();
+    let mut offsets = counter.to_docbuf(&mut buffer)?;
+
+    let vtable = Counter::vtable()?;
+
+    // The list needs a two byte length prefix, moving the start of the elements.
+    let names = (0..200).map(|i| i.to_string()).collect::<Vec<_>>();
+    let new_offset = vtable.docbuf_map_replace(
+        &names,
+        offsets.as_ref()[2].clone(),
+        &mut buffer,
+        &mut offsets,
+    )?;
+
+    assert_eq!(new_offset.range().start, 1 + 1 + 2);
+
+    let mapped: Vec<String> = vtable.docbuf_map(&buffer, &new_offset)?;
+    assert_eq!(mapped, names);
+
+    let expected = Counter { names, ..counter };
+    assert_eq!(Counter::from_docbuf(&buffer)?, expected);
+
+    let mut new_buffer = Vec::new();
+    let new_offsets = expected.to_docbuf(&mut new_buffer)?;
+
+    assert_eq!(offsets.to_vec(), new_offsets.to_vec());
+
+    Ok(())
+}
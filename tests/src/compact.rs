@@ -0,0 +1,258 @@
+use crate::{SetTestValues, TestHarness};
+
+use std::collections::HashMap;
+
+use docbuf_core::{
+    error::Error,
+    traits::{DocBuf, DocBufMap},
+    vtable::{self, DocBufReader, VTable, VTableEncoding},
+};
+use docbuf_macros::*;
+use serde::{Deserialize, Serialize};
+
+#[docbuf {
+    namespace = "compact";
+    encoding = "compact";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Counter {
+    pub count: u32,
+    pub delta: i32,
+    pub name: String,
+}
+
+#[docbuf {
+    namespace = "compact";
+    encoding = "compact";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Metrics {
+    pub label: String,
+    pub hits: u64,
+    pub offset: i64,
+    pub small: u16,
+    pub wide: u128,
+    pub signed_wide: i128,
+    pub ratio: f32,
+    pub flag: bool,
+    pub tags: Vec<String>,
+    pub counts: HashMap<String, u32>,
+    pub note: Option<String>,
+    pub counter: Counter,
+    pub payload: Vec<u8>,
+}
+
+/// The same document as `Metrics`, with the default fixed encoding.
+#[docbuf {
+    namespace = "compact";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct FixedMetrics {
+    pub label: String,
+    pub hits: u64,
+    pub offset: i64,
+    pub small: u16,
+    pub wide: u128,
+    pub signed_wide: i128,
+    pub ratio: f32,
+    pub flag: bool,
+    pub tags: Vec<String>,
+    pub counts: HashMap<String, u32>,
+    pub note: Option<String>,
+    pub counter: Counter,
+    pub payload: Vec<u8>,
+}
+
+impl Metrics {
+    pub fn dummy() -> Self {
+        Self {
+            label: "requests".to_string(),
+            hits: 1_000,
+            offset: -64,
+            small: 7,
+            wide: u128::MAX,
+            signed_wide: i128::MIN,
+            ratio: 0.5,
+            flag: true,
+            tags: vec!["a".to_string(), "b".to_string()],
+            counts: HashMap::from([("x".to_string(), 1), ("y".to_string(), 300)]),
+            note: Some("note".to_string()),
+            counter: Counter {
+                count: 3,
+                delta: -3,
+                name: "c".to_string(),
+            },
+            payload: vec![1, 2, 3],
+        }
+    }
+}
+
+impl SetTestValues for Metrics {}
+
+impl<'de> TestHarness<'de> for Metrics {}
+
+#[test]
+fn test_compact_wire_format() -> Result<(), Error> {
+    let counter = Counter {
+        count: 300,
+        delta: -2,
+        name: "ab".to_string(),
+    };
+
+    let mut buffer = Vec::new();
+    counter.to_docbuf(&mut buffer)?;
+
+    // 300 as a varint, -2 zigzag encoded as 3, and the string length as a varint.
+    assert_eq!(buffer, vec![0xac, 0x02, 0x03, 0x02, b'a', b'b']);
+
+    assert_eq!(Counter::from_docbuf(&buffer)?, counter);
+
+    Ok(())
+}
+
+#[test]
+fn test_compact_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let mut buffer = Vec::new();
+    Metrics::dummy().assert_serialization_round_trip(&mut buffer)?;
+
+    assert_eq!(Metrics::from_docbuf(&buffer)?, Metrics::dummy());
+
+    // Extreme values round trip.
+    let mut metrics = Metrics::dummy();
+    metrics.hits = u64::MAX;
+    metrics.offset = i64::MIN;
+    metrics.small = u16::MAX;
+    metrics.wide = 0;
+    metrics.signed_wide = i128::MAX;
+    metrics.note = None;
+
+    metrics.to_docbuf(&mut buffer)?;
+    assert_eq!(Metrics::from_docbuf(&buffer)?, metrics);
+
+    Ok(())
+}
+
+#[test]
+fn test_compact_smaller_than_fixed() -> Result<(), Error> {
+    let metrics = Metrics::dummy();
+
+    let fixed = FixedMetrics {
+        label: metrics.label.clone(),
+        hits: metrics.hits,
+        offset: metrics.offset,
+        small: metrics.small,
+        wide: metrics.wide,
+        signed_wide: metrics.signed_wide,
+        ratio: metrics.ratio,
+        flag: metrics.flag,
+        tags: metrics.tags.clone(),
+        counts: metrics.counts.clone(),
+        note: metrics.note.clone(),
+        counter: metrics.counter.clone(),
+        payload: metrics.payload.clone(),
+    };
+
+    let mut compact_buffer = Vec::new();
+    metrics.to_docbuf(&mut compact_buffer)?;
+
+    let mut fixed_buffer = Vec::new();
+    fixed.to_docbuf(&mut fixed_buffer)?;
+
+    assert!(
+        compact_buffer.len() < fixed_buffer.len(),
+        "compact byte length: {}\nfixed byte length: {}",
+        compact_buffer.len(),
+        fixed_buffer.len()
+    );
+
+    // Nested documents use the encoding of the document they are nested in.
+    assert_eq!(FixedMetrics::vtable()?.encoding(), VTableEncoding::Fixed);
+    assert!(FixedMetrics::vtable()?
+        .struct_by_name("Counter")?
+        .fields
+        .iter()
+        .all(|field| field.encoding == VTableEncoding::Fixed));
+
+    Ok(())
+}
+
+#[test]
+fn test_compact_vtable_encoding() -> Result<(), Error> {
+    let vtable = Metrics::vtable()?;
+
+    assert_eq!(vtable.encoding(), VTableEncoding::Compact);
+
+    // The encoding is recorded in the serialized vtable.
+    let mut vtable_buf = Vec::new();
+    vtable.write_to_buffer(&mut vtable_buf)?;
+
+    let decoded = VTable::read_from_buffer(&mut DocBufReader::new(&vtable_buf))?;
+
+    assert_eq!(&decoded, vtable);
+    assert_eq!(decoded.encoding(), VTableEncoding::Compact);
+    assert!(decoded
+        .items()
+        .iter()
+        .filter_map(|item| match item {
+            vtable::VTableItem::Struct(vtable_struct) => Some(vtable_struct),
+            _ => None,
+        })
+        .flat_map(|vtable_struct| vtable_struct.fields.iter())
+        .all(|field| field.encoding == VTableEncoding::Compact));
+
+    // A buffer written with the compact encoding does not decode as fixed.
+    let mut buffer = Vec::new();
+    Metrics::dummy().to_docbuf(&mut buffer)?;
+
+    assert!(FixedMetrics::from_docbuf(&buffer).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_compact_docbuf_map() -> Result<(), Error> {
+    let counter = Counter {
+        count: 300,
+        delta: -2,
+        name: "ab".to_string(),
+    };
+
+    let mut buffer = Vec::new();
+    let mut offsets = counter.to_docbuf(&mut buffer)?;
+
+    let vtable = Counter::vtable()?;
+
+    let count_offset = offsets.as_ref()[0].clone();
+    let delta_offset = offsets.as_ref()[1].clone();
+    let name_offset = offsets.as_ref()[2].clone();
+
+    let count: u32 = vtable.docbuf_map(&buffer, &count_offset)?;
+    let delta: i32 = vtable.docbuf_map(&buffer, &delta_offset)?;
+    let name: String = vtable.docbuf_map(&buffer, &name_offset)?;
+
+    assert_eq!((count, delta, name), (300, -2, "ab".to_string()));
+
+    // The new name needs a two byte length prefix, moving the start of the value.
+    let long_name = "n".repeat(200);
+    let new_offset =
+        vtable.docbuf_map_replace(&long_name, name_offset, &mut buffer, &mut offsets)?;
+
+    assert_eq!(new_offset.range(), 5..205);
+    assert_eq!(offsets.as_ref()[2].range(), new_offset.range());
+
+    let name: String = vtable.docbuf_map(&buffer, &new_offset)?;
+    assert_eq!(name, long_name);
+
+    let decoded = Counter::from_docbuf(&buffer)?;
+    assert_eq!(decoded.name, long_name);
+    assert_eq!(decoded.count, 300);
+
+    // And back to a single byte length prefix.
+    let new_offset =
+        vtable.docbuf_map_replace(&"ab".to_string(), new_offset, &mut buffer, &mut offsets)?;
+
+    assert_eq!(new_offset.range(), 4..6);
+    assert_eq!(Counter::from_docbuf(&buffer)?, counter);
+
+    Ok(())
+}
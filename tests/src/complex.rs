@@ -209,7 +209,7 @@ fn test_serialize_complex() -> Result<(), docbuf_core::error::Error> {
         .assert_serialization_size(&mut buffer)
         .expect("Failed encoding benchmark");
 
-    let _doc = Document::from_docbuf(&mut buffer)?;
+    let _doc = Document::from_docbuf(&buffer)?;
 
     // println!("doc: {:?}", doc);
 
@@ -286,7 +286,7 @@ fn test_docbuf_map() -> Result<(), docbuf_core::error::Error> {
         &mut offsets,
     )?;
 
-    let doc = Document::from_docbuf(&mut buffer)?;
+    let doc = Document::from_docbuf(&buffer)?;
 
     assert_eq!(field_data, doc.author);
 
@@ -330,7 +330,7 @@ fn test_vtable_size() -> Result<(), docbuf_core::error::Error> {
 
     println!("Num page entries: {:?}", page_size % buffer.len());
 
-    let avg_field_size = buffer.len() / (vtable.num_fields - vtable.num_items as u16) as usize;
+    let avg_field_size = buffer.len() / (vtable.num_fields() - vtable.num_items() as u16) as usize;
 
     println!("Avg Field Size: {:?}", avg_field_size);
 
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+
+use docbuf_core::{
+    crypto::{ed25519_dalek::SigningKey, sha2::Sha256},
+    error::Error,
+    traits::{DocBuf, DocBufCrypto},
+    vtable,
+};
+use docbuf_macros::*;
+use serde::{Deserialize, Serialize};
+
+#[docbuf {
+    namespace = "crypto";
+    sign = true;
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Ledger {
+    pub account: u128,
+    pub owner: Option<u128>,
+    pub balances: HashMap<String, u64>,
+    pub rate: f64,
+}
+
+// The same fields as the ledger, in a different vtable.
+#[docbuf {
+    namespace = "crypto";
+    sign = true;
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Journal {
+    pub account: u128,
+    pub owner: Option<u128>,
+    pub balances: HashMap<String, u64>,
+    pub rate: f64,
+}
+
+// The same fields as the ledger, in compact encoding.
+#[docbuf {
+    namespace = "crypto";
+    encoding = "compact";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct CompactLedger {
+    pub account: u128,
+    pub owner: Option<u128>,
+    pub balances: HashMap<String, u64>,
+    pub rate: f64,
+}
+
+impl Ledger {
+    pub fn dummy() -> Self {
+        Self {
+            account: 42,
+            owner: Some(7),
+            balances: (0..32).map(|i| (format!("account-{i}"), i * 100)).collect(),
+            rate: 0.25,
+        }
+    }
+}
+
+#[test]
+fn test_canonical_round_trip() -> Result<(), Error> {
+    let ledger = Ledger::dummy();
+
+    let mut buffer = Vec::new();
+    ledger.to_docbuf(&mut buffer)?;
+
+    let decoded = Ledger::from_docbuf(&buffer)?;
+
+    // The map entries of the decoded ledger are iterated in a different order,
+    // the canonical form sorts them.
+    let mut canonical = Vec::new();
+    ledger.to_canonical_docbuf(&mut canonical)?;
+
+    let mut decoded_canonical = Vec::new();
+    decoded.to_canonical_docbuf(&mut decoded_canonical)?;
+
+    assert_eq!(canonical, decoded_canonical);
+
+    let key = SigningKey::from_bytes(&[7; 32]);
+    let signature = ledger.sign(&mut Sha256::default(), key.clone())?;
+
+    decoded.verify(&mut Sha256::default(), &signature, key.verifying_key())?;
+
+    let tampered = Ledger {
+        account: 43,
+        ..decoded
+    };
+
+    assert!(tampered
+        .verify(&mut Sha256::default(), &signature, key.verifying_key())
+        .is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_canonical_hash() -> Result<(), Error> {
+    let ledger = Ledger::dummy();
+    let hash = ledger.hash(&mut Sha256::default())?;
+
+    assert_eq!(hash, ledger.hash(&mut Sha256::default())?);
+
+    // Documents of different vtables with the same encoding hash differently.
+    let journal = Journal {
+        account: ledger.account,
+        owner: ledger.owner,
+        balances: ledger.balances.clone(),
+        rate: ledger.rate,
+    };
+
+    let (mut ledger_bytes, mut journal_bytes) = (Vec::new(), Vec::new());
+    ledger.to_canonical_docbuf(&mut ledger_bytes)?;
+    journal.to_canonical_docbuf(&mut journal_bytes)?;
+
+    assert_eq!(ledger_bytes, journal_bytes);
+    assert_ne!(hash, journal.hash(&mut Sha256::default())?);
+
+    // Options are tagged in the canonical form.
+    let some = Ledger {
+        owner: Some(0),
+        ..Ledger::dummy()
+    };
+    let none = Ledger {
+        owner: None,
+        ..Ledger::dummy()
+    };
+
+    assert_ne!(
+        some.hash(&mut Sha256::default())?,
+        none.hash(&mut Sha256::default())?
+    );
+
+    // NaN floats have no canonical encoding.
+    let nan = Ledger {
+        rate: f64::NAN,
+        ..Ledger::dummy()
+    };
+
+    assert!(matches!(
+        nan.hash(&mut Sha256::default()),
+        Err(Error::VTable(vtable::Error::CanonicalNaN(field))) if field == "rate"
+    ));
+
+    // The document buffer is unchanged.
+    let mut buffer = Vec::new();
+    nan.to_docbuf(&mut buffer)?;
+
+    assert!(Ledger::from_docbuf(&buffer)?.rate.is_nan());
+
+    Ok(())
+}
+
+#[test]
+fn test_canonical_forged_length() -> Result<(), Error> {
+    let ledger = Ledger {
+        balances: HashMap::from([("a".to_string(), 1)]),
+        ..Ledger::dummy()
+    };
+
+    let mut buffer = Vec::new();
+    ledger.to_docbuf(&mut buffer)?;
+
+    // The map length prefix is followed by the key, its value and the rate.
+    let position = buffer.len() - 8 - 8 - 1 - 4 - 4;
+    assert_eq!(&buffer[position..position + 4], &1u32.to_le_bytes());
+
+    // Map entries are not allocated from the length prefix, the entries run
+    // out of bytes.
+    let mut forged = buffer.clone();
+    forged[position..position + 4].copy_from_slice(&0x0fff_ffffu32.to_le_bytes());
+
+    let mut canonical = Vec::new();
+    assert!(Ledger::vtable()?
+        .canonical_docbuf(&forged, &mut canonical)
+        .is_err());
+    assert!(Ledger::from_docbuf(&forged).is_err());
+
+    // String lengths are bounded by the remaining bytes.
+    let position = position + 4;
+    buffer[position..position + 4].copy_from_slice(&0x0fff_ffffu32.to_le_bytes());
+
+    assert!(matches!(
+        Ledger::vtable()?.canonical_docbuf(&buffer, &mut canonical),
+        Err(Error::VTable(vtable::Error::LengthExceedsBuffer(
+            0x0fff_ffff,
+            17
+        )))
+    ));
+    assert!(matches!(
+        Ledger::from_docbuf(&buffer),
+        Err(Error::VTable(vtable::Error::LengthExceedsBuffer(..)))
+    ));
+
+    // A varint length of u64::MAX in compact encoding.
+    let ledger = CompactLedger {
+        account: ledger.account,
+        owner: ledger.owner,
+        balances: ledger.balances,
+        rate: ledger.rate,
+    };
+
+    let mut buffer = Vec::new();
+    ledger.to_docbuf(&mut buffer)?;
+
+    let position = buffer.len() - 8 - 1 - 1 - 1;
+    assert_eq!(buffer[position], 1);
+
+    buffer.splice(position..position + 1, [0xff; 9].into_iter().chain([0x01]));
+
+    assert!(matches!(
+        CompactLedger::vtable()?.canonical_docbuf(&buffer, &mut canonical),
+        Err(Error::VTable(vtable::Error::LengthExceedsBuffer(len, 10))) if len == u64::MAX as usize
+    ));
+    assert!(matches!(
+        CompactLedger::from_docbuf(&buffer),
+        Err(Error::VTable(vtable::Error::LengthExceedsBuffer(..)))
+    ));
+
+    Ok(())
+}
@@ -261,7 +261,7 @@ pub async fn test_write_docbuf_request() -> Result<(), Box<dyn std::error::Error
     let _req_offsets = request.to_docbuf(&mut req_buffer)?;
 
     // Deserialize the request.
-    let _req = WriteDocBufRequest::from_docbuf(&mut req_buffer)?;
+    let _req = WriteDocBufRequest::from_docbuf(&req_buffer)?;
 
     Ok(())
 }
@@ -319,7 +319,7 @@ pub async fn test_predicate_serialization() -> Result<(), Box<dyn std::error::Er
     println!("Preparing to deserialize predicates...\n\n");
 
     // Deserialize the request.
-    let predicates_deserialized = Predicates::from_docbuf(&mut predicates_buffer)?;
+    let predicates_deserialized = Predicates::from_docbuf(&predicates_buffer)?;
 
     println!("Predicates: {predicates_deserialized:?}\n\n");
 
@@ -0,0 +1,240 @@
+use crate::{SetTestValues, TestHarness};
+
+use docbuf_core::{
+    traits::DocBuf,
+    vtable::{
+        self, DocBufReader, VTable, VTableEnum, VTableFieldType, VTableItem, VTableVariantKind,
+    },
+};
+use docbuf_macros::*;
+use serde::{Deserialize, Serialize};
+
+#[docbuf {
+    namespace = "enums";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Order {
+    pub id: u64,
+    pub status: Status,
+    pub previous_status: Option<Status>,
+    pub events: Vec<Event>,
+}
+
+#[docbuf {
+    namespace = "enums";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub enum Status {
+    #[default]
+    Pending,
+    Paid(u64),
+    Shipped(String, u32),
+    Cancelled {
+        #[docbuf {
+            max_length = 32;
+        }]
+        reason: String,
+        refunded: bool,
+    },
+}
+
+#[docbuf {
+    namespace = "enums";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Event {
+    Created,
+    Updated(Address),
+    Noted { author: String, tags: Vec<String> },
+}
+
+#[docbuf {
+    namespace = "enums";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Address {
+    pub street: String,
+    pub zip: u32,
+}
+
+impl SetTestValues for Order {}
+
+impl<'de> TestHarness<'de> for Order {}
+
+impl Order {
+    pub fn dummy(status: Status) -> Self {
+        Self {
+            id: 42,
+            status,
+            previous_status: Some(Status::Paid(100)),
+            events: vec![
+                Event::Created,
+                Event::Updated(Address {
+                    street: "Main St".to_string(),
+                    zip: 12345,
+                }),
+                Event::Noted {
+                    author: "docbuf".to_string(),
+                    tags: vec!["a".to_string(), "b".to_string()],
+                },
+            ],
+        }
+    }
+}
+
+#[test]
+fn test_enum_variants_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let statuses = vec![
+        Status::Pending,
+        Status::Paid(u64::MAX),
+        Status::Shipped("tracking".to_string(), 7),
+        Status::Cancelled {
+            reason: "out of stock".to_string(),
+            refunded: true,
+        },
+    ];
+
+    let mut buffer = Vec::with_capacity(1024);
+
+    for status in statuses {
+        let order = Order::dummy(status);
+
+        order.to_docbuf(&mut buffer)?;
+
+        let decoded = Order::from_docbuf(&buffer)?;
+
+        assert_eq!(order, decoded);
+
+        order.assert_serialization_round_trip(&mut buffer)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_enum_none_and_empty() -> Result<(), Box<dyn std::error::Error>> {
+    let order = Order {
+        previous_status: None,
+        events: vec![],
+        ..Order::dummy(Status::Pending)
+    };
+
+    let mut buffer = Vec::with_capacity(1024);
+    order.to_docbuf(&mut buffer)?;
+
+    assert_eq!(order, Order::from_docbuf(&buffer)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_enum_root_document() -> Result<(), Box<dyn std::error::Error>> {
+    let mut buffer = Vec::with_capacity(1024);
+
+    let status = Status::Shipped("tracking".to_string(), 7);
+    let offsets = status.to_docbuf(&mut buffer)?;
+
+    // discriminant + string length + string + u32
+    assert_eq!(buffer.len(), 1 + 4 + 8 + 4);
+    assert_eq!(buffer[0], 2);
+
+    // Enum values are addressed by a single offset.
+    assert_eq!(offsets.len(), 1);
+    assert_eq!(offsets.len(), Status::vtable()?.num_offsets() as usize);
+
+    assert_eq!(status, Status::from_docbuf(&buffer)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_enum_field_rules() -> Result<(), Box<dyn std::error::Error>> {
+    let order = Order::dummy(Status::Cancelled {
+        reason: ["X"; 64].concat(),
+        refunded: false,
+    });
+
+    let mut buffer = Vec::with_capacity(1024);
+
+    assert!(order.to_docbuf(&mut buffer).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_enum_invalid_discriminant() -> Result<(), Box<dyn std::error::Error>> {
+    let mut buffer = Vec::with_capacity(1024);
+    Status::Pending.to_docbuf(&mut buffer)?;
+
+    buffer[0] = 4;
+
+    assert!(Status::from_docbuf(&buffer).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_enum_vtable() -> Result<(), Box<dyn std::error::Error>> {
+    let vtable = Order::vtable()?;
+
+    let status_field = vtable.struct_by_name("Order")?.field_by_name("status")?;
+    assert_eq!(
+        status_field.r#type,
+        VTableFieldType::Enum("Status".to_string())
+    );
+
+    let events_field = vtable.struct_by_name("Order")?.field_by_name("events")?;
+    assert_eq!(
+        events_field.r#type,
+        VTableFieldType::Vec(Box::new(VTableFieldType::Enum("Event".to_string())))
+    );
+
+    let status = vtable.enum_by_name("Status")?;
+    assert_eq!(status.num_variants, 4);
+
+    let shipped = status.variant_by_name("Shipped")?;
+    assert_eq!(shipped.index, 2);
+    assert_eq!(shipped.kind, VTableVariantKind::Tuple);
+    assert_eq!(shipped.field_by_index(&1)?.name, "1");
+
+    let cancelled = status.variant_by_index(3)?;
+    assert_eq!(cancelled.kind, VTableVariantKind::Struct);
+    assert_eq!(cancelled.field_by_name("reason")?.rules.max_length, Some(32));
+
+    // The nested struct of the `Event::Updated` payload is merged into the vtable.
+    assert!(vtable.struct_by_name("Address").is_ok());
+
+    // Root item is the order struct.
+    assert!(matches!(vtable.root_item()?, VTableItem::Struct(_)));
+
+    // The vtable binary format round trips enum items.
+    let mut vtable_buf = Vec::new();
+    vtable.write_to_buffer(&mut vtable_buf)?;
+    let decoded = VTable::read_from_buffer(&mut DocBufReader::new(&vtable_buf))?;
+
+    assert_eq!(vtable, &decoded);
+
+    Ok(())
+}
+
+#[test]
+fn test_enum_variants_max() -> Result<(), Box<dyn std::error::Error>> {
+    let mut vtable_enum = VTableEnum::new("Opcode", None);
+
+    // Variant indices are a single byte, the number of variants is 255 at most.
+    for index in 0..u8::MAX {
+        let variant_index =
+            vtable_enum.add_variant(&format!("Op{index}"), VTableVariantKind::Unit)?;
+        assert_eq!(variant_index, index);
+    }
+
+    assert_eq!(vtable_enum.num_variants, u8::MAX);
+
+    assert!(matches!(
+        vtable_enum.add_variant("Overflow", VTableVariantKind::Unit),
+        Err(vtable::Error::VariantIndexExceedsMax(255))
+    ));
+    assert_eq!(vtable_enum.variants.len(), 255);
+
+    Ok(())
+}
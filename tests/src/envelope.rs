@@ -0,0 +1,109 @@
+use crate::complex::Document;
+
+use docbuf_core::{
+    envelope::{self, DocBufEnvelope, ENVELOPE_HEADER_SIZE, ENVELOPE_MAGIC, ENVELOPE_VERSION},
+    error::Error,
+    traits::DocBuf,
+    vtable::VTableId,
+};
+
+fn enveloped_document() -> Result<(Document, Vec<u8>), Error> {
+    let doc = Document::dummy();
+
+    let mut buffer = Vec::with_capacity(1024);
+    doc.to_enveloped(&mut buffer)?;
+
+    Ok((doc, buffer))
+}
+
+#[test]
+fn test_envelope_round_trip() -> Result<(), Error> {
+    let (doc, buffer) = enveloped_document()?;
+
+    let mut body = Vec::with_capacity(1024);
+    doc.to_docbuf(&mut body)?;
+
+    assert_eq!(buffer.len(), ENVELOPE_HEADER_SIZE + body.len());
+    assert_eq!(&buffer[..4], &ENVELOPE_MAGIC);
+    assert_eq!(&buffer[ENVELOPE_HEADER_SIZE..], body.as_slice());
+
+    assert_eq!(Document::from_enveloped(&buffer)?, doc);
+
+    Ok(())
+}
+
+#[test]
+fn test_envelope_header() -> Result<(), Error> {
+    let (_, buffer) = enveloped_document()?;
+
+    // The header can be read without decoding the body, e.g. to route the document.
+    let (header, body) = DocBufEnvelope::read_from_buffer(&buffer)?;
+
+    assert_eq!(header.version, ENVELOPE_VERSION);
+    assert_eq!(header.flags, 0);
+    assert_eq!(&header.vtable_id, Document::vtable()?.id());
+    assert_eq!(header.body_len as usize, body.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_envelope_vtable_id_mismatch() -> Result<(), Error> {
+    let (_, mut buffer) = enveloped_document()?;
+
+    // Overwrite the vtable id
+    buffer[6..14].copy_from_slice(&[0xff; 8]);
+
+    match Document::from_enveloped(&buffer) {
+        Err(Error::Envelope(envelope::Error::VTableIdMismatch { expected, found })) => {
+            assert_eq!(&expected, Document::vtable()?.id());
+            assert_eq!(found, VTableId::new([0xff; 8]));
+        }
+        result => panic!("expected vtable id mismatch, found {result:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_envelope_invalid_header() -> Result<(), Error> {
+    let (_, buffer) = enveloped_document()?;
+
+    let mut invalid_magic = buffer.clone();
+    invalid_magic[0] = b'X';
+    assert!(matches!(
+        Document::from_enveloped(&invalid_magic),
+        Err(Error::Envelope(envelope::Error::InvalidMagic(_)))
+    ));
+
+    let mut invalid_version = buffer.clone();
+    invalid_version[4] = ENVELOPE_VERSION + 1;
+    assert!(matches!(
+        Document::from_enveloped(&invalid_version),
+        Err(Error::Envelope(envelope::Error::UnsupportedVersion(_)))
+    ));
+
+    let mut unknown_flags = buffer.clone();
+    unknown_flags[5] = 0b1000_0000;
+    assert!(matches!(
+        Document::from_enveloped(&unknown_flags),
+        Err(Error::Envelope(envelope::Error::UnknownFlags(_)))
+    ));
+
+    let mut trailing_bytes = buffer.clone();
+    trailing_bytes.push(0);
+    assert!(matches!(
+        Document::from_enveloped(&trailing_bytes),
+        Err(Error::Envelope(envelope::Error::BodyLengthMismatch { .. }))
+    ));
+
+    assert!(matches!(
+        Document::from_enveloped(&buffer[..ENVELOPE_HEADER_SIZE - 1]),
+        Err(Error::Envelope(envelope::Error::Truncated))
+    ));
+
+    // A raw document buffer is not an enveloped document buffer.
+    assert!(Document::from_enveloped(&buffer[ENVELOPE_HEADER_SIZE..]).is_err());
+
+    Ok(())
+}
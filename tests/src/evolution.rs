@@ -0,0 +1,302 @@
+use docbuf_core::{
+    error::Error,
+    traits::DocBuf,
+    vtable::{self, DocBufReader, VTable, VTableChange, VTableFieldType},
+};
+
+/// The document as it was first written.
+pub mod v1 {
+    use docbuf_macros::*;
+    use serde::{Deserialize, Serialize};
+
+    #[docbuf {
+        namespace = "evolution";
+    }]
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    pub struct Profile {
+        pub id: u32,
+        pub name: String,
+        pub nickname: String,
+        pub address: Address,
+        pub status: Status,
+    }
+
+    #[docbuf {
+        namespace = "evolution";
+    }]
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    pub struct Address {
+        pub street: String,
+        pub zip: u16,
+    }
+
+    #[docbuf {
+        namespace = "evolution";
+    }]
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    pub enum Status {
+        Active,
+        Suspended(String),
+    }
+}
+
+/// The document after fields were added, removed, reordered and widened.
+pub mod v2 {
+    use docbuf_macros::*;
+    use serde::{Deserialize, Serialize};
+
+    #[docbuf {
+        namespace = "evolution";
+    }]
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    pub struct Profile {
+        pub name: String,
+        pub id: u64,
+        pub email: Option<String>,
+        #[serde(default)]
+        pub visits: u32,
+        pub address: Address,
+        pub status: Status,
+    }
+
+    #[docbuf {
+        namespace = "evolution";
+    }]
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    pub struct Address {
+        pub zip: u32,
+        pub street: Option<String>,
+    }
+
+    #[docbuf {
+        namespace = "evolution";
+    }]
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    pub enum Status {
+        Active,
+        Deleted { at: u64 },
+        Suspended(String),
+    }
+}
+
+/// The document after a field changed to an incompatible type.
+pub mod v3 {
+    use docbuf_macros::*;
+    use serde::{Deserialize, Serialize};
+
+    #[docbuf {
+        namespace = "evolution";
+    }]
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    pub struct Profile {
+        pub id: String,
+        pub name: String,
+    }
+}
+
+/// The document after a required field was added.
+pub mod v4 {
+    use docbuf_macros::*;
+    use serde::{Deserialize, Serialize};
+
+    #[docbuf {
+        namespace = "evolution";
+    }]
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    pub struct Profile {
+        pub id: u32,
+        pub name: String,
+        pub age: u32,
+    }
+}
+
+/// The document after a field was added to a struct with a serde default.
+pub mod v5 {
+    use docbuf_macros::*;
+    use serde::{Deserialize, Serialize};
+
+    #[docbuf {
+        namespace = "evolution";
+    }]
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+    #[serde(default)]
+    pub struct Profile {
+        pub id: u32,
+        pub name: String,
+        pub age: u32,
+    }
+}
+
+fn v1_profile(status: v1::Status) -> v1::Profile {
+    v1::Profile {
+        id: 42,
+        name: "Alice".to_string(),
+        nickname: "al".to_string(),
+        address: v1::Address {
+            street: "1 Main St".to_string(),
+            zip: 12345,
+        },
+        status,
+    }
+}
+
+#[test]
+fn test_evolution_compatibility_report() -> Result<(), Error> {
+    let compatibility = v1::Profile::vtable()?.compatibility(v2::Profile::vtable()?);
+
+    assert!(compatibility.is_compatible());
+    assert!(!compatibility.is_identical());
+
+    let changes = compatibility.changes;
+
+    assert!(changes.contains(&VTableChange::FieldTypeChanged {
+        path: "Profile.id".to_string(),
+        writer: VTableFieldType::U32,
+        reader: VTableFieldType::U64,
+    }));
+    assert!(changes.contains(&VTableChange::FieldAdded {
+        path: "Profile.email".to_string(),
+        r#type: VTableFieldType::Option(Box::new(VTableFieldType::String)),
+        default: false,
+    }));
+    assert!(changes.contains(&VTableChange::FieldAdded {
+        path: "Profile.visits".to_string(),
+        r#type: VTableFieldType::U32,
+        default: true,
+    }));
+    assert!(changes.contains(&VTableChange::FieldRemoved {
+        path: "Profile.nickname".to_string(),
+        r#type: VTableFieldType::String,
+    }));
+    assert!(changes.contains(&VTableChange::FieldMoved {
+        path: "Address.zip".to_string(),
+        writer: 1,
+        reader: 0,
+    }));
+    assert!(changes.contains(&VTableChange::VariantAdded {
+        path: "Status::Deleted".to_string(),
+    }));
+
+    // Removing a variant breaks buffers written with the variant.
+    let compatibility = v2::Profile::vtable()?.compatibility(v1::Profile::vtable()?);
+
+    assert!(!compatibility.is_compatible());
+    assert!(compatibility.incompatible().any(|change| change
+        == &VTableChange::VariantRemoved {
+            path: "Status::Deleted".to_string(),
+        }));
+
+    Ok(())
+}
+
+#[test]
+fn test_evolution_decode_compat() -> Result<(), Error> {
+    // The writer's vtable is read back from its serialized form, as it would be
+    // from the database `.vtable` file.
+    let writer = {
+        let bytes = v1::Profile::vtable()?.to_vec()?;
+        VTable::read_from_buffer(&mut DocBufReader::new(&bytes))?
+    };
+
+    for (status, expected) in [
+        (v1::Status::Active, v2::Status::Active),
+        (
+            v1::Status::Suspended("spam".to_string()),
+            v2::Status::Suspended("spam".to_string()),
+        ),
+    ] {
+        let mut buffer = Vec::new();
+        v1_profile(status).to_docbuf(&mut buffer)?;
+
+        let profile = v2::Profile::from_docbuf_compat(&writer, &buffer)?;
+
+        assert_eq!(
+            profile,
+            v2::Profile {
+                name: "Alice".to_string(),
+                id: 42,
+                email: None,
+                visits: 0,
+                address: v2::Address {
+                    zip: 12345,
+                    street: Some("1 Main St".to_string()),
+                },
+                status: expected,
+            }
+        );
+    }
+
+    // Decoding with the document's own vtable is a plain decode.
+    let mut buffer = Vec::new();
+    let profile = v1_profile(v1::Status::Active);
+    profile.to_docbuf(&mut buffer)?;
+
+    assert_eq!(
+        v1::Profile::from_docbuf_compat(v1::Profile::vtable()?, &buffer)?,
+        profile
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_evolution_incompatible_change() -> Result<(), Error> {
+    let mut buffer = Vec::new();
+    v1_profile(v1::Status::Active).to_docbuf(&mut buffer)?;
+
+    match v3::Profile::from_docbuf_compat(v1::Profile::vtable()?, &buffer) {
+        Err(Error::VTable(vtable::Error::IncompatibleVTable(compatibility))) => {
+            let incompatible = compatibility.incompatible().collect::<Vec<_>>();
+
+            assert_eq!(
+                incompatible,
+                vec![&VTableChange::FieldTypeChanged {
+                    path: "Profile.id".to_string(),
+                    writer: VTableFieldType::U32,
+                    reader: VTableFieldType::String,
+                }]
+            );
+        }
+        result => panic!("expected incompatible vtable, found {result:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_evolution_required_field_added() -> Result<(), Error> {
+    let mut buffer = Vec::new();
+    v1_profile(v1::Status::Active).to_docbuf(&mut buffer)?;
+
+    // A required field without a default cannot be read from older buffers.
+    let compatibility = v1::Profile::vtable()?.compatibility(v4::Profile::vtable()?);
+
+    assert_eq!(
+        compatibility.incompatible().collect::<Vec<_>>(),
+        vec![&VTableChange::FieldAdded {
+            path: "Profile.age".to_string(),
+            r#type: VTableFieldType::U32,
+            default: false,
+        }]
+    );
+
+    assert!(matches!(
+        v4::Profile::from_docbuf_compat(v1::Profile::vtable()?, &buffer),
+        Err(Error::VTable(vtable::Error::IncompatibleVTable(_)))
+    ));
+
+    // Fields of a struct with a serde default have a default.
+    let compatibility = v1::Profile::vtable()?.compatibility(v5::Profile::vtable()?);
+    assert!(compatibility.is_compatible());
+
+    assert_eq!(
+        v5::Profile::from_docbuf_compat(v1::Profile::vtable()?, &buffer)?,
+        v5::Profile {
+            id: 42,
+            name: "Alice".to_string(),
+            age: 0,
+        }
+    );
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use crate::complex::Document;
+use crate::enums::{Order, Status};
+
+use std::io::{BufReader, BufWriter, ErrorKind, Seek, SeekFrom};
+
+use docbuf_core::{error::Error, traits::DocBuf, vtable::VTable};
+
+#[test]
+fn test_framed_documents_back_to_back() -> Result<(), Box<dyn std::error::Error>> {
+    let doc = Document::dummy();
+    let order = Order::dummy(Status::Shipped("tracking".to_string(), 7));
+
+    let mut stream = Vec::new();
+
+    Document::vtable()?.to_writer(&mut stream)?;
+    doc.to_writer(&mut stream)?;
+    order.to_writer(&mut stream)?;
+    doc.to_writer(&mut stream)?;
+
+    // Documents are read back one at a time, without knowing their lengths.
+    let mut reader = stream.as_slice();
+
+    assert_eq!(&VTable::from_reader(&mut reader)?, Document::vtable()?);
+    assert_eq!(Document::from_reader(&mut reader)?, doc);
+    assert_eq!(Order::from_reader(&mut reader)?, order);
+    assert_eq!(Document::from_reader(&mut reader)?, doc);
+
+    assert!(reader.is_empty());
+
+    // Reading past the end of the stream is an unexpected EOF.
+    match Document::from_reader(&mut reader) {
+        Err(Error::Io(e)) => assert_eq!(e.kind(), ErrorKind::UnexpectedEof),
+        result => panic!("expected unexpected EOF, found {result:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_framed_document_truncated() -> Result<(), Box<dyn std::error::Error>> {
+    let mut stream = Vec::new();
+    Document::dummy().to_writer(&mut stream)?;
+
+    for len in 0..stream.len() {
+        assert!(Document::from_reader(&stream[..len]).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_framed_documents_file() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::temp_dir().join("docbuf_test_framed_documents.dbuf");
+
+    let docs = (0..8)
+        .map(|i| {
+            let mut doc = Document::dummy();
+            doc.metadata.u32_data = i;
+            doc
+        })
+        .collect::<Vec<_>>();
+
+    let mut file = std::fs::File::options()
+        .create(true)
+        .truncate(true)
+        .read(true)
+        .write(true)
+        .open(&path)?;
+
+    {
+        let mut writer = BufWriter::new(&mut file);
+
+        for doc in docs.iter() {
+            doc.to_writer(&mut writer)?;
+        }
+    }
+
+    file.seek(SeekFrom::Start(0))?;
+    let mut reader = BufReader::new(&mut file);
+
+    for doc in docs.iter() {
+        assert_eq!(&Document::from_reader(&mut reader)?, doc);
+    }
+
+    std::fs::remove_file(&path)?;
+
+    Ok(())
+}
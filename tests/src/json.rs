@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+
+use docbuf_core::{
+    error::Error,
+    json::{self, serde_json},
+    traits::DocBuf,
+    vtable,
+};
+
+use crate::validation::{Account, Article};
+use crate::value::{Counter, Location, Reading, SensorState};
+
+#[test]
+fn test_json_decode() -> Result<(), Box<dyn std::error::Error>> {
+    let mut buffer = Vec::new();
+    Reading::dummy().to_docbuf(&mut buffer)?;
+
+    let json = Reading::vtable()?.decode_json(&buffer)?;
+
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "_uuid": "07070707-0707-0707-0707-070707070707",
+            "sensor": "thermo-1",
+            "sequence": 42,
+            "offset": -3,
+            "celsius": 21.5,
+            "unit": "C",
+            "calibrated": true,
+            "raw": "3q0=",
+            "note": null,
+            "samples": [1, 2, 3],
+            "labels": { "room": -1 },
+            "position": [1.5, -2.25],
+            "range": [5, -10],
+            "location": { "site": "lab", "floor": 3 },
+            "state": { "Faulted": { "code": 500, "message": "overheat" } },
+            "nothing": null,
+        })
+    );
+
+    // Struct fields keep the vtable order.
+    let keys = json
+        .as_object()
+        .map(|object| object.keys().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+    assert_eq!(keys.first().map(String::as_str), Some("_uuid"));
+    assert_eq!(keys.last().map(String::as_str), Some("nothing"));
+
+    // Unit and newtype variants, and integers outside the range of JSON numbers.
+    let reading = Reading {
+        range: (0, i128::MIN),
+        state: SensorState::Active(9),
+        ..Reading::dummy()
+    };
+
+    let mut buffer = Vec::new();
+    reading.to_docbuf(&mut buffer)?;
+
+    let json = Reading::vtable()?.decode_json(&buffer)?;
+    assert_eq!(json["range"], serde_json::json!([0, i128::MIN.to_string()]));
+    assert_eq!(json["state"], serde_json::json!({ "Active": 9 }));
+
+    let reading = Reading {
+        state: SensorState::Idle,
+        ..Reading::dummy()
+    };
+
+    let mut buffer = Vec::new();
+    reading.to_docbuf(&mut buffer)?;
+
+    let json = Reading::vtable()?.decode_json(&buffer)?;
+    assert_eq!(json["state"], serde_json::json!("Idle"));
+
+    Ok(())
+}
+
+#[test]
+fn test_json_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let vtable = Reading::vtable()?;
+
+    for reading in [
+        Reading::dummy(),
+        Reading {
+            celsius: 0.1,
+            note: Some("calibrated".to_string()),
+            labels: HashMap::from([
+                ("room".to_string(), -1),
+                ("rack".to_string(), i64::MAX),
+                ("row".to_string(), 4),
+            ]),
+            range: (u8::MAX, i128::MAX),
+            state: SensorState::Active(9),
+            ..Reading::dummy()
+        },
+        Reading {
+            position: [f64::INFINITY, f64::NAN],
+            location: Location {
+                floor: None,
+                ..Reading::dummy().location
+            },
+            state: SensorState::Idle,
+            ..Reading::dummy()
+        },
+    ] {
+        let mut buffer = Vec::new();
+        reading.to_docbuf(&mut buffer)?;
+
+        // Through the JSON text, as the value would be sent or stored.
+        let text = serde_json::to_string(&vtable.decode_json(&buffer)?)?;
+        let json = serde_json::from_str(&text)?;
+
+        assert_eq!(vtable.encode_json(&json)?, buffer, "{text}");
+    }
+
+    // A compact encoded vtable.
+    let counter = Counter {
+        count: 300,
+        delta: -2,
+        names: vec!["a".to_string()],
+    };
+
+    let mut buffer = Vec::new();
+    counter.to_docbuf(&mut buffer)?;
+
+    let json = Counter::vtable()?.decode_json(&buffer)?;
+    assert_eq!(Counter::vtable()?.encode_json(&json)?, buffer);
+
+    Ok(())
+}
+
+#[test]
+fn test_json_encode() -> Result<(), Box<dyn std::error::Error>> {
+    let vtable = Location::vtable()?;
+
+    // Missing `Option` fields are `None`.
+    let buffer = vtable.encode_json(&serde_json::json!({ "site": "annex" }))?;
+
+    assert_eq!(
+        Location::from_docbuf(&buffer)?,
+        Location {
+            site: "annex".to_string(),
+            floor: None,
+        }
+    );
+
+    // Values must match the field type.
+    assert!(matches!(
+        vtable.encode_json(&serde_json::json!({ "site": "annex", "floor": 70000 })),
+        Err(Error::Json(json::Error::InvalidValue { path, .. })) if path == "$.floor"
+    ));
+
+    assert!(matches!(
+        vtable.encode_json(&serde_json::json!({ "floor": 2 })),
+        Err(Error::Json(json::Error::MissingField(path))) if path == "$.site"
+    ));
+
+    assert!(matches!(
+        vtable.encode_json(&serde_json::json!({ "site": "annex", "basement": true })),
+        Err(Error::Json(json::Error::UnknownField(path))) if path == "$.basement"
+    ));
+
+    let vtable = Reading::vtable()?;
+
+    let mut buffer = Vec::new();
+    Reading::dummy().to_docbuf(&mut buffer)?;
+    let json = vtable.decode_json(&buffer)?;
+
+    let mut invalid = json.clone();
+    invalid["state"] = serde_json::json!({ "Rebooting": null });
+
+    assert!(matches!(
+        vtable.encode_json(&invalid),
+        Err(Error::Json(json::Error::UnknownVariant { variant, .. })) if variant == "Rebooting"
+    ));
+
+    let mut invalid = json.clone();
+    invalid["raw"] = serde_json::json!("not base64!");
+
+    assert!(matches!(
+        vtable.encode_json(&invalid),
+        Err(Error::Json(json::Error::Base64(_)))
+    ));
+
+    // The field rules of the vtable are checked.
+    let mut invalid = json.clone();
+    invalid["sensor"] = serde_json::json!("a-very-long-sensor-name");
+
+    assert!(matches!(
+        vtable.encode_json(&invalid),
+        Err(Error::VTable(vtable::Error::FieldRulesLength(_)))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_json_schema() -> Result<(), Box<dyn std::error::Error>> {
+    let schema = Article::vtable()?.to_json_schema();
+
+    assert_eq!(schema["$schema"], json::JSON_SCHEMA_DIALECT);
+    assert_eq!(schema["$ref"], "#/$defs/Article");
+
+    let defs = schema["$defs"].as_object().ok_or("missing $defs")?;
+    for item in ["Article", "Metadata", "Revision", "Status"] {
+        assert!(defs.contains_key(item), "{item}");
+    }
+
+    let article = &schema["$defs"]["Article"];
+    assert_eq!(
+        article["properties"]["title"],
+        serde_json::json!({ "type": "string", "minLength": 1, "maxLength": 16 })
+    );
+    assert_eq!(
+        article["properties"]["rating"],
+        serde_json::json!({ "type": "integer", "minimum": 1, "maximum": 5 })
+    );
+    assert_eq!(
+        article["properties"]["revisions"],
+        serde_json::json!({ "type": "array", "items": { "$ref": "#/$defs/Revision" } })
+    );
+    assert_eq!(article["additionalProperties"], false);
+
+    // The field rules apply to the strings nested in the field.
+    let metadata = &schema["$defs"]["Metadata"];
+    assert_eq!(
+        metadata["properties"]["tags"]["items"]["pattern"],
+        "^[a-z]+$"
+    );
+    assert_eq!(
+        metadata["properties"]["labels"]["additionalProperties"]["maxLength"],
+        4
+    );
+    assert_eq!(metadata["required"], serde_json::json!(["tags", "labels"]));
+
+    assert_eq!(
+        schema["$defs"]["Status"]["oneOf"],
+        serde_json::json!([
+            { "const": "Draft" },
+            {
+                "type": "object",
+                "properties": {
+                    "Rejected": {
+                        "type": "object",
+                        "properties": { "reason": { "type": "string", "maxLength": 8 } },
+                        "required": ["reason"],
+                        "additionalProperties": false,
+                    },
+                },
+                "required": ["Rejected"],
+                "additionalProperties": false,
+            },
+        ])
+    );
+
+    let account = &Account::vtable()?.to_json_schema()["$defs"]["Account"]["properties"];
+    assert_eq!(
+        account["role"]["enum"],
+        serde_json::json!(["admin", "member", "guest"])
+    );
+    assert_eq!(account["tier"]["enum"], serde_json::json!([1, 2, 4]));
+    assert_eq!(account["email"]["format"], "email");
+    assert_eq!(account["created_at"]["format"], "date-time");
+    assert_eq!(account["groups"]["minItems"], 1);
+    assert_eq!(account["verified"], serde_json::json!({ "enum": [true] }));
+
+    let reading = &Reading::vtable()?.to_json_schema()["$defs"]["Reading"];
+    assert_eq!(
+        reading["properties"]["raw"],
+        serde_json::json!({ "type": "string", "contentEncoding": "base64" })
+    );
+    assert_eq!(reading["properties"]["_uuid"]["format"], "uuid");
+    assert_eq!(
+        reading["properties"]["note"],
+        serde_json::json!({ "anyOf": [{ "type": "string" }, { "type": "null" }] })
+    );
+    assert_eq!(
+        reading["properties"]["range"]["prefixItems"][1]["type"],
+        serde_json::json!(["integer", "string"])
+    );
+
+    Ok(())
+}
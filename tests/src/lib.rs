@@ -2,14 +2,33 @@
 #![allow(dead_code)]
 
 // pub mod benchmarks;
+pub mod arrays;
+pub mod borrowed;
+pub mod bufmap;
+pub mod compact;
 pub mod complex;
+pub mod crypto;
 #[cfg(feature = "db")]
 pub mod database;
+pub mod enums;
+pub mod envelope;
+pub mod evolution;
+pub mod framing;
+pub mod json;
+pub mod keyring;
+pub mod merkle;
+pub mod patch;
+pub mod path;
 pub mod process;
+pub mod registry;
 #[cfg(feature = "rpc")]
 pub mod rpc;
+pub mod signed;
 pub mod strings;
+pub mod structs;
 pub mod unsigned_integers;
+pub mod validation;
+pub mod value;
 pub mod vtable;
 
 use docbuf_core::traits::DocBuf;
@@ -55,7 +74,10 @@ trait TestHarness<'de>:
     fn assert_serialization_round_trip<'a>(
         self,
         buffer: &'a mut Vec<u8>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
+    ) -> Result<Self, Box<dyn std::error::Error>>
+    where
+        Self::Doc: serde::de::DeserializeOwned + Serialize,
+    {
         // Serialize
         self.to_docbuf(buffer)?;
 
@@ -66,7 +88,10 @@ trait TestHarness<'de>:
         // deserialize
         let doc = Self::from_docbuf(buffer)?;
 
-        assert_eq!(buffer.len(), 0);
+        // The buffer is left intact by deserialization.
+        assert_eq!(&docbuf, buffer);
+
+        buffer.clear();
 
         // Serialize again
         doc.to_docbuf(buffer)?;
@@ -79,6 +104,13 @@ trait TestHarness<'de>:
         // keys are serialized in a different order.
         assert_eq!(docbuf.len(), buffer.len());
 
+        // The canonical form is the same, regardless of the order of the map entries.
+        let (mut canonical, mut doc_canonical) = (Vec::new(), Vec::new());
+        self.to_canonical_docbuf(&mut canonical)?;
+        doc.to_canonical_docbuf(&mut doc_canonical)?;
+
+        assert_eq!(canonical, doc_canonical);
+
         Ok(self)
     }
 }
@@ -0,0 +1,171 @@
+use docbuf_core::{
+    crypto::{self, ed25519_dalek::SigningKey},
+    error::Error,
+    merkle::{self, DocBufRedacted},
+    signed,
+    traits::{DocBuf, DocBufCrypto},
+    value::Value,
+    vtable::{NumericValue, VTableFieldOffsetIndex},
+};
+
+use crate::crypto::Ledger;
+use crate::signed::Contract;
+
+/// Return the offset index of a field of an item of the contract vtable.
+fn index(item: &str, field: &str) -> Result<VTableFieldOffsetIndex, Error> {
+    Ok(Contract::vtable()?
+        .struct_by_name(item)?
+        .field_by_name(field)?
+        .offset_index())
+}
+
+#[test]
+fn test_merkle_disclose() -> Result<(), Error> {
+    let key = SigningKey::from_bytes(&[21; 32]);
+    let vtable = Contract::vtable()?;
+
+    let tree = Contract::dummy().commit_fields(&key)?;
+
+    // A leaf per offset of the document.
+    let mut buffer = Vec::new();
+    let offsets = Contract::dummy().to_docbuf(&mut buffer)?;
+    assert_eq!(tree.leaves().len(), offsets.len());
+
+    let redacted = tree.disclose(&[index("Contract", "id")?, index("Terms", "amount")?])?;
+    redacted.verify(vtable)?;
+
+    assert_eq!(
+        redacted.value(vtable, index("Contract", "id")?)?,
+        Value::Numeric(NumericValue::U64(1))
+    );
+    assert_eq!(
+        redacted.value(vtable, index("Terms", "amount")?)?,
+        Value::Numeric(NumericValue::U64(500))
+    );
+
+    // Hidden fields are not disclosed.
+    assert!(matches!(
+        redacted.value(vtable, index("Contract", "memo")?),
+        Err(Error::Merkle(merkle::Error::FieldNotDisclosed(_)))
+    ));
+
+    // The redacted document is verified after being sent.
+    let json = serde_json::to_vec(&redacted).expect("serialize redacted document");
+    let received: DocBufRedacted = serde_json::from_slice(&json).expect("parse redacted document");
+
+    received.verify(vtable)?;
+
+    // Every field of the tree has a valid proof.
+    let indices = tree
+        .leaves()
+        .iter()
+        .map(|leaf| leaf.index)
+        .collect::<Vec<_>>();
+
+    let redacted = tree.disclose(&indices)?;
+    redacted.verify(vtable)?;
+
+    assert_eq!(
+        redacted.value(vtable, index("Contract", "memo")?)?,
+        Value::from("draft")
+    );
+
+    // Fields are salted, the same document commits to a different root.
+    assert_ne!(Contract::dummy().commit_fields(&key)?.root(), tree.root());
+
+    Ok(())
+}
+
+#[test]
+fn test_merkle_errors() -> Result<(), Error> {
+    let key = SigningKey::from_bytes(&[21; 32]);
+    let vtable = Contract::vtable()?;
+
+    let tree = Contract::dummy().commit_fields(&key)?;
+    let redacted = tree.disclose(&[index("Contract", "memo")?, index("Terms", "note")?])?;
+
+    let invalid_proof = |redacted: &DocBufRedacted| {
+        matches!(
+            redacted.verify(vtable),
+            Err(Error::Merkle(merkle::Error::InvalidProof(_)))
+        )
+    };
+
+    let mut tampered = redacted.clone();
+    tampered.fields[0].leaf.data = b"\x05\x00\x00\x00final".to_vec();
+    assert!(invalid_proof(&tampered));
+
+    let mut tampered = redacted.clone();
+    tampered.fields[1].leaf.salt = [0; merkle::MERKLE_SALT_SIZE];
+    assert!(invalid_proof(&tampered));
+
+    let mut tampered = redacted.clone();
+    tampered.fields[1].position += 1;
+    assert!(invalid_proof(&tampered));
+
+    // A leaf cannot be disclosed as another field.
+    let mut tampered = redacted.clone();
+    tampered.fields[0].leaf.index = index("Contract", "id")?;
+    assert!(invalid_proof(&tampered));
+
+    // The root is signed.
+    let other = Contract::dummy().commit_fields(&key)?;
+    let tampered = DocBufRedacted {
+        root: other.root(),
+        ..redacted.clone()
+    };
+    assert!(matches!(
+        tampered.verify(vtable),
+        Err(Error::Merkle(merkle::Error::Crypto(
+            crypto::Error::VerificationFailed
+        )))
+    ));
+
+    assert!(matches!(
+        redacted.verify(Ledger::vtable()?),
+        Err(Error::Merkle(merkle::Error::VTableIdMismatch { .. }))
+    ));
+
+    // Only the fields of the document can be disclosed.
+    assert!(matches!(
+        tree.disclose(&[(index("Contract", "id")?.0, 100)]),
+        Err(merkle::Error::FieldNotCommitted(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_merkle_signed_message() -> Result<(), Error> {
+    let key = SigningKey::from_bytes(&[21; 32]);
+    let vtable = Contract::vtable()?;
+
+    let tree = Contract::dummy().commit_fields(&key)?;
+
+    // The root is signed with the merkle message tag.
+    let num_leaves = tree.leaves().len() as u32;
+    let message = tree.hash_algorithm.hash(&[
+        merkle::MERKLE_MESSAGE_TAG,
+        vtable.id().as_ref(),
+        &num_leaves.to_le_bytes(),
+        &tree.root(),
+    ]);
+
+    tree.signature_algorithm
+        .verify(&tree.public_key, &message, &tree.signature)?;
+
+    // Documents are signed with the signed message tag.
+    let mut buffer = Vec::new();
+    Contract::dummy().to_docbuf(&mut buffer)?;
+
+    let mut canonical = Vec::new();
+    vtable.canonical_signed_docbuf(&buffer, &mut canonical)?;
+
+    let message =
+        tree.hash_algorithm
+            .hash(&[signed::SIGNED_MESSAGE_TAG, vtable.id().as_ref(), &canonical]);
+
+    assert_eq!(Contract::dummy().signed_message()?, message);
+
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use docbuf_core::{
+    deps::uuid::Uuid,
+    error::Error,
+    traits::{DocBuf, DocBufMap},
+    vtable::{self, DocBufPatch, VTableFieldOffsetIndex},
+};
+
+use crate::bufmap::{Device, Owner, Position};
+
+/// Return the offset index of a field of an item of the device vtable.
+fn index(item: &str, field: &str) -> Result<VTableFieldOffsetIndex, Error> {
+    Ok(Device::vtable()?
+        .struct_by_name(item)?
+        .field_by_name(field)?
+        .offset_index())
+}
+
+#[test]
+fn test_patch_apply() -> Result<(), Error> {
+    let mut buffer = Vec::new();
+    let offsets = Device::dummy().to_docbuf(&mut buffer)?;
+
+    let vtable = Device::vtable()?;
+
+    let id = Uuid::from_bytes([5; 16]);
+    let position = Position {
+        label: "bay-4".to_string(),
+        x: 12,
+    };
+
+    let patch = DocBufPatch::new(vtable)
+        .replace(index("Device", "enabled")?, &false)?
+        .replace(index("Device", "id")?, &id)?
+        .replace(index("Device", "alias")?, &Some("gateway"))?
+        .replace(index("Device", "readings")?, &vec![7u32; 40])?
+        .replace(index("Device", "position")?, &position)?
+        .replace(index("Device", "owner")?, &None::<Owner>)?
+        // The last value of a field is kept.
+        .replace(index("Device", "alias")?, &Some("router"))?;
+
+    assert_eq!(patch.len(), 6);
+
+    let offsets = patch.apply(&mut buffer, &offsets)?;
+
+    let expected = Device {
+        id,
+        alias: Some("router".to_string()),
+        readings: vec![7; 40],
+        position,
+        owner: None,
+        enabled: false,
+        ..Device::dummy()
+    };
+
+    assert_eq!(Device::from_docbuf(&buffer)?, expected);
+
+    // The offsets match the offsets of the patched document.
+    let mut new_buffer = Vec::new();
+    let new_offsets = expected.to_docbuf(&mut new_buffer)?;
+
+    assert_eq!(buffer, new_buffer);
+    assert_eq!(offsets.to_vec(), new_offsets.to_vec());
+
+    let label: String = vtable.docbuf_map(
+        &buffer,
+        offsets
+            .offset(index("Position", "label")?)
+            .ok_or(Error::FieldNotFound)?,
+    )?;
+    assert_eq!(label, "bay-4");
+
+    Ok(())
+}
+
+#[test]
+fn test_patch_errors() -> Result<(), Error> {
+    let device = Device {
+        owner: None,
+        ..Device::dummy()
+    };
+
+    let mut buffer = Vec::new();
+    let offsets = device.to_docbuf(&mut buffer)?;
+
+    let vtable = Device::vtable()?;
+
+    // The field rules are checked as values are added.
+    assert!(matches!(
+        DocBufPatch::new(vtable).replace(index("Device", "readings")?, &Vec::<u32>::new()),
+        Err(vtable::Error::FieldRulesNotEmpty(_))
+    ));
+
+    // A struct and one of its fields overlap.
+    let patch = DocBufPatch::new(vtable)
+        .replace(index("Device", "class")?, &'X')?
+        .replace(index("Position", "label")?, &"aisle")?
+        .replace(index("Device", "position")?, &Position::default())?;
+
+    assert!(matches!(
+        patch.apply(&mut buffer, &offsets),
+        Err(vtable::Error::DocBufPatchOverlap(a, b)) if a == "position" && b == "label"
+    ));
+
+    // The fields of a `None` struct have no offsets.
+    let patch = DocBufPatch::new(vtable)
+        .replace(
+            index("Device", "tags")?,
+            &HashMap::from([("a".to_string(), 1u16)]),
+        )?
+        .replace(index("Owner", "name")?, &"nobody")?;
+
+    assert!(matches!(
+        patch.apply(&mut buffer, &offsets),
+        Err(vtable::Error::FieldNotFound)
+    ));
+
+    // The buffer is left intact.
+    assert_eq!(Device::from_docbuf(&buffer)?, device);
+
+    Ok(())
+}
@@ -0,0 +1,274 @@
+use docbuf_core::{
+    deps::uuid::Uuid,
+    error::Error,
+    traits::DocBuf,
+    value::Value,
+    vtable::{self, VTableFieldOffsets, VTableFieldPath, VTableFieldPathSegment},
+};
+
+use crate::validation::{Article, Metadata, Revision};
+use crate::value::{Counter, Location, Reading, SensorState};
+
+#[test]
+fn test_path_parse() -> Result<(), Error> {
+    let path = VTableFieldPath::parse(r#"metadata.labels["a.b"].tags[0]"#)?;
+
+    assert_eq!(
+        path.segments(),
+        &[
+            VTableFieldPathSegment::Field("metadata".to_string()),
+            VTableFieldPathSegment::Field("labels".to_string()),
+            VTableFieldPathSegment::Index("a.b".to_string()),
+            VTableFieldPathSegment::Field("tags".to_string()),
+            VTableFieldPathSegment::Index("0".to_string()),
+        ]
+    );
+    assert_eq!(path.to_string(), r#"metadata.labels["a.b"].tags[0]"#);
+
+    for invalid in [
+        "",
+        "metadata.",
+        "metadata..tags",
+        "tags[0",
+        "tags[]",
+        "tags[0]x",
+    ] {
+        assert!(
+            matches!(
+                VTableFieldPath::parse(invalid),
+                Err(vtable::Error::FieldPathInvalid(_))
+            ),
+            "{invalid}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_path_get() -> Result<(), Error> {
+    let mut buffer = Vec::new();
+    let offsets = Reading::dummy().to_docbuf(&mut buffer)?;
+
+    let vtable = Reading::vtable()?;
+
+    assert_eq!(
+        vtable.get::<String>(&buffer, &offsets, "sensor")?,
+        "thermo-1"
+    );
+    assert_eq!(
+        vtable.get::<Uuid>(&buffer, &offsets, "_uuid")?.as_bytes(),
+        &[7; 16]
+    );
+    assert_eq!(
+        vtable.get::<Option<String>>(&buffer, &offsets, "note")?,
+        None
+    );
+    assert_eq!(vtable.get::<u32>(&buffer, &offsets, "samples[1]")?, 2);
+    assert_eq!(vtable.get::<i64>(&buffer, &offsets, "labels[room]")?, -1);
+    assert_eq!(
+        vtable.get::<i64>(&buffer, &offsets, r#"labels["room"]"#)?,
+        -1
+    );
+    assert_eq!(vtable.get::<f64>(&buffer, &offsets, "position[1]")?, -2.25);
+    assert_eq!(vtable.get::<i128>(&buffer, &offsets, "range[1]")?, -10);
+    assert_eq!(
+        vtable.get::<String>(&buffer, &offsets, "location.site")?,
+        "lab"
+    );
+    assert_eq!(
+        vtable.get::<Option<u16>>(&buffer, &offsets, "location.floor")?,
+        Some(3)
+    );
+    assert_eq!(
+        vtable.get::<String>(&buffer, &offsets, "state.message")?,
+        "overheat"
+    );
+    assert_eq!(
+        vtable.get_value(&buffer, &offsets, "location")?.get("site"),
+        Some(&Value::from("lab"))
+    );
+
+    // The same values are found without the offsets of the document.
+    assert_eq!(
+        vtable.get::<String>(&buffer, &VTableFieldOffsets::new(), "state.message")?,
+        "overheat"
+    );
+
+    let mut buffer = Vec::new();
+    let offsets = Article::dummy().to_docbuf(&mut buffer)?;
+
+    let vtable = Article::vtable()?;
+
+    assert_eq!(
+        vtable.get::<String>(&buffer, &offsets, "metadata.tags[1]")?,
+        "docbuf"
+    );
+    assert_eq!(
+        vtable.get::<String>(&buffer, &offsets, "revisions[0].author")?,
+        "alice"
+    );
+    assert_eq!(
+        vtable.get::<Option<Vec<u8>>>(&buffer, &offsets, "metadata.checksum")?,
+        Some(vec![1, 2, 3, 4])
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_path_get_errors() -> Result<(), Error> {
+    let article = Article {
+        metadata: Metadata {
+            checksum: None,
+            ..Article::dummy().metadata
+        },
+        ..Article::dummy()
+    };
+
+    let mut buffer = Vec::new();
+    let offsets = article.to_docbuf(&mut buffer)?;
+
+    let vtable = Article::vtable()?;
+
+    assert!(matches!(
+        vtable.get_value(&buffer, &offsets, "metadata.author"),
+        Err(Error::VTable(vtable::Error::FieldPathUnknownField { path, field }))
+            if path == "metadata.author" && field == "author"
+    ));
+    assert!(matches!(
+        vtable.get_value(&buffer, &offsets, "revisions[1].author"),
+        Err(Error::VTable(vtable::Error::FieldPathIndexOutOfBounds { path, len: 1, .. }))
+            if path == "revisions[1]"
+    ));
+    assert!(matches!(
+        vtable.get_value(&buffer, &offsets, "metadata.labels[region]"),
+        Err(Error::VTable(vtable::Error::FieldPathKeyNotFound { key, .. })) if key == "region"
+    ));
+    assert!(matches!(
+        vtable.get_value(&buffer, &offsets, "metadata.checksum[0]"),
+        Err(Error::VTable(vtable::Error::FieldPathNone(path))) if path == "metadata.checksum"
+    ));
+    assert!(matches!(
+        vtable.get_value(&buffer, &offsets, "title.length"),
+        Err(Error::VTable(vtable::Error::FieldPathInvalidType { path, .. })) if path == "title.length"
+    ));
+
+    // The value must be of the requested type.
+    assert!(matches!(
+        vtable.get::<u16>(&buffer, &offsets, "rating"),
+        Err(Error::VTable(vtable::Error::InvalidValueType(..)))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_path_replace() -> Result<(), Error> {
+    let mut buffer = Vec::new();
+    let mut offsets = Reading::dummy().to_docbuf(&mut buffer)?;
+
+    let vtable = Reading::vtable()?;
+
+    vtable.replace(&mut buffer, &mut offsets, "location.site", "laboratory")?;
+    vtable.replace(&mut buffer, &mut offsets, "samples[1]", 20u32)?;
+    vtable.replace(&mut buffer, &mut offsets, "labels[room]", 7i64)?;
+    vtable.replace(&mut buffer, &mut offsets, "note", "moved")?;
+    vtable.replace(&mut buffer, &mut offsets, "location.floor", None::<u16>)?;
+    vtable.replace(&mut buffer, &mut offsets, "state.message", "cooling down")?;
+    let offset = vtable.replace(&mut buffer, &mut offsets, "sensor", "t-2")?;
+
+    let expected = Reading {
+        sensor: "t-2".to_string(),
+        note: Some("moved".to_string()),
+        samples: vec![1, 20, 3],
+        labels: [("room".to_string(), 7)].into(),
+        location: Location {
+            site: "laboratory".to_string(),
+            floor: None,
+        },
+        state: SensorState::Faulted {
+            code: 500,
+            message: "cooling down".to_string(),
+        },
+        ..Reading::dummy()
+    };
+
+    assert_eq!(Reading::from_docbuf(&buffer)?, expected);
+    assert_eq!(vtable.get::<String>(&buffer, &offsets, "sensor")?, "t-2");
+    assert_eq!(&buffer[offset.range()], b"t-2");
+
+    // The offsets are shifted with the buffer.
+    let mut new_buffer = Vec::new();
+    let new_offsets = expected.to_docbuf(&mut new_buffer)?;
+
+    assert_eq!(offsets.to_vec(), new_offsets.to_vec());
+
+    // The field rules of the new value are checked, leaving the buffer intact.
+    assert!(matches!(
+        vtable.replace(
+            &mut buffer,
+            &mut offsets,
+            "sensor",
+            "a-very-long-sensor-name"
+        ),
+        Err(Error::VTable(vtable::Error::FieldRulesLength(_)))
+    ));
+    assert_eq!(buffer, new_buffer);
+
+    let mut buffer = Vec::new();
+    let mut offsets = Article::dummy().to_docbuf(&mut buffer)?;
+
+    Article::vtable()?.replace(
+        &mut buffer,
+        &mut offsets,
+        "revisions[0]",
+        Value::Struct {
+            name: "Revision".to_string(),
+            fields: vec![("author".to_string(), Value::from("bob"))],
+        },
+    )?;
+
+    assert_eq!(
+        Article::from_docbuf(&buffer)?.revisions,
+        vec![Revision {
+            author: "bob".to_string(),
+        }]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_path_replace_compact() -> Result<(), Error> {
+    let counter = Counter {
+        count: 300,
+        delta: -2,
+        names: vec!["a".to_string(), "b".to_string()],
+    };
+
+    let mut buffer = Vec::new();
+    let mut offsets = counter.to_docbuf(&mut buffer)?;
+
+    let vtable = Counter::vtable()?;
+
+    // The new name needs a two byte length prefix.
+    let name = "n".repeat(200);
+    vtable.replace(&mut buffer, &mut offsets, "names[0]", name.as_str())?;
+    vtable.replace(&mut buffer, &mut offsets, "count", 70000u64)?;
+
+    let expected = Counter {
+        count: 70000,
+        names: vec![name, "b".to_string()],
+        ..counter
+    };
+
+    assert_eq!(Counter::from_docbuf(&buffer)?, expected);
+
+    let mut new_buffer = Vec::new();
+    let new_offsets = expected.to_docbuf(&mut new_buffer)?;
+
+    assert_eq!(offsets.to_vec(), new_offsets.to_vec());
+
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use crate::value::{Location, Reading};
+
+use docbuf_core::{
+    error::Error,
+    registry::{self, DocBufRegistry},
+    traits::DocBuf,
+    value::Value,
+};
+
+mod v1 {
+    use docbuf_macros::*;
+    use serde::{Deserialize, Serialize};
+
+    #[docbuf {
+        namespace = "registry";
+    }]
+    #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+    pub struct Order {
+        pub item: String,
+    }
+}
+
+mod v2 {
+    use docbuf_macros::*;
+    use serde::{Deserialize, Serialize};
+
+    #[docbuf {
+        namespace = "registry";
+    }]
+    #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+    pub struct Order {
+        pub item: String,
+        pub quantity: u32,
+    }
+}
+
+mod copy {
+    use docbuf_macros::*;
+    use serde::{Deserialize, Serialize};
+
+    // An identical copy of `v1::Order`, with the same vtable id.
+    #[docbuf {
+        namespace = "registry";
+    }]
+    #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+    pub struct Order {
+        pub item: String,
+    }
+}
+
+#[test]
+fn test_registry_lookup() -> Result<(), Error> {
+    let registry = DocBufRegistry::new();
+
+    let id = registry.register::<Reading>()?;
+    registry.register::<Location>()?;
+
+    // Registering a type again is a no-op.
+    assert_eq!(registry.register::<Reading>()?, id);
+
+    assert_eq!(&id, Reading::vtable()?.id());
+
+    let registration = registry.get(&id)?;
+    assert_eq!(registration.name, "value::Reading");
+    assert!(registration.is::<Reading>());
+    assert!(!registration.is::<Location>());
+
+    assert_eq!(registry.get_by_name("value::Reading")?.id, id);
+
+    // Registrations are enumerated by name.
+    let names = registry
+        .registrations()?
+        .iter()
+        .map(|registration| registration.name.to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["value::Location", "value::Reading"]);
+
+    assert!(matches!(
+        registry.get(v1::Order::vtable()?.id()),
+        Err(Error::Registry(registry::Error::IdNotRegistered(_)))
+    ));
+    assert!(matches!(
+        registry.get_by_name("registry::Order"),
+        Err(Error::Registry(registry::Error::NameNotRegistered(_)))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_registry_decode() -> Result<(), Error> {
+    let registry = DocBufRegistry::new();
+    registry.register::<Reading>()?;
+    registry.register::<v1::Order>()?;
+
+    let reading = Reading::dummy();
+
+    let mut buffer = Vec::new();
+    reading.to_enveloped(&mut buffer)?;
+
+    // Dispatch by the vtable id of the envelope.
+    let (registration, doc) = registry.decode_enveloped(&buffer)?;
+    assert!(registration.is::<Reading>());
+    assert_eq!(doc.downcast_ref::<Reading>(), Some(&reading));
+
+    let mut buffer = Vec::new();
+    let order = v1::Order {
+        item: "lamp".to_string(),
+    };
+    order.to_docbuf(&mut buffer)?;
+
+    let id = v1::Order::vtable()?.id();
+    let doc = registry.decode(id, &buffer)?;
+    assert_eq!(doc.downcast_ref::<v1::Order>(), Some(&order));
+
+    let value = registry.decode_value(id, &buffer)?;
+    assert_eq!(value.get("item"), Some(&Value::from("lamp")));
+
+    // Invalid documents are rejected.
+    assert!(registry.get(id)?.validate(&buffer[..2]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_registry_collisions() -> Result<(), Error> {
+    let registry = DocBufRegistry::new();
+    let id = registry.register::<v1::Order>()?;
+
+    // Another type with the same vtable.
+    assert_eq!(copy::Order::vtable()?.id(), &id);
+    assert!(matches!(
+        registry.register::<copy::Order>(),
+        Err(Error::Registry(registry::Error::IdCollision { id: found, .. })) if found == id
+    ));
+
+    // Another version of the document, with the same name.
+    assert!(matches!(
+        registry.register::<v2::Order>(),
+        Err(Error::Registry(registry::Error::NameCollision { name, .. })) if name == "registry::Order"
+    ));
+
+    assert_eq!(registry.registrations()?.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_registry_global() -> Result<(), Error> {
+    let id = registry::register::<Location>()?;
+
+    assert!(DocBufRegistry::global().get(&id)?.is::<Location>());
+
+    Ok(())
+}
@@ -0,0 +1,501 @@
+use std::collections::HashMap;
+
+use docbuf_core::{
+    crypto::{
+        self, ed25519_dalek::SigningKey, k256, p256, DocBufSigner, HashAlgorithm,
+        SignatureAlgorithm,
+    },
+    error::Error,
+    signed::{self, DocBufSignature},
+    traits::{DocBuf, DocBufCrypto},
+};
+use docbuf_macros::*;
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::Ledger;
+
+#[docbuf {
+    namespace = "signed";
+    sign = true;
+    crypto = "ed25519";
+    hash = "sha256";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Contract {
+    #[docbuf {
+        sign = true;
+    }]
+    pub id: u64,
+    #[docbuf {
+        sign = true;
+    }]
+    pub parties: Vec<String>,
+    pub terms: Terms,
+    pub memo: String,
+    pub attachments: HashMap<String, u32>,
+}
+
+#[docbuf {
+    namespace = "signed";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Terms {
+    #[docbuf {
+        sign = true;
+    }]
+    pub amount: u64,
+    pub note: String,
+}
+
+#[docbuf {
+    namespace = "signed";
+    sign = true;
+    crypto = "p256";
+    hash = "sha3-256";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Receipt {
+    #[docbuf {
+        sign = true;
+    }]
+    pub id: u64,
+    pub memo: String,
+}
+
+#[docbuf {
+    namespace = "signed";
+    sign = true;
+    crypto = "secp256k1";
+    hash = "blake3";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Transfer {
+    #[docbuf {
+        sign = true;
+    }]
+    pub id: u64,
+    pub memo: String,
+}
+
+#[docbuf {
+    namespace = "signed";
+    sign = true;
+    hash = "sha512";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Invoice {
+    #[docbuf {
+        sign = true;
+    }]
+    pub id: u64,
+    pub memo: String,
+}
+
+#[docbuf {
+    namespace = "signed";
+    sign = true;
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Order {
+    #[docbuf {
+        sign = true;
+    }]
+    pub id: u64,
+    pub lines: Vec<Line>,
+    pub extras: HashMap<String, Line>,
+}
+
+#[docbuf {
+    namespace = "signed";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Line {
+    #[docbuf {
+        sign = true;
+    }]
+    pub amount: u64,
+    pub note: String,
+}
+
+impl Line {
+    pub fn new(amount: u64, note: &str) -> Self {
+        Self {
+            amount,
+            note: note.to_string(),
+        }
+    }
+}
+
+impl Order {
+    pub fn dummy() -> Self {
+        Self {
+            id: 1,
+            lines: vec![Line::new(10, "first"), Line::new(20, "second")],
+            extras: (0..4)
+                .map(|i| (format!("extra-{i}"), Line::new(i, "extra")))
+                .collect(),
+        }
+    }
+}
+
+impl Contract {
+    pub fn dummy() -> Self {
+        Self {
+            id: 1,
+            parties: vec!["alice".to_string(), "bob".to_string()],
+            terms: Terms {
+                amount: 500,
+                note: "net 30".to_string(),
+            },
+            memo: "draft".to_string(),
+            attachments: (0..8).map(|i| (format!("page-{i}"), i)).collect(),
+        }
+    }
+}
+
+/// Return the signed document buffer of the header and a new body.
+fn with_body(signature: &DocBufSignature, body: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut buffer = Vec::new();
+
+    DocBufSignature {
+        body_len: body.len() as u32,
+        ..signature.clone()
+    }
+    .write_to_buffer(&mut buffer)?;
+
+    buffer.extend_from_slice(body);
+
+    Ok(buffer)
+}
+
+#[test]
+fn test_signed_round_trip() -> Result<(), Error> {
+    let key = SigningKey::from_bytes(&[11; 32]);
+
+    let mut buffer = Vec::new();
+    Contract::dummy().to_signed_docbuf(&key, &mut buffer)?;
+
+    assert_eq!(Contract::from_signed_docbuf(&buffer)?, Contract::dummy());
+
+    let (signature, body) = DocBufSignature::read_from_buffer(&buffer)?;
+
+    assert_eq!(signature.signature_algorithm, SignatureAlgorithm::Ed25519);
+    assert_eq!(signature.hash_algorithm, HashAlgorithm::Sha256);
+    assert_eq!(&signature.vtable_id, Contract::vtable()?.id());
+    assert_eq!(signature.public_key, key.verifying_key().to_bytes());
+
+    // The body is the document buffer of the contract, and its canonical form
+    // is the canonical form of the contract.
+    assert_eq!(Contract::from_docbuf(body)?, Contract::dummy());
+
+    let (mut canonical, mut canonical_body) = (Vec::new(), Vec::new());
+    Contract::dummy().to_canonical_docbuf(&mut canonical)?;
+    Contract::vtable()?.canonical_docbuf(body, &mut canonical_body)?;
+
+    assert_eq!(canonical, canonical_body);
+
+    // Documents without signed fields are covered as a whole.
+    Ledger::dummy().to_signed_docbuf(&key, &mut buffer)?;
+    assert_eq!(Ledger::from_signed_docbuf(&buffer)?, Ledger::dummy());
+
+    Ok(())
+}
+
+#[test]
+fn test_signed_fields() -> Result<(), Error> {
+    let key = SigningKey::from_bytes(&[11; 32]);
+
+    let mut buffer = Vec::new();
+    Contract::dummy().to_signed_docbuf(&key, &mut buffer)?;
+
+    let (signature, _) = DocBufSignature::read_from_buffer(&buffer)?;
+
+    // Fields without the `sign` rule are not covered.
+    let unsigned = Contract {
+        memo: "final".to_string(),
+        attachments: HashMap::new(),
+        terms: Terms {
+            note: "net 60".to_string(),
+            ..Contract::dummy().terms
+        },
+        ..Contract::dummy()
+    };
+
+    let mut body = Vec::new();
+    unsigned.to_docbuf(&mut body)?;
+
+    assert_eq!(
+        Contract::from_signed_docbuf(&with_body(&signature, &body)?)?,
+        unsigned
+    );
+
+    // Signed fields, including the fields nested in unsigned structs, are covered.
+    for signed in [
+        Contract {
+            id: 2,
+            ..Contract::dummy()
+        },
+        Contract {
+            parties: vec!["alice".to_string()],
+            ..Contract::dummy()
+        },
+        Contract {
+            terms: Terms {
+                amount: 5000,
+                ..Contract::dummy().terms
+            },
+            ..Contract::dummy()
+        },
+    ] {
+        signed.to_docbuf(&mut body)?;
+
+        assert!(matches!(
+            Contract::from_signed_docbuf(&with_body(&signature, &body)?),
+            Err(Error::Signed(signed::Error::Crypto(
+                crypto::Error::VerificationFailed
+            )))
+        ));
+    }
+
+    // The detached signatures cover the same fields.
+    let mut digest = crypto::sha2::Sha256::default();
+    let detached = Contract::dummy().sign(&mut digest, key.clone())?;
+
+    unsigned.verify(&mut digest, &detached, key.verifying_key())?;
+
+    assert_eq!(detached.to_bytes().to_vec(), signature.signature);
+
+    Ok(())
+}
+
+#[test]
+fn test_signed_nested_fields() -> Result<(), Error> {
+    let key = SigningKey::from_bytes(&[11; 32]);
+
+    let mut digest = crypto::sha2::Sha256::default();
+    let detached = Order::dummy().sign(&mut digest, key.clone())?;
+
+    // Unsigned fields of the structs in lists and maps are not covered.
+    let mut unsigned = Order::dummy();
+    unsigned.lines[0].note = "changed".to_string();
+    unsigned
+        .extras
+        .values_mut()
+        .for_each(|line| line.note = "changed".to_string());
+
+    unsigned.verify(&mut digest, &detached, key.verifying_key())?;
+
+    // Signed fields nested in lists and maps are covered, as are the lengths of
+    // the lists and maps.
+    let mut signed = Vec::new();
+
+    let mut order = Order::dummy();
+    order.lines[1].amount = 999_999;
+    signed.push(order);
+
+    let mut order = Order::dummy();
+    order.lines.pop();
+    signed.push(order);
+
+    let mut order = Order::dummy();
+    order.extras.get_mut("extra-2").expect("extra").amount = 999_999;
+    signed.push(order);
+
+    let mut order = Order::dummy();
+    order.extras.remove("extra-0");
+    signed.push(order);
+
+    for order in signed {
+        assert!(order
+            .verify(&mut digest, &detached, key.verifying_key())
+            .is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_signed_errors() -> Result<(), Error> {
+    let key = SigningKey::from_bytes(&[11; 32]);
+
+    let mut buffer = Vec::new();
+    Contract::dummy().to_signed_docbuf(&key, &mut buffer)?;
+
+    // The signature is verified with the vtable of the document.
+    assert!(matches!(
+        Ledger::from_signed_docbuf(&buffer),
+        Err(Error::Signed(signed::Error::VTableIdMismatch { .. }))
+    ));
+
+    let mut invalid = buffer.clone();
+    invalid[0] = b'X';
+    assert!(matches!(
+        Contract::from_signed_docbuf(&invalid),
+        Err(Error::Signed(signed::Error::InvalidMagic(_)))
+    ));
+
+    let mut invalid = buffer.clone();
+    invalid[5] = 9;
+    assert!(matches!(
+        Contract::from_signed_docbuf(&invalid),
+        Err(Error::Signed(signed::Error::Crypto(
+            crypto::Error::UnknownSignatureAlgorithm(9)
+        )))
+    ));
+
+    // The signature is checked, rather than the public key being trusted.
+    let (signature, body) = DocBufSignature::read_from_buffer(&buffer)?;
+    let other = SigningKey::from_bytes(&[12; 32]);
+
+    let forged = DocBufSignature {
+        public_key: other.verifying_key().to_bytes().to_vec(),
+        ..signature
+    };
+
+    assert!(matches!(
+        Contract::from_signed_docbuf(&with_body(&forged, body)?),
+        Err(Error::Signed(signed::Error::Crypto(
+            crypto::Error::VerificationFailed
+        )))
+    ));
+
+    assert!(matches!(
+        Contract::from_signed_docbuf(&buffer[..buffer.len() - 1]),
+        Err(Error::Signed(signed::Error::BodyLengthMismatch { .. }))
+    ));
+
+    Ok(())
+}
+
+/// Sign the document with the signer, checking the algorithms of the signed
+/// document buffer and the detached signatures of the document.
+fn check_algorithms<T>(
+    doc: T,
+    changed: T,
+    signer: &impl DocBufSigner,
+    signature_algorithm: SignatureAlgorithm,
+    hash_algorithm: HashAlgorithm,
+) -> Result<(), Error>
+where
+    T: DocBufCrypto<Doc = T> + Serialize + for<'de> Deserialize<'de> + PartialEq + std::fmt::Debug,
+{
+    let mut buffer = Vec::new();
+    doc.to_signed_docbuf(signer, &mut buffer)?;
+
+    assert_eq!(buffer[5], signature_algorithm.id());
+    assert_eq!(buffer[6], hash_algorithm.id());
+    assert_eq!(T::from_signed_docbuf(&buffer)?, doc);
+
+    // Verifiers select the algorithms from the signed document buffer.
+    let (signature, body) = DocBufSignature::read_from_buffer(&buffer)?;
+
+    assert_eq!(signature.signature_algorithm, signature_algorithm);
+    assert_eq!(signature.hash_algorithm, hash_algorithm);
+    assert_eq!(signature.public_key, signer.public_key());
+
+    signature.verify(T::vtable()?, body)?;
+
+    // Detached signatures are the signatures of signed document buffers.
+    let detached = doc.sign_detached(signer)?;
+    assert_eq!(detached, signature.signature);
+
+    doc.verify_detached(&signer.public_key(), &detached)?;
+
+    assert!(matches!(
+        changed.verify_detached(&signer.public_key(), &detached),
+        Err(Error::Crypto(crypto::Error::VerificationFailed))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_signed_algorithms() -> Result<(), Error> {
+    let key = SigningKey::from_bytes(&[11; 32]);
+    let p256_key = p256::ecdsa::SigningKey::from_slice(&[13; 32]).expect("p256 key");
+    let k256_key = k256::ecdsa::SigningKey::from_slice(&[14; 32]).expect("k256 key");
+
+    check_algorithms(
+        Receipt {
+            id: 1,
+            memo: "paid".to_string(),
+        },
+        Receipt::default(),
+        &p256_key,
+        SignatureAlgorithm::EcdsaP256,
+        HashAlgorithm::Sha3_256,
+    )?;
+
+    check_algorithms(
+        Transfer {
+            id: 1,
+            memo: "sent".to_string(),
+        },
+        Transfer::default(),
+        &k256_key,
+        SignatureAlgorithm::EcdsaSecp256k1,
+        HashAlgorithm::Blake3,
+    )?;
+
+    check_algorithms(
+        Invoice {
+            id: 1,
+            memo: "due".to_string(),
+        },
+        Invoice::default(),
+        &key,
+        SignatureAlgorithm::Ed25519,
+        HashAlgorithm::Sha512,
+    )?;
+
+    // Documents are signed with the signature algorithm of the document.
+    let mut buffer = Vec::new();
+    assert!(matches!(
+        Receipt::default().to_signed_docbuf(&key, &mut buffer),
+        Err(Error::Signed(signed::Error::SignatureAlgorithmMismatch {
+            expected: SignatureAlgorithm::EcdsaP256,
+            found: SignatureAlgorithm::Ed25519,
+        }))
+    ));
+
+    Transfer::default().to_signed_docbuf(&k256_key, &mut buffer)?;
+    assert!(matches!(
+        Receipt::from_signed_docbuf(&buffer),
+        Err(Error::Signed(
+            signed::Error::SignatureAlgorithmMismatch { .. }
+        ))
+    ));
+
+    // Malleable ECDSA signatures are rejected.
+    let signature =
+        p256::ecdsa::Signature::from_slice(&Receipt::default().sign_detached(&p256_key)?)
+            .expect("p256 signature");
+    let malleable = p256::ecdsa::Signature::from_scalars(*signature.r(), -*signature.s())
+        .expect("p256 signature");
+
+    assert!(matches!(
+        Receipt::default().verify_detached(&p256_key.public_key(), &malleable.to_vec()),
+        Err(Error::Crypto(crypto::Error::InvalidSignature(
+            SignatureAlgorithm::EcdsaP256
+        )))
+    ));
+
+    for algorithm in [
+        SignatureAlgorithm::Ed25519,
+        SignatureAlgorithm::EcdsaP256,
+        SignatureAlgorithm::EcdsaSecp256k1,
+    ] {
+        assert_eq!(SignatureAlgorithm::from_id(algorithm.id())?, algorithm);
+    }
+
+    for algorithm in [
+        HashAlgorithm::Sha256,
+        HashAlgorithm::Sha512,
+        HashAlgorithm::Sha3_256,
+        HashAlgorithm::Blake3,
+    ] {
+        assert_eq!(HashAlgorithm::from_id(algorithm.id())?, algorithm);
+    }
+
+    Ok(())
+}
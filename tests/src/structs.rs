@@ -0,0 +1,187 @@
+use crate::{SetTestValues, TestHarness};
+
+use docbuf_core::{
+    error::Error,
+    traits::DocBuf,
+    vtable::{DocBufReader, VTable, VTableFieldType},
+};
+use docbuf_macros::*;
+use serde::{Deserialize, Serialize};
+
+#[docbuf {
+    namespace = "structs";
+}]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq)]
+pub struct AccountId(pub u64);
+
+#[docbuf {
+    namespace = "structs";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Point(pub i32, pub i32);
+
+#[docbuf {
+    namespace = "structs";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Marker;
+
+#[docbuf {
+    namespace = "structs";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Account {
+    pub id: AccountId,
+    pub origin: Point,
+    pub marker: Marker,
+    pub initial: char,
+    pub nothing: (),
+    pub owner: Option<AccountId>,
+    pub friends: Vec<AccountId>,
+    pub name: String,
+}
+
+// Lists of values encoded as zero bytes.
+#[docbuf {
+    namespace = "structs";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Markers {
+    pub markers: Vec<Marker>,
+    pub nothings: Vec<()>,
+}
+
+#[docbuf {
+    namespace = "structs";
+    encoding = "compact";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Glyph(pub char, pub u32);
+
+impl Account {
+    pub fn dummy() -> Self {
+        Self {
+            id: AccountId(42),
+            origin: Point(-1, 2),
+            marker: Marker,
+            initial: 'ß',
+            nothing: (),
+            owner: Some(AccountId(7)),
+            friends: vec![AccountId(1), AccountId(2)],
+            name: "Alice".to_string(),
+        }
+    }
+}
+
+impl SetTestValues for Account {}
+
+impl<'de> TestHarness<'de> for Account {}
+
+#[test]
+fn test_structs_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let mut buffer = Vec::new();
+    Account::dummy().assert_serialization_round_trip(&mut buffer)?;
+
+    let account = Account {
+        initial: '🦀',
+        owner: None,
+        friends: vec![],
+        ..Account::dummy()
+    };
+
+    account.to_docbuf(&mut buffer)?;
+    assert_eq!(Account::from_docbuf(&buffer)?, account);
+
+    Ok(())
+}
+
+#[test]
+fn test_structs_root_documents() -> Result<(), Error> {
+    let mut buffer = Vec::new();
+
+    // Newtype structs encode their inner value.
+    AccountId(u64::MAX).to_docbuf(&mut buffer)?;
+    assert_eq!(buffer, u64::MAX.to_le_bytes());
+    assert_eq!(AccountId::from_docbuf(&buffer)?, AccountId(u64::MAX));
+
+    // Tuple structs encode their fields in order.
+    let offsets = Point(-1, 2).to_docbuf(&mut buffer)?;
+    assert_eq!(buffer, [(-1i32).to_le_bytes(), 2i32.to_le_bytes()].concat());
+    assert_eq!(offsets.len(), 2);
+    assert_eq!(Point::from_docbuf(&buffer)?, Point(-1, 2));
+
+    // Unit structs are empty.
+    Marker.to_docbuf(&mut buffer)?;
+    assert!(buffer.is_empty());
+    assert_eq!(Marker::from_docbuf(&buffer)?, Marker);
+
+    // Chars are encoded as their unicode scalar value.
+    let glyph = Glyph('é', 300);
+    glyph.to_docbuf(&mut buffer)?;
+    assert_eq!(buffer, vec![0xe9, 0x01, 0xac, 0x02]);
+    assert_eq!(Glyph::from_docbuf(&buffer)?, glyph);
+
+    // Invalid scalar values are rejected.
+    assert!(Glyph::from_docbuf(&[0x80, 0xb0, 0x03, 0x00]).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_structs_zero_size_elements() -> Result<(), Error> {
+    let markers = Markers {
+        markers: vec![Marker; 3],
+        nothings: vec![(); 2],
+    };
+
+    // Only the lengths of the lists are encoded.
+    let mut buffer = Vec::new();
+    markers.to_docbuf(&mut buffer)?;
+    assert_eq!(buffer, [3u32.to_le_bytes(), 2u32.to_le_bytes()].concat());
+
+    assert_eq!(Markers::from_docbuf(&buffer)?, markers);
+
+    let vtable = Markers::vtable()?;
+    assert_eq!(vtable.encode_value(&vtable.decode_value(&buffer)?)?, buffer);
+
+    let mut canonical = Vec::new();
+    vtable.canonical_docbuf(&buffer, &mut canonical)?;
+    assert_eq!(canonical, buffer);
+
+    Ok(())
+}
+
+#[test]
+fn test_structs_vtable() -> Result<(), Error> {
+    let vtable = Account::vtable()?;
+
+    // Unnamed fields are named by their position.
+    let point = vtable.struct_by_name("Point")?;
+    assert_eq!(point.num_fields, 2);
+    assert_eq!(point.field_by_index(&0)?.name, "0");
+    assert_eq!(point.field_by_index(&1)?.name, "1");
+    assert_eq!(point.field_by_name("1")?.r#type, VTableFieldType::I32);
+
+    let account_id = vtable.struct_by_name("AccountId")?;
+    assert_eq!(account_id.field_by_name("0")?.r#type, VTableFieldType::U64);
+
+    assert_eq!(vtable.struct_by_name("Marker")?.num_fields, 0);
+
+    let account = vtable.struct_by_name("Account")?;
+    assert_eq!(
+        account.field_by_name("initial")?.r#type,
+        VTableFieldType::Char
+    );
+    assert_eq!(
+        account.field_by_name("nothing")?.r#type,
+        VTableFieldType::Unit
+    );
+
+    // The new field types survive the vtable round trip.
+    let bytes = vtable.to_vec()?;
+    let decoded = VTable::read_from_buffer(&mut DocBufReader::new(&bytes))?;
+
+    assert_eq!(&decoded, vtable);
+
+    Ok(())
+}
@@ -0,0 +1,563 @@
+use std::collections::HashMap;
+
+use docbuf_core::{
+    error::Error,
+    traits::DocBuf,
+    validate::{ValidationError, ValidationRule},
+    vtable::{self, DocBufReader, NumericValue, VTable, VTableFieldFormat, VTableFieldWhitespace},
+};
+use docbuf_macros::*;
+use serde::{Deserialize, Serialize};
+
+#[docbuf {
+    namespace = "validation";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Article {
+    #[docbuf {
+        min_length = 1;
+        max_length = 16;
+    }]
+    pub title: String,
+    #[docbuf {
+        min_value = 1;
+        max_value = 5;
+    }]
+    pub rating: u8,
+    pub metadata: Metadata,
+    pub revisions: Vec<Revision>,
+    pub status: Status,
+}
+
+#[docbuf {
+    namespace = "validation";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Metadata {
+    #[docbuf {
+        regex = r"^[a-z]+$";
+    }]
+    pub tags: Vec<String>,
+    #[docbuf {
+        max_length = 4;
+    }]
+    pub labels: HashMap<String, String>,
+    #[docbuf {
+        length = 4;
+    }]
+    #[serde(with = "serde_bytes")]
+    pub checksum: Option<Vec<u8>>,
+}
+
+#[docbuf {
+    namespace = "validation";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Revision {
+    #[docbuf {
+        max_length = 8;
+    }]
+    pub author: String,
+}
+
+#[docbuf {
+    namespace = "validation";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub enum Status {
+    #[default]
+    Draft,
+    Rejected {
+        #[docbuf {
+            max_length = 8;
+        }]
+        reason: String,
+    },
+}
+
+impl Article {
+    pub fn dummy() -> Self {
+        Self {
+            title: "Validation".to_string(),
+            rating: 4,
+            metadata: Metadata {
+                tags: vec!["rust".to_string(), "docbuf".to_string()],
+                labels: HashMap::from([("lang".to_string(), "en".to_string())]),
+                checksum: Some(vec![1, 2, 3, 4]),
+            },
+            revisions: vec![Revision {
+                author: "alice".to_string(),
+            }],
+            status: Status::Draft,
+        }
+    }
+}
+
+#[test]
+fn test_validation_valid_document() -> Result<(), Box<dyn std::error::Error>> {
+    let article = Article::dummy();
+
+    assert!(article.validate().is_ok());
+
+    let article = Article {
+        metadata: Metadata {
+            checksum: None,
+            ..Article::dummy().metadata
+        },
+        status: Status::Rejected {
+            reason: "spam".to_string(),
+        },
+        ..Article::dummy()
+    };
+
+    assert!(article.validate().is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn test_validation_report() -> Result<(), Box<dyn std::error::Error>> {
+    let article = Article {
+        title: String::new(),
+        rating: 9,
+        metadata: Metadata {
+            tags: vec![
+                "rust".to_string(),
+                "docbuf".to_string(),
+                "ok".to_string(),
+                "Not Lowercase".to_string(),
+            ],
+            labels: HashMap::from([("lang".to_string(), "english".to_string())]),
+            checksum: Some(vec![1, 2]),
+        },
+        revisions: vec![
+            Revision {
+                author: "alice".to_string(),
+            },
+            Revision {
+                author: "bartholomew".to_string(),
+            },
+        ],
+        status: Status::Rejected {
+            reason: "not relevant".to_string(),
+        },
+    };
+
+    let report = article.validate().expect_err("expected violations");
+
+    // Every violation is collected, rather than stopping at the first.
+    assert_eq!(report.errors.len(), 7, "{report}");
+
+    let error = |path: &str| -> ValidationError {
+        report
+            .errors_at(path)
+            .next()
+            .cloned()
+            .unwrap_or_else(|| panic!("expected a violation at {path}: {report}"))
+    };
+
+    assert_eq!(error("title").rule, ValidationRule::MinLength);
+    assert_eq!(error("title").value, "");
+
+    assert_eq!(error("rating").rule, ValidationRule::MaxValue);
+    assert_eq!(error("rating").value, "9");
+
+    assert_eq!(error("metadata.tags[3]").rule, ValidationRule::Regex);
+    assert_eq!(error("metadata.tags[3]").value, "Not Lowercase");
+
+    assert_eq!(
+        error("metadata.labels[\"lang\"]").rule,
+        ValidationRule::MaxLength
+    );
+    assert_eq!(error("metadata.labels[\"lang\"]").value, "english");
+
+    assert_eq!(error("metadata.checksum").rule, ValidationRule::Length);
+    assert_eq!(error("metadata.checksum").value, "0102");
+
+    assert_eq!(error("revisions[1].author").rule, ValidationRule::MaxLength);
+    assert_eq!(error("revisions[1].author").value, "bartholomew");
+
+    assert_eq!(
+        error("status.Rejected.reason").rule,
+        ValidationRule::MaxLength
+    );
+
+    // The same document is rejected when encoded.
+    let mut buffer = Vec::new();
+    assert!(article.to_docbuf(&mut buffer).is_err());
+
+    Ok(())
+}
+
+#[docbuf {
+    namespace = "validation";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Account {
+    #[docbuf {
+        one_of = ["admin", "member", "guest"];
+    }]
+    pub role: String,
+    #[docbuf {
+        one_of = [1, 2, 4];
+    }]
+    pub tier: u8,
+    #[docbuf {
+        format = "email";
+    }]
+    pub email: String,
+    #[docbuf {
+        format = "uri";
+    }]
+    pub homepage: String,
+    #[docbuf {
+        format = "uuid";
+    }]
+    pub id: String,
+    #[docbuf {
+        format = "rfc3339";
+    }]
+    pub created_at: String,
+    #[docbuf {
+        format = "ipv4";
+    }]
+    pub ipv4: String,
+    #[docbuf {
+        format = "ipv6";
+    }]
+    pub ipv6: String,
+    #[docbuf {
+        total_digits = 5;
+        fraction_digits = 2;
+    }]
+    pub balance: f64,
+    #[docbuf {
+        whitespace = "collapse";
+    }]
+    pub display_name: String,
+    #[docbuf {
+        whitespace = "replace";
+    }]
+    pub bio: String,
+    #[docbuf {
+        not_empty = true;
+    }]
+    pub groups: Vec<String>,
+    #[docbuf {
+        not_empty = true;
+    }]
+    pub settings: HashMap<String, String>,
+    #[docbuf {
+        one_of = ["true"];
+    }]
+    pub verified: bool,
+}
+
+impl Account {
+    pub fn dummy() -> Self {
+        Self {
+            role: "admin".to_string(),
+            tier: 2,
+            email: "alice@example.com".to_string(),
+            homepage: "https://example.com/~alice?lang=en%20US".to_string(),
+            id: "67e55044-10b1-426f-9247-bb680e5fe0c8".to_string(),
+            created_at: "2024-02-29T23:59:60.123+05:30".to_string(),
+            ipv4: "192.168.0.1".to_string(),
+            ipv6: "2001:db8::1".to_string(),
+            balance: 123.45,
+            display_name: "Alice Smith".to_string(),
+            bio: "Likes  spaces".to_string(),
+            groups: vec!["staff".to_string()],
+            settings: HashMap::from([("theme".to_string(), "dark".to_string())]),
+            verified: true,
+        }
+    }
+}
+
+#[test]
+fn test_validation_facets() -> Result<(), Box<dyn std::error::Error>> {
+    let account = Account::dummy();
+
+    assert!(account.validate().is_ok());
+
+    let mut buffer = Vec::new();
+    account.to_docbuf(&mut buffer)?;
+    assert_eq!(Account::from_docbuf(&buffer)?, account);
+
+    let account = Account {
+        role: "owner".to_string(),
+        tier: 3,
+        email: "alice@localhost".to_string(),
+        homepage: "example.com/%zz".to_string(),
+        id: "67e55044-10b1-426f-9247-bb680e5fe0c".to_string(),
+        created_at: "2023-02-29T12:00:00Z".to_string(),
+        ipv4: "256.0.0.1".to_string(),
+        ipv6: "2001:db8:::1".to_string(),
+        balance: 1234.567,
+        display_name: " Alice  Smith".to_string(),
+        bio: "Line\nbreak".to_string(),
+        groups: vec![],
+        settings: HashMap::new(),
+        verified: false,
+    };
+
+    let report = account.validate().expect_err("expected violations");
+
+    let rules = report
+        .errors
+        .iter()
+        .map(|error| (error.path.as_str(), error.rule))
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        rules,
+        vec![
+            ("role", ValidationRule::OneOf),
+            ("tier", ValidationRule::OneOf),
+            ("email", ValidationRule::Format),
+            ("homepage", ValidationRule::Format),
+            ("id", ValidationRule::Format),
+            ("created_at", ValidationRule::Format),
+            ("ipv4", ValidationRule::Format),
+            ("ipv6", ValidationRule::Format),
+            ("balance", ValidationRule::TotalDigits),
+            ("balance", ValidationRule::FractionDigits),
+            ("display_name", ValidationRule::Whitespace),
+            ("bio", ValidationRule::Whitespace),
+            ("groups", ValidationRule::NotEmpty),
+            ("settings", ValidationRule::NotEmpty),
+            ("verified", ValidationRule::OneOf),
+        ]
+    );
+
+    // Each facet is enforced when encoding.
+    let rejected = |account: Account| {
+        let mut buffer = Vec::new();
+        account.to_docbuf(&mut buffer).is_err()
+    };
+
+    assert!(rejected(Account {
+        tier: 3,
+        ..Account::dummy()
+    }));
+    assert!(rejected(Account {
+        created_at: "2024-02-29 12:00:00Z".to_string(),
+        ..Account::dummy()
+    }));
+    assert!(rejected(Account {
+        balance: 0.125,
+        ..Account::dummy()
+    }));
+    assert!(rejected(Account {
+        groups: vec![],
+        ..Account::dummy()
+    }));
+    assert!(rejected(Account {
+        settings: HashMap::new(),
+        ..Account::dummy()
+    }));
+    assert!(rejected(Account {
+        verified: false,
+        ..Account::dummy()
+    }));
+
+    Ok(())
+}
+
+#[test]
+fn test_validation_facets_vtable() -> Result<(), Box<dyn std::error::Error>> {
+    let vtable = Account::vtable()?;
+    let account = vtable.struct_by_name("Account")?;
+
+    let role = &account.field_by_name("role")?.rules;
+    assert_eq!(
+        role.one_of,
+        Some(vec![
+            "admin".to_string(),
+            "member".to_string(),
+            "guest".to_string()
+        ])
+    );
+
+    let tier = &account.field_by_name("tier")?.rules;
+    assert_eq!(
+        tier.one_of_values,
+        Some(vec![
+            NumericValue::U8(1),
+            NumericValue::U8(2),
+            NumericValue::U8(4)
+        ])
+    );
+
+    let email = &account.field_by_name("email")?.rules;
+    assert_eq!(email.format, Some(VTableFieldFormat::Email));
+
+    let balance = &account.field_by_name("balance")?.rules;
+    assert_eq!(balance.total_digits, Some(5));
+    assert_eq!(balance.fraction_digits, Some(2));
+
+    let display_name = &account.field_by_name("display_name")?.rules;
+    assert_eq!(
+        display_name.whitespace,
+        Some(VTableFieldWhitespace::Collapse)
+    );
+
+    assert!(account.field_by_name("groups")?.rules.not_empty);
+
+    // The facets are persisted in the vtable binary format.
+    let bytes = vtable.to_vec()?;
+    let decoded = VTable::read_from_buffer(&mut DocBufReader::new(&bytes))?;
+
+    assert_eq!(&decoded, vtable);
+
+    Ok(())
+}
+
+#[docbuf {
+    namespace = "validation";
+    validate_with = "check_booking";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Booking {
+    #[docbuf {
+        max_value = 24;
+    }]
+    pub start: u8,
+    #[docbuf {
+        max_value = 24;
+    }]
+    pub end: u8,
+    #[docbuf {
+        validate_with = "check_room";
+    }]
+    pub room: String,
+}
+
+#[docbuf {
+    namespace = "validation";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Schedule {
+    pub bookings: Vec<Booking>,
+}
+
+fn check_booking(booking: &Booking) -> Result<(), String> {
+    if booking.start < booking.end {
+        Ok(())
+    } else {
+        Err(format!(
+            "start {} must be before end {}",
+            booking.start, booking.end
+        ))
+    }
+}
+
+fn check_room(room: &str) -> Result<(), String> {
+    if room.starts_with("room-") {
+        Ok(())
+    } else {
+        Err(format!("unknown room {room}"))
+    }
+}
+
+impl Booking {
+    pub fn dummy() -> Self {
+        Self {
+            start: 9,
+            end: 10,
+            room: "room-1".to_string(),
+        }
+    }
+}
+
+#[test]
+fn test_validation_validate_with() -> Result<(), Box<dyn std::error::Error>> {
+    let booking = Booking::dummy();
+
+    assert!(booking.validate().is_ok());
+
+    let mut buffer = Vec::new();
+    booking.to_docbuf(&mut buffer)?;
+    assert_eq!(Booking::from_docbuf(&buffer)?, booking);
+
+    // The document hook error carries the message of the function.
+    let invalid = Booking {
+        start: 12,
+        ..Booking::dummy()
+    };
+
+    match invalid.to_docbuf(&mut Vec::new()) {
+        Err(Error::VTable(vtable::Error::DocumentValidation(message))) => {
+            assert_eq!(message, "start 12 must be before end 10");
+        }
+        result => panic!("expected a document validation error, got {result:?}"),
+    }
+
+    // The field hook error carries the field name and the message of the function.
+    match (Booking {
+        room: "hall".to_string(),
+        ..Booking::dummy()
+    })
+    .to_docbuf(&mut Vec::new())
+    {
+        Err(Error::VTable(vtable::Error::FieldValidation { field, message })) => {
+            assert_eq!(field, "room");
+            assert_eq!(message, "unknown room hall");
+        }
+        result => panic!("expected a field validation error, got {result:?}"),
+    }
+
+    // Hooks run once the field rules have passed.
+    let report = Booking {
+        start: 30,
+        ..Booking::dummy()
+    }
+    .validate()
+    .expect_err("expected violations");
+
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].rule, ValidationRule::MaxValue);
+
+    let report = invalid.validate().expect_err("expected violations");
+
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].rule, ValidationRule::ValidateWith);
+    assert_eq!(report.errors[0].message, "start 12 must be before end 10");
+
+    // Decoding a buffer that fails the hooks is rejected.
+    let mut buffer = Vec::new();
+    Booking {
+        start: 10,
+        end: 10,
+        room: "room-1".to_string(),
+    }
+    .to_docbuf(&mut buffer)
+    .expect_err("expected a document validation error");
+
+    let mut buffer = Vec::new();
+    Booking {
+        start: 9,
+        end: 10,
+        room: "room-1".to_string(),
+    }
+    .to_docbuf(&mut buffer)?;
+
+    // Swap the start and end bytes.
+    buffer.swap(0, 1);
+    assert!(matches!(
+        Booking::from_docbuf(&buffer),
+        Err(Error::VTable(vtable::Error::DocumentValidation(_)))
+    ));
+
+    // The hooks of nested documents are run.
+    let schedule = Schedule {
+        bookings: vec![Booking::dummy(), invalid],
+    };
+
+    assert!(matches!(
+        schedule.to_docbuf(&mut Vec::new()),
+        Err(Error::VTable(vtable::Error::DocumentValidation(_)))
+    ));
+
+    Ok(())
+}
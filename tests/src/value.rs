@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+
+use docbuf_core::{
+    error::Error,
+    traits::DocBuf,
+    value::Value,
+    vtable::{self, DocBufReader, NumericValue, VTable},
+};
+use docbuf_macros::*;
+use serde::{Deserialize, Serialize};
+
+#[docbuf {
+    namespace = "value";
+    uuid = true;
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Reading {
+    #[docbuf {
+        max_length = 16;
+    }]
+    pub sensor: String,
+    pub sequence: u64,
+    pub offset: i16,
+    pub celsius: f32,
+    pub unit: char,
+    pub calibrated: bool,
+    #[serde(with = "serde_bytes")]
+    pub raw: Vec<u8>,
+    pub note: Option<String>,
+    pub samples: Vec<u32>,
+    pub labels: HashMap<String, i64>,
+    pub position: [f64; 2],
+    pub range: (u8, i128),
+    pub location: Location,
+    pub state: SensorState,
+    pub nothing: (),
+}
+
+#[docbuf {
+    namespace = "value";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Location {
+    pub site: String,
+    pub floor: Option<u16>,
+}
+
+#[docbuf {
+    namespace = "value";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub enum SensorState {
+    #[default]
+    Idle,
+    Active(u32),
+    Faulted {
+        code: u16,
+        message: String,
+    },
+}
+
+#[docbuf {
+    namespace = "value";
+    encoding = "compact";
+}]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Counter {
+    pub count: u64,
+    pub delta: i32,
+    pub names: Vec<String>,
+}
+
+impl Reading {
+    pub fn dummy() -> Self {
+        Self {
+            _uuid: [7; 16],
+            sensor: "thermo-1".to_string(),
+            sequence: 42,
+            offset: -3,
+            celsius: 21.5,
+            unit: 'C',
+            calibrated: true,
+            raw: vec![0xde, 0xad],
+            note: None,
+            samples: vec![1, 2, 3],
+            labels: HashMap::from([("room".to_string(), -1)]),
+            position: [1.5, -2.25],
+            range: (5, -10),
+            location: Location {
+                site: "lab".to_string(),
+                floor: Some(3),
+            },
+            state: SensorState::Faulted {
+                code: 500,
+                message: "overheat".to_string(),
+            },
+            nothing: (),
+        }
+    }
+}
+
+#[test]
+fn test_value_decode() -> Result<(), Box<dyn std::error::Error>> {
+    let reading = Reading::dummy();
+
+    let mut buffer = Vec::new();
+    reading.to_docbuf(&mut buffer)?;
+
+    // Decode with a vtable read from its binary format, without the Rust type.
+    let bytes = Reading::vtable()?.to_vec()?;
+    let vtable = VTable::read_from_buffer(&mut DocBufReader::new(&bytes))?;
+
+    let value = vtable.decode_value(&buffer)?;
+
+    assert_eq!(
+        value.fields().map(|fields| fields.len()),
+        Some(16),
+        "{value}"
+    );
+    assert_eq!(value.get("_uuid"), Some(&Value::Uuid([7; 16])));
+    assert_eq!(value.get("sensor"), Some(&Value::from("thermo-1")));
+    assert_eq!(
+        value.get("sequence"),
+        Some(&Value::Numeric(NumericValue::U64(42)))
+    );
+    assert_eq!(
+        value.get("offset"),
+        Some(&Value::Numeric(NumericValue::I16(-3)))
+    );
+    assert_eq!(value.get("unit"), Some(&Value::Char('C')));
+    assert_eq!(value.get("calibrated"), Some(&Value::Bool(true)));
+    assert_eq!(value.get("raw"), Some(&Value::Bytes(vec![0xde, 0xad])));
+    assert_eq!(value.get("note"), Some(&Value::Option(None)));
+    assert_eq!(
+        value.get("samples"),
+        Some(&Value::List(vec![
+            Value::Numeric(NumericValue::U32(1)),
+            Value::Numeric(NumericValue::U32(2)),
+            Value::Numeric(NumericValue::U32(3)),
+        ]))
+    );
+    assert_eq!(
+        value.get("labels"),
+        Some(&Value::Map(vec![(
+            Value::from("room"),
+            Value::Numeric(NumericValue::I64(-1))
+        )]))
+    );
+    assert_eq!(
+        value.get("position"),
+        Some(&Value::Array(vec![
+            Value::Numeric(NumericValue::F64(1.5)),
+            Value::Numeric(NumericValue::F64(-2.25)),
+        ]))
+    );
+    assert_eq!(
+        value.get("range"),
+        Some(&Value::Tuple(vec![
+            Value::Numeric(NumericValue::U8(5)),
+            Value::Numeric(NumericValue::I128(-10)),
+        ]))
+    );
+    assert_eq!(
+        value.get("location"),
+        Some(&Value::Struct {
+            name: "Location".to_string(),
+            fields: vec![
+                ("site".to_string(), Value::from("lab")),
+                (
+                    "floor".to_string(),
+                    Value::from(Some(Value::Numeric(NumericValue::U16(3))))
+                ),
+            ],
+        })
+    );
+    assert_eq!(
+        value.get("state"),
+        Some(&Value::Enum {
+            name: "SensorState".to_string(),
+            variant: "Faulted".to_string(),
+            fields: vec![
+                ("code".to_string(), Value::Numeric(NumericValue::U16(500))),
+                ("message".to_string(), Value::from("overheat")),
+            ],
+        })
+    );
+    assert_eq!(value.get("nothing"), Some(&Value::Unit));
+
+    assert_eq!(
+        value.get("location").map(|location| location.to_string()),
+        Some("Location { site: \"lab\", floor: Some(3) }".to_string())
+    );
+
+    // Trailing bytes are rejected.
+    buffer.push(0);
+    assert!(vtable.decode_value(&buffer).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_value_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    let vtable = Reading::vtable()?;
+
+    for reading in [
+        Reading::dummy(),
+        Reading {
+            note: Some("calibrated".to_string()),
+            samples: vec![],
+            labels: HashMap::new(),
+            state: SensorState::Active(9),
+            ..Reading::dummy()
+        },
+        Reading {
+            location: Location {
+                floor: None,
+                ..Reading::dummy().location
+            },
+            state: SensorState::Idle,
+            ..Reading::dummy()
+        },
+    ] {
+        let mut buffer = Vec::new();
+        reading.to_docbuf(&mut buffer)?;
+
+        // A decoded value encodes to the same buffer.
+        let value = vtable.decode_value(&buffer)?;
+        assert_eq!(vtable.encode_value(&value)?, buffer);
+        assert_eq!(
+            Reading::from_docbuf(&vtable.encode_value(&value)?)?,
+            reading
+        );
+    }
+
+    // Values of a compact encoded vtable.
+    let counter = Counter {
+        count: 300,
+        delta: -2,
+        names: vec!["a".to_string()],
+    };
+
+    let mut buffer = Vec::new();
+    counter.to_docbuf(&mut buffer)?;
+
+    let value = Counter::vtable()?.decode_value(&buffer)?;
+    assert_eq!(
+        value.get("count"),
+        Some(&Value::Numeric(NumericValue::U64(300)))
+    );
+    assert_eq!(Counter::vtable()?.encode_value(&value)?, buffer);
+
+    Ok(())
+}
+
+#[test]
+fn test_value_encode() -> Result<(), Box<dyn std::error::Error>> {
+    let vtable = Location::vtable()?;
+
+    let location = Value::Struct {
+        name: "Location".to_string(),
+        fields: vec![
+            ("site".to_string(), Value::from("annex")),
+            (
+                "floor".to_string(),
+                Value::from(Some(Value::Numeric(NumericValue::U16(2)))),
+            ),
+        ],
+    };
+
+    let buffer = vtable.encode_value(&location)?;
+
+    assert_eq!(
+        Location::from_docbuf(&buffer)?,
+        Location {
+            site: "annex".to_string(),
+            floor: Some(2),
+        }
+    );
+
+    // Values must match the field type.
+    let mut invalid = location.clone();
+    *invalid.get_mut("floor").expect("floor field") =
+        Value::from(Some(Value::Numeric(NumericValue::U32(2))));
+
+    assert!(matches!(
+        vtable.encode_value(&invalid),
+        Err(Error::VTable(vtable::Error::InvalidValueType(..)))
+    ));
+
+    // Every field must have a value, and every value a field.
+    let missing = Value::Struct {
+        name: "Location".to_string(),
+        fields: vec![("site".to_string(), Value::from("annex"))],
+    };
+
+    assert!(matches!(
+        vtable.encode_value(&missing),
+        Err(Error::VTable(vtable::Error::MissingFieldValue(field))) if field == "floor"
+    ));
+
+    let mut unknown = location.clone();
+    if let Value::Struct { fields, .. } = &mut unknown {
+        fields.push(("basement".to_string(), Value::Bool(true)));
+    }
+
+    assert!(matches!(
+        vtable.encode_value(&unknown),
+        Err(Error::VTable(vtable::Error::UnknownFieldValue(field))) if field == "basement"
+    ));
+
+    // The field rules of the vtable are checked.
+    let vtable = Reading::vtable()?;
+
+    let mut buffer = Vec::new();
+    Reading::dummy().to_docbuf(&mut buffer)?;
+
+    let mut value = vtable.decode_value(&buffer)?;
+    *value.get_mut("sensor").expect("sensor field") = Value::from("a-very-long-sensor-name");
+
+    assert!(matches!(
+        vtable.encode_value(&value),
+        Err(Error::VTable(vtable::Error::FieldRulesLength(_)))
+    ));
+
+    Ok(())
+}
@@ -2,7 +2,7 @@ use crate::complex::Document;
 
 use docbuf_core::{
     traits::DocBuf,
-    vtable::{VTable, VTableItem},
+    vtable::{self, DocBufReader, VTable, VTableItem, VTABLE_FORMAT_VERSION, VTABLE_MAGIC},
 };
 
 #[test]
@@ -12,9 +12,93 @@ fn test_vtable_serialization() -> Result<(), docbuf_db::Error> {
     let mut vtable_buf = Vec::new();
     vtable.write_to_buffer(&mut vtable_buf)?;
 
-    let vtable2 = VTable::read_from_buffer(&mut vtable_buf)?;
+    let vtable2 = VTable::read_from_buffer(&mut DocBufReader::new(&vtable_buf))?;
 
     assert_eq!(*vtable, vtable2);
 
     Ok(())
 }
+
+#[test]
+fn test_vtable_format_version() -> Result<(), docbuf_db::Error> {
+    let vtable_buf = Document::vtable()?.to_vec()?;
+
+    assert_eq!(vtable_buf[..4], VTABLE_MAGIC);
+    assert_eq!(vtable_buf[4], VTABLE_FORMAT_VERSION);
+
+    // Vtables written before the format was versioned start with the namespace.
+    assert!(matches!(
+        VTable::read_from_buffer(&mut DocBufReader::new(&vtable_buf[5..])),
+        Err(vtable::Error::UnversionedVTableFormat)
+    ));
+
+    let mut future = vtable_buf.clone();
+    future[4] = VTABLE_FORMAT_VERSION + 1;
+    assert!(matches!(
+        VTable::read_from_buffer(&mut DocBufReader::new(&future)),
+        Err(vtable::Error::UnsupportedVTableFormat(version)) if version == VTABLE_FORMAT_VERSION + 1
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_vtable_reader() -> Result<(), docbuf_db::Error> {
+    let vtable = Document::vtable()?;
+
+    let mut vtable_buf = Vec::new();
+    vtable.write_to_buffer(&mut vtable_buf)?;
+
+    // Write a second vtable back to back with the first one.
+    let vtable_len = vtable_buf.len();
+    vtable_buf.extend_from_within(..);
+
+    let mut reader = DocBufReader::new(&vtable_buf);
+
+    assert_eq!(*vtable, VTable::read_from_buffer(&mut reader)?);
+    assert_eq!(reader.position(), vtable_len);
+
+    assert_eq!(*vtable, VTable::read_from_buffer(&mut reader)?);
+    assert!(reader.is_empty());
+
+    // Truncated buffers error rather than panic.
+    for len in 0..vtable_len {
+        assert!(VTable::read_from_buffer(&mut DocBufReader::new(&vtable_buf[..len])).is_err());
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_vtable_id() -> Result<(), Box<dyn std::error::Error>> {
+    let vtable = Document::vtable()?;
+
+    // Every vtable has its own id.
+    let other = crate::value::Reading::vtable()?;
+    assert_ne!(vtable.id(), other.id());
+
+    // The id is the fingerprint of the vtable, read back from its binary format.
+    let bytes = vtable.to_vec()?;
+    let read = VTable::read_from_buffer(&mut DocBufReader::new(&bytes))?;
+    assert_eq!(read.id(), vtable.id());
+
+    // Any change to the vtable, e.g. a field rule, changes the id, also
+    // once the id of the vtable is computed.
+    let mut changed = read.clone();
+    if let Some(VTableItem::Struct(item)) = changed.items_mut().inner_mut().first_mut() {
+        if let Some(field) = item.fields.0.first_mut() {
+            field.rules = field.rules.clone().set_max_length(7);
+        }
+    }
+    assert_ne!(changed.id(), vtable.id());
+
+    let read_changed = VTable::read_from_buffer(&mut DocBufReader::new(&changed.to_vec()?))?;
+    assert_eq!(read_changed.id(), changed.id());
+
+    // The id is recomputed when items are added.
+    let mut extended = read.clone();
+    extended.add_item(other.root_item()?.to_owned());
+    assert_ne!(extended.id(), vtable.id());
+
+    Ok(())
+}